@@ -273,7 +273,7 @@ pub fn generate_bn_wrapper(
         ) #return_type #where_clause {
             let __input_wires = #input_wires_object;
 
-            #context_param_name.with_named_child((#key_generation), __input_wires, |__comp, __inputs| {
+            #context_param_name.with_named_child(concat!(module_path!(), "::", #fn_name_str), (#key_generation), __input_wires, |__comp, __inputs| {
                 // Unpack inputs into individual variables
                 #unpack_inputs
                 #transformed_body
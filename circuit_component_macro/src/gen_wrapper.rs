@@ -275,7 +275,7 @@ pub fn generate_wrapper(sig: &ComponentSignature, original_fn: &ItemFn) -> Resul
         ) #return_type #where_clause {
             let __input_wires = #input_wires_object;
 
-            #context_param_name.with_named_child((#key_generation), __input_wires, |mut __comp, __inputs| {
+            #context_param_name.with_named_child(concat!(module_path!(), "::", #fn_name_str), (#key_generation), __input_wires, |mut __comp, __inputs| {
                 // Unpack inputs into individual variables
                 #unpack_inputs
                 #transformed_body
@@ -36,6 +36,7 @@ trait CircuitContext {
     fn issue_wire(&mut self) -> WireId;
     fn with_named_child<I: crate::circuit::streaming::WiresObject, O: crate::circuit::streaming::into_wire_list::FromWires>(
         &mut self,
+        _name: &str,
         _key: [u8; 8],
         _inputs: I,
         f: impl Fn(&mut Self, &I) -> O,
@@ -46,6 +46,7 @@ trait CircuitContext {
     fn add_gate(&mut self, gate: Gate);
     fn with_named_child<I: crate::circuit::streaming::WiresObject, O: crate::circuit::streaming::into_wire_list::FromWires>(
         &mut self,
+        _name: &str,
         _key: [u8; 8],
         _inputs: I,
         f: impl Fn(&mut Self, &I) -> O,
@@ -0,0 +1,62 @@
+//! Differential fuzzing of the BLS12-381 Fq gadgets against an ark-ff field over the same modulus.
+
+#![no_main]
+
+use g16ckt::{
+    WireId,
+    ark::{Field, PrimeField},
+    circuit::CircuitBuilder,
+    gadgets::bls12_381::fq::{ArkFq, Fq},
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::common::{FuzzWire, Input};
+
+#[path = "common.rs"]
+mod common;
+
+impl FuzzWire for Fq {
+    type Value = ArkFq;
+
+    fn issue(issue: impl FnMut() -> WireId) -> Self {
+        Fq::new(issue)
+    }
+
+    fn bits_fn(wires: &Self, value: &ArkFq) -> impl Fn(WireId) -> Option<bool> {
+        Fq::get_wire_bits_fn(wires, &Fq::as_montgomery(*value)).unwrap()
+    }
+}
+
+type FqInput<const N: usize> = Input<Fq, N>;
+
+fuzz_target!(|data: [[u8; 48]; 2]| {
+    let a = ArkFq::from_le_bytes_mod_order(&data[0]);
+    let b = ArkFq::from_le_bytes_mod_order(&data[1]);
+
+    let sum = CircuitBuilder::streaming_execute::<_, _, ArkFq>(
+        FqInput::new([a, b]),
+        10_000,
+        |ctx, [a, b]| Fq::add(ctx, a, b),
+    );
+    assert_eq!(sum.output_value, a + b, "add({a}, {b})");
+
+    let difference = CircuitBuilder::streaming_execute::<_, _, ArkFq>(
+        FqInput::new([a, b]),
+        10_000,
+        |ctx, [a, b]| Fq::sub(ctx, a, b),
+    );
+    assert_eq!(difference.output_value, a - b, "sub({a}, {b})");
+
+    let product = CircuitBuilder::streaming_execute::<_, _, ArkFq>(
+        FqInput::new([a, b]),
+        10_000,
+        |ctx, [a, b]| Fq::mul_montgomery(ctx, a, b),
+    );
+    assert_eq!(product.output_value, a * b, "mul({a}, {b})");
+
+    let square =
+        CircuitBuilder::streaming_execute::<_, _, ArkFq>(FqInput::new([a]), 10_000, |ctx, [a]| {
+            Fq::square_montgomery(ctx, a)
+        });
+    assert_eq!(square.output_value, a.square(), "square({a})");
+});
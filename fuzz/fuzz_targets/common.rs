@@ -0,0 +1,50 @@
+//! Circuit input shared by the fuzz targets, each of which includes this file with `#[path]`.
+
+use g16ckt::{
+    WireId,
+    circuit::{CircuitInput, CircuitMode, EncodeInput, WiresObject},
+};
+
+/// Wires of one value a fuzz target feeds to its circuits
+pub trait FuzzWire: WiresObject {
+    /// Off-circuit value carried by the wires
+    type Value;
+
+    /// Issue the wires of one value
+    fn issue(issue: impl FnMut() -> WireId) -> Self;
+
+    /// Bit of each wire in `wires` encoding `value`, in the form the gadgets under test expect
+    fn bits_fn(wires: &Self, value: &Self::Value) -> impl Fn(WireId) -> Option<bool>;
+}
+
+/// `N` values, each fed to its own `W`
+pub struct Input<W: FuzzWire, const N: usize>([W::Value; N]);
+
+impl<W: FuzzWire, const N: usize> Input<W, N> {
+    pub fn new(values: [W::Value; N]) -> Self {
+        Self(values)
+    }
+}
+
+impl<W: FuzzWire, const N: usize> CircuitInput for Input<W, N> {
+    type WireRepr = [W; N];
+
+    fn allocate(&self, mut issue: impl FnMut() -> WireId) -> Self::WireRepr {
+        std::array::from_fn(|_| W::issue(&mut issue))
+    }
+
+    fn collect_wire_ids(repr: &Self::WireRepr) -> Vec<WireId> {
+        repr.iter().flat_map(|wires| wires.to_wires_vec()).collect()
+    }
+}
+
+impl<W: FuzzWire, const N: usize, M: CircuitMode<WireValue = bool>> EncodeInput<M> for Input<W, N> {
+    fn encode(&self, repr: &Self::WireRepr, cache: &mut M) {
+        for (value, wires) in self.0.iter().zip(repr) {
+            let bits_fn = W::bits_fn(wires, value);
+            for wire in wires.to_wires_vec() {
+                cache.feed_wire(wire, bits_fn(wire).unwrap());
+            }
+        }
+    }
+}
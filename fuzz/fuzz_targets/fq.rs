@@ -0,0 +1,76 @@
+//! Differential fuzzing of the BN254 Fq gadgets against ark's Fq.
+
+#![no_main]
+
+use g16ckt::{
+    FpImpl, FqWire as Fq, WireId,
+    ark::{self, Field, PrimeField},
+    circuit::{CircuitBuilder, WiresObject, modes::Execute},
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::common::{FuzzWire, Input};
+
+#[path = "common.rs"]
+mod common;
+
+/// Elements fed in standard form, so integer-level gadgets see the canonical values
+impl FuzzWire for Fq {
+    type Value = ark::Fq;
+
+    fn issue(issue: impl FnMut() -> WireId) -> Self {
+        Fq::new(issue)
+    }
+
+    fn bits_fn(wires: &Self, value: &ark::Fq) -> impl Fn(WireId) -> Option<bool> {
+        Fq::get_wire_bits_fn(wires, value).unwrap()
+    }
+}
+
+type FqInput<const N: usize> = Input<Fq, N>;
+
+fn run<const N: usize>(
+    values: [ark::Fq; N],
+    f: impl Fn(&mut Execute, &[Fq; N]) -> Vec<WireId>,
+) -> Vec<bool> {
+    CircuitBuilder::streaming_execute::<_, _, Vec<bool>>(FqInput::new(values), 10_000, f)
+        .output_value
+}
+
+fuzz_target!(|data: [[u8; 32]; 2]| {
+    let a = ark::Fq::from_le_bytes_mod_order(&data[0]);
+    let b = ark::Fq::from_le_bytes_mod_order(&data[1]);
+
+    let sum = run([a, b], |ctx, [a, b]| Fq::add(ctx, a, b).to_wires_vec());
+    assert_eq!(Fq::from_bits(sum), a + b, "add({a}, {b})");
+
+    let difference = run([a, b], |ctx, [a, b]| Fq::sub(ctx, a, b).to_wires_vec());
+    assert_eq!(Fq::from_bits(difference), a - b, "sub({a}, {b})");
+
+    let negation = run([a], |ctx, [a]| Fq::neg(ctx, a).to_wires_vec());
+    assert_eq!(Fq::from_bits(negation), -a, "neg({a})");
+
+    let mut wrapped = run([a, b], |ctx, [a, b]| {
+        let (difference, borrow) = Fq::sub_with_borrow(ctx, a, b);
+        let mut wires = difference.to_wires_vec();
+        wires.push(borrow);
+        wires
+    });
+    let borrow = wrapped.pop().unwrap();
+    assert_eq!(
+        borrow,
+        a.into_bigint() < b.into_bigint(),
+        "borrow({a}, {b})"
+    );
+    // The difference wraps modulo 2^254 when there is a borrow
+    let wrap = if borrow {
+        ark::Fq::from(2u8).pow([Fq::N_BITS as u64])
+    } else {
+        ark::Fq::from(0u8)
+    };
+    assert_eq!(
+        Fq::from_bits(wrapped),
+        a - b + wrap,
+        "sub_with_borrow({a}, {b})"
+    );
+});
@@ -0,0 +1,73 @@
+//! Differential fuzzing of the Fq12 Montgomery gadgets against ark's Fq12.
+
+#![no_main]
+
+use g16ckt::{
+    Fq12Wire as Fq12, WireId,
+    ark::{self, Field, PrimeField},
+    circuit::{CircuitBuilder, modes::Execute},
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::common::{FuzzWire, Input};
+
+#[path = "common.rs"]
+mod common;
+
+impl FuzzWire for Fq12 {
+    type Value = ark::Fq12;
+
+    fn issue(issue: impl FnMut() -> WireId) -> Self {
+        Fq12::new(issue)
+    }
+
+    fn bits_fn(wires: &Self, value: &ark::Fq12) -> impl Fn(WireId) -> Option<bool> {
+        Fq12::get_wire_bits_fn(wires, &Fq12::as_montgomery(*value)).unwrap()
+    }
+}
+
+type Fq12Input<const N: usize> = Input<Fq12, N>;
+
+fn fq12(data: &[[u8; 32]]) -> ark::Fq12 {
+    let mut fqs = data
+        .iter()
+        .map(|bytes| ark::Fq::from_le_bytes_mod_order(bytes));
+    let mut fq2 = || ark::Fq2::new(fqs.next().unwrap(), fqs.next().unwrap());
+    let mut fq6 = || ark::Fq6::new(fq2(), fq2(), fq2());
+    ark::Fq12::new(fq6(), fq6())
+}
+
+fn run<const N: usize>(
+    values: [ark::Fq12; N],
+    f: impl Fn(&mut Execute, &[Fq12; N]) -> Fq12,
+) -> ark::Fq12 {
+    CircuitBuilder::streaming_execute::<_, _, ark::Fq12>(Fq12Input::new(values), 10_000, f)
+        .output_value
+}
+
+fuzz_target!(|data: [[u8; 32]; 24]| {
+    let a = fq12(&data[..12]);
+    let b = fq12(&data[12..]);
+
+    let product = run([a, b], |ctx, [a, b]| Fq12::mul_montgomery(ctx, a, b));
+    assert_eq!(product, a * b, "mul({a}, {b})");
+
+    let square = run([a], |ctx, [a]| Fq12::square_montgomery(ctx, a));
+    assert_eq!(square, a.square(), "square({a})");
+
+    let conjugate = run([a], |ctx, [a]| Fq12::conjugate(ctx, a));
+    let mut expected = a;
+    expected.conjugate_in_place();
+    assert_eq!(conjugate, expected, "conjugate({a})");
+
+    for i in 1..=3 {
+        let frobenius = run([a], |ctx, [a]| Fq12::frobenius_montgomery(ctx, a, i));
+        assert_eq!(frobenius, a.frobenius_map(i), "frobenius_map({a}, {i})");
+    }
+
+    // Zero has no inverse
+    if let Some(expected) = a.inverse() {
+        let inverse = run([a], |ctx, [a]| Fq12::inverse_montgomery(ctx, a));
+        assert_eq!(inverse, expected, "inverse({a})");
+    }
+});
@@ -0,0 +1,85 @@
+//! Differential fuzzing of the Fq2 Montgomery gadgets against ark's Fq2.
+
+#![no_main]
+
+use g16ckt::{
+    Fq2Wire as Fq2, WireId,
+    ark::{self, Field, PrimeField},
+    circuit::CircuitBuilder,
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::common::{FuzzWire, Input};
+
+#[path = "common.rs"]
+mod common;
+
+impl FuzzWire for Fq2 {
+    type Value = ark::Fq2;
+
+    fn issue(issue: impl FnMut() -> WireId) -> Self {
+        Fq2::new(issue)
+    }
+
+    fn bits_fn(wires: &Self, value: &ark::Fq2) -> impl Fn(WireId) -> Option<bool> {
+        Fq2::get_wire_bits_fn(wires, &Fq2::as_montgomery(*value)).unwrap()
+    }
+}
+
+type Fq2Input<const N: usize> = Input<Fq2, N>;
+
+fn fq2(c0: &[u8; 32], c1: &[u8; 32]) -> ark::Fq2 {
+    ark::Fq2::new(
+        ark::Fq::from_le_bytes_mod_order(c0),
+        ark::Fq::from_le_bytes_mod_order(c1),
+    )
+}
+
+fuzz_target!(|data: [[u8; 32]; 4]| {
+    let a = fq2(&data[0], &data[1]);
+    let b = fq2(&data[2], &data[3]);
+
+    let product = CircuitBuilder::streaming_execute::<_, _, ark::Fq2>(
+        Fq2Input::new([a, b]),
+        10_000,
+        |ctx, [a, b]| Fq2::mul_montgomery(ctx, a, b),
+    );
+    assert_eq!(product.output_value, a * b, "mul({a}, {b})");
+
+    let square = CircuitBuilder::streaming_execute::<_, _, ark::Fq2>(
+        Fq2Input::new([a]),
+        10_000,
+        |ctx, [a]| Fq2::square_montgomery(ctx, a),
+    );
+    assert_eq!(square.output_value, a.square(), "square({a})");
+
+    // Zero has no inverse
+    if let Some(expected) = a.inverse() {
+        let inverse = CircuitBuilder::streaming_execute::<_, _, ark::Fq2>(
+            Fq2Input::new([a]),
+            10_000,
+            |ctx, [a]| Fq2::inverse_montgomery(ctx, a),
+        );
+        assert_eq!(inverse.output_value, expected, "inverse({a})");
+    }
+
+    // Purely real inputs go through `sqrt_c1_zero_montgomery`
+    if a.c1 != ark::Fq::from(0u8) {
+        let is_qr = CircuitBuilder::streaming_execute::<_, _, bool>(
+            Fq2Input::new([a]),
+            10_000,
+            |ctx, [a]| Fq2::sqrt_general_montgomery(ctx, a).1,
+        );
+        assert_eq!(is_qr.output_value, a.sqrt().is_some(), "is_qr({a})");
+
+        // Non-residues have no root
+        if let Some(expected) = a.sqrt() {
+            let sqrt = CircuitBuilder::streaming_execute::<_, _, ark::Fq2>(
+                Fq2Input::new([a]),
+                10_000,
+                |ctx, [a]| Fq2::sqrt_general_montgomery(ctx, a).0,
+            );
+            assert_eq!(sqrt.output_value, expected, "sqrt({a})");
+        }
+    }
+});
@@ -0,0 +1,83 @@
+//! Differential fuzzing of the Fq6 Montgomery gadgets against ark's Fq6.
+
+#![no_main]
+
+use g16ckt::{
+    Fq6Wire as Fq6, WireId,
+    ark::{self, Field, PrimeField},
+    circuit::{CircuitBuilder, modes::Execute},
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::common::{FuzzWire, Input};
+
+#[path = "common.rs"]
+mod common;
+
+impl FuzzWire for Fq6 {
+    type Value = ark::Fq6;
+
+    fn issue(issue: impl FnMut() -> WireId) -> Self {
+        Fq6::new(issue)
+    }
+
+    fn bits_fn(wires: &Self, value: &ark::Fq6) -> impl Fn(WireId) -> Option<bool> {
+        Fq6::get_wire_bits_fn(wires, &Fq6::as_montgomery(*value)).unwrap()
+    }
+}
+
+type Fq6Input<const N: usize> = Input<Fq6, N>;
+
+fn fq6(data: &[[u8; 32]]) -> ark::Fq6 {
+    let fq2 = |c0, c1| {
+        ark::Fq2::new(
+            ark::Fq::from_le_bytes_mod_order(c0),
+            ark::Fq::from_le_bytes_mod_order(c1),
+        )
+    };
+    ark::Fq6::new(
+        fq2(&data[0], &data[1]),
+        fq2(&data[2], &data[3]),
+        fq2(&data[4], &data[5]),
+    )
+}
+
+fn run<const N: usize>(
+    values: [ark::Fq6; N],
+    f: impl Fn(&mut Execute, &[Fq6; N]) -> Fq6,
+) -> ark::Fq6 {
+    CircuitBuilder::streaming_execute::<_, _, ark::Fq6>(Fq6Input::new(values), 10_000, f)
+        .output_value
+}
+
+fuzz_target!(|data: [[u8; 32]; 12]| {
+    let a = fq6(&data[..6]);
+    let b = fq6(&data[6..]);
+
+    let sum = run([a, b], |ctx, [a, b]| Fq6::add(ctx, a, b));
+    assert_eq!(sum, a + b, "add({a}, {b})");
+
+    let difference = run([a, b], |ctx, [a, b]| Fq6::sub(ctx, a, b));
+    assert_eq!(difference, a - b, "sub({a}, {b})");
+
+    let product = run([a, b], |ctx, [a, b]| Fq6::mul_montgomery(ctx, a, b));
+    assert_eq!(product, a * b, "mul({a}, {b})");
+
+    let square = run([a], |ctx, [a]| Fq6::square_montgomery(ctx, a));
+    assert_eq!(square, a.square(), "square({a})");
+
+    // Multiplying by v shifts the components up, with v³ = 9 + u
+    let xi = ark::Fq2::new(ark::Fq::from(9u8), ark::Fq::from(1u8));
+    let shifted = run([a], |ctx, [a]| Fq6::mul_by_nonresidue(ctx, a));
+    assert_eq!(
+        shifted,
+        ark::Fq6::new(xi * a.c2, a.c0, a.c1),
+        "mul_by_nonresidue({a})"
+    );
+
+    // Zero has no inverse
+    if let Some(expected) = a.inverse() {
+        let inverse = run([a], |ctx, [a]| Fq6::inverse_montgomery(ctx, a));
+        assert_eq!(inverse, expected, "inverse({a})");
+    }
+});
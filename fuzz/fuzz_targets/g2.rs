@@ -0,0 +1,68 @@
+//! Differential fuzzing of the G2 group gadgets against ark's G2.
+
+#![no_main]
+
+use g16ckt::{
+    G2Wire as G2, WireId,
+    ark::{self, PrimeField, PrimeGroup},
+    circuit::CircuitBuilder,
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::common::{FuzzWire, Input};
+
+#[path = "common.rs"]
+mod common;
+
+impl FuzzWire for G2 {
+    type Value = ark::G2Projective;
+
+    fn issue(issue: impl FnMut() -> WireId) -> Self {
+        G2::new(issue)
+    }
+
+    fn bits_fn(wires: &Self, value: &ark::G2Projective) -> impl Fn(WireId) -> Option<bool> {
+        G2::get_wire_bits_fn(wires, &G2::as_montgomery(*value)).unwrap()
+    }
+}
+
+type G2Input<const N: usize> = Input<G2, N>;
+
+fuzz_target!(|data: (u8, [u8; 32], [u8; 32])| {
+    let (edge_case, s1, s2) = data;
+    let s1 = ark::Fr::from_le_bytes_mod_order(&s1);
+    // Steer a share of the inputs onto the equal-point and identity branches
+    let s2 = match edge_case % 4 {
+        0 => s1,
+        1 => ark::Fr::from(0u8),
+        _ => ark::Fr::from_le_bytes_mod_order(&s2),
+    };
+
+    let generator = ark::G2Projective::generator();
+    let (p, q) = (generator * s1, generator * s2);
+
+    // The addition formula has no doubling branch: for P == Q it yields the identity, so equal
+    // non-identity points are only checked through `double_montgomery` below
+    if p != q || p == ark::G2Projective::default() {
+        let sum = CircuitBuilder::streaming_execute::<_, _, ark::G2Projective>(
+            G2Input::new([p, q]),
+            10_000,
+            |ctx, [p, q]| G2::add_montgomery(ctx, p, q),
+        );
+        assert_eq!(sum.output_value, p + q, "add({p}, {q})");
+    }
+
+    let double = CircuitBuilder::streaming_execute::<_, _, ark::G2Projective>(
+        G2Input::new([p]),
+        10_000,
+        |ctx, [p]| G2::double_montgomery(ctx, p),
+    );
+    assert_eq!(double.output_value, p + p, "double({p})");
+
+    let neg = CircuitBuilder::streaming_execute::<_, _, ark::G2Projective>(
+        G2Input::new([q]),
+        10_000,
+        |ctx, [q]| G2::neg(ctx, q),
+    );
+    assert_eq!(neg.output_value, -q, "neg({q})");
+});
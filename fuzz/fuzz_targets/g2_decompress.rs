@@ -0,0 +1,73 @@
+//! Round-trips G2 points through the (x, y-flag) compression used for Groth16 proof inputs.
+
+#![no_main]
+
+use g16ckt::{
+    Fq2Wire, WireId,
+    ark::{self, AffineRepr, CurveGroup, Field, PrimeField, PrimeGroup},
+    circuit::CircuitBuilder,
+    gadgets::groth16::{CompressedG2Wires, decompress_g2_from_compressed},
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::common::{FuzzWire, Input};
+
+#[path = "common.rs"]
+mod common;
+
+/// Compressed point: x in standard form, and whether y is the root `sqrt` picks.
+struct CompressedG2 {
+    x: ark::Fq2,
+    y_flag: bool,
+}
+
+impl CompressedG2 {
+    /// Same convention as `Groth16VerifyCompressedInput`
+    fn compress(point: ark::G2Affine) -> Self {
+        let y_flag = point.y.square().sqrt().expect("y^2 must be QR in Fq2") == point.y;
+        Self { x: point.x, y_flag }
+    }
+}
+
+impl FuzzWire for CompressedG2Wires {
+    type Value = CompressedG2;
+
+    fn issue(issue: impl FnMut() -> WireId) -> Self {
+        CompressedG2Wires::new(issue)
+    }
+
+    fn bits_fn(wires: &Self, value: &CompressedG2) -> impl Fn(WireId) -> Option<bool> {
+        let x_fn = Fq2Wire::get_wire_bits_fn(&wires.p, &Fq2Wire::as_montgomery(value.x)).unwrap();
+        let (y_flag_wire, y_flag) = (wires.y_flag, value.y_flag);
+        move |wire| {
+            if wire == y_flag_wire {
+                Some(y_flag)
+            } else {
+                x_fn(wire)
+            }
+        }
+    }
+}
+
+fuzz_target!(|scalar: [u8; 32]| {
+    let scalar = ark::Fr::from_le_bytes_mod_order(&scalar);
+    let point = (ark::G2Projective::generator() * scalar).into_affine();
+    // The identity has no affine x to compress
+    if point.is_zero() {
+        return;
+    }
+
+    let compressed = CompressedG2::compress(point);
+    let (x, y_flag) = (compressed.x, compressed.y_flag);
+
+    let result = CircuitBuilder::streaming_execute::<_, _, ark::G2Projective>(
+        Input::<CompressedG2Wires, 1>::new([compressed]),
+        10_000,
+        |ctx, [compressed]| decompress_g2_from_compressed(ctx, compressed),
+    );
+    assert_eq!(
+        result.output_value.into_affine(),
+        point,
+        "decompress(x = {x}, y_flag = {y_flag})"
+    );
+});
@@ -0,0 +1,87 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+};
+
+/// Components that allocated each of `wires`, outermost first, read from the sidecar at `path`
+/// that `g16gen generate --components` writes next to the circuit.
+///
+/// Each sidecar line is `<first> <end> <component>` for one component call, covering wires
+/// `first..end`. Calls nest, and a nested call is written before the one containing it. The file
+/// is streamed rather than loaded, as it has a line per call of the whole circuit.
+pub fn locate(path: &str, wires: &[u64]) -> io::Result<Vec<Vec<String>>> {
+    let mut calls = vec![Vec::new(); wires.len()];
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("malformed line {line:?} in {path}"),
+            )
+        };
+        let mut fields = line.splitn(3, ' ');
+        let (Some(first), Some(end), Some(name)) = (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(invalid());
+        };
+        let (Ok(first), Ok(end)) = (first.parse::<u64>(), end.parse::<u64>()) else {
+            return Err(invalid());
+        };
+
+        for (wire, calls) in wires.iter().zip(&mut calls) {
+            if (first..end).contains(wire) {
+                calls.push((end - first, name.to_string()));
+            }
+        }
+    }
+
+    Ok(calls
+        .into_iter()
+        .map(|mut calls| {
+            // Widest first; a call containing nothing but one nested call is as wide, but later
+            calls.reverse();
+            calls.sort_by_key(|&(width, _)| std::cmp::Reverse(width));
+            calls.into_iter().map(|(_, name)| name).collect()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_wires_resolve_to_enclosing_calls() {
+        let path = std::env::temp_dir().join("g16check_components_test.components");
+        // outer allocates 4..10; inner 6..9 and its own nested call 6..9 as well
+        fs::write(&path, "6 9 nested\n6 9 inner\n4 10 outer\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        let calls = locate(path, &[2, 5, 7, 9, 10]).unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(
+            calls,
+            [
+                vec![],
+                vec!["outer"],
+                vec!["outer", "inner", "nested"],
+                vec!["outer"],
+                vec![],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_malformed_line_is_rejected() {
+        let path = std::env::temp_dir().join("g16check_components_malformed_test.components");
+        fs::write(&path, "4 10 outer\n4 x inner\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        let error = locate(path, &[5]).unwrap_err();
+        fs::remove_file(path).unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
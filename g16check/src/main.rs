@@ -1,21 +1,154 @@
 use ahash::{HashMap, HashMapExt, HashSet};
-use ckt_fmtv5_types::v5::a::reader::CircuitReaderV5a;
+use ckt_fmtv5_types::{GateType, v5::a::reader::CircuitReaderV5a};
 use cynosure::hints::unlikely;
 use fixedbitset::FixedBitSet;
+use g16io::{eval::simulate_ckt, input_bits::read_input_bits};
 use indicatif::ProgressBar;
+use prefetch::BlockPrefetcher;
 
-#[monoio::main]
-async fn main() {
-    let mut reader = CircuitReaderV5a::open("/home/user/g16.ckt").unwrap();
-    let mut available_wires = FixedBitSet::with_capacity(2usize.pow(34));
-    for i in 0..reader.header().primary_inputs + 2 {
+mod components;
+mod prefetch;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Args {
+    path: String,
+    /// Initial capacity of the wire bitset, derived from the header when not given
+    max_wires: Option<usize>,
+    max_problems: usize,
+    /// Input bits to evaluate the circuit on after checking it
+    inputs: Option<String>,
+    /// Sidecar naming the components that allocated each wire, to report problems with
+    components: Option<String>,
+}
+
+const DEFAULT_MAX_PROBLEMS: usize = 20;
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut path = None;
+    let mut max_wires = None;
+    let mut max_problems = DEFAULT_MAX_PROBLEMS;
+    let mut inputs = None;
+    let mut components = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--max-wires" => {
+                let value = args.next().ok_or("--max-wires needs a value")?;
+                let value = value
+                    .parse()
+                    .map_err(|e| format!("invalid --max-wires {value:?}: {e}"))?;
+                max_wires = Some(value);
+            }
+            "--max-problems" => {
+                let value = args.next().ok_or("--max-problems needs a value")?;
+                max_problems = value
+                    .parse()
+                    .map_err(|e| format!("invalid --max-problems {value:?}: {e}"))?;
+            }
+            "--inputs" => inputs = Some(args.next().ok_or("--inputs needs a path")?),
+            "--components" => components = Some(args.next().ok_or("--components needs a path")?),
+            _ if path.is_none() => path = Some(arg),
+            _ => return Err(format!("unexpected argument {arg:?}")),
+        }
+    }
+
+    Ok(Args {
+        path: path.ok_or("missing circuit path")?,
+        max_wires,
+        max_problems,
+        inputs,
+        components,
+    })
+}
+
+/// Circuit statistics gathered while checking.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Stats {
+    and_gates: u64,
+    xor_gates: u64,
+    total_gates: u64,
+    /// Most intermediate wires holding unspent credits at once, constants and inputs excluded
+    max_live_wires: usize,
+    output_wires: usize,
+}
+
+#[derive(Debug, Default)]
+struct Report {
+    stats: Stats,
+    /// First problems found, up to the requested maximum
+    problems: Vec<String>,
+    /// Wire each of `problems` is about: the one the failing gate writes, or the dangling output
+    problem_wires: Vec<u64>,
+    problem_count: u64,
+}
+
+impl Report {
+    fn problem(&mut self, max_problems: usize, wire: u64, problem: impl FnOnce() -> String) {
+        if self.problems.len() < max_problems {
+            self.problems.push(problem());
+            self.problem_wires.push(wire);
+        }
+        self.problem_count += 1;
+    }
+
+    /// Name the components each problem comes from, given the component sidecar at `path`
+    fn locate_problems(&mut self, path: &str) -> std::io::Result<()> {
+        let calls = components::locate(path, &self.problem_wires)?;
+        for (problem, calls) in self.problems.iter_mut().zip(calls) {
+            if !calls.is_empty() {
+                *problem = format!("{problem} in {}", calls.join(" > "));
+            }
+        }
+        Ok(())
+    }
+
+    fn print(&self) {
+        let stats = &self.stats;
+        println!("AND gates:        {}", stats.and_gates);
+        println!("XOR gates:        {}", stats.xor_gates);
+        println!("Total gates:      {}", stats.total_gates);
+        println!("Max live wires:   {}", stats.max_live_wires);
+        println!("Output wires:     {}", stats.output_wires);
+
+        if self.problem_count == 0 {
+            println!("No problems found");
+            return;
+        }
+        println!("{} problems found:", self.problem_count);
+        for problem in &self.problems {
+            println!("    {problem}");
+        }
+        if self.problem_count > self.problems.len() as u64 {
+            println!(
+                "    ... and {} more",
+                self.problem_count - self.problems.len() as u64
+            );
+        }
+    }
+}
+
+/// Check that every gate in the circuit at `path` only reads wires that are available, with
+/// credits left, at that point, and that every output wire is defined, keeping the first
+/// `max_problems` violations.
+async fn check(path: &str, max_wires: Option<usize>, max_problems: usize) -> Report {
+    let reader = CircuitReaderV5a::open(path).unwrap();
+    // FALSE, TRUE and the primary inputs can be read any number of times. The credits pass never
+    // counts their uses (see `FanoutCounter::always_available`), so they carry no credits here.
+    let always_available = reader.header().primary_inputs + 2;
+
+    // Every gate writes one wire, so this only grows if a gate skips over IDs
+    let capacity = max_wires.unwrap_or((always_available + reader.header().total_gates()) as usize);
+    let mut available_wires = FixedBitSet::with_capacity(capacity);
+    for i in 0..always_available {
         available_wires.insert(i as usize);
     }
     let pb = ProgressBar::new(reader.header().total_gates());
     let mut wire_map = HashMap::new();
     let mut cur = 0;
-    let always_available = reader.header().primary_inputs + 2;
     let outputs = reader.outputs().iter().copied().collect::<HashSet<_>>();
+    let mut report = Report::default();
+    report.stats.output_wires = reader.outputs().len();
 
     let lookup_wire = |map: &mut HashMap<u64, u32>, wire: u64| -> bool {
         if wire < always_available {
@@ -33,32 +166,306 @@ async fn main() {
         }
         true
     };
-    while let Some(block) = reader.next_block_soa().await.unwrap() {
-        for i in 0..block.gates_in_block {
+    // Reading ahead on another thread keeps I/O off the checking loop
+    for block in BlockPrefetcher::spawn(path, prefetch::DEFAULT_DEPTH) {
+        for i in 0..block.len() {
             if unlikely(outputs.contains(&block.out[i])) {
                 println!(
                     "{:?} gate {} {} -> {} with {} creds",
                     block.gate_types[i], block.in1[i], block.in2[i], block.out[i], block.credits[i]
                 );
             }
+            match block.gate_types[i] {
+                GateType::AND => report.stats.and_gates += 1,
+                GateType::XOR => report.stats.xor_gates += 1,
+            }
+
             let in1_available = lookup_wire(&mut wire_map, block.in1[i]);
             let in2_available = lookup_wire(&mut wire_map, block.in2[i]);
             if unlikely(!in1_available) {
-                panic!(
-                    "Wire {cur} not possible: {} (NA) {} -> {}",
-                    block.in1[i], block.in2[i], block.out[i]
-                );
-            } else if unlikely(!in2_available) {
-                panic!(
-                    "Wire {cur} not possible: {} {} (NA) -> {}",
-                    block.in1[i], block.in2[i], block.out[i]
-                );
+                report.problem(max_problems, block.out[i], || {
+                    format!(
+                        "Wire {cur} not possible: {} (NA) {} -> {}",
+                        block.in1[i], block.in2[i], block.out[i]
+                    )
+                });
+            }
+            if unlikely(!in2_available) {
+                report.problem(max_problems, block.out[i], || {
+                    format!(
+                        "Wire {cur} not possible: {} {} (NA) -> {}",
+                        block.in1[i], block.in2[i], block.out[i]
+                    )
+                });
+            }
+            let out = block.out[i] as usize;
+            if unlikely(out >= available_wires.len()) {
+                available_wires.grow(out + 1);
+            }
+            available_wires.insert(out);
+            if block.credits[i] > 0 {
+                wire_map.insert(block.out[i], block.credits[i]);
             }
-            available_wires.insert(block.out[i] as usize);
-            wire_map.insert(block.out[i], block.credits[i]);
+            report.stats.max_live_wires = report.stats.max_live_wires.max(wire_map.len());
             cur += 1;
         }
-        pb.inc(block.gates_in_block as u64);
+        pb.inc(block.len() as u64);
     }
     pb.finish();
+
+    // Outputs must be constants, primary inputs, or written by some gate
+    let mut dangling = outputs
+        .into_iter()
+        .filter(|&wire| !available_wires.contains(wire as usize))
+        .collect::<Vec<_>>();
+    dangling.sort_unstable();
+    for wire in dangling {
+        report.problem(max_problems, wire, || {
+            format!("Output wire {wire} is never produced by any gate")
+        });
+    }
+
+    report.stats.total_gates = cur;
+    report
+}
+
+fn print_usage() {
+    eprintln!(
+        "Usage: g16check <circuit.ckt> [--max-wires <n>] [--max-problems <n>] [--inputs <inputs.txt|inputs.bin>]"
+    );
+    eprintln!("                [--components <circuit.ckt.components>]");
+}
+
+#[monoio::main]
+async fn main() {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            print_usage();
+            std::process::exit(2);
+        }
+    };
+
+    let mut report = check(&args.path, args.max_wires, args.max_problems).await;
+    if let Some(components) = &args.components
+        && let Err(e) = report.locate_problems(components)
+    {
+        eprintln!("Error reading {components}: {e}");
+    }
+    report.print();
+    if report.problem_count > 0 {
+        std::process::exit(1);
+    }
+
+    if let Some(inputs) = args.inputs {
+        let input_bits = match read_input_bits(&inputs) {
+            Ok(input_bits) => input_bits,
+            Err(e) => {
+                eprintln!("Error reading input bits: {e}");
+                std::process::exit(2);
+            }
+        };
+        let outputs = simulate_ckt(&args.path, &input_bits).await;
+        println!("Output values:    {outputs:?}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ckt_fmtv5_types::{
+        GateType,
+        v5::a::{GateV5a, writer::CircuitWriterV5a},
+    };
+    use monoio::{FusionDriver, RuntimeBuilder};
+
+    use super::*;
+
+    pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+        RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    /// Write `gates` as a circuit with `primary_inputs` inputs to a temporary file.
+    pub(crate) async fn write_circuit(
+        name: &str,
+        primary_inputs: u64,
+        gates: &[GateV5a],
+        outputs: Vec<u64>,
+    ) -> String {
+        let path = std::env::temp_dir().join(name);
+        let mut writer = CircuitWriterV5a::new(path.clone(), primary_inputs, outputs)
+            .await
+            .unwrap();
+        for gate in gates {
+            writer.write_gate(*gate).await.unwrap();
+        }
+        writer.finalize().await.unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    pub(crate) fn gate(gate_type: GateType, in1: u64, in2: u64, out: u64, credits: u32) -> GateV5a {
+        GateV5a {
+            in1,
+            in2,
+            out,
+            credits,
+            gate_type,
+        }
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = |args: &[&str]| parse_args(args.iter().map(|arg| arg.to_string()));
+
+        assert_eq!(
+            args(&["g16.ckt"]),
+            Ok(Args {
+                path: "g16.ckt".to_string(),
+                max_wires: None,
+                max_problems: DEFAULT_MAX_PROBLEMS,
+                inputs: None,
+                components: None,
+            })
+        );
+        assert_eq!(
+            args(&["--max-wires", "1024", "small.ckt", "--max-problems", "3"]),
+            Ok(Args {
+                path: "small.ckt".to_string(),
+                max_wires: Some(1024),
+                max_problems: 3,
+                inputs: None,
+                components: None,
+            })
+        );
+        assert_eq!(
+            args(&["g16.ckt", "--inputs", "inputs.txt"]).unwrap().inputs,
+            Some("inputs.txt".to_string())
+        );
+        assert_eq!(
+            args(&["g16.ckt", "--components", "g16.ckt.components"])
+                .unwrap()
+                .components,
+            Some("g16.ckt.components".to_string())
+        );
+        assert!(args(&[]).is_err());
+        assert!(args(&["g16.ckt", "--max-wires"]).is_err());
+    }
+
+    #[test]
+    fn test_check_small_circuit() {
+        block_on(async {
+            // Wires 0/1 are the constants, 2..4 the primary inputs
+            let gates = [
+                gate(GateType::XOR, 2, 3, 4, 1),
+                gate(GateType::AND, 4, 1, 5, 0),
+            ];
+            let path = write_circuit("g16check_small.ckt", 2, &gates, vec![5]).await;
+
+            assert_eq!(check(&path, None, 10).await.problem_count, 0);
+            // Undersized capacity just grows
+            assert_eq!(check(&path, Some(1), 10).await.problem_count, 0);
+
+            std::fs::remove_file(path).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_stats_for_fixed_circuit() {
+        block_on(async {
+            let gates = [
+                gate(GateType::XOR, 2, 3, 5, 2),
+                gate(GateType::AND, 2, 4, 6, 1),
+                gate(GateType::AND, 5, 6, 7, 1),
+                gate(GateType::XOR, 5, 1, 8, 1),
+                gate(GateType::XOR, 7, 8, 9, 0),
+            ];
+            let path = write_circuit("g16check_stats.ckt", 3, &gates, vec![9]).await;
+
+            let report = check(&path, None, 10).await;
+            std::fs::remove_file(path).unwrap();
+
+            assert_eq!(
+                report.stats,
+                Stats {
+                    and_gates: 2,
+                    xor_gates: 3,
+                    total_gates: 5,
+                    max_live_wires: 2,
+                    output_wires: 1,
+                }
+            );
+            assert_eq!(report.problem_count, 0);
+        });
+    }
+
+    #[test]
+    fn test_problems_are_collected() {
+        block_on(async {
+            // Wire 4 only has one credit but is read three times
+            let gates = [
+                gate(GateType::XOR, 2, 3, 4, 1),
+                gate(GateType::AND, 4, 1, 5, 1),
+                gate(GateType::AND, 4, 5, 6, 1),
+                gate(GateType::XOR, 4, 6, 7, 0),
+            ];
+            let path = write_circuit("g16check_problems.ckt", 2, &gates, vec![7]).await;
+
+            let report = check(&path, None, 1).await;
+            std::fs::remove_file(path).unwrap();
+
+            assert_eq!(report.stats.total_gates, 4);
+            assert_eq!(report.problem_count, 2);
+            assert_eq!(report.problems.len(), 1);
+        });
+    }
+
+    #[test]
+    fn test_problems_name_their_components() {
+        block_on(async {
+            // Wire 4 only has one credit but is read twice, by the gate writing 6
+            let gates = [
+                gate(GateType::XOR, 2, 3, 4, 1),
+                gate(GateType::AND, 4, 1, 5, 1),
+                gate(GateType::AND, 4, 5, 6, 0),
+            ];
+            let path = write_circuit("g16check_located.ckt", 2, &gates, vec![6]).await;
+            let components = format!("{path}.components");
+            std::fs::write(&components, "5 7 inner\n4 7 outer\n").unwrap();
+
+            let mut report = check(&path, None, 10).await;
+            report.locate_problems(&components).unwrap();
+            std::fs::remove_file(path).unwrap();
+            std::fs::remove_file(components).unwrap();
+
+            assert_eq!(
+                report.problems,
+                ["Wire 2 not possible: 4 (NA) 5 -> 6 in outer > inner"]
+            );
+        });
+    }
+
+    #[test]
+    fn test_dangling_output_is_reported() {
+        block_on(async {
+            let gates = [
+                gate(GateType::XOR, 2, 3, 4, 1),
+                gate(GateType::AND, 4, 1, 5, 0),
+            ];
+            // Wire 3 is a primary input, wire 9 is never written
+            let path = write_circuit("g16check_dangling.ckt", 2, &gates, vec![5, 3, 9]).await;
+
+            let report = check(&path, None, 10).await;
+            std::fs::remove_file(path).unwrap();
+
+            assert_eq!(report.problem_count, 1);
+            assert_eq!(
+                report.problems,
+                ["Output wire 9 is never produced by any gate"]
+            );
+        });
+    }
 }
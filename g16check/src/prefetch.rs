@@ -0,0 +1,236 @@
+use std::{
+    num::NonZeroUsize,
+    panic,
+    sync::mpsc::{Receiver, sync_channel},
+    thread::{self, JoinHandle},
+};
+
+use ckt_fmtv5_types::{GateType, v5::a::reader::CircuitReaderV5a};
+use monoio::{FusionDriver, RuntimeBuilder};
+
+/// Blocks read but not yet taken by the consumer: one ready while the next is being read
+pub const DEFAULT_DEPTH: NonZeroUsize = NonZeroUsize::new(2).unwrap();
+
+/// One block of gates, copied out of the reader's buffer so it can cross threads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub gate_types: Vec<GateType>,
+    pub in1: Vec<u64>,
+    pub in2: Vec<u64>,
+    pub out: Vec<u64>,
+    pub credits: Vec<u32>,
+}
+
+impl Block {
+    /// Copy of one block's columns, each cut to the gates actually in the block
+    fn from_columns(
+        gate_types: &[GateType],
+        in1: &[u64],
+        in2: &[u64],
+        out: &[u64],
+        credits: &[u32],
+    ) -> Self {
+        Self {
+            gate_types: gate_types.to_vec(),
+            in1: in1.to_vec(),
+            in2: in2.to_vec(),
+            out: out.to_vec(),
+            credits: credits.to_vec(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.out.len()
+    }
+}
+
+/// Gate blocks of a `.ckt` file, read on a separate thread that stays up to `depth` blocks ahead
+/// of the consumer, so I/O overlaps with whatever is done with the previous block.
+///
+/// The header and outputs aren't part of the stream; open a reader on the file for those.
+#[derive(Debug)]
+pub struct BlockPrefetcher {
+    blocks: Receiver<Block>,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl BlockPrefetcher {
+    pub fn spawn(path: &str, depth: NonZeroUsize) -> Self {
+        let path = path.to_string();
+        // A block blocked in `send` is already ahead of the consumer, so the channel buffers one
+        // fewer
+        let (sender, blocks) = sync_channel(depth.get() - 1);
+
+        let reader = thread::spawn(move || {
+            RuntimeBuilder::<FusionDriver>::new()
+                .enable_all()
+                .build()
+                .unwrap()
+                .block_on(async move {
+                    let mut reader = CircuitReaderV5a::open(&path).unwrap();
+                    while let Some(block) = reader.next_block_soa().await.unwrap() {
+                        let n = block.gates_in_block;
+                        let copy = Block::from_columns(
+                            &block.gate_types[..n],
+                            &block.in1[..n],
+                            &block.in2[..n],
+                            &block.out[..n],
+                            &block.credits[..n],
+                        );
+                        // The consumer stopped early
+                        if sender.send(copy).is_err() {
+                            break;
+                        }
+                    }
+                });
+        });
+
+        Self {
+            blocks,
+            reader: Some(reader),
+        }
+    }
+}
+
+impl Iterator for BlockPrefetcher {
+    type Item = Block;
+
+    /// Next block in file order; a panic on the reader thread is re-raised here.
+    fn next(&mut self) -> Option<Block> {
+        if let Ok(block) = self.blocks.recv() {
+            return Some(block);
+        }
+
+        if let Some(reader) = self.reader.take()
+            && let Err(panic) = reader.join()
+        {
+            panic::resume_unwind(panic);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use ckt_fmtv5_types::v5::a::GateV5a;
+
+    use super::*;
+    use crate::tests::{block_on, gate, write_circuit};
+
+    /// Blocks read serially, the way the checker used to
+    async fn read_serial(path: &str) -> Vec<Block> {
+        let mut reader = CircuitReaderV5a::open(path).unwrap();
+        let mut blocks = Vec::new();
+        while let Some(block) = reader.next_block_soa().await.unwrap() {
+            let n = block.gates_in_block;
+            blocks.push(Block::from_columns(
+                &block.gate_types[..n],
+                &block.in1[..n],
+                &block.in2[..n],
+                &block.out[..n],
+                &block.credits[..n],
+            ));
+        }
+        blocks
+    }
+
+    /// A chain of `count` gates, each reading the previous output and a primary input
+    fn chain(count: u64) -> Vec<GateV5a> {
+        (0..count)
+            .map(|i| {
+                let gate_type = if i % 3 == 0 {
+                    GateType::AND
+                } else {
+                    GateType::XOR
+                };
+                let credits = if i + 1 == count { 0 } else { 1 };
+                gate(gate_type, 4 + i, 2 + i % 2, 5 + i, credits)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_prefetched_blocks_match_serial_read() {
+        block_on(async {
+            let count = 1 << 20;
+            let gates = chain(count);
+            let path = write_circuit("g16check_prefetch.ckt", 3, &gates, vec![4 + count]).await;
+
+            let serial = read_serial(&path).await;
+            assert_eq!(serial.iter().map(Block::len).sum::<usize>() as u64, count);
+            for depth in [1, 2, 8] {
+                let depth = NonZeroUsize::new(depth).unwrap();
+                let prefetched = BlockPrefetcher::spawn(&path, depth).collect::<Vec<_>>();
+                assert_eq!(prefetched, serial);
+            }
+
+            // Dropping the consumer early must not leave the reader thread stuck
+            let mut prefetcher = BlockPrefetcher::spawn(&path, NonZeroUsize::MIN);
+            assert_eq!(prefetcher.next().as_ref(), serial.first());
+            drop(prefetcher);
+
+            let report = crate::check(&path, None, 10).await;
+            assert_eq!(report.problem_count, 0);
+            assert_eq!(report.stats.total_gates, count);
+
+            std::fs::remove_file(path).unwrap();
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_reader_panic_is_propagated() {
+        BlockPrefetcher::spawn("/nonexistent/g16check.ckt", DEFAULT_DEPTH).for_each(drop);
+    }
+
+    /// Stand-in for the checker's per-gate work, heavy enough to take about as long as the read
+    fn gate_work(in1: u64, out: u64) -> u64 {
+        (0..16).fold(in1 ^ out, |h, _| {
+            h.rotate_left(5).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+        })
+    }
+
+    /// Wall time of the same per-gate work over a large fixture, fed by a serial read and by the
+    /// prefetcher, which must come out ahead once reading overlaps the work. Timing-dependent, so
+    /// run by hand with `cargo test --release -p g16check -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_prefetch_throughput() {
+        block_on(async {
+            let count = 20_000_000;
+            let gates = chain(count);
+            let path =
+                write_circuit("g16check_prefetch_bench.ckt", 3, &gates, vec![4 + count]).await;
+
+            let start = Instant::now();
+            let mut serial_sum = 0u64;
+            let mut reader = CircuitReaderV5a::open(&path).unwrap();
+            while let Some(block) = reader.next_block_soa().await.unwrap() {
+                for i in 0..block.gates_in_block {
+                    serial_sum = serial_sum.wrapping_add(gate_work(block.in1[i], block.out[i]));
+                }
+            }
+            let serial = start.elapsed();
+
+            let start = Instant::now();
+            let mut prefetched_sum = 0u64;
+            for block in BlockPrefetcher::spawn(&path, DEFAULT_DEPTH) {
+                for i in 0..block.len() {
+                    prefetched_sum =
+                        prefetched_sum.wrapping_add(gate_work(block.in1[i], block.out[i]));
+                }
+            }
+            let prefetched = start.elapsed();
+
+            std::fs::remove_file(path).unwrap();
+            assert_eq!(prefetched_sum, serial_sum);
+            println!("{count} gates: serial {serial:?}, prefetched {prefetched:?}");
+            assert!(
+                prefetched < serial,
+                "prefetching took {prefetched:?}, no faster than the serial {serial:?}"
+            );
+        });
+    }
+}
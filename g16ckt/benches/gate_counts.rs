@@ -0,0 +1,169 @@
+// Gate counts of the key gadgets, as a table to diff across versions.
+// Run with `cargo bench -p g16ckt --bench gate_counts`; every input comes from a fixed seed, so
+// the output only changes when a gadget does.
+
+use g16ckt::{
+    GateCount, Groth16VerifyInput, WireId,
+    ark::{self, AffineRepr, CircuitSpecificSetupSNARK, SNARK, UniformRand},
+    circuit::{CircuitBuilder, CircuitInput, EncodeInput, FromWires, WiresObject, modes::Execute},
+    gadgets::{
+        bigint::BigIntWires,
+        bn254::{fq::Fq, fq2::Fq2, fr::Fr, g2::G2Projective},
+        field::FpImpl,
+    },
+    groth16_verify,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+/// Constraints of the proven circuit, as in the gate-count example
+const K: usize = 6;
+
+/// Window size of the constant-base scalar multiplication
+const W: usize = 10;
+
+#[derive(Copy, Clone)]
+struct DummyCircuit<F: ark::PrimeField> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<F: ark::PrimeField> ark::ConstraintSynthesizer<F> for DummyCircuit<F> {
+    fn generate_constraints(
+        self,
+        cs: ark::ConstraintSystemRef<F>,
+    ) -> Result<(), ark::SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(ark::SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(ark::SynthesisError::AssignmentMissing))?;
+        let c = cs.new_input_variable(|| {
+            let a = self.a.ok_or(ark::SynthesisError::AssignmentMissing)?;
+            let b = self.b.ok_or(ark::SynthesisError::AssignmentMissing)?;
+            Ok(a * b)
+        })?;
+
+        for _ in 0..(1 << K) - 1 {
+            cs.enforce_constraint(ark::lc!() + a, ark::lc!() + b, ark::lc!() + c)?;
+        }
+        cs.enforce_constraint(ark::lc!(), ark::lc!(), ark::lc!())?;
+        Ok(())
+    }
+}
+
+/// Gates of `f` run on `inputs`
+fn count<I, O>(inputs: I, f: impl Fn(&mut Execute, &I::WireRepr) -> O) -> GateCount
+where
+    I: CircuitInput + EncodeInput<g16ckt::circuit::modes::ExecuteMode>,
+    O: WiresObject,
+{
+    CircuitBuilder::streaming_execute::<_, _, Vec<bool>>(inputs, 160_000, |ctx, wires| {
+        f(ctx, wires).to_wires_vec()
+    })
+    .gate_count
+}
+
+/// Bits of field elements in Montgomery form, in wire order
+fn fq_bits<const N: usize>(values: impl IntoIterator<Item = ark::Fq>) -> [bool; N] {
+    values
+        .into_iter()
+        .flat_map(|value| Fq::to_bits(Fq::as_montgomery(value)))
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+}
+
+fn g2_fq_values(p: ark::G2Projective) -> [ark::Fq; 6] {
+    [p.x.c0, p.x.c1, p.y.c0, p.y.c1, p.z.c0, p.z.c1]
+}
+
+fn fq(wires: &[WireId]) -> Fq {
+    Fq(BigIntWires::from_bits(wires.iter().copied()))
+}
+
+fn main() {
+    let mut rng = ChaCha20Rng::seed_from_u64(12345);
+    let mut rows: Vec<(&str, GateCount)> = Vec::new();
+
+    let (a, b) = (ark::Fq::rand(&mut rng), ark::Fq::rand(&mut rng));
+    rows.push((
+        "Fq::mul_montgomery",
+        count(fq_bits::<{ 2 * Fq::N_BITS }>([a, b]), |ctx, wires| {
+            let (a, b) = wires.split_at(Fq::N_BITS);
+            Fq::mul_montgomery(ctx, &fq(a), &fq(b))
+        }),
+    ));
+
+    let (a, b) = (ark::Fq2::rand(&mut rng), ark::Fq2::rand(&mut rng));
+    rows.push((
+        "Fq2::mul_montgomery",
+        count(
+            fq_bits::<{ 2 * Fq2::N_BITS }>([a.c0, a.c1, b.c0, b.c1]),
+            |ctx, wires| {
+                let (a, b) = wires.split_at(Fq2::N_BITS);
+                let a = Fq2::from_wires(a).unwrap();
+                let b = Fq2::from_wires(b).unwrap();
+                Fq2::mul_montgomery(ctx, &a, &b)
+            },
+        ),
+    ));
+
+    let (p, q) = (
+        ark::G2Projective::rand(&mut rng),
+        ark::G2Projective::rand(&mut rng),
+    );
+    rows.push((
+        "G2Projective::add_montgomery",
+        count(
+            fq_bits::<{ 2 * G2Projective::N_BITS }>(
+                g2_fq_values(p).into_iter().chain(g2_fq_values(q)),
+            ),
+            |ctx, wires| {
+                let (p, q) = wires.split_at(G2Projective::N_BITS);
+                let p = G2Projective::from_wires(p).unwrap();
+                let q = G2Projective::from_wires(q).unwrap();
+                G2Projective::add_montgomery(ctx, &p, &q)
+            },
+        ),
+    ));
+
+    let (s, base) = (ark::Fr::rand(&mut rng), ark::G2Projective::rand(&mut rng));
+    let s_bits: [bool; Fr::N_BITS] = Fr::to_bits(s).try_into().unwrap();
+    rows.push((
+        "G2Projective::scalar_mul_by_constant_base_montgomery",
+        count(s_bits, |ctx, wires| {
+            let s = Fr(BigIntWires::from_bits(wires.iter().copied()));
+            G2Projective::scalar_mul_by_constant_base_montgomery::<_, W>(ctx, &s, &base)
+        }),
+    ));
+
+    let circuit = DummyCircuit::<ark::Fr> {
+        a: Some(ark::Fr::rand(&mut rng)),
+        b: Some(ark::Fr::rand(&mut rng)),
+    };
+    let (pk, vk) = ark::Groth16::<ark::Bn254>::setup(circuit, &mut rng).unwrap();
+    let proof = ark::Groth16::<ark::Bn254>::prove(&pk, circuit, &mut rng).unwrap();
+    let verify = Groth16VerifyInput {
+        public: vec![circuit.a.unwrap() * circuit.b.unwrap()],
+        a: proof.a.into_group(),
+        b: proof.b.into_group(),
+        c: proof.c.into_group(),
+        vk,
+    };
+    rows.push((
+        "groth16_verify",
+        count(verify, |ctx, input| groth16_verify(ctx, input).ok),
+    ));
+
+    let width = rows.iter().map(|(name, _)| name.len()).max().unwrap();
+    println!(
+        "{:<width$} {:>14} {:>14} {:>14}",
+        "gadget", "nonfree (AND)", "free (XOR)", "total"
+    );
+    for (name, gate_count) in rows {
+        let total = gate_count.total_gate_count();
+        let nonfree = gate_count.nonfree_gate_count();
+        println!(
+            "{name:<width$} {nonfree:>14} {:>14} {total:>14}",
+            total - nonfree
+        );
+    }
+}
@@ -105,16 +105,17 @@ fn main() {
 
     let (verified, gate_count) = if is_compressed {
         // Compressed path includes decompression gadgets; allocate more gates
-        let result: StreamingResult<_, _, bool> = CircuitBuilder::streaming_execute(
-            verify.compress(),
-            160_000,
-            groth16_verify_compressed,
-        );
+        let result: StreamingResult<_, _, bool> =
+            CircuitBuilder::streaming_execute(verify.compress().unwrap(), 160_000, |ctx, input| {
+                groth16_verify_compressed(ctx, input).ok
+            });
 
         (result.output_value, result.gate_count)
     } else {
         let result: StreamingResult<_, _, bool> =
-            CircuitBuilder::streaming_execute(verify, 160_000, groth16_verify);
+            CircuitBuilder::streaming_execute(verify, 160_000, |ctx, input| {
+                groth16_verify(ctx, input).ok
+            });
 
         (result.output_value, result.gate_count)
     };
@@ -15,11 +15,30 @@ pub trait CircuitContext: Sized {
     /// Allocates a new wire and returns its identifier
     fn issue_wire(&mut self) -> WireId;
 
+    /// Allocates `n` wires at once, in the order [`CircuitContext::issue_wire`] would issue them
+    fn issue_wires(&mut self, n: usize) -> Vec<WireId> {
+        (0..n).map(|_| self.issue_wire()).collect()
+    }
+
     /// Adds a gate to the current component
     fn add_gate(&mut self, gate: Gate);
 
+    /// Constrains `wire` to be true: it is ANDed into the validity accumulator returned by
+    /// [`CircuitContext::validity_output`], so a gadget can reject malformed inputs without
+    /// returning an extra boolean.
+    fn assert_true(&mut self, wire: WireId);
+
+    /// Conjunction of every wire passed to [`CircuitContext::assert_true`] so far, `TRUE_WIRE` if
+    /// there were none.
+    ///
+    /// The returned wire can be read once, so the accumulator restarts from `TRUE_WIRE`.
+    fn validity_output(&mut self) -> WireId;
+
+    /// Runs `f` on `inputs` as a call of the component `name`, whose calls share the template
+    /// cached under `key`.
     fn with_named_child<I: WiresObject, O: FromWires>(
         &mut self,
+        name: &str,
         key: ComponentKey,
         inputs: I,
         f: impl Fn(&mut Self, &I) -> O,
@@ -43,6 +62,6 @@ pub trait CircuitContext: Sized {
             arity,
             input_wires.len(),
         );
-        self.with_named_child(key, inputs, f, arity)
+        self.with_named_child("test_child", key, inputs, f, arity)
     }
 }
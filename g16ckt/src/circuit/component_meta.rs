@@ -92,7 +92,9 @@ impl ComponentMetaBuilder {
                     .get_mut(idx)
                     .expect("internal wire out of bounds");
 
-                *slot += credit.get();
+                *slot = slot
+                    .checked_add(credit.get())
+                    .unwrap_or_else(|| panic!("credits of wire {id:?} overflow {}", Credits::MAX));
             }
             id => {
                 // Wire ID from outside our isolated mock range - ignore
@@ -204,7 +206,13 @@ impl ComponentMetaTemplate {
                     }
                 }
                 OutputWireType::Internal(internal_index) => {
-                    credits_stack[*internal_index] += credits;
+                    let slot = &mut credits_stack[*internal_index];
+                    *slot = slot.checked_add(*credits).unwrap_or_else(|| {
+                        panic!(
+                            "credits of internal wire {internal_index} overflow {}",
+                            Credits::MAX
+                        )
+                    });
                     debug!(
                         "Output wire {output_wire_type:?} is internal at index {internal_index} add credits: {credits}, total is {}",
                         credits_stack[*internal_index]
@@ -266,6 +274,15 @@ impl CircuitContext for ComponentMetaBuilder {
         next
     }
 
+    /// One contiguous range of IDs and a single resize of the credits stack
+    fn issue_wires(&mut self, n: usize) -> Vec<WireId> {
+        let start = self.cursor.0;
+        self.cursor.0 += n;
+        self.credits_stack.resize(self.credits_stack.len() + n, 0);
+
+        (start..self.cursor.0).map(WireId).collect()
+    }
+
     #[inline(always)]
     fn add_gate(&mut self, gate: Gate) {
         trace!(
@@ -281,8 +298,19 @@ impl CircuitContext for ComponentMetaBuilder {
         self.bump_credit_for_wire(gate.wire_b, NonZero::<Credits>::MIN);
     }
 
+    /// The accumulator reads `wire` once; the accumulator wires themselves only exist in the
+    /// execution pass and never enter a template.
+    fn assert_true(&mut self, wire: WireId) {
+        self.bump_credit_for_wire(wire, NonZero::<Credits>::MIN);
+    }
+
+    fn validity_output(&mut self) -> WireId {
+        TRUE_WIRE
+    }
+
     fn with_named_child<I: WiresObject, O: FromWires>(
         &mut self,
+        _name: &str,
         _k: ComponentKey,
         inputs: I,
         _f: impl Fn(&mut Self, &I) -> O,
@@ -330,3 +358,30 @@ impl CircuitMode for Empty {
 
     fn add_credits(&mut self, _wires: &[WireId], _credits: NonZero<Credits>) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "credits of wire WireId(3) overflow 65535")]
+    fn test_credit_overflow_names_the_wire() {
+        let (_, mut meta) = ComponentMetaBuilder::new_with_input(&[false]);
+        let wire = meta.issue_wire();
+
+        meta.add_credits(&[wire], NonZero::<Credits>::MAX);
+        meta.add_credits(&[wire], NonZero::<Credits>::MIN);
+    }
+
+    #[test]
+    fn test_issue_wires_is_contiguous() {
+        let (_, mut meta) = ComponentMetaBuilder::new_with_input(&[false]);
+        let first = meta.issue_wire();
+
+        let wires = meta.issue_wires(254);
+        let expected = (1..=254).map(|i| WireId(first.0 + i)).collect::<Vec<_>>();
+        assert_eq!(wires, expected);
+        assert_eq!(meta.issue_wire(), WireId(first.0 + 255));
+        assert_eq!(meta.credits_stack.len(), 1 + 1 + 254 + 1);
+    }
+}
@@ -4,7 +4,8 @@ use crate::{
     WireId,
     gadgets::{
         bigint::BigIntWires,
-        bn254::{Fp254Impl, Fq, Fq12, G1Projective, G2Projective, fq2::Fq2, fq6::Fq6, fr::Fr},
+        bn254::{Fq, Fq12, G1Projective, G2Projective, fq2::Fq2, fq6::Fq6, fr::Fr},
+        field::FpImpl,
     },
 };
 
@@ -13,6 +14,14 @@ impl<const N: usize> WiresObject for [WireId; N] {
         self.to_vec()
     }
 
+    fn for_each_wire(&self, f: impl FnMut(WireId)) {
+        self.iter().copied().for_each(f)
+    }
+
+    fn wire_count(&self) -> usize {
+        N
+    }
+
     fn clone_from(&self, wire_gen: &mut impl FnMut() -> WireId) -> Self {
         std::array::from_fn(|_| wire_gen())
     }
@@ -41,6 +50,14 @@ macro_rules! impl_wires_object_for_tuples {
                     wires
                 }
 
+                fn for_each_wire(&self, mut f: impl FnMut(WireId)) {
+                    $(self.$idx.for_each_wire(&mut f);)*
+                }
+
+                fn wire_count(&self) -> usize {
+                    0 $(+ self.$idx.wire_count())*
+                }
+
                 fn clone_from(&self, wire_gen: &mut impl FnMut() -> WireId) -> Self {
                     ($(self.$idx.clone_from(wire_gen),)*)
                 }
@@ -52,7 +69,7 @@ macro_rules! impl_wires_object_for_tuples {
                     let mut offset = 0;
                     $(
                         let $T = $T::from_wires(&wires[offset..])?;
-                        offset += $T.to_wires_vec().len();
+                        offset += $T.wire_count();
                     )*
                     Some(($($T,)*))
                 }
@@ -159,11 +176,97 @@ impl WiresArity for G2Projective {
 pub trait WiresObject: Sized {
     fn to_wires_vec(&self) -> Vec<WireId>;
 
+    /// Visit the wires of [`WiresObject::to_wires_vec`] in order, ideally without building it
+    fn for_each_wire(&self, f: impl FnMut(WireId)) {
+        self.to_wires_vec().into_iter().for_each(f)
+    }
+
+    /// Number of wires in [`WiresObject::to_wires_vec`], ideally without building it
+    fn wire_count(&self) -> usize {
+        self.to_wires_vec().len()
+    }
+
     fn clone_from(&self, wire_gen: &mut impl FnMut() -> WireId) -> Self;
 }
 
 pub trait FromWires: WiresObject {
     fn from_wires(wires: &[WireId]) -> Option<Self>;
+
+    /// Like [`FromWires::from_wires`], but reports why the wires don't fit.
+    fn try_from_wires(wires: &[WireId]) -> Result<Self, FromWiresError> {
+        Self::from_wires(wires).ok_or(FromWiresError::Invalid {
+            ty: std::any::type_name::<Self>(),
+            actual: wires.len(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FromWiresError {
+    #[error("{ty}: expected {expected} wires, got {actual}")]
+    Length {
+        ty: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("{ty}: can't be built from {actual} wires")]
+    Invalid { ty: &'static str, actual: usize },
+    #[error("{ty}.{field}: {source}")]
+    Field {
+        ty: &'static str,
+        field: &'static str,
+        source: Box<FromWiresError>,
+    },
+}
+
+/// Splits a wire slice into consecutive fields of a [`FromWires`] type, tagging failures with the
+/// field they occurred in.
+pub struct WireFields<'a> {
+    ty: &'static str,
+    wires: &'a [WireId],
+    total: usize,
+    expected: usize,
+}
+
+impl<'a> WireFields<'a> {
+    pub fn new(ty: &'static str, wires: &'a [WireId]) -> Self {
+        Self {
+            ty,
+            wires,
+            total: wires.len(),
+            expected: 0,
+        }
+    }
+
+    /// Take the next `len` wires (or whatever is left) as `field`.
+    pub fn field<T: FromWires>(
+        &mut self,
+        field: &'static str,
+        len: usize,
+    ) -> Result<T, FromWiresError> {
+        let (head, tail) = self.wires.split_at(len.min(self.wires.len()));
+        self.wires = tail;
+        self.expected += len;
+
+        T::try_from_wires(head).map_err(|source| FromWiresError::Field {
+            ty: self.ty,
+            field,
+            source: Box::new(source),
+        })
+    }
+
+    /// Fail if wires are left over after the last field.
+    pub fn finish(self) -> Result<(), FromWiresError> {
+        if self.wires.is_empty() {
+            Ok(())
+        } else {
+            Err(FromWiresError::Length {
+                ty: self.ty,
+                expected: self.expected,
+                actual: self.total,
+            })
+        }
+    }
 }
 
 impl WiresObject for WireId {
@@ -171,6 +274,14 @@ impl WiresObject for WireId {
         vec![*self]
     }
 
+    fn for_each_wire(&self, mut f: impl FnMut(WireId)) {
+        f(*self)
+    }
+
+    fn wire_count(&self) -> usize {
+        1
+    }
+
     fn clone_from(&self, wire_gen: &mut impl FnMut() -> WireId) -> Self {
         wire_gen()
     }
@@ -187,6 +298,14 @@ impl WiresObject for BigIntWires {
         self.iter().copied().collect()
     }
 
+    fn for_each_wire(&self, f: impl FnMut(WireId)) {
+        self.iter().copied().for_each(f)
+    }
+
+    fn wire_count(&self) -> usize {
+        self.len()
+    }
+
     fn clone_from(&self, wire_gen: &mut impl FnMut() -> WireId) -> Self {
         BigIntWires::from_bits((0..self.len()).map(|_| wire_gen()))
     }
@@ -203,6 +322,14 @@ impl WiresObject for Vec<WireId> {
         self.clone()
     }
 
+    fn for_each_wire(&self, f: impl FnMut(WireId)) {
+        self.iter().copied().for_each(f)
+    }
+
+    fn wire_count(&self) -> usize {
+        self.len()
+    }
+
     fn clone_from(&self, wire_gen: &mut impl FnMut() -> WireId) -> Self {
         (0..self.len()).map(|_| wire_gen()).collect()
     }
@@ -300,6 +427,10 @@ impl WiresObject for Vec<Fr> {
         self.iter().flat_map(|t| t.to_wires_vec()).collect()
     }
 
+    fn wire_count(&self) -> usize {
+        self.iter().map(Fr::wire_count).sum()
+    }
+
     fn clone_from(&self, wire_gen: &mut impl FnMut() -> WireId) -> Self {
         self.iter().map(move |fr| fr.clone_from(wire_gen)).collect()
     }
@@ -512,4 +643,40 @@ mod tests {
             Some((WireId(1), WireId(2)))
         );
     }
+
+    fn visited<T: WiresObject>(obj: &T) -> Vec<WireId> {
+        let mut wires = Vec::new();
+        obj.for_each_wire(|wire| wires.push(wire));
+        wires
+    }
+
+    #[test]
+    fn test_for_each_wire_matches_to_wires_vec() {
+        let mut next = 0;
+        let mut issue = || {
+            next += 1;
+            WireId(next)
+        };
+
+        let fr = Fr::new(&mut issue);
+        let fq6 = Fq6::new(&mut issue);
+        let fq12 = Fq12::new(&mut issue);
+        let g1 = G1Projective::new(&mut issue);
+        let g2 = G2Projective::new(&mut issue);
+        let pair = (g2.clone(), fq6.clone());
+
+        // Fresh objects issue their wires in order, so the visit must count up from the first
+        for (wires, vec) in [
+            (visited(&fr), fr.to_wires_vec()),
+            (visited(&fq6), fq6.to_wires_vec()),
+            (visited(&fq12), fq12.to_wires_vec()),
+            (visited(&g1), g1.to_wires_vec()),
+            (visited(&g2), g2.to_wires_vec()),
+        ] {
+            assert_eq!(wires, vec);
+            assert!(wires.windows(2).all(|w| w[1].0 == w[0].0 + 1));
+        }
+        assert_eq!(visited(&pair), pair.to_wires_vec());
+        assert_eq!(visited(&pair).len(), pair.wire_count());
+    }
 }
@@ -6,13 +6,13 @@ use tracing::info;
 use crate::{S, WireId, circuit::component_meta::ComponentMetaBuilder, core::gate_type::GateCount};
 
 mod into_wire_list;
-pub use into_wire_list::{FromWires, WiresArity, WiresObject};
+pub use into_wire_list::{FromWires, FromWiresError, WireFields, WiresArity, WiresObject};
 
 mod circuit_context_trait;
 pub use circuit_context_trait::{CircuitContext, FALSE_WIRE, TRUE_WIRE};
 
 mod component_key;
-pub use component_key::{generate_component_key, hash_param};
+pub use component_key::{ComponentKey, generate_component_key, hash_param};
 
 mod offcircuit_param;
 pub use offcircuit_param::OffCircuitParam;
@@ -114,6 +114,34 @@ impl CircuitBuilder<ExecuteMode> {
     {
         CircuitBuilder::run_streaming(inputs, ExecuteMode::with_capacity(live_wires_capacity), f)
     }
+
+    /// [`Self::streaming_execute`] with the capacity set to the peak of live wires, measured by a
+    /// first run of the circuit. The second run is held to that capacity, so a wrong estimate
+    /// panics instead of reallocating.
+    ///
+    /// This evaluates the circuit twice; pass a capacity directly where the peak is known.
+    pub fn streaming_execute_auto<I, F, O>(inputs: I, f: F) -> StreamingResult<ExecuteMode, I, O>
+    where
+        I: CircuitInput + EncodeInput<ExecuteMode>,
+        O: CircuitOutput<ExecuteMode>,
+        O::WireRepr: Debug,
+        F: Fn(&mut StreamingMode<ExecuteMode>, &I::WireRepr) -> O::WireRepr,
+    {
+        let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(&inputs);
+        let mut root_meta = StreamingMode::<ExecuteMode>::MetadataPass(root_meta);
+        let root_meta_output = f(&mut root_meta, &allocated_inputs);
+
+        let (mut ctx, allocated_inputs) =
+            root_meta.to_root_ctx(ExecuteMode::growable(), &inputs, &root_meta_output);
+        f(&mut ctx, &allocated_inputs);
+        let StreamingMode::ExecutionPass(ctx) = ctx else {
+            unreachable!()
+        };
+        let capacity = ctx.mode.peak_live_wires();
+        info!("auto-sized live wires capacity: {capacity}");
+
+        CircuitBuilder::run_streaming(inputs, ExecuteMode::with_fixed_capacity(capacity), f)
+    }
 }
 
 pub trait CiphertextHandler: Sized {
@@ -157,10 +185,7 @@ impl<M: CircuitMode> CircuitBuilder<M> {
 
         let root_meta_output = f(&mut root_meta, &allocated_inputs);
 
-        let root_meta_output_wires = root_meta_output.to_wires_vec();
-
-        let (mut ctx, allocated_inputs) =
-            root_meta.to_root_ctx(mode, &inputs, &root_meta_output_wires);
+        let (mut ctx, allocated_inputs) = root_meta.to_root_ctx(mode, &inputs, &root_meta_output);
 
         let input_values = I::collect_wire_ids(&allocated_inputs)
             .into_iter()
@@ -219,6 +244,11 @@ pub trait CircuitInput {
 
     fn allocate(&self, ctx: impl FnMut() -> WireId) -> Self::WireRepr;
     fn collect_wire_ids(repr: &Self::WireRepr) -> Vec<WireId>;
+
+    /// Number of input wires in `repr`; override when it can be counted without collecting them
+    fn input_wire_count(repr: &Self::WireRepr) -> usize {
+        Self::collect_wire_ids(repr).len()
+    }
 }
 
 /// Trait for encoding semantic values into mode-specific caches
@@ -282,6 +312,21 @@ impl CircuitOutput<ExecuteMode> for bool {
     }
 }
 
+/// Look up the value of every wire of `wires`, in [`WiresObject::to_wires_vec`] order
+///
+/// Shared by the decoders of field and curve outputs, which rebuild the off-circuit value
+/// with their `from_bits` helpers.
+///
+/// # Panics
+/// If any of the wires has no value in `cache`.
+pub fn lookup_bits(wires: &impl WiresObject, cache: &mut ExecuteMode) -> Vec<bool> {
+    wires
+        .to_wires_vec()
+        .into_iter()
+        .map(|wire| bool::decode(wire, cache))
+        .collect()
+}
+
 pub mod arity_check;
 pub use arity_check::{ArityChecker, WireCount, verify_arity};
 
@@ -432,6 +477,53 @@ mod exec_test {
         assert!(!output.output_value[0])
     }
 
+    #[test]
+    fn test_assert_true_flips_validity_output() {
+        use circuit_component_macro::component;
+
+        /// `a AND b`, asserting on the way that `a == b`
+        #[component]
+        fn and_of_equal<C: CircuitContext>(ctx: &mut C, a: WireId, b: WireId) -> WireId {
+            let equal = ctx.issue_wire();
+            ctx.add_gate(Gate::xnor(a, b, equal));
+            ctx.assert_true(equal);
+
+            let result = ctx.issue_wire();
+            ctx.add_gate(Gate::and(a, b, result));
+            result
+        }
+
+        let run = |inputs: [bool; 5]| {
+            let output: StreamingResult<ExecuteMode, _, Vec<bool>> =
+                CircuitBuilder::streaming_execute(inputs, 10_000, |root, inputs_wire| {
+                    let left = and_of_equal(root, inputs_wire[0], inputs_wire[1]);
+                    let right = and_of_equal(root, inputs_wire[2], inputs_wire[3]);
+                    root.assert_true(inputs_wire[4]);
+
+                    let result = root.issue_wire();
+                    root.add_gate(Gate::or(left, right, result));
+                    vec![result, root.validity_output()]
+                });
+            (output.output_value[0], output.output_value[1])
+        };
+
+        assert_eq!(run([true, true, false, false, true]), (true, true));
+        assert_eq!(run([false, false, false, false, true]), (false, true));
+        assert_eq!(run([true, true, true, false, true]), (true, false));
+        assert_eq!(run([true, false, false, false, true]), (false, false));
+        assert_eq!(run([true, true, false, false, false]), (true, false));
+    }
+
+    #[test]
+    fn test_validity_output_without_assertions_is_true() {
+        let output: StreamingResult<ExecuteMode, _, Vec<bool>> =
+            CircuitBuilder::streaming_execute([false], 10_000, |root, _| {
+                vec![root.validity_output()]
+            });
+
+        assert_eq!(output.output_value, vec![true]);
+    }
+
     #[test]
     fn test_multi_wire_inputs() {
         // Define input values
@@ -703,4 +795,41 @@ mod exec_test {
 
         assert!(output.output_value[0]); // Should still be true after 1000 AND operations with TRUE
     }
+
+    /// `a * b` of two 32-bit numbers: enough gates for hundreds of live wires
+    fn mul_32(ctx: &mut StreamingMode<ExecuteMode>, wires: &[WireId; 64]) -> Vec<WireId> {
+        let a = crate::gadgets::bigint::BigIntWires::from_bits(wires[..32].iter().copied());
+        let b = crate::gadgets::bigint::BigIntWires::from_bits(wires[32..].iter().copied());
+        crate::gadgets::bigint::mul_naive(ctx, &a, &b).bits
+    }
+
+    fn mul_32_inputs(a: u64, b: u64) -> [bool; 64] {
+        array::from_fn(|i| ((a | (b << 32)) >> i) & 1 == 1)
+    }
+
+    fn from_bits(bits: &[bool]) -> u64 {
+        bits.iter()
+            .enumerate()
+            .fold(0, |acc, (i, bit)| acc | (u64::from(*bit) << i))
+    }
+
+    #[test]
+    fn test_streaming_execute_auto() {
+        let (a, b) = (0xdead_beef_u64, 0x1234_5678_u64);
+
+        let output: StreamingResult<_, _, Vec<bool>> =
+            CircuitBuilder::streaming_execute_auto(mul_32_inputs(a, b), mul_32);
+
+        assert_eq!(from_bits(&output.output_value), a * b);
+    }
+
+    #[test]
+    #[should_panic(expected = "over the fixed capacity of 16")]
+    fn test_too_small_fixed_capacity_panics() {
+        let _: StreamingResult<_, _, Vec<bool>> = CircuitBuilder::run_streaming(
+            mul_32_inputs(3, 5),
+            ExecuteMode::with_fixed_capacity(16),
+            mul_32,
+        );
+    }
 }
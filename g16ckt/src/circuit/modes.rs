@@ -1,9 +1,19 @@
 use std::{fmt, num::NonZero};
 
-use crate::{Gate, WireId, storage::Credits};
+use crate::{Gate, WireId, circuit::ComponentKey, storage::Credits};
 
+mod dedup_mode;
+mod dot_mode;
 mod execute_mode;
+mod reference_check_mode;
+mod tee_mode;
+mod well_formedness_mode;
+pub use dedup_mode::DedupMode;
+pub use dot_mode::DotMode;
 pub use execute_mode::{ExecuteMode, OptionalBoolean};
+pub use reference_check_mode::{Divergence, ReferenceCheckMode};
+pub use tee_mode::TeeMode;
+pub use well_formedness_mode::WellFormednessMode;
 // Back-compat alias used widely in tests/gadgets
 pub type Execute = crate::circuit::StreamingMode<ExecuteMode>;
 
@@ -29,12 +39,28 @@ pub trait CircuitMode: Sized + fmt::Debug {
 
     fn allocate_wire(&mut self, credits: Credits) -> WireId;
 
+    /// Allocate one wire per entry of `credits`, in order, as that many
+    /// [`CircuitMode::allocate_wire`] calls would. Modes handing out consecutive IDs take the
+    /// whole range in one step instead.
+    fn allocate_wires(&mut self, credits: &[Credits]) -> Vec<WireId> {
+        credits
+            .iter()
+            .map(|&credits| self.allocate_wire(credits))
+            .collect()
+    }
+
     fn lookup_wire(&mut self, _wire: WireId) -> Option<Self::WireValue>;
 
     fn feed_wire(&mut self, _wire: WireId, _value: Self::WireValue);
 
     fn add_credits(&mut self, wires: &[WireId], credits: NonZero<Credits>);
 
+    /// A call of the component `name`, with template `key`, starts executing: until the matching
+    /// [`CircuitMode::exit_component`], gates and wires belong to it or to components nested in it.
+    fn enter_component(&mut self, _key: ComponentKey, _name: &str) {}
+
+    fn exit_component(&mut self) {}
+
     fn finalize_ciphertext_accumulator(self) -> Self::CiphertextAcc {
         Self::CiphertextAcc::default()
     }
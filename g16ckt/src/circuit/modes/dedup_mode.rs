@@ -0,0 +1,311 @@
+use std::{collections::HashMap, num::NonZero};
+
+use crate::{
+    Gate, GateType, WireId,
+    circuit::{CircuitMode, FALSE_WIRE, TRUE_WIRE},
+    storage::Credits,
+};
+
+/// Memo key: gate type plus (canonicalized) input pair.
+type GateKey = (GateType, WireId, WireId);
+
+/// Common-subexpression elimination wrapper around another mode.
+///
+/// Every gate forwarded to the inner mode is memoized as `(GateType, wire_a, wire_b) -> wire_c`.
+/// When a structurally identical gate shows up again while the memoized output is still live, the
+/// gate is not forwarded; the memoized value is copied onto the new output wire instead. Inputs of
+/// symmetric gates (AND, XOR, ...) are put in canonical order, so `AND(a, b)` and `AND(b, a)` share
+/// one entry.
+///
+/// Remaining-use credits are mirrored per wire, so an entry is dropped as soon as one of its wires
+/// is released by the inner mode (after which its id may be recycled for an unrelated wire).
+#[derive(Debug)]
+pub struct DedupMode<M: CircuitMode> {
+    inner: M,
+    memo: HashMap<GateKey, WireId>,
+    /// Memo keys each live wire participates in, either as an input or as the output.
+    dependents: HashMap<WireId, Vec<GateKey>>,
+    credits: HashMap<WireId, Credits>,
+    deduplicated: usize,
+}
+
+impl<M: CircuitMode> DedupMode<M> {
+    pub fn new(inner: M) -> Self {
+        Self {
+            inner,
+            memo: HashMap::new(),
+            dependents: HashMap::new(),
+            credits: HashMap::new(),
+            deduplicated: 0,
+        }
+    }
+
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> M {
+        self.inner
+    }
+
+    /// Number of gates answered from the memo instead of being forwarded.
+    pub fn deduplicated_count(&self) -> usize {
+        self.deduplicated
+    }
+
+    fn key(gate: &Gate) -> GateKey {
+        use GateType::*;
+
+        let Gate {
+            wire_a,
+            wire_b,
+            gate_type,
+            ..
+        } = *gate;
+
+        match gate_type {
+            And | Nand | Or | Nor | Xor | Xnor if wire_b < wire_a => (gate_type, wire_b, wire_a),
+            _ => (gate_type, wire_a, wire_b),
+        }
+    }
+
+    fn is_live(&self, wire: WireId) -> bool {
+        matches!(wire, TRUE_WIRE | FALSE_WIRE) || self.credits.contains_key(&wire)
+    }
+
+    /// Mirror one read of `wire`, forgetting it once its credits are exhausted.
+    fn consume(&mut self, wire: WireId) {
+        let Some(credits) = self.credits.get_mut(&wire) else {
+            return;
+        };
+
+        *credits -= 1;
+        if *credits == 0 {
+            self.credits.remove(&wire);
+            self.evict(wire);
+        }
+    }
+
+    fn evict(&mut self, wire: WireId) {
+        for key in self.dependents.remove(&wire).into_iter().flatten() {
+            self.memo.remove(&key);
+        }
+    }
+
+    fn memoize(&mut self, key: GateKey, wire_c: WireId) {
+        let (_, a, b) = key;
+        if !(self.is_live(a) && self.is_live(b) && self.is_live(wire_c)) {
+            return;
+        }
+
+        self.memo.insert(key, wire_c);
+        for wire in [a, b, wire_c] {
+            self.dependents.entry(wire).or_default().push(key);
+        }
+    }
+}
+
+impl<M: CircuitMode> CircuitMode for DedupMode<M> {
+    type WireValue = M::WireValue;
+    type CiphertextAcc = M::CiphertextAcc;
+
+    fn false_value(&self) -> Self::WireValue {
+        self.inner.false_value()
+    }
+
+    fn true_value(&self) -> Self::WireValue {
+        self.inner.true_value()
+    }
+
+    fn evaluate_gate(&mut self, gate: &Gate) {
+        // In-place NOT reuses its input wire as output, so it never produces a fresh value.
+        let memoizable = gate.wire_c != WireId::UNREACHABLE && gate.gate_type != GateType::Not;
+        let key = Self::key(gate);
+
+        if memoizable && let Some(&existing) = self.memo.get(&key) {
+            // Read the memoized output without touching its own budget.
+            self.inner.add_credits(&[existing], NonZero::<Credits>::MIN);
+            if let Some(value) = self.inner.lookup_wire(existing) {
+                self.inner.feed_wire(gate.wire_c, value);
+            }
+
+            // Consume input credits exactly as the skipped gate would have.
+            let _ = self.lookup_wire(gate.wire_a);
+            let _ = self.lookup_wire(gate.wire_b);
+
+            self.deduplicated += 1;
+            return;
+        }
+
+        self.inner.evaluate_gate(gate);
+        self.consume(gate.wire_a);
+        self.consume(gate.wire_b);
+
+        if memoizable {
+            self.memoize(key, gate.wire_c);
+        }
+    }
+
+    fn allocate_wire(&mut self, credits: Credits) -> WireId {
+        let wire = self.inner.allocate_wire(credits);
+
+        if wire != WireId::UNREACHABLE {
+            // A recycled id must not match entries recorded for its previous owner.
+            self.evict(wire);
+            self.credits.insert(wire, credits);
+        }
+
+        wire
+    }
+
+    fn lookup_wire(&mut self, wire: WireId) -> Option<Self::WireValue> {
+        let value = self.inner.lookup_wire(wire);
+        self.consume(wire);
+        value
+    }
+
+    fn feed_wire(&mut self, wire: WireId, value: Self::WireValue) {
+        self.inner.feed_wire(wire, value);
+    }
+
+    fn add_credits(&mut self, wires: &[WireId], credits: NonZero<Credits>) {
+        self.inner.add_credits(wires, credits);
+
+        for wire in wires {
+            if let Some(remaining) = self.credits.get_mut(wire) {
+                *remaining = remaining.saturating_add(credits.get());
+            }
+        }
+    }
+
+    fn finalize_ciphertext_accumulator(self) -> Self::CiphertextAcc {
+        self.inner.finalize_ciphertext_accumulator()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::circuit::{
+        CircuitBuilder, CircuitContext, CircuitInput, EncodeInput, ExecuteMode, StreamingResult,
+    };
+
+    /// `ExecuteMode` that counts the AND gates it receives.
+    #[derive(Debug)]
+    struct AndCounter {
+        inner: ExecuteMode,
+        and_gates: usize,
+    }
+
+    impl CircuitMode for AndCounter {
+        type WireValue = bool;
+        type CiphertextAcc = usize;
+
+        fn false_value(&self) -> bool {
+            false
+        }
+
+        fn true_value(&self) -> bool {
+            true
+        }
+
+        fn evaluate_gate(&mut self, gate: &Gate) {
+            if gate.gate_type == GateType::And {
+                self.and_gates += 1;
+            }
+            self.inner.evaluate_gate(gate);
+        }
+
+        fn allocate_wire(&mut self, credits: Credits) -> WireId {
+            self.inner.allocate_wire(credits)
+        }
+
+        fn lookup_wire(&mut self, wire: WireId) -> Option<bool> {
+            self.inner.lookup_wire(wire)
+        }
+
+        fn feed_wire(&mut self, wire: WireId, value: bool) {
+            self.inner.feed_wire(wire, value);
+        }
+
+        fn add_credits(&mut self, wires: &[WireId], credits: NonZero<Credits>) {
+            self.inner.add_credits(wires, credits);
+        }
+
+        fn finalize_ciphertext_accumulator(self) -> usize {
+            self.and_gates
+        }
+    }
+
+    struct Inputs([bool; 2]);
+
+    impl CircuitInput for Inputs {
+        type WireRepr = [WireId; 2];
+
+        fn allocate(&self, mut issue: impl FnMut() -> WireId) -> Self::WireRepr {
+            [(issue)(), (issue)()]
+        }
+
+        fn collect_wire_ids(repr: &Self::WireRepr) -> Vec<WireId> {
+            repr.to_vec()
+        }
+    }
+
+    impl<M: CircuitMode<WireValue = bool>> EncodeInput<M> for Inputs {
+        fn encode(&self, repr: &Self::WireRepr, cache: &mut M) {
+            for (wire, value) in repr.iter().zip(self.0) {
+                cache.feed_wire(*wire, value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_duplicate_and_reaches_inner_once() {
+        let mode = DedupMode::new(AndCounter {
+            inner: ExecuteMode::with_capacity(100),
+            and_gates: 0,
+        });
+
+        let result: StreamingResult<_, _, Vec<bool>> =
+            CircuitBuilder::run_streaming(Inputs([true, true]), mode, |ctx, &[a, b]| {
+                let first = ctx.issue_wire();
+                ctx.add_gate(Gate::and(a, b, first));
+
+                // Same gate with swapped inputs
+                let second = ctx.issue_wire();
+                ctx.add_gate(Gate::and(b, a, second));
+
+                let diff = ctx.issue_wire();
+                ctx.add_gate(Gate::xor(first, second, diff));
+
+                vec![first, second, diff]
+            });
+
+        assert_eq!(result.output_value, vec![true, true, false]);
+        assert_eq!(result.ciphertext_handler_result, 1);
+    }
+
+    #[test]
+    fn test_distinct_gates_are_forwarded() {
+        let mode = DedupMode::new(AndCounter {
+            inner: ExecuteMode::with_capacity(100),
+            and_gates: 0,
+        });
+
+        let result: StreamingResult<_, _, Vec<bool>> =
+            CircuitBuilder::run_streaming(Inputs([true, false]), mode, |ctx, &[a, b]| {
+                let first = ctx.issue_wire();
+                ctx.add_gate(Gate::and(a, b, first));
+
+                let second = ctx.issue_wire();
+                ctx.add_gate(Gate::and(a, TRUE_WIRE, second));
+
+                vec![first, second]
+            });
+
+        assert_eq!(result.output_value, vec![false, true]);
+        assert_eq!(result.ciphertext_handler_result, 2);
+    }
+}
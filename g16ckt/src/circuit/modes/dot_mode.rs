@@ -0,0 +1,230 @@
+use std::{fmt::Write, num::NonZero};
+
+use tracing::warn;
+
+use crate::{
+    Gate, GateType, WireId,
+    circuit::{CircuitMode, FALSE_WIRE, TRUE_WIRE},
+    storage::Credits,
+};
+
+/// GraphViz export of a gadget's gate structure.
+///
+/// Every wire becomes a node and every gate an edge from each of its inputs to its output,
+/// labeled with the [`GateType`]. Wire IDs are never recycled, so the graph shows the actual data
+/// flow. Values aren't tracked: every lookup reads `false`, which is enough to drive a circuit
+/// through but says nothing about its outputs.
+///
+/// Rendering only works for small gadgets, so recording stops once more than `max_gates` gates
+/// were seen and [`DotMode::finish`] has nothing to return.
+#[derive(Debug)]
+pub struct DotMode {
+    next_wire: usize,
+    nodes: String,
+    edges: String,
+    gates: usize,
+    max_gates: usize,
+}
+
+impl Default for DotMode {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MAX_GATES)
+    }
+}
+
+impl DotMode {
+    /// Beyond this, GraphViz layouts take too long to be useful
+    pub const DEFAULT_MAX_GATES: usize = 10_000;
+
+    pub fn new(max_gates: usize) -> Self {
+        let mut nodes = String::new();
+        writeln!(nodes, "  w{} [label=\"FALSE\"];", FALSE_WIRE.0).unwrap();
+        writeln!(nodes, "  w{} [label=\"TRUE\"];", TRUE_WIRE.0).unwrap();
+
+        Self {
+            next_wire: TRUE_WIRE.0 + 1,
+            nodes,
+            edges: String::new(),
+            gates: 0,
+            max_gates,
+        }
+    }
+
+    fn truncated(&self) -> bool {
+        self.gates > self.max_gates
+    }
+
+    /// The DOT source, or `None` if the circuit exceeded `max_gates`
+    pub fn finish(self) -> Option<String> {
+        if self.truncated() {
+            return None;
+        }
+
+        Some(format!(
+            "digraph circuit {{\n{}{}}}\n",
+            self.nodes, self.edges
+        ))
+    }
+}
+
+impl CircuitMode for DotMode {
+    type WireValue = bool;
+    type CiphertextAcc = Option<String>;
+
+    fn false_value(&self) -> bool {
+        false
+    }
+
+    fn true_value(&self) -> bool {
+        true
+    }
+
+    fn evaluate_gate(&mut self, gate: &Gate) {
+        self.gates += 1;
+        if self.gates == self.max_gates + 1 {
+            warn!(
+                "DotMode: more than {} gates, dropping the graph",
+                self.max_gates
+            );
+            self.nodes.clear();
+            self.edges.clear();
+        }
+        if self.truncated() {
+            return;
+        }
+
+        let inputs = match gate.gate_type {
+            GateType::Not => &[gate.wire_a][..],
+            _ => &[gate.wire_a, gate.wire_b][..],
+        };
+        for input in inputs {
+            writeln!(
+                self.edges,
+                "  w{} -> w{} [label=\"{:?}\"];",
+                input.0, gate.wire_c.0, gate.gate_type
+            )
+            .unwrap();
+        }
+    }
+
+    fn allocate_wire(&mut self, _credits: Credits) -> WireId {
+        let wire = WireId(self.next_wire);
+        self.next_wire += 1;
+
+        if !self.truncated() {
+            writeln!(self.nodes, "  w{};", wire.0).unwrap();
+        }
+        wire
+    }
+
+    fn lookup_wire(&mut self, _wire: WireId) -> Option<bool> {
+        Some(false)
+    }
+
+    fn feed_wire(&mut self, _wire: WireId, _value: bool) {}
+
+    fn add_credits(&mut self, _wires: &[WireId], _credits: NonZero<Credits>) {}
+
+    fn finalize_ciphertext_accumulator(self) -> Option<String> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use test_log::test;
+
+    use super::*;
+    use crate::{
+        CircuitContext,
+        circuit::{CircuitBuilder, WiresObject},
+        gadgets::{bigint::BigIntWires, bn254::fq::Fq, field::FpImpl},
+    };
+
+    /// Node names and edge endpoints of a DOT graph as written by [`DotMode`]
+    fn parse(dot: &str) -> (HashSet<&str>, Vec<(&str, &str)>) {
+        let body = dot
+            .strip_prefix("digraph circuit {\n")
+            .and_then(|dot| dot.strip_suffix("}\n"))
+            .expect("not a digraph");
+
+        let (mut nodes, mut edges) = (HashSet::new(), Vec::new());
+        for line in body.lines() {
+            let statement = line
+                .trim()
+                .strip_suffix(';')
+                .expect("unterminated statement");
+            let (target, attributes) = statement
+                .split_once(" [")
+                .map_or((statement, None), |(target, attributes)| {
+                    (target, Some(attributes))
+                });
+            if let Some(attributes) = attributes {
+                assert!(attributes.starts_with("label=\"") && attributes.ends_with("\"]"));
+            }
+
+            match target.split_once(" -> ") {
+                Some((from, to)) => edges.push((from, to)),
+                None => assert!(nodes.insert(target), "node {target} declared twice"),
+            }
+        }
+
+        (nodes, edges)
+    }
+
+    #[test]
+    fn test_fq_mul_montgomery_graph() {
+        let result = CircuitBuilder::run_streaming::<[bool; 2 * Fq::N_BITS], _, Vec<bool>>(
+            [false; 2 * Fq::N_BITS],
+            DotMode::new(1 << 20),
+            |ctx, wires| {
+                let a = Fq(BigIntWires::from_bits(wires[..Fq::N_BITS].iter().copied()));
+                let b = Fq(BigIntWires::from_bits(wires[Fq::N_BITS..].iter().copied()));
+                Fq::mul_montgomery(ctx, &a, &b).to_wires_vec()
+            },
+        );
+
+        let dot = result.ciphertext_handler_result.unwrap();
+        let (nodes, edges) = parse(&dot);
+
+        let gates = result.gate_count.total_gate_count() as usize;
+        let nots = result.gate_count.0[GateType::Not as usize] as usize;
+        assert!(gates > 0);
+
+        // Both inputs of every gate but in-place NOT, which only reads its own output
+        assert_eq!(edges.len(), 2 * gates - nots);
+        // Constants, inputs, and one fresh output per gate
+        assert_eq!(nodes.len(), 2 + 2 * Fq::N_BITS + gates - nots);
+        for (from, to) in edges {
+            assert!(nodes.contains(from) && nodes.contains(to), "{from} -> {to}");
+        }
+    }
+
+    #[test]
+    fn test_graph_is_dropped_above_max_gates() {
+        let run = |max_gates| {
+            CircuitBuilder::run_streaming::<[bool; 2], _, Vec<bool>>(
+                [false; 2],
+                DotMode::new(max_gates),
+                |ctx, &[a, b]| {
+                    let [c, d] = [ctx.issue_wire(), ctx.issue_wire()];
+                    ctx.add_gate(Gate::and(a, b, c));
+                    ctx.add_gate(Gate::xor(c, a, d));
+                    vec![d]
+                },
+            )
+            .ciphertext_handler_result
+        };
+
+        let dot = run(2).unwrap();
+        assert_eq!(
+            dot,
+            "digraph circuit {\n  w0 [label=\"FALSE\"];\n  w1 [label=\"TRUE\"];\n  w2;\n  w3;\n  \
+             w4;\n  w5;\n  w2 -> w4 [label=\"And\"];\n  w3 -> w4 [label=\"And\"];\n  w4 -> w5 \
+             [label=\"Xor\"];\n  w2 -> w5 [label=\"Xor\"];\n}\n"
+        );
+        assert_eq!(run(1), None);
+    }
+}
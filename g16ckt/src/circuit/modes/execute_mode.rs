@@ -17,17 +17,90 @@ pub enum OptionalBoolean {
 }
 
 /// Execute mode - direct boolean evaluation
+///
+/// Wire values live in a credit-tracked slab: each read consumes one credit and the final read
+/// frees the slot for reuse, so memory is bounded by the number of simultaneously live wires
+/// rather than by the total wire count.
 #[derive(Debug)]
 pub struct ExecuteMode {
     storage: Storage<WireId, Option<bool>>,
     gate_index: usize,
+    peak_live_wires: usize,
+    /// Live wires allowed at once; `None` lets the slab grow past its initial capacity
+    max_live_wires: Option<usize>,
+    /// Every value fed to a wire, in order; only kept when requested at construction
+    dump: Option<Vec<(WireId, bool)>>,
 }
 
 impl ExecuteMode {
+    /// Preallocates `capacity` wire slots.
+    ///
+    /// Running over that is not an error: the slab grows and keeps every value, but the
+    /// reallocation is logged, since it copies all live wires mid-run.
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
             storage: Storage::new(capacity),
             gate_index: 0,
+            peak_live_wires: 0,
+            max_live_wires: None,
+            dump: None,
+        }
+    }
+
+    /// Like [`Self::with_capacity`], but more than `capacity` wires live at once is a panic
+    /// instead of a reallocation.
+    pub fn with_fixed_capacity(capacity: usize) -> Self {
+        Self {
+            max_live_wires: Some(capacity),
+            ..Self::with_capacity(capacity)
+        }
+    }
+
+    /// No preallocated slots; the slab grows as needed without logging it. Meant for a run that
+    /// only measures [`Self::peak_live_wires`].
+    pub fn growable() -> Self {
+        Self {
+            storage: Storage::growable(),
+            ..Self::with_capacity(0)
+        }
+    }
+
+    /// Like [`Self::with_capacity`], but also records every wire value for [`Self::dump_values`].
+    ///
+    /// Memory grows with the total number of wires, so this is meant for debugging gadgets.
+    pub fn with_value_dump(capacity: usize) -> Self {
+        Self {
+            dump: Some(Vec::new()),
+            ..Self::with_capacity(capacity)
+        }
+    }
+
+    /// Values fed to wires so far, in evaluation order, if recording was enabled.
+    ///
+    /// Wire IDs are recycled once their credits run out, so the same ID may appear several times;
+    /// comparing two dumps position by position finds the first diverging wire.
+    pub fn dump_values(&self) -> Option<&[(WireId, bool)]> {
+        self.dump.as_deref()
+    }
+
+    /// Number of wires currently holding a slot (allocated and not yet fully consumed).
+    pub fn live_wires(&self) -> usize {
+        self.storage.len()
+    }
+
+    /// Highest [`Self::live_wires`] observed so far.
+    pub fn peak_live_wires(&self) -> usize {
+        self.peak_live_wires
+    }
+
+    /// Value of `wire` without consuming a credit; `None` once its last read has freed it, or if
+    /// no value was fed yet.
+    pub fn peek_wire(&self, wire: WireId) -> Option<bool> {
+        match wire {
+            TRUE_WIRE => Some(true),
+            FALSE_WIRE => Some(false),
+            WireId::UNREACHABLE => None,
+            _ => self.storage.peek(wire).copied().flatten(),
         }
     }
 }
@@ -49,7 +122,17 @@ impl CircuitMode for ExecuteMode {
     /// Allocate a wire with its initial remaining-use counter (`credits`).
     #[inline]
     fn allocate_wire(&mut self, credits: Credits) -> WireId {
-        self.storage.allocate(None, credits)
+        let wire_id = self.storage.allocate(None, credits);
+        self.peak_live_wires = self.peak_live_wires.max(self.storage.len());
+        if let Some(capacity) = self.max_live_wires {
+            assert!(
+                self.storage.len() <= capacity,
+                "{} wires are live at once, over the fixed capacity of {capacity}; size it from \
+                 `peak_live_wires` or use `CircuitBuilder::streaming_execute_auto`",
+                self.storage.len()
+            );
+        }
+        wire_id
     }
 
     #[inline]
@@ -115,6 +198,10 @@ impl CircuitMode for ExecuteMode {
         self.storage
             .set(wire_id, |entry| *entry = Some(value))
             .unwrap();
+
+        if let Some(dump) = &mut self.dump {
+            dump.push((wire_id, value));
+        }
     }
 
     /// Bump remaining-use counters for `wires` by `credits`.
@@ -131,3 +218,98 @@ impl CircuitMode for ExecuteMode {
 fn uninit_wire_panic(wire_id: WireId) -> ! {
     panic!("Called `lookup_wire` for a WireId {wire_id} that was created but not initialized")
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use test_log::test;
+
+    use super::*;
+    use crate::{
+        circuit::{CircuitContext, StreamingMode, component_meta::ComponentMetaBuilder},
+        gadgets::bigint::{BigIntWires, mul_naive},
+    };
+
+    #[test]
+    fn test_live_wires_bounded_by_credits() {
+        let (a, b) = (0xdead_beef_u64, 0x1234_5678_u64);
+        let inputs: [bool; 64] = std::array::from_fn(|i| ((a | (b << 32)) >> i) & 1 == 1);
+
+        let circuit = |ctx: &mut StreamingMode<ExecuteMode>, wires: &[WireId; 64]| {
+            let a = BigIntWires::from_bits(wires[..32].iter().copied());
+            let b = BigIntWires::from_bits(wires[32..].iter().copied());
+            mul_naive(ctx, &a, &b).bits
+        };
+
+        let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(&inputs);
+        let mut root_meta = StreamingMode::<ExecuteMode>::MetadataPass(root_meta);
+        let meta_output_wires = circuit(&mut root_meta, &allocated_inputs);
+
+        let (mut ctx, allocated_inputs) = root_meta.to_root_ctx(
+            ExecuteMode::with_capacity(1_000),
+            &inputs,
+            &meta_output_wires,
+        );
+        let output_wires = circuit(&mut ctx, &allocated_inputs);
+
+        let output = output_wires
+            .iter()
+            .enumerate()
+            .fold(0u64, |acc, (i, wire)| {
+                acc | (u64::from(ctx.lookup_wire(*wire).unwrap()) << i)
+            });
+        assert_eq!(output, a * b);
+
+        let StreamingMode::ExecutionPass(ctx) = ctx else {
+            unreachable!()
+        };
+        let total_wires = ctx.gate_count.total_gate_count() as usize;
+        let peak = ctx.mode.peak_live_wires();
+
+        assert!(
+            peak * 10 < total_wires,
+            "peak of {peak} live wires out of {total_wires}"
+        );
+    }
+
+    #[test]
+    fn test_dump_values_records_intermediate_wire() {
+        let inputs = [true, false, true];
+        let intermediate = Cell::new(WireId::UNREACHABLE);
+
+        let circuit = |ctx: &mut StreamingMode<ExecuteMode>, wires: &[WireId; 3]| {
+            let [a, b, c] = *wires;
+            let a_xor_b = ctx.issue_wire();
+            ctx.add_gate(Gate::xor(a, b, a_xor_b));
+            intermediate.set(a_xor_b);
+
+            let out = ctx.issue_wire();
+            ctx.add_gate(Gate::and(a_xor_b, c, out));
+            vec![out]
+        };
+
+        let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(&inputs);
+        let mut root_meta = StreamingMode::<ExecuteMode>::MetadataPass(root_meta);
+        let meta_output_wires = circuit(&mut root_meta, &allocated_inputs);
+
+        let (mut ctx, allocated_inputs) = root_meta.to_root_ctx(
+            ExecuteMode::with_value_dump(100),
+            &inputs,
+            &meta_output_wires,
+        );
+        let output_wires = circuit(&mut ctx, &allocated_inputs);
+        assert_eq!(ctx.lookup_wire(output_wires[0]), Some(true));
+
+        let StreamingMode::ExecutionPass(ctx) = ctx else {
+            unreachable!()
+        };
+        let dump = ctx.mode.dump_values().unwrap();
+
+        // Inputs are fed first, then each gate output in order
+        assert_eq!(dump.len(), inputs.len() + 2);
+        assert_eq!(dump[inputs.len()], (intermediate.get(), true));
+
+        assert!(ExecuteMode::with_capacity(100).dump_values().is_none());
+    }
+}
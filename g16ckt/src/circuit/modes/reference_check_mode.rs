@@ -0,0 +1,156 @@
+use std::num::NonZero;
+
+use crate::{
+    Gate, WireId,
+    circuit::{CircuitMode, ExecuteMode},
+    storage::Credits,
+};
+
+/// First wire found to disagree with its reference value, or to have no value to compare
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// Label the wire was checked under, see [`ReferenceCheckMode::check`]
+    pub label: String,
+    /// Position of the wire among the wires checked under `label`
+    pub index: usize,
+    pub wire: WireId,
+    /// Reference value
+    pub expected: bool,
+    /// Value the circuit computed, the opposite of `expected`, or `None` if the wire held no live
+    /// value when checked
+    pub actual: Option<bool>,
+}
+
+/// [`ExecuteMode`] that can compare intermediate values against references computed off-circuit,
+/// e.g. by ark, as the circuit evaluates.
+///
+/// A gadget-level harness calls [`ReferenceCheckMode::check`] on the wires of an intermediate
+/// result right after computing it. Checks only peek at values, so they leave credits alone. The
+/// first divergence is kept and returned as the ciphertext accumulator of the run; later ones are
+/// ignored, since they usually follow from it.
+#[derive(Debug)]
+pub struct ReferenceCheckMode {
+    execute: ExecuteMode,
+    divergence: Option<Divergence>,
+}
+
+impl ReferenceCheckMode {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            execute: ExecuteMode::with_capacity(capacity),
+            divergence: None,
+        }
+    }
+
+    /// Compares `wires` against `reference`, which gives the expected bit of each wire. Wires it
+    /// has no bit for are skipped.
+    ///
+    /// Every other wire must still be live: one that was never fed or whose last read freed it is
+    /// a divergence too. Call this right after computing `wires`, since the slot of a freed wire
+    /// goes to the next one allocated, and a check of it would read that wire's value.
+    pub fn check(
+        &mut self,
+        label: &str,
+        wires: &[WireId],
+        reference: impl Fn(WireId) -> Option<bool>,
+    ) {
+        if self.divergence.is_some() {
+            return;
+        }
+
+        for (index, &wire) in wires.iter().enumerate() {
+            let Some(expected) = reference(wire) else {
+                continue;
+            };
+
+            let actual = self.execute.peek_wire(wire);
+            if actual != Some(expected) {
+                self.divergence = Some(Divergence {
+                    label: label.to_string(),
+                    index,
+                    wire,
+                    expected,
+                    actual,
+                });
+                return;
+            }
+        }
+    }
+
+    pub fn divergence(&self) -> Option<&Divergence> {
+        self.divergence.as_ref()
+    }
+}
+
+impl CircuitMode for ReferenceCheckMode {
+    type WireValue = bool;
+    type CiphertextAcc = Option<Divergence>;
+
+    fn false_value(&self) -> bool {
+        self.execute.false_value()
+    }
+
+    fn true_value(&self) -> bool {
+        self.execute.true_value()
+    }
+
+    fn evaluate_gate(&mut self, gate: &Gate) {
+        self.execute.evaluate_gate(gate);
+    }
+
+    fn allocate_wire(&mut self, credits: Credits) -> WireId {
+        self.execute.allocate_wire(credits)
+    }
+
+    fn lookup_wire(&mut self, wire: WireId) -> Option<bool> {
+        self.execute.lookup_wire(wire)
+    }
+
+    fn feed_wire(&mut self, wire: WireId, value: bool) {
+        self.execute.feed_wire(wire, value);
+    }
+
+    fn add_credits(&mut self, wires: &[WireId], credits: NonZero<Credits>) {
+        self.execute.add_credits(wires, credits);
+    }
+
+    fn finalize_ciphertext_accumulator(self) -> Option<Divergence> {
+        self.divergence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::TRUE_WIRE;
+
+    #[test]
+    fn test_wire_without_live_value_diverges() {
+        let mut mode = ReferenceCheckMode::with_capacity(2);
+        let [fed, freed] = [(); 2].map(|_| mode.allocate_wire(1));
+        mode.feed_wire(fed, true);
+        mode.feed_wire(freed, true);
+        // Its only read frees it
+        assert_eq!(mode.lookup_wire(freed), Some(true));
+
+        mode.check("live", &[TRUE_WIRE, fed], |_| Some(true));
+        assert_eq!(mode.divergence(), None);
+
+        mode.check("freed", &[fed, freed], |_| Some(true));
+        assert_eq!(
+            mode.divergence(),
+            Some(&Divergence {
+                label: "freed".to_string(),
+                index: 1,
+                wire: freed,
+                expected: true,
+                actual: None,
+            })
+        );
+
+        let mut mode = ReferenceCheckMode::with_capacity(1);
+        let unfed = mode.allocate_wire(1);
+        mode.check("unfed", &[unfed], |_| Some(false));
+        assert_eq!(mode.divergence().unwrap().actual, None);
+    }
+}
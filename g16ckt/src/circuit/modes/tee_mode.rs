@@ -0,0 +1,168 @@
+use std::{collections::HashMap, num::NonZero};
+
+use crate::{
+    Gate, WireId,
+    circuit::{CircuitMode, ComponentKey},
+    storage::Credits,
+};
+
+/// Runs one pass through two modes at once, e.g. translating and counting gates without building
+/// the circuit twice.
+///
+/// `A` is the primary mode: its wire IDs are the ones the circuit sees, and values are read from
+/// it. `B` allocates its own IDs alongside, and every gate and wire is mirrored to it through a
+/// map from `A`'s IDs to `B`'s. The map only holds wires whose IDs differ, so two modes that
+/// allocate alike never fill it.
+#[derive(Debug)]
+pub struct TeeMode<A: CircuitMode, B: CircuitMode<WireValue = A::WireValue>> {
+    first: A,
+    second: B,
+    /// `B`'s ID of each live wire of `A`, where the two differ
+    mirrored: HashMap<WireId, WireId>,
+}
+
+impl<A: CircuitMode, B: CircuitMode<WireValue = A::WireValue>> TeeMode<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self {
+            first,
+            second,
+            mirrored: HashMap::new(),
+        }
+    }
+
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+
+    fn mirror(&self, wire: WireId) -> WireId {
+        self.mirrored.get(&wire).copied().unwrap_or(wire)
+    }
+}
+
+impl<A: CircuitMode, B: CircuitMode<WireValue = A::WireValue>> CircuitMode for TeeMode<A, B> {
+    type WireValue = A::WireValue;
+    type CiphertextAcc = (A::CiphertextAcc, B::CiphertextAcc);
+
+    fn false_value(&self) -> Self::WireValue {
+        self.first.false_value()
+    }
+
+    fn true_value(&self) -> Self::WireValue {
+        self.first.true_value()
+    }
+
+    fn evaluate_gate(&mut self, gate: &Gate) {
+        let mirrored = Gate {
+            wire_a: self.mirror(gate.wire_a),
+            wire_b: self.mirror(gate.wire_b),
+            wire_c: self.mirror(gate.wire_c),
+            gate_type: gate.gate_type,
+        };
+
+        self.first.evaluate_gate(gate);
+        self.second.evaluate_gate(&mirrored);
+    }
+
+    fn allocate_wire(&mut self, credits: Credits) -> WireId {
+        let wire = self.first.allocate_wire(credits);
+        let mirrored = self.second.allocate_wire(credits);
+
+        // `A` may recycle `wire`, so an entry left over from its previous use must go. A wire
+        // without credits is never read, and `A` may give every one of them the same sentinel.
+        if wire == mirrored || wire == WireId::UNREACHABLE {
+            self.mirrored.remove(&wire);
+        } else {
+            self.mirrored.insert(wire, mirrored);
+        }
+        wire
+    }
+
+    fn lookup_wire(&mut self, wire: WireId) -> Option<Self::WireValue> {
+        // Both modes consume a credit on every read
+        let _ = self.second.lookup_wire(self.mirror(wire));
+        self.first.lookup_wire(wire)
+    }
+
+    fn feed_wire(&mut self, wire: WireId, value: Self::WireValue) {
+        self.second.feed_wire(self.mirror(wire), value.clone());
+        self.first.feed_wire(wire, value);
+    }
+
+    fn add_credits(&mut self, wires: &[WireId], credits: NonZero<Credits>) {
+        let mirrored = wires
+            .iter()
+            .map(|wire| self.mirror(*wire))
+            .collect::<Vec<_>>();
+
+        self.first.add_credits(wires, credits);
+        self.second.add_credits(&mirrored, credits);
+    }
+
+    fn enter_component(&mut self, key: ComponentKey, name: &str) {
+        self.first.enter_component(key, name);
+        self.second.enter_component(key, name);
+    }
+
+    fn exit_component(&mut self) {
+        self.first.exit_component();
+        self.second.exit_component();
+    }
+
+    fn finalize_ciphertext_accumulator(self) -> Self::CiphertextAcc {
+        (
+            self.first.finalize_ciphertext_accumulator(),
+            self.second.finalize_ciphertext_accumulator(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::{
+        CircuitContext,
+        circuit::{
+            CircuitBuilder, ExecuteMode, StreamingResult, WiresObject, modes::WellFormednessMode,
+        },
+        gadgets::{bigint::BigIntWires, bn254::fq::Fq, field::FpImpl},
+    };
+
+    /// `a + b` over Fq
+    fn add<C: CircuitContext>(ctx: &mut C, wires: &[WireId; 2 * Fq::N_BITS]) -> Vec<WireId> {
+        let a = Fq(BigIntWires::from_bits(wires[..Fq::N_BITS].iter().copied()));
+        let b = Fq(BigIntWires::from_bits(wires[Fq::N_BITS..].iter().copied()));
+        Fq::add(ctx, &a, &b).to_wires_vec()
+    }
+
+    #[test]
+    fn test_tee_matches_standalone_run() {
+        let bits: [bool; 2 * Fq::N_BITS] = std::array::from_fn(|i| i % 3 == 0);
+
+        let standalone: StreamingResult<_, _, Vec<bool>> =
+            CircuitBuilder::streaming_execute(bits, 10_000, add);
+        let teed: StreamingResult<_, _, Vec<bool>> = CircuitBuilder::run_streaming(
+            bits,
+            TeeMode::new(
+                ExecuteMode::with_capacity(10_000),
+                WellFormednessMode::new(),
+            ),
+            add,
+        );
+
+        assert_eq!(teed.output_value, standalone.output_value);
+        assert_eq!(
+            teed.gate_count.total_gate_count(),
+            standalone.gate_count.total_gate_count()
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::num::NonZero;
+
+use crate::{
+    Gate, WireId,
+    circuit::{CircuitMode, FALSE_WIRE, TRUE_WIRE},
+    storage::Credits,
+};
+
+/// Sanity check of a gadget's wiring: every wire a gate reads must be a constant, a primary input,
+/// or the output of an earlier gate. A read of anything else panics with the offending gate.
+///
+/// This is the in-memory counterpart of what `g16check` verifies on a translated circuit, so
+/// wiring bugs show up before a full translation. Wire IDs are never recycled, which keeps "was
+/// produced" exact. Values aren't tracked: every lookup reads `false`.
+#[derive(Debug, Default)]
+pub struct WellFormednessMode {
+    /// Indexed by wire ID; IDs are handed out densely, so this stays as small as a set would
+    produced: Vec<bool>,
+    gates: usize,
+}
+
+impl WellFormednessMode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn is_produced(&self, wire: WireId) -> bool {
+        matches!(wire, TRUE_WIRE | FALSE_WIRE) || self.produced.get(wire.0).copied() == Some(true)
+    }
+
+    fn produce(&mut self, wire: WireId) {
+        if matches!(wire, TRUE_WIRE | FALSE_WIRE | WireId::UNREACHABLE) {
+            return;
+        }
+        if wire.0 >= self.produced.len() {
+            self.produced.resize(wire.0 + 1, false);
+        }
+        self.produced[wire.0] = true;
+    }
+}
+
+impl CircuitMode for WellFormednessMode {
+    type WireValue = bool;
+    type CiphertextAcc = ();
+
+    fn false_value(&self) -> bool {
+        false
+    }
+
+    fn true_value(&self) -> bool {
+        true
+    }
+
+    fn evaluate_gate(&mut self, gate: &Gate) {
+        for wire in [gate.wire_a, gate.wire_b] {
+            assert!(
+                self.is_produced(wire),
+                "gate #{} {gate:?} reads wire {wire:?}, which was never produced",
+                self.gates
+            );
+        }
+
+        if gate.wire_c == WireId::UNREACHABLE {
+            return;
+        }
+        self.gates += 1;
+        self.produce(gate.wire_c);
+    }
+
+    fn allocate_wire(&mut self, _credits: Credits) -> WireId {
+        let wire = WireId(self.produced.len().max(TRUE_WIRE.0 + 1));
+        self.produced.resize(wire.0 + 1, false);
+        wire
+    }
+
+    fn lookup_wire(&mut self, wire: WireId) -> Option<bool> {
+        assert!(
+            self.is_produced(wire),
+            "wire {wire:?} is read but was never produced"
+        );
+        Some(false)
+    }
+
+    fn feed_wire(&mut self, wire: WireId, _value: bool) {
+        self.produce(wire);
+    }
+
+    fn add_credits(&mut self, _wires: &[WireId], _credits: NonZero<Credits>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::{
+        CircuitContext,
+        circuit::{CircuitBuilder, WiresObject},
+        gadgets::{bigint::BigIntWires, bn254::fq::Fq, field::FpImpl},
+    };
+
+    #[test]
+    fn test_fq_mul_montgomery_is_well_formed() {
+        let result = CircuitBuilder::run_streaming::<[bool; 2 * Fq::N_BITS], _, Vec<bool>>(
+            [false; 2 * Fq::N_BITS],
+            WellFormednessMode::new(),
+            |ctx, wires| {
+                let a = Fq(BigIntWires::from_bits(wires[..Fq::N_BITS].iter().copied()));
+                let b = Fq(BigIntWires::from_bits(wires[Fq::N_BITS..].iter().copied()));
+                Fq::mul_montgomery(ctx, &a, &b).to_wires_vec()
+            },
+        );
+
+        assert!(result.gate_count.total_gate_count() > 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "which was never produced")]
+    fn test_undefined_wire_panics() {
+        CircuitBuilder::run_streaming::<[bool; 2], _, Vec<bool>>(
+            [false; 2],
+            WellFormednessMode::new(),
+            |ctx, &[a, b]| {
+                let [c, d] = [ctx.issue_wire(), ctx.issue_wire()];
+                ctx.add_gate(Gate::and(a, b, c));
+                // `d` is issued but no gate ever drives it
+                let e = ctx.issue_wire();
+                ctx.add_gate(Gate::xor(c, d, e));
+                vec![e]
+            },
+        );
+    }
+}
@@ -52,6 +52,13 @@ impl OffCircuitParam for ark_bn254::Fq2 {
     }
 }
 
+impl OffCircuitParam for crate::gadgets::bls12_381::fq::ArkFq {
+    fn to_key_bytes(&self) -> Vec<u8> {
+        use ark_ff::{BigInteger, PrimeField};
+        self.into_bigint().to_bytes_le()
+    }
+}
+
 // Generic implementation for Projective points
 // Works for both G1 and G2 since they have different coordinate representations
 impl<P: ark_ec::short_weierstrass::SWCurveConfig> OffCircuitParam
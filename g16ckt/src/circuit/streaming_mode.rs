@@ -1,13 +1,13 @@
-use std::{iter, num::NonZero};
+use std::{iter, mem, num::NonZero};
 
 use tracing::{debug, trace};
 
 use crate::{
     CircuitContext, Gate, WireId,
     circuit::{
-        CircuitMode, ComponentMetaBuilder, ComponentTemplatePool, EncodeInput, FALSE_WIRE,
-        TRUE_WIRE, WiresObject, component_key::ComponentKey, component_meta::ComponentMetaInstance,
-        into_wire_list::FromWires,
+        CircuitMode, CircuitOutput, ComponentMetaBuilder, ComponentTemplatePool, EncodeInput,
+        FALSE_WIRE, TRUE_WIRE, WiresObject, component_key::ComponentKey,
+        component_meta::ComponentMetaInstance, into_wire_list::FromWires,
     },
     core::gate_type::GateCount,
     storage::Credits,
@@ -22,6 +22,8 @@ pub struct StreamingContext<M: CircuitMode> {
     pub stack: Vec<ComponentMetaInstance>,
     pub templates: ComponentTemplatePool,
     pub gate_count: GateCount,
+    /// Conjunction of the asserted wires, see [`CircuitContext::assert_true`]
+    pub validity: WireId,
 }
 
 /// Two-phase streaming execution: metadata collection (fanout totals) and execution
@@ -75,23 +77,38 @@ impl<M: CircuitMode> StreamingMode<M> {
         }
     }
 
+    /// Decode the output returned by the execution pass into its typed value.
+    pub fn decode_output<O: CircuitOutput<M>>(&mut self, output: O::WireRepr) -> O {
+        match self {
+            StreamingMode::MetadataPass(_meta) => panic!("no values in the metadata pass"),
+            StreamingMode::ExecutionPass(ctx) => O::decode(output, &mut ctx.mode),
+        }
+    }
+
     // Build execution context from collected metadata and encode inputs.
-    pub fn to_root_ctx<I: EncodeInput<M>>(
+    //
+    // `meta_output` is whatever the metadata pass returned, in any `WiresObject` shape.
+    pub fn to_root_ctx<I: EncodeInput<M>, O: WiresObject>(
         self,
         mode: M,
         input: &I,
-        meta_output_wires: &[WireId],
+        meta_output: &O,
     ) -> (Self, I::WireRepr) {
         if let StreamingMode::MetadataPass(meta) = self {
-            let meta = meta.build(meta_output_wires);
+            let meta_output_wires = meta_output.to_wires_vec();
+            let meta = meta.build(&meta_output_wires);
 
             // Seed with 1 to make each input externally readable once (result extraction).
-            let mut input_credits = vec![1; meta.get_input_len()];
+            let mut input_credits: Vec<Credits> = vec![1; meta.get_input_len()];
 
             let mut instance =
                 meta.to_instance(&vec![1; meta_output_wires.len()], |index, credits| {
                     let rev_index = meta.get_input_len() - 1 - index;
-                    input_credits[rev_index] += credits.get();
+                    input_credits[rev_index] = input_credits[rev_index]
+                        .checked_add(credits.get())
+                        .unwrap_or_else(|| {
+                            panic!("credits of input {index} overflow {}", Credits::MAX)
+                        });
                 });
 
             // Extend the credit stack with input remaining-use counters.
@@ -106,6 +123,7 @@ impl<M: CircuitMode> StreamingMode<M> {
                     pool
                 },
                 gate_count: GateCount::default(),
+                validity: TRUE_WIRE,
             });
 
             let input_repr = input.allocate(|| ctx.issue_wire());
@@ -131,6 +149,16 @@ impl<M: CircuitMode> CircuitContext for StreamingMode<M> {
         }
     }
 
+    fn issue_wires(&mut self, n: usize) -> Vec<WireId> {
+        match self {
+            StreamingMode::MetadataPass(meta) => meta.issue_wires(n),
+            StreamingMode::ExecutionPass(ctx) => {
+                let credits = ctx.pop_credits(n);
+                ctx.mode.allocate_wires(&credits)
+            }
+        }
+    }
+
     fn add_gate(&mut self, gate: Gate) {
         match self {
             StreamingMode::MetadataPass(meta) => {
@@ -147,8 +175,23 @@ impl<M: CircuitMode> CircuitContext for StreamingMode<M> {
         }
     }
 
+    fn assert_true(&mut self, wire: WireId) {
+        match self {
+            StreamingMode::MetadataPass(meta) => meta.assert_true(wire),
+            StreamingMode::ExecutionPass(ctx) => ctx.assert_true(wire),
+        }
+    }
+
+    fn validity_output(&mut self) -> WireId {
+        match self {
+            StreamingMode::MetadataPass(meta) => meta.validity_output(),
+            StreamingMode::ExecutionPass(ctx) => mem::replace(&mut ctx.validity, TRUE_WIRE),
+        }
+    }
+
     fn with_named_child<I: WiresObject, O: FromWires>(
         &mut self,
+        name: &str,
         key: ComponentKey,
         inputs: I,
         f: impl Fn(&mut Self, &I) -> O,
@@ -158,7 +201,7 @@ impl<M: CircuitMode> CircuitContext for StreamingMode<M> {
 
         match self {
             StreamingMode::MetadataPass(meta) => {
-                debug!("with_named_child: metapass enter name={key:?} arity={arity}");
+                debug!("with_named_child: metapass enter name={name} arity={arity}");
                 meta.increment_credits(&input_wires);
 
                 // We just pre-alloc all outputs for handle credits
@@ -169,7 +212,7 @@ impl<M: CircuitMode> CircuitContext for StreamingMode<M> {
                 O::from_wires(&mock_output).unwrap()
             }
             StreamingMode::ExecutionPass(ctx) => {
-                debug!("with_named_child: enter name={key:?} arity={arity}");
+                debug!("with_named_child: enter name={name} arity={arity}");
 
                 // Extract per-output remaining-use counters and push to stack
                 let pre_alloc_output_credits = {
@@ -231,16 +274,18 @@ impl<M: CircuitMode> CircuitContext for StreamingMode<M> {
                     }
                 }
                 ctx.stack.push(instance);
+                ctx.mode.enter_component(key, name);
 
                 let output = f(self, &inputs);
 
                 if let StreamingMode::ExecutionPass(ctx) = self {
+                    ctx.mode.exit_component();
                     let _used_child_meta = ctx.stack.pop();
                     #[cfg(test)]
                     assert!(_used_child_meta.unwrap().is_empty());
                 }
 
-                debug!("with_named_child: exit name={key:?} arity={arity}");
+                debug!("with_named_child: exit name={name} arity={arity}");
                 output
             }
         }
@@ -270,6 +315,25 @@ impl<M: CircuitMode> StreamingContext<M> {
         }
     }
 
+    /// AND `wire` into the validity accumulator.
+    ///
+    /// The accumulator wires aren't part of any component's metadata: each is allocated outside
+    /// the credit stack with the single read it gets, either by the next assertion or by whoever
+    /// takes [`CircuitContext::validity_output`]. The metadata pass accounts for the read of
+    /// `wire` itself.
+    pub fn assert_true(&mut self, wire: WireId) {
+        if self.validity == TRUE_WIRE {
+            self.validity = wire;
+            return;
+        }
+
+        let validity = self.mode.allocate_wire(1);
+        let gate = Gate::and(self.validity, wire, validity);
+        self.gate_count.handle(gate.gate_type);
+        self.mode.evaluate_gate(&gate);
+        self.validity = validity;
+    }
+
     pub fn lookup_wire(&mut self, wire: WireId) -> Option<M::WireValue> {
         self.mode.lookup_wire(wire)
     }
@@ -81,7 +81,9 @@ pub fn multiplexer<C: CircuitContext>(
     assert_eq!(a.len(), n);
     assert_eq!(s.len(), w);
 
-    // Iteratively reduce pairs controlled by selector bits from LSB to MSB.
+    // Iteratively reduce pairs controlled by selector bits from LSB to MSB, forming a balanced
+    // tree of 2:1 selects. Each select is `low ^ (sel & (high ^ low))`: a single AND, so the
+    // AND-depth of the whole tree is `w`.
     // Use an in-place reduction to avoid allocating a new Vec each level.
     let mut cur: Vec<WireId> = a.to_vec();
     let mut cur_len = cur.len();
@@ -92,7 +94,11 @@ pub fn multiplexer<C: CircuitContext>(
         while i < cur_len {
             let low = cur[i];
             let high = cur[i + 1];
-            cur[j] = selector(circuit, high, low, sel);
+            let [diff, masked, selected] = array::from_fn(|_| circuit.issue_wire());
+            circuit.add_gate(Gate::xor(high, low, diff));
+            circuit.add_gate(Gate::and(diff, sel, masked));
+            circuit.add_gate(Gate::xor(low, masked, selected));
+            cur[j] = selected;
             i += 2;
             j += 1;
         }
@@ -105,7 +111,7 @@ pub fn multiplexer<C: CircuitContext>(
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use test_log::test;
 
     use super::*;
@@ -391,4 +397,90 @@ mod tests {
 
         assert_eq!(output, expected);
     }
+
+    /// Longest chain of non-free gates from the inputs to any wire.
+    #[derive(Debug, Default)]
+    pub(crate) struct AndDepth {
+        next_wire: usize,
+        depth: std::collections::HashMap<WireId, usize>,
+        max_depth: usize,
+    }
+
+    impl crate::circuit::CircuitMode for AndDepth {
+        type WireValue = bool;
+        type CiphertextAcc = usize;
+
+        fn false_value(&self) -> bool {
+            false
+        }
+
+        fn true_value(&self) -> bool {
+            true
+        }
+
+        fn evaluate_gate(&mut self, gate: &Gate) {
+            let depth = |wire| self.depth.get(&wire).copied().unwrap_or(0);
+            let depth = depth(gate.wire_a).max(depth(gate.wire_b)) + usize::from(!gate.is_free());
+            self.depth.insert(gate.wire_c, depth);
+            self.max_depth = self.max_depth.max(depth);
+        }
+
+        fn allocate_wire(&mut self, _credits: crate::storage::Credits) -> WireId {
+            self.next_wire += 1;
+            WireId(self.next_wire + 1)
+        }
+
+        fn lookup_wire(&mut self, _wire: WireId) -> Option<bool> {
+            Some(false)
+        }
+
+        fn feed_wire(&mut self, _wire: WireId, _value: bool) {}
+
+        fn add_credits(
+            &mut self,
+            _wires: &[WireId],
+            _credits: std::num::NonZero<crate::storage::Credits>,
+        ) {
+        }
+
+        fn finalize_ciphertext_accumulator(self) -> usize {
+            self.max_depth
+        }
+    }
+
+    #[test]
+    fn test_multiplexer_selects_every_index() {
+        let data = [true, false, false, true, true, true, false, false];
+
+        for index in 0..data.len() {
+            let mut inputs = [false; 11];
+            inputs[..8].copy_from_slice(&data);
+            for bit in 0..3 {
+                inputs[8 + bit] = (index >> bit) & 1 == 1;
+            }
+
+            let output = CircuitBuilder::streaming_execute::<[bool; 11], _, Vec<bool>>(
+                inputs,
+                10_000,
+                |circuit, wires| vec![multiplexer(circuit, &wires[..8], &wires[8..], 3)],
+            )
+            .output_value[0];
+
+            assert_eq!(output, data[index], "index {index}");
+        }
+    }
+
+    #[test]
+    fn test_multiplexer_and_depth() {
+        const W: usize = 4;
+
+        let result = CircuitBuilder::run_streaming::<[bool; (1 << W) + W], _, Vec<bool>>(
+            [false; (1 << W) + W],
+            AndDepth::default(),
+            |circuit, wires| vec![multiplexer(circuit, &wires[..1 << W], &wires[1 << W..], W)],
+        );
+
+        // One AND per tree level; a NAND-based select would need two
+        assert_eq!(result.ciphertext_handler_result, W);
+    }
 }
@@ -40,6 +40,66 @@ pub fn self_or_zero_inv<C: CircuitContext>(
     }
 }
 
+/// AND of `leaves`, each `(wire, negated)` read as `wire ^ negated`, combined as a balanced tree so
+/// the AND-depth is logarithmic in the leaf count. `negate_result` is folded into the root gate.
+fn and_tree<C: CircuitContext>(
+    circuit: &mut C,
+    mut leaves: Vec<(WireId, bool)>,
+    negate_result: bool,
+) -> WireId {
+    assert!(!leaves.is_empty(), "can't reduce an empty set of wires");
+
+    while leaves.len() > 2 {
+        let mut next = Vec::with_capacity(leaves.len().div_ceil(2));
+        for pair in leaves.chunks(2) {
+            match *pair {
+                [(a, negate_a), (b, negate_b)] => {
+                    let w = circuit.issue_wire();
+                    circuit.add_gate(Gate::and_variant(a, b, w, [negate_a, negate_b, false]));
+                    next.push((w, false));
+                }
+                [leaf] => next.push(leaf),
+                _ => unreachable!(),
+            }
+        }
+        leaves = next;
+    }
+
+    match leaves[..] {
+        [(a, negate_a)] if negate_a == negate_result => a,
+        [(a, _)] => {
+            let w = circuit.issue_wire();
+            //this xor might be negated with innate NOT maintenance
+            circuit.add_gate(Gate::not_with_xor(a, w));
+            w
+        }
+        [(a, negate_a), (b, negate_b)] => {
+            let w = circuit.issue_wire();
+            circuit.add_gate(Gate::and_variant(
+                a,
+                b,
+                w,
+                [negate_a, negate_b, negate_result],
+            ));
+            w
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// AND of all `wires`, as a balanced tree of depth `ceil(log2(wires.len()))`
+#[component]
+pub fn reduce_and<C: CircuitContext>(circuit: &mut C, wires: &[WireId]) -> WireId {
+    and_tree(circuit, wires.iter().map(|w| (*w, false)).collect(), false)
+}
+
+/// OR of all `wires`, as a balanced tree of depth `ceil(log2(wires.len()))`
+#[component]
+pub fn reduce_or<C: CircuitContext>(circuit: &mut C, wires: &[WireId]) -> WireId {
+    // a | b | ... == !(!a & !b & ...)
+    and_tree(circuit, wires.iter().map(|w| (*w, true)).collect(), true)
+}
+
 #[component]
 pub fn equal<C: CircuitContext>(circuit: &mut C, a: &BigIntWires, b: &BigIntWires) -> WireId {
     assert_eq!(a.len(), b.len());
@@ -64,46 +124,19 @@ pub fn equal_constant<C: CircuitContext>(circuit: &mut C, a: &BigIntWires, b: &B
     }
 
     let b_bits = bits_from_biguint_with_len(b, a.len()).unwrap();
-    let one_ind = b_bits.first_one().unwrap();
 
-    let mut res = a.get(one_ind).unwrap();
-    a.iter().enumerate().for_each(|(i, a_i)| {
-        if i == one_ind {
-            return;
-        }
-        let new_res = circuit.issue_wire();
-        circuit.add_gate(Gate::and_variant(
-            *a_i,
-            res,
-            new_res,
-            [!b_bits[i], false, false],
-        ));
-        res = new_res;
-    });
-
-    res
+    // Every bit of `a` must match `b`: keep the set bits, negate the clear ones
+    let leaves = a
+        .iter()
+        .zip(b_bits.iter())
+        .map(|(a_i, b_i)| (*a_i, !*b_i))
+        .collect();
+    and_tree(circuit, leaves, false)
 }
 
 #[component]
 pub fn equal_zero<C: CircuitContext>(circuit: &mut C, a: &BigIntWires) -> WireId {
-    if a.len() == 1 {
-        let is_bit_zero = circuit.issue_wire();
-
-        //this xor might be negated with innate NOT maintenance
-        circuit.add_gate(Gate::not_with_xor(a.get(0).unwrap(), is_bit_zero));
-
-        return is_bit_zero;
-    }
-
-    let mut res = circuit.issue_wire();
-    circuit.add_gate(Gate::xnor(a.get(0).unwrap(), a.get(1).unwrap(), res));
-
-    a.iter().skip(1).for_each(|a_i| {
-        let next_res = circuit.issue_wire();
-        circuit.add_gate(Gate::and_variant(*a_i, res, next_res, [true, false, false]));
-        res = next_res;
-    });
-    res
+    and_tree(circuit, a.iter().map(|a_i| (*a_i, true)).collect(), false)
 }
 
 #[component]
@@ -193,6 +226,89 @@ pub fn multiplexer<C: CircuitContext>(
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use ark_ff::PrimeField;
+    use rand::Rng;
+    use test_log::test;
+
+    use super::*;
+    use crate::{circuit::CircuitBuilder, gadgets::basic::tests::AndDepth, test_utils::trng};
+
+    const N: usize = 254;
+
+    #[test]
+    fn test_reduce_matches_naive_fold() {
+        let mut rng = trng();
+
+        for len in [1, 2, 3, 5, 8, 13, N] {
+            for density in [0.0, 0.01, 0.5, 0.99, 1.0] {
+                let mut bits = [false; N];
+                bits[..len]
+                    .iter_mut()
+                    .for_each(|bit| *bit = rng.gen_bool(density));
+
+                let output = CircuitBuilder::streaming_execute::<[bool; N], _, Vec<bool>>(
+                    bits,
+                    10_000,
+                    |circuit, wires| {
+                        vec![
+                            reduce_and(circuit, &wires[..len]),
+                            reduce_or(circuit, &wires[..len]),
+                        ]
+                    },
+                )
+                .output_value;
+
+                let and = bits[..len].iter().fold(true, |acc, bit| acc & bit);
+                let or = bits[..len].iter().fold(false, |acc, bit| acc | bit);
+                assert_eq!(output, [and, or], "len {len}, bits {:?}", &bits[..len]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reduce_and_depth() {
+        for or in [false, true] {
+            let result = CircuitBuilder::run_streaming::<[bool; N], _, Vec<bool>>(
+                [false; N],
+                AndDepth::default(),
+                |circuit, wires| match or {
+                    false => vec![reduce_and(circuit, wires)],
+                    true => vec![reduce_or(circuit, wires)],
+                },
+            );
+
+            // A left fold would be N - 1 deep
+            assert_eq!(
+                result.ciphertext_handler_result,
+                N.next_power_of_two().ilog2() as usize
+            );
+        }
+    }
+
+    #[test]
+    fn test_equal_constant_depth() {
+        let modulus = BigUint::from(ark_bn254::Fq::MODULUS);
+
+        let result = CircuitBuilder::run_streaming::<[bool; 2 * N], _, Vec<bool>>(
+            [false; 2 * N],
+            AndDepth::default(),
+            |circuit, wires| {
+                let a = BigIntWires::from_bits(wires[..N].iter().copied());
+                let b = BigIntWires::from_bits(wires[N..].iter().copied());
+                vec![
+                    equal_constant(circuit, &a, &modulus),
+                    equal_zero(circuit, &a),
+                    equal(circuit, &a, &b),
+                ]
+            },
+        );
+
+        assert_eq!(result.ciphertext_handler_result, 8);
+    }
+}
+
 //#[cfg(test)]
 //mod tests {
 //    use debug;
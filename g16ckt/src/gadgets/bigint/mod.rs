@@ -61,9 +61,7 @@ impl BigIntWires {
 
     pub fn from_ctx<C: CircuitContext>(circuit: &mut C, len: usize) -> Self {
         Self {
-            bits: iter::repeat_with(|| circuit.issue_wire())
-                .take(len)
-                .collect(),
+            bits: circuit.issue_wires(len),
         }
     }
 
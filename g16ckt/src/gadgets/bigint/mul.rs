@@ -278,9 +278,11 @@ pub fn mul_by_constant_modulo_power_two<C: CircuitContext>(
         let power_bytes = power.to_le_bytes();
         let chunk_idx_bytes = chunk_idx.to_le_bytes();
 
+        let name = "mul_by_const_mod_2p";
         result_bits = circuit.with_named_child(
+            name,
             crate::component_key!(
-                "mul_by_const_mod_2p",
+                name,
                 a_len = &a_len_bytes[..],
                 power = &power_bytes[..],
                 chunk_idx = &chunk_idx_bytes[..];
@@ -0,0 +1,364 @@
+use std::ops::{Deref, DerefMut};
+
+use ark_ff::{Fp384, MontBackend, PrimeField, UniformRand};
+use num_bigint::BigUint;
+use rand::Rng;
+
+use crate::{
+    CircuitContext, WireId,
+    circuit::{
+        CircuitMode, CircuitOutput, ExecuteMode, FromWires, FromWiresError, WiresArity, WiresObject,
+    },
+    gadgets::{
+        bigint::{BigIntWires, Error},
+        field::FpImpl,
+    },
+};
+
+// The derive expands to `feature = "asm"` checks that only make sense inside ark-ff
+#[allow(unexpected_cfgs)]
+mod config {
+    use ark_ff::MontConfig;
+
+    #[derive(MontConfig)]
+    #[modulus = "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559787"]
+    #[generator = "2"]
+    pub struct FqConfig;
+}
+pub use config::FqConfig;
+
+/// Off-circuit BLS12-381 base field element, the same field as `ark_bls12_381::Fq`
+pub type ArkFq = Fp384<MontBackend<FqConfig, 6>>;
+
+/// BLS12-381 base field Fq implementation
+#[derive(Clone, Debug)]
+pub struct Fq(pub BigIntWires);
+
+impl Deref for Fq {
+    type Target = BigIntWires;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Fq {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl WiresObject for Fq {
+    fn to_wires_vec(&self) -> Vec<WireId> {
+        self.0.iter().copied().collect()
+    }
+
+    fn wire_count(&self) -> usize {
+        self.0.len()
+    }
+
+    fn clone_from(&self, wire_gen: &mut impl FnMut() -> WireId) -> Self {
+        Self(self.0.clone_from(wire_gen))
+    }
+}
+
+impl FromWires for Fq {
+    fn from_wires(wires: &[WireId]) -> Option<Self> {
+        Self::try_from_wires(wires).ok()
+    }
+
+    fn try_from_wires(wires: &[WireId]) -> Result<Self, FromWiresError> {
+        if wires.len() != Self::N_BITS {
+            return Err(FromWiresError::Length {
+                ty: "bls12_381::Fq",
+                expected: Self::N_BITS,
+                actual: wires.len(),
+            });
+        }
+
+        Ok(Self(BigIntWires::from_bits(wires.iter().copied())))
+    }
+}
+
+impl WiresArity for Fq {
+    const ARITY: usize = Self::N_BITS;
+}
+
+/// Reads back a BLS12-381 base field element held in Montgomery form, returning it in standard
+/// form.
+impl CircuitOutput<ExecuteMode> for ArkFq {
+    type WireRepr = Fq;
+
+    fn decode(wires: Self::WireRepr, cache: &mut ExecuteMode) -> Self {
+        let value = BigUint::decode(wires.0, cache);
+        Fq::from_montgomery(ArkFq::from(value))
+    }
+}
+
+impl FpImpl for Fq {
+    type Field = ArkFq;
+
+    const MODULUS: &'static str = "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559787";
+    const MONTGOMERY_M_INVERSE: &'static str = "2664409753273775110221200151442771786793239700188535927135561653559151420143311517290279233983125732648962633170947";
+    const MONTGOMERY_R_INVERSE: &'static str = "1837228624611166976027917846994533426568846492471753528422348748112049434051049035222295460076611125488383084478718";
+    const N_BITS: usize = 381;
+
+    fn half_modulus() -> BigUint {
+        BigUint::from(ArkFq::from(1) / ArkFq::from(2))
+    }
+
+    fn one_third_modulus() -> BigUint {
+        BigUint::from(ArkFq::from(1) / ArkFq::from(3))
+    }
+
+    fn two_third_modulus() -> BigUint {
+        BigUint::from(ArkFq::from(2) / ArkFq::from(3))
+    }
+}
+
+impl Fq {
+    pub fn random(rng: &mut impl Rng) -> ArkFq {
+        ArkFq::rand(rng)
+    }
+
+    pub fn new_constant(u: &ArkFq) -> Result<Fq, Error> {
+        Ok(Fq(BigIntWires::new_constant(
+            Self::N_BITS,
+            &BigUint::from(u.into_bigint()),
+        )?))
+    }
+
+    pub fn from_ctx<C: CircuitContext>(circuit: &mut C) -> Fq {
+        Fq(BigIntWires::from_ctx(circuit, Self::N_BITS))
+    }
+
+    pub fn new(issue: impl FnMut() -> WireId) -> Fq {
+        Fq(BigIntWires::new(issue, Self::N_BITS))
+    }
+
+    /// See [`FpImpl::from_bits_checked`]
+    pub fn from_bits_checked<C: CircuitContext>(
+        circuit: &mut C,
+        bits: impl IntoIterator<Item = WireId>,
+    ) -> (Fq, WireId) {
+        let (a, valid) = <Self as FpImpl>::from_bits_checked(circuit, bits);
+        (Fq(a), valid)
+    }
+
+    pub fn get_wire_bits_fn(
+        wires: &Fq,
+        value: &ArkFq,
+    ) -> Result<impl Fn(WireId) -> Option<bool> + use<>, Error> {
+        wires
+            .0
+            .get_wire_bits_fn(&BigUint::from(value.into_bigint()))
+    }
+
+    /// Feed every wire of `wires` with its bit of `value`, as an input encoder does
+    pub fn encode<M: CircuitMode<WireValue = bool>>(wires: &Fq, value: &ArkFq, cache: &mut M) {
+        let bits = Self::get_wire_bits_fn(wires, value).unwrap();
+        for &wire_id in wires.iter() {
+            if let Some(bit) = bits(wire_id) {
+                cache.feed_wire(wire_id, bit);
+            }
+        }
+    }
+
+    /// Convert a field element to Montgomery form (a * R mod p)
+    pub fn as_montgomery(a: ArkFq) -> ArkFq {
+        <Self as FpImpl>::as_montgomery(a)
+    }
+
+    /// Convert a field element from Montgomery form back to standard form
+    pub fn from_montgomery(a: ArkFq) -> ArkFq {
+        a / ArkFq::from(Self::montgomery_r_as_biguint())
+    }
+
+    // Field arithmetic methods (directly using FpImpl trait methods)
+    pub fn add(circuit: &mut impl CircuitContext, a: &Fq, b: &Fq) -> Fq {
+        Fq(<Self as FpImpl>::add(circuit, &a.0, &b.0))
+    }
+
+    pub fn add_constant(circuit: &mut impl CircuitContext, a: &Fq, b: &ArkFq) -> Fq {
+        Fq(<Self as FpImpl>::add_constant(circuit, &a.0, b))
+    }
+
+    pub fn sub(circuit: &mut impl CircuitContext, a: &Fq, b: &Fq) -> Fq {
+        Fq(<Self as FpImpl>::sub(circuit, &a.0, &b.0))
+    }
+
+    pub fn neg(circuit: &mut impl CircuitContext, a: &Fq) -> Fq {
+        Fq(<Self as FpImpl>::neg(circuit, &a.0))
+    }
+
+    pub fn double(circuit: &mut impl CircuitContext, a: &Fq) -> Fq {
+        Fq(<Self as FpImpl>::double(circuit, &a.0))
+    }
+
+    pub fn half(circuit: &mut impl CircuitContext, a: &Fq) -> Fq {
+        Fq(<Self as FpImpl>::half(circuit, &a.0))
+    }
+
+    pub fn mul_montgomery(circuit: &mut impl CircuitContext, a: &Fq, b: &Fq) -> Fq {
+        Fq(<Self as FpImpl>::mul_montgomery(circuit, &a.0, &b.0))
+    }
+
+    /// Montgomery multiplication by a constant `b` given in Montgomery form
+    pub fn mul_by_constant_montgomery(circuit: &mut impl CircuitContext, a: &Fq, b: &ArkFq) -> Fq {
+        Fq(<Self as FpImpl>::mul_by_constant_montgomery(
+            circuit, &a.0, b,
+        ))
+    }
+
+    pub fn square_montgomery(circuit: &mut impl CircuitContext, a: &Fq) -> Fq {
+        Fq(<Self as FpImpl>::square_montgomery(circuit, &a.0))
+    }
+
+    pub fn montgomery_reduce(circuit: &mut impl CircuitContext, x: &BigIntWires) -> Fq {
+        Fq(<Self as FpImpl>::montgomery_reduce(circuit, x))
+    }
+
+    pub fn equal_constant(circuit: &mut impl CircuitContext, a: &Fq, b: &ArkFq) -> WireId {
+        <Self as FpImpl>::equal_constant(circuit, &a.0, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::array;
+
+    use ark_ff::{AdditiveGroup, Field};
+    use test_log::test;
+
+    use super::*;
+    use crate::{
+        circuit::{CircuitBuilder, CircuitInput, EncodeInput},
+        gadgets::bigint,
+        math::montgomery::calculate_montgomery_constants,
+        test_utils::trng,
+    };
+
+    struct FqInput<const N: usize>([ArkFq; N]);
+
+    impl<const N: usize> CircuitInput for FqInput<N> {
+        type WireRepr = [Fq; N];
+
+        fn allocate(&self, mut issue: impl FnMut() -> WireId) -> Self::WireRepr {
+            array::from_fn(|_| Fq::new(&mut issue))
+        }
+
+        fn collect_wire_ids(repr: &Self::WireRepr) -> Vec<WireId> {
+            repr.iter().flat_map(|fq| fq.to_wires_vec()).collect()
+        }
+    }
+
+    impl<const N: usize, M: CircuitMode<WireValue = bool>> EncodeInput<M> for FqInput<N> {
+        fn encode(&self, repr: &Self::WireRepr, cache: &mut M) {
+            for (value, wires) in self.0.iter().zip(repr) {
+                Fq::encode(wires, &Fq::as_montgomery(*value), cache);
+            }
+        }
+    }
+
+    /// Run `op` on `N` random Montgomery-form inputs and compare against `expected`
+    fn check<const N: usize>(
+        op: impl Fn(&mut crate::circuit::StreamingMode<ExecuteMode>, &[Fq; N]) -> Fq,
+        expected: impl Fn([ArkFq; N]) -> ArkFq,
+    ) {
+        let mut rng = trng();
+        for _ in 0..3 {
+            let values = array::from_fn(|_| Fq::random(&mut rng));
+            let result =
+                CircuitBuilder::streaming_execute::<_, _, ArkFq>(FqInput(values), 10_000, &op);
+            assert_eq!(result.output_value, expected(values), "inputs {values:?}");
+        }
+    }
+
+    #[test]
+    fn test_bls12_381_fq_montgomery_constants() {
+        let (r_inv, m_inv) =
+            calculate_montgomery_constants(Fq::modulus_as_biguint(), Fq::montgomery_r_as_biguint());
+        assert_eq!(r_inv, Fq::montgomery_r_inverse_as_biguint());
+        assert_eq!(m_inv, Fq::montgomery_m_inverse_as_biguint());
+        assert_eq!(
+            Fq::montgomery_r_as_biguint(),
+            BigUint::from(1u8) << Fq::N_BITS
+        );
+        assert_eq!(Fq::modulus_as_biguint(), BigUint::from(ArkFq::MODULUS));
+    }
+
+    #[test]
+    fn test_bls12_381_fq_add() {
+        check(|ctx, [a, b]| Fq::add(ctx, a, b), |[a, b]| a + b);
+    }
+
+    #[test]
+    fn test_bls12_381_fq_add_constant() {
+        let b = Fq::random(&mut trng());
+        check(
+            |ctx, [a]| Fq::add_constant(ctx, a, &Fq::as_montgomery(b)),
+            |[a]| a + b,
+        );
+    }
+
+    #[test]
+    fn test_bls12_381_fq_sub() {
+        check(|ctx, [a, b]| Fq::sub(ctx, a, b), |[a, b]| a - b);
+    }
+
+    #[test]
+    fn test_bls12_381_fq_neg() {
+        check(|ctx, [a]| Fq::neg(ctx, a), |[a]| -a);
+    }
+
+    #[test]
+    fn test_bls12_381_fq_double() {
+        check(|ctx, [a]| Fq::double(ctx, a), |[a]| a.double());
+    }
+
+    #[test]
+    fn test_bls12_381_fq_half() {
+        check(|ctx, [a]| Fq::half(ctx, a), |[a]| a / ArkFq::from(2));
+    }
+
+    #[test]
+    fn test_bls12_381_fq_mul_montgomery() {
+        check(|ctx, [a, b]| Fq::mul_montgomery(ctx, a, b), |[a, b]| a * b);
+    }
+
+    #[test]
+    fn test_bls12_381_fq_square_montgomery() {
+        check(|ctx, [a]| Fq::square_montgomery(ctx, a), |[a]| a.square());
+    }
+
+    #[test]
+    fn test_bls12_381_fq_mul_by_constant_montgomery() {
+        let b = Fq::random(&mut trng());
+        check(
+            |ctx, [a]| Fq::mul_by_constant_montgomery(ctx, a, &Fq::as_montgomery(b)),
+            |[a]| a * b,
+        );
+    }
+
+    #[test]
+    fn test_bls12_381_fq_from_bits_checked() {
+        let modulus = Fq::modulus_as_biguint();
+        let cases = [
+            (&modulus - 1u8, true),
+            (modulus.clone(), false),
+            ((BigUint::from(1u8) << Fq::N_BITS) - 1u8, false),
+        ];
+
+        for (value, valid) in cases {
+            let bits = bigint::bits_from_biguint_with_len(&value, Fq::N_BITS).unwrap();
+            let input: [bool; Fq::N_BITS] = array::from_fn(|i| bits[i]);
+
+            let result =
+                CircuitBuilder::streaming_execute::<_, _, Vec<bool>>(input, 10_000, |ctx, bits| {
+                    vec![Fq::from_bits_checked(ctx, bits.iter().copied()).1]
+                });
+
+            assert_eq!(result.output_value, [valid], "value {value}");
+        }
+    }
+}
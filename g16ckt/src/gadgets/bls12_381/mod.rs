@@ -0,0 +1,8 @@
+//! BLS12-381 elliptic curve field arithmetic implementations
+//!
+//! Circuit-based counterparts of the [`super::bn254`] gadgets for the BLS12-381 curve. Only the
+//! 381-bit base field is implemented so far; the extension fields, scalar field and curve groups
+//! are still to follow before a Groth16 verifier can be instantiated over this curve.
+
+pub mod fq;
+pub use fq::Fq;
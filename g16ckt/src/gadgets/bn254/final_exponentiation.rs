@@ -105,12 +105,13 @@ pub fn final_exponentiation_montgomery<C: CircuitContext>(circuit: &mut C, f: &F
     let u_frobenius = Fq12::frobenius_montgomery(circuit, &u, 2);
     let r = Fq12::mul_montgomery(circuit, &u_frobenius, &u);
 
+    // `r` is in the cyclotomic subgroup, and so is everything derived from it below
     let y0 = exp_by_neg_x_montgomery(circuit, &r);
-    let y1 = Fq12::square_montgomery(circuit, &y0);
-    let y2 = Fq12::square_montgomery(circuit, &y1);
+    let y1 = Fq12::cyclotomic_square_montgomery(circuit, &y0);
+    let y2 = Fq12::cyclotomic_square_montgomery(circuit, &y1);
     let y3 = Fq12::mul_montgomery(circuit, &y1, &y2);
     let y4 = exp_by_neg_x_montgomery(circuit, &y3);
-    let y5 = Fq12::square_montgomery(circuit, &y4);
+    let y5 = Fq12::cyclotomic_square_montgomery(circuit, &y4);
     let y6 = exp_by_neg_x_montgomery(circuit, &y5);
     let y7 = Fq12::conjugate(circuit, &y3);
     let y8 = Fq12::conjugate(circuit, &y6);
@@ -146,10 +147,8 @@ mod tests {
         },
         gadgets::{
             bigint::{BigUint as BigUintOutput, bits_from_biguint_with_len},
-            bn254::{
-                fp254impl::Fp254Impl, fq::Fq as FqWire, fq6::Fq6 as Fq6Wires,
-                fq12::Fq12 as Fq12Wires,
-            },
+            bn254::{fq::Fq as FqWire, fq6::Fq6 as Fq6Wires, fq12::Fq12 as Fq12Wires},
+            field::FpImpl,
         },
     };
 
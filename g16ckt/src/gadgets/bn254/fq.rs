@@ -7,10 +7,10 @@ use ark_ff::{Field, PrimeField};
 use num_bigint::BigUint;
 use rand::Rng;
 
-use super::super::{bigint::BigIntWires, bn254::fp254impl::Fp254Impl};
+use super::super::{bigint::BigIntWires, field::FpImpl};
 use crate::{
     CircuitContext, WireId,
-    circuit::{FromWires, WiresObject},
+    circuit::{FromWires, FromWiresError, WiresObject},
     gadgets::{
         self,
         bigint::{self, Error},
@@ -40,6 +40,14 @@ impl WiresObject for Fq {
         self.0.iter().copied().collect()
     }
 
+    fn for_each_wire(&self, f: impl FnMut(WireId)) {
+        self.0.iter().copied().for_each(f)
+    }
+
+    fn wire_count(&self) -> usize {
+        self.0.len()
+    }
+
     fn clone_from(&self, wire_gen: &mut impl FnMut() -> WireId) -> Self {
         Self(self.0.clone_from(wire_gen))
     }
@@ -47,13 +55,27 @@ impl WiresObject for Fq {
 
 impl FromWires for Fq {
     fn from_wires(wires: &[WireId]) -> Option<Self> {
-        Some(Self(crate::gadgets::bigint::BigIntWires::from_bits(
+        Self::try_from_wires(wires).ok()
+    }
+
+    fn try_from_wires(wires: &[WireId]) -> Result<Self, FromWiresError> {
+        if wires.len() != Self::N_BITS {
+            return Err(FromWiresError::Length {
+                ty: "Fq",
+                expected: Self::N_BITS,
+                actual: wires.len(),
+            });
+        }
+
+        Ok(Self(crate::gadgets::bigint::BigIntWires::from_bits(
             wires.iter().copied(),
         )))
     }
 }
 
-impl Fp254Impl for Fq {
+impl FpImpl for Fq {
+    type Field = ark_bn254::Fq;
+
     const MODULUS: &'static str =
         "21888242871839275222246405745257275088696311157297823662689037894645226208583";
     const MONTGOMERY_M_INVERSE: &'static str =
@@ -76,6 +98,10 @@ impl Fp254Impl for Fq {
 }
 
 impl Fq {
+    /// (MODULUS+1)/4 for square root operations
+    pub const MODULUS_ADD_1_DIV_4: &'static str =
+        "5472060717959818805561601436314318772174077789324455915672259473661306552146";
+
     pub fn random(rng: &mut impl Rng) -> ark_bn254::Fq {
         let bytes: [u8; 31] = rng.r#gen();
         ark_bn254::Fq::from_random_bytes(&bytes).unwrap()
@@ -96,6 +122,20 @@ impl Fq {
         Fq(BigIntWires::new(issue, Self::N_BITS))
     }
 
+    /// See [`FpImpl::from_bits_checked`]
+    pub fn from_bits_checked<C: CircuitContext>(
+        circuit: &mut C,
+        bits: impl IntoIterator<Item = WireId>,
+    ) -> (Fq, WireId) {
+        let (a, valid) = <Self as FpImpl>::from_bits_checked(circuit, bits);
+        (Fq(a), valid)
+    }
+
+    /// See [`FpImpl::is_reduced`]
+    pub fn is_reduced<C: CircuitContext>(circuit: &mut C, a: &Fq) -> WireId {
+        <Self as FpImpl>::is_reduced(circuit, &a.0)
+    }
+
     pub fn get_wire_bits_fn(
         wires: &Fq,
         value: &ark_bn254::Fq,
@@ -191,45 +231,61 @@ impl Fq {
         bigint::equal(circuit, &y.0, &neg_one_mont.0)
     }
 
-    // Field arithmetic methods (directly using Fp254Impl trait methods)
+    // Field arithmetic methods (directly using FpImpl trait methods)
     pub fn add(circuit: &mut impl crate::CircuitContext, a: &Fq, b: &Fq) -> Fq {
-        Fq(<Self as Fp254Impl>::add(circuit, &a.0, &b.0))
+        Fq(<Self as FpImpl>::add(circuit, &a.0, &b.0))
     }
 
     pub fn add_constant(circuit: &mut impl crate::CircuitContext, a: &Fq, b: &ark_bn254::Fq) -> Fq {
-        Fq(<Self as Fp254Impl>::add_constant(circuit, &a.0, b))
+        Fq(<Self as FpImpl>::add_constant(circuit, &a.0, b))
     }
 
     pub fn sub(circuit: &mut impl crate::CircuitContext, a: &Fq, b: &Fq) -> Fq {
-        Fq(<Self as Fp254Impl>::sub(circuit, &a.0, &b.0))
+        Fq(<Self as FpImpl>::sub(circuit, &a.0, &b.0))
+    }
+
+    /// `a - b` on the integers the wires hold, without reduction modulo the field, along with the
+    /// borrow out of the top bit: the difference wraps modulo `2^254` and the borrow is set iff
+    /// `a < b`. Meant for comparisons and conditional reductions; [`Fq::sub`] is the field
+    /// subtraction.
+    pub fn sub_with_borrow<C: CircuitContext>(circuit: &mut C, a: &Fq, b: &Fq) -> (Fq, WireId) {
+        let mut difference = bigint::sub(circuit, &a.0, &b.0);
+        let borrow = difference.pop().unwrap();
+        (Fq(difference), borrow)
     }
 
     pub fn neg(circuit: &mut impl crate::CircuitContext, a: &Fq) -> Fq {
-        Fq(<Self as Fp254Impl>::neg(circuit, &a.0))
+        Fq(<Self as FpImpl>::neg(circuit, &a.0))
+    }
+
+    /// Product with the non-residue `u^2` that builds Fq2 over Fq. For BN254 that is `-1`, so this
+    /// is a negation, and it works alike on Montgomery and standard form.
+    pub fn mul_by_nonresidue(circuit: &mut impl crate::CircuitContext, a: &Fq) -> Fq {
+        Self::neg(circuit, a)
     }
 
     pub fn double(circuit: &mut impl crate::CircuitContext, a: &Fq) -> Fq {
-        Fq(<Self as Fp254Impl>::double(circuit, &a.0))
+        Fq(<Self as FpImpl>::double(circuit, &a.0))
     }
 
     pub fn half(circuit: &mut impl crate::CircuitContext, a: &Fq) -> Fq {
-        Fq(<Self as Fp254Impl>::half(circuit, &a.0))
+        Fq(<Self as FpImpl>::half(circuit, &a.0))
     }
 
     pub fn triple(circuit: &mut impl crate::CircuitContext, a: &Fq) -> Fq {
-        Fq(<Self as Fp254Impl>::triple(circuit, &a.0))
+        Fq(<Self as FpImpl>::triple(circuit, &a.0))
     }
 
     pub fn div6(circuit: &mut impl crate::CircuitContext, a: &Fq) -> Fq {
-        Fq(<Self as Fp254Impl>::div6(circuit, &a.0))
+        Fq(<Self as FpImpl>::div6(circuit, &a.0))
     }
 
     pub fn inverse(circuit: &mut impl crate::CircuitContext, a: &Fq) -> Fq {
-        Fq(<Self as Fp254Impl>::inverse(circuit, &a.0))
+        Fq(<Self as FpImpl>::inverse(circuit, &a.0))
     }
 
     pub fn mul_montgomery(circuit: &mut impl crate::CircuitContext, a: &Fq, b: &Fq) -> Fq {
-        Fq(<Self as Fp254Impl>::mul_montgomery(circuit, &a.0, &b.0))
+        Fq(<Self as FpImpl>::mul_montgomery(circuit, &a.0, &b.0))
     }
 
     pub fn mul_by_constant_montgomery(
@@ -237,21 +293,21 @@ impl Fq {
         a: &Fq,
         b: &ark_bn254::Fq,
     ) -> Fq {
-        Fq(<Self as Fp254Impl>::mul_by_constant_montgomery(
+        Fq(<Self as FpImpl>::mul_by_constant_montgomery(
             circuit, &a.0, b,
         ))
     }
 
     pub fn square_montgomery(circuit: &mut impl crate::CircuitContext, a: &Fq) -> Fq {
-        Fq(<Self as Fp254Impl>::square_montgomery(circuit, &a.0))
+        Fq(<Self as FpImpl>::square_montgomery(circuit, &a.0))
     }
 
     pub fn montgomery_reduce(circuit: &mut impl crate::CircuitContext, x: &BigIntWires) -> Fq {
-        Fq(<Self as Fp254Impl>::montgomery_reduce(circuit, x))
+        Fq(<Self as FpImpl>::montgomery_reduce(circuit, x))
     }
 
     pub fn inverse_montgomery(circuit: &mut impl crate::CircuitContext, a: &Fq) -> Fq {
-        Fq(<Self as Fp254Impl>::inverse_montgomery(circuit, &a.0))
+        Fq(<Self as FpImpl>::inverse_montgomery(circuit, &a.0))
     }
 
     pub fn exp_by_constant_montgomery(
@@ -259,7 +315,7 @@ impl Fq {
         a: &Fq,
         exp: &BigUint,
     ) -> Fq {
-        Fq(<Self as Fp254Impl>::exp_by_constant_montgomery(
+        Fq(<Self as FpImpl>::exp_by_constant_montgomery(
             circuit, &a.0, exp,
         ))
     }
@@ -271,12 +327,7 @@ impl Fq {
         w: usize,
     ) -> Fq {
         let bigint_array: Vec<BigIntWires> = a.iter().map(|fq| fq.0.clone()).collect();
-        Fq(<Self as Fp254Impl>::multiplexer(
-            circuit,
-            &bigint_array,
-            s,
-            w,
-        ))
+        Fq(<Self as FpImpl>::multiplexer(circuit, &bigint_array, s, w))
     }
 
     pub fn equal_constant(
@@ -284,7 +335,7 @@ impl Fq {
         a: &Fq,
         b: &ark_bn254::Fq,
     ) -> WireId {
-        <Self as Fp254Impl>::equal_constant(circuit, &a.0, b)
+        <Self as FpImpl>::equal_constant(circuit, &a.0, b)
     }
 
     /// Square root in Montgomery form (assuming input is quadratic residue)
@@ -297,13 +348,22 @@ impl Fq {
             &BigUint::from_str(Self::MODULUS_ADD_1_DIV_4).unwrap(),
         )
     }
+
+    /// [`Fq::sqrt_montgomery`] along with a wire set iff `a` is a quadratic residue, i.e. iff the
+    /// root squares back to `a`; for a non-residue the root is meaningless
+    pub fn sqrt_checked_montgomery<C: CircuitContext>(circuit: &mut C, a: &Fq) -> (Fq, WireId) {
+        let root = Self::sqrt_montgomery(circuit, a);
+        let square = Self::square_montgomery(circuit, &root);
+        let is_qr = bigint::equal(circuit, &square.0, &a.0);
+        (root, is_qr)
+    }
 }
 
 #[cfg(test)]
 pub(super) mod tests {
     use std::{array, iter};
 
-    use ark_ff::AdditiveGroup;
+    use ark_ff::{AdditiveGroup, Fp2Config};
     use rand::Rng;
     use test_log::test;
     use tracing::trace;
@@ -311,12 +371,12 @@ pub(super) mod tests {
     use super::*;
     use crate::{
         circuit::{
-            CircuitBuilder, CircuitInput, CircuitMode, CircuitOutput, EncodeInput,
-            modes::ExecuteMode,
+            CircuitBuilder, CircuitInput, CircuitMode, CircuitOutput, EncodeInput, StreamingMode,
+            component_meta::ComponentMetaBuilder, modes::ExecuteMode,
         },
         gadgets::{
             bigint::{BigUint as BigUintOutput, bits_from_biguint_with_len},
-            bn254::fp254impl::Fp254Impl,
+            field::FpImpl,
         },
         test_utils::trng,
     };
@@ -569,6 +629,7 @@ pub(super) mod tests {
     test_fq!(unary test_fq_half, Fq::half, (|a: ark_bn254::Fq| a / ark_bn254::Fq::from(2u32)));
     test_fq!(unary test_fq_inverse, Fq::inverse, (|a: ark_bn254::Fq| a.inverse().unwrap()));
     test_fq!(unary test_fq_neg, Fq::neg, (|a: ark_bn254::Fq| -a));
+    test_fq!(unary test_fq_mul_by_nonresidue, Fq::mul_by_nonresidue, (|mut a: ark_bn254::Fq| *ark_bn254::Fq2Config::mul_fp_by_nonresidue_in_place(&mut a)));
     test_fq!(unary test_fq_triple, Fq::triple, (|a: ark_bn254::Fq| a + a + a));
 
     // Montgomery form operations
@@ -594,6 +655,58 @@ pub(super) mod tests {
     // Special operations
     test_fq!(unary test_fq_div6, Fq::div6, (|a: ark_bn254::Fq| a / ark_bn254::Fq::from(6u32)));
 
+    #[test]
+    fn test_fq_mul_by_small_constant_matches_full_multiply() {
+        let big = ark_bn254::Fq::from(2u32).pow([200]);
+        let constants = [
+            ark_bn254::Fq::ONE,
+            ark_bn254::Fq::from(2u32),
+            ark_bn254::Fq::from(9u32),
+            ark_bn254::Fq::from(0x8003u32),
+            -ark_bn254::Fq::ONE,
+            -ark_bn254::Fq::from(3u32),
+            big,
+            rnd(),
+        ];
+
+        let a_v = rnd();
+        for c in constants {
+            let b_mont = Fq::as_montgomery(c);
+            let gates = |full: bool| {
+                let result = CircuitBuilder::streaming_execute::<_, _, FqOutput>(
+                    FqInput::new([Fq::as_montgomery(a_v)]),
+                    10_000,
+                    |ctx, input| {
+                        let [a] = input;
+                        if full {
+                            let product = crate::gadgets::bigint::mul_by_constant(
+                                ctx,
+                                &a.0,
+                                &b_mont.into_bigint().into(),
+                            );
+                            Fq::montgomery_reduce(ctx, &product)
+                        } else {
+                            Fq::mul_by_constant_montgomery(ctx, a, &b_mont)
+                        }
+                    },
+                );
+                assert_eq!(
+                    result.output_value.value,
+                    Fq::as_montgomery(a_v * c),
+                    "c = {c}"
+                );
+                result.gate_count.nonfree_gate_count()
+            };
+
+            let (chained, full) = (gates(false), gates(true));
+            if c == big || c == constants[7] {
+                assert_eq!(chained, full, "c = {c}");
+            } else {
+                assert!(chained * 2 < full, "c = {c}: {chained} vs {full}");
+            }
+        }
+    }
+
     #[test]
     fn test_fq_montgomery_reduce() {
         // Test with a random value multiplied by R (to create valid Montgomery form input)
@@ -657,6 +770,102 @@ pub(super) mod tests {
         assert_eq!(result.output_value.value, expected);
     }
 
+    #[test]
+    fn test_fq_typed_output_through_root_ctx() {
+        let (a_v, b_v) = (rnd(), rnd());
+        let input = FqInput::new([a_v, b_v]);
+        let circuit = |ctx: &mut StreamingMode<ExecuteMode>, [a, b]: &[Fq; 2]| Fq::add(ctx, a, b);
+
+        let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(&input);
+        let mut root_meta = StreamingMode::<ExecuteMode>::MetadataPass(root_meta);
+        let meta_output = circuit(&mut root_meta, &allocated_inputs);
+
+        let (mut ctx, allocated_inputs) =
+            root_meta.to_root_ctx(ExecuteMode::with_capacity(10_000), &input, &meta_output);
+        let output = circuit(&mut ctx, &allocated_inputs);
+
+        let decoded: FqOutput = ctx.decode_output(output);
+        assert_eq!(decoded.value, a_v + b_v);
+    }
+
+    #[test]
+    fn test_fq_from_bits_checked() {
+        let modulus = Fq::modulus_as_biguint();
+        let cases = [
+            (BigUint::ZERO, true),
+            (BigUint::from(rnd()), true),
+            (&modulus - 1u8, true),
+            (modulus.clone(), false),
+            (&modulus + 1u8, false),
+            ((BigUint::from(1u8) << Fq::N_BITS) - 1u8, false),
+        ];
+
+        for (value, valid) in cases {
+            let bits = bits_from_biguint_with_len(&value, Fq::N_BITS).unwrap();
+            let input: [bool; Fq::N_BITS] = array::from_fn(|i| bits[i]);
+
+            let result =
+                CircuitBuilder::streaming_execute::<_, _, Vec<bool>>(input, 10_000, |ctx, bits| {
+                    let (fq, valid) = Fq::from_bits_checked(ctx, bits.iter().copied());
+                    assert_eq!(fq.0.bits, bits);
+                    vec![valid]
+                });
+
+            assert_eq!(result.output_value, [valid], "value {value}");
+        }
+    }
+
+    #[test]
+    fn test_fq_sub_with_borrow() {
+        let modulus = Fq::modulus_as_biguint();
+        let max = &modulus - 1u8;
+        let mut rng = trng();
+        let x = BigUint::from(Fq::random(&mut rng));
+        let y = BigUint::from(Fq::random(&mut rng));
+        let cases = [
+            (BigUint::ZERO, BigUint::ZERO),
+            (BigUint::ZERO, BigUint::from(1u8)),
+            (BigUint::from(1u8), BigUint::ZERO),
+            (max.clone(), BigUint::ZERO),
+            (BigUint::ZERO, max.clone()),
+            (max.clone(), max),
+            (x.clone(), x.clone()),
+            (x.clone(), y.clone()),
+            (y, x),
+        ];
+
+        for (a, b) in cases {
+            let a_bits = bits_from_biguint_with_len(&a, Fq::N_BITS).unwrap();
+            let b_bits = bits_from_biguint_with_len(&b, Fq::N_BITS).unwrap();
+            let input: [bool; 2 * Fq::N_BITS] = array::from_fn(|i| {
+                if i < Fq::N_BITS {
+                    a_bits[i]
+                } else {
+                    b_bits[i - Fq::N_BITS]
+                }
+            });
+
+            let result =
+                CircuitBuilder::streaming_execute::<_, _, Vec<bool>>(input, 10_000, |ctx, bits| {
+                    let a = Fq(BigIntWires::from_bits(bits[..Fq::N_BITS].iter().copied()));
+                    let b = Fq(BigIntWires::from_bits(bits[Fq::N_BITS..].iter().copied()));
+                    let (difference, borrow) = Fq::sub_with_borrow(ctx, &a, &b);
+                    let mut wires = difference.to_wires_vec();
+                    wires.push(borrow);
+                    wires
+                });
+
+            let wrap = BigUint::from(1u8) << Fq::N_BITS;
+            let expected = (&a + &wrap - &b) % &wrap;
+            let mut expected = bits_from_biguint_with_len(&expected, Fq::N_BITS)
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<_>>();
+            expected.push(a < b);
+            assert_eq!(result.output_value, expected, "{a} - {b}");
+        }
+    }
+
     #[test]
     fn test_fq_sqrt_montgomery() {
         let a_v = rnd();
@@ -680,6 +889,26 @@ pub(super) mod tests {
         assert_eq!(result.output_value.value, expected_c);
     }
 
+    #[test]
+    fn test_fq_sqrt_checked_montgomery() {
+        let mut qnr = rnd();
+        while !qnr.legendre().is_qnr() {
+            qnr = rnd();
+        }
+        let a = rnd();
+
+        for (value, is_qr) in [(a * a, true), (qnr, false)] {
+            let input = FqInput::new([Fq::as_montgomery(value)]);
+            let result: crate::circuit::StreamingResult<_, _, Vec<bool>> =
+                CircuitBuilder::streaming_execute(input, 10_000, |ctx, input| {
+                    let [value] = input;
+                    vec![Fq::sqrt_checked_montgomery(ctx, value).1]
+                });
+
+            assert_eq!(result.output_value, [is_qr], "{value}");
+        }
+    }
+
     #[test]
     fn test_fq_multiplexer() {
         let w = 1;
@@ -4,9 +4,12 @@ use rand::Rng;
 
 use super::fq6::Fq6Components;
 use crate::{
-    CircuitContext, Gate, WireId,
-    circuit::{FromWires, WiresObject},
-    gadgets::bn254::{fq::Fq, fq2::Fq2, fq6::Fq6},
+    CircuitContext, WireId,
+    circuit::{CircuitOutput, ExecuteMode, FromWires, WiresObject},
+    gadgets::{
+        bigint,
+        bn254::{fq::Fq, fq2::Fq2, fq6::Fq6},
+    },
 };
 
 pub type Fq12Element<T> = (Fq6Components<T>, Fq6Components<T>);
@@ -16,11 +19,17 @@ pub struct Fq12(pub [Fq6; 2]);
 
 impl WiresObject for Fq12 {
     fn to_wires_vec(&self) -> Vec<WireId> {
-        self.0[0]
-            .to_wires_vec()
-            .into_iter()
-            .chain(self.0[1].to_wires_vec())
-            .collect()
+        let mut wires = Vec::with_capacity(self.wire_count());
+        self.for_each_wire(|wire| wires.push(wire));
+        wires
+    }
+
+    fn for_each_wire(&self, mut f: impl FnMut(WireId)) {
+        self.0.iter().for_each(|c| c.for_each_wire(&mut f))
+    }
+
+    fn wire_count(&self) -> usize {
+        self.0.iter().map(Fq6::wire_count).sum()
     }
 
     fn clone_from(&self, mut wire_gen: &mut impl FnMut() -> WireId) -> Self {
@@ -47,6 +56,19 @@ impl FromWires for Fq12 {
     }
 }
 
+/// Reads back an `Fq12` held in Montgomery form by decoding both of its `Fq6` halves.
+impl CircuitOutput<ExecuteMode> for ark_bn254::Fq12 {
+    type WireRepr = Fq12;
+
+    fn decode(wires: Self::WireRepr, cache: &mut ExecuteMode) -> Self {
+        let Fq12([c0, c1]) = wires;
+        ark_bn254::Fq12::new(
+            ark_bn254::Fq6::decode(c0, cache),
+            ark_bn254::Fq6::decode(c1, cache),
+        )
+    }
+}
+
 impl Fq12 {
     /// Access c0 component (first Fq6)
     pub fn c0(&self) -> &Fq6 {
@@ -162,9 +184,7 @@ impl Fq12 {
     ) -> WireId {
         let u = Fq6::equal_constant(circuit, a.c0(), &b.c0);
         let v = Fq6::equal_constant(circuit, a.c1(), &b.c1);
-        let w = circuit.issue_wire();
-        circuit.add_gate(Gate::and(u, v, w));
-        w
+        bigint::reduce_and(circuit, &[u, v])
     }
 
     pub fn add<C: CircuitContext>(circuit: &mut C, a: &Fq12, b: &Fq12) -> Fq12 {
@@ -323,6 +343,8 @@ impl Fq12 {
         Fq12::from_components(c0, c1)
     }
 
+    /// Granger-Scott squaring, valid only for elements of the cyclotomic subgroup (anything past
+    /// the easy part of the final exponentiation), where it is much cheaper than `square_montgomery`.
     pub fn cyclotomic_square_montgomery<C: CircuitContext>(circuit: &mut C, a: &Fq12) -> Fq12 {
         // https://eprint.iacr.org/2009/565.pdf
         // based on the implementation in arkworks-rs, fq12_2over3over2.rs
@@ -451,7 +473,7 @@ impl Fq12 {
 mod tests {
     use std::{array, str::FromStr};
 
-    use ark_ff::{CyclotomicMultSubgroup, PrimeField};
+    use ark_ff::{AdditiveGroup, CyclotomicMultSubgroup, PrimeField};
     use num_bigint::BigUint;
     use test_log::test;
 
@@ -463,7 +485,8 @@ mod tests {
         },
         gadgets::{
             bigint::{BigUint as BigUintOutput, bits_from_biguint_with_len},
-            bn254::{Fp254Impl, fq::Fq},
+            bn254::fq::Fq,
+            field::FpImpl,
         },
         test_utils::trng,
     };
@@ -904,6 +927,39 @@ mod tests {
         assert_eq!(result.output_value.value, expected);
     }
 
+    #[test]
+    fn test_fq12_mul_by_034_matches_dense_mul() {
+        let mut rng = trng();
+        let a = Fq12::random(&mut rng);
+        let (c0, c3, c4) = (
+            Fq2::random(&mut rng),
+            Fq2::random(&mut rng),
+            Fq2::random(&mut rng),
+        );
+        // A line evaluation: only the coefficients of 1, w^3 and w^4 are nonzero
+        let line = ark_bn254::Fq12::new(
+            ark_bn254::Fq6::new(c0, ark_bn254::Fq2::ZERO, ark_bn254::Fq2::ZERO),
+            ark_bn254::Fq6::new(c3, c4, ark_bn254::Fq2::ZERO),
+        );
+        let input = || Fq12Input::new([Fq12::as_montgomery(a), Fq12::as_montgomery(line)]);
+
+        let dense =
+            CircuitBuilder::streaming_execute::<_, _, Fq12Output>(input(), 10_000, |ctx, input| {
+                let [a, line] = input;
+                Fq12::mul_montgomery(ctx, a, line)
+            });
+        let sparse =
+            CircuitBuilder::streaming_execute::<_, _, Fq12Output>(input(), 10_000, |ctx, input| {
+                let [a, line] = input;
+                let [c0, c3, c4] = [&line.0[0].0[0], &line.0[1].0[0], &line.0[1].0[1]];
+                Fq12::mul_by_034_montgomery(ctx, a, c0, c3, c4)
+            });
+
+        assert_eq!(sparse.output_value.value, dense.output_value.value);
+        assert_eq!(sparse.output_value.value, Fq12::as_montgomery(a * line));
+        assert!(sparse.gate_count.nonfree_gate_count() < dense.gate_count.nonfree_gate_count());
+    }
+
     #[test]
     fn test_fq12_mul_by_034_constant4_montgomery() {
         let a = random();
@@ -1029,6 +1085,24 @@ mod tests {
         assert_eq!(result.output_value.value, expected);
     }
 
+    #[test]
+    fn test_fq12_cyclotomic_square_matches_square() {
+        // The easy part of the final exponentiation maps any element into the cyclotomic subgroup
+        let f = Fq12::random(&mut trng());
+        let f = f.frobenius_map(6) * f.inverse().unwrap();
+        let a = f.frobenius_map(2) * f;
+        let a_m = Fq12::as_montgomery(a);
+
+        let input = Fq12Input::new([a_m]);
+        let result =
+            CircuitBuilder::streaming_execute::<_, _, Fq12Output>(input, 10_000, |ctx, input| {
+                let [a] = input;
+                Fq12::cyclotomic_square_montgomery(ctx, a)
+            });
+
+        assert_eq!(result.output_value.value, Fq12::as_montgomery(a.square()));
+    }
+
     #[test]
     fn test_fq12_frobenius_montgomery() {
         for i in [0, 1, 2, 3] {
@@ -1052,6 +1126,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fq12_frobenius_twelve_times_is_identity() {
+        let a = random();
+
+        let input = Fq12Input::new([Fq12::as_montgomery(a)]);
+        let result = CircuitBuilder::streaming_execute::<_, _, ark_bn254::Fq12>(
+            input,
+            10_000,
+            |ctx, [a]| {
+                (0..12).fold(a.clone(), |power, _| {
+                    Fq12::frobenius_montgomery(ctx, &power, 1)
+                })
+            },
+        );
+
+        assert_eq!(result.output_value, a);
+    }
+
     #[test]
     fn test_fq12_inverse_montgomery() {
         let a = random();
@@ -9,14 +9,20 @@
 
 use ark_ff::{Field, Fp2Config, PrimeField};
 use circuit_component_macro::component;
+use num_bigint::BigUint;
+use num_traits::Zero;
 use rand::Rng;
 
 use crate::{
-    CircuitContext, Gate, WireId,
-    circuit::WiresObject,
+    CircuitContext, WireId,
+    circuit::{
+        CircuitMode, CircuitOutput, ExecuteMode, FromWires, FromWiresError, WireFields,
+        WiresObject, lookup_bits,
+    },
     gadgets::{
-        bigint::{BigIntWires, select},
-        bn254::{fp254impl::Fp254Impl, fq::Fq},
+        bigint::{self, BigIntWires, select},
+        bn254::fq::Fq,
+        field::FpImpl,
     },
 };
 
@@ -40,23 +46,49 @@ impl WiresObject for Fq2 {
         bits1.iter().chain(bits2.iter()).copied().collect()
     }
 
+    fn for_each_wire(&self, f: impl FnMut(WireId)) {
+        let [
+            Fq(BigIntWires { bits: bits1 }),
+            Fq(BigIntWires { bits: bits2 }),
+        ] = &self.0;
+
+        bits1.iter().chain(bits2.iter()).copied().for_each(f)
+    }
+
+    fn wire_count(&self) -> usize {
+        self.c0().wire_count() + self.c1().wire_count()
+    }
+
     fn clone_from(&self, wire_gen: &mut impl FnMut() -> WireId) -> Self {
         let Self([f1, f2]) = self;
         Self([f1.clone_from(wire_gen), f2.clone_from(wire_gen)])
     }
 }
 
-impl crate::circuit::FromWires for Fq2 {
+impl FromWires for Fq2 {
     fn from_wires(wires: &[WireId]) -> Option<Self> {
-        if wires.len() >= 508 {
-            // 2 * 254 bits
-            let (fq1_wires, fq2_wires) = wires.split_at(254);
-            let fq1 = Fq::from_wires(fq1_wires)?;
-            let fq2 = Fq::from_wires(fq2_wires)?;
-            Some(Self([fq1, fq2]))
-        } else {
-            None
-        }
+        Self::try_from_wires(wires).ok()
+    }
+
+    fn try_from_wires(wires: &[WireId]) -> Result<Self, FromWiresError> {
+        let mut fields = WireFields::new("Fq2", wires);
+        let c0 = fields.field("c0", Fq::N_BITS)?;
+        let c1 = fields.field("c1", Fq::N_BITS)?;
+        fields.finish()?;
+
+        Ok(Self([c0, c1]))
+    }
+}
+
+/// Reads back an `Fq2` the gadgets computed in Montgomery form, returning it in standard form.
+impl CircuitOutput<ExecuteMode> for ark_bn254::Fq2 {
+    type WireRepr = Fq2;
+
+    fn decode(wires: Self::WireRepr, cache: &mut ExecuteMode) -> Self {
+        let bits = lookup_bits(&wires, cache);
+
+        let (c0, c1) = bits.split_at(Fq::N_BITS);
+        Fq2::from_montgomery(Fq2::from_bits((c0.to_vec(), c1.to_vec())))
     }
 }
 
@@ -152,9 +184,34 @@ impl Fq2 {
     ) -> WireId {
         let u = Fq::equal_constant(circuit, a.c0(), &b.c0);
         let v = Fq::equal_constant(circuit, a.c1(), &b.c1);
-        let w = circuit.issue_wire();
-        circuit.add_gate(Gate::and(u, v, w));
-        w
+        bigint::reduce_and(circuit, &[u, v])
+    }
+
+    /// Whether `a == b`, comparing the bits of both components in one balanced tree
+    pub fn equal<C: CircuitContext>(circuit: &mut C, a: &Fq2, b: &Fq2) -> WireId {
+        let bits = |x: &Fq2| BigIntWires {
+            bits: x.iter().copied().collect(),
+        };
+        bigint::equal(circuit, &bits(a), &bits(b))
+    }
+
+    /// Whether `a` is zero, which in Montgomery form too means all its bits are clear: one
+    /// balanced NOR tree over both components.
+    pub fn is_zero<C: CircuitContext>(circuit: &mut C, a: &Fq2) -> WireId {
+        let bits = a.iter().copied().collect();
+        bigint::equal_zero(circuit, &BigIntWires { bits })
+    }
+
+    /// Select `a` if `flag` is set, `b` otherwise
+    pub fn conditional_select<C: CircuitContext>(
+        circuit: &mut C,
+        a: &Fq2,
+        b: &Fq2,
+        flag: WireId,
+    ) -> Fq2 {
+        let c0 = select(circuit, a.c0(), b.c0(), flag);
+        let c1 = select(circuit, a.c1(), b.c1(), flag);
+        Fq2::from_components(Fq(c0), Fq(c1))
     }
 
     pub fn add<C: CircuitContext>(circuit: &mut C, a: &Fq2, b: &Fq2) -> Fq2 {
@@ -371,6 +428,35 @@ impl Fq2 {
         Fq2::from_components(c0, c1)
     }
 
+    /// `a^exp` by left-to-right square-and-multiply, in Montgomery form
+    #[component(offcircuit_args = "exp")]
+    pub fn pow_by_constant_montgomery<C: CircuitContext>(
+        circuit: &mut C,
+        a: &Fq2,
+        exp: &BigUint,
+    ) -> Fq2 {
+        assert_eq!(a.c0().len(), Self::N_BITS / 2);
+        assert_eq!(a.c1().len(), Self::N_BITS / 2);
+
+        if exp.is_zero() {
+            let one = Fq2::as_montgomery(ark_bn254::Fq2::ONE);
+            return Fq2::from_components(
+                Fq::new_constant(&one.c0).unwrap(),
+                Fq::new_constant(&one.c1).unwrap(),
+            );
+        }
+
+        let mut result = a.clone();
+        for i in (0..exp.bits() - 1).rev() {
+            result = Self::square_montgomery(circuit, &result);
+            if exp.bit(i) {
+                result = Self::mul_montgomery(circuit, &result, a);
+            }
+        }
+
+        result
+    }
+
     pub fn frobenius_montgomery<C: CircuitContext>(circuit: &mut C, a: &Fq2, i: usize) -> Fq2 {
         assert_eq!(a.c0().len(), Self::N_BITS / 2);
         assert_eq!(a.c1().len(), Self::N_BITS / 2);
@@ -394,7 +480,7 @@ impl Fq2 {
     }
 
     // Calculate c0² + c1²
-    fn norm_montgomery<C: CircuitContext>(circuit: &mut C, c0: &Fq, c1: &Fq) -> Fq {
+    pub fn norm_montgomery<C: CircuitContext>(circuit: &mut C, c0: &Fq, c1: &Fq) -> Fq {
         let c0_square = Fq::square_montgomery(circuit, c0);
         let c1_square = Fq::square_montgomery(circuit, c1);
 
@@ -421,9 +507,21 @@ impl Fq2 {
         Fq2::from_components(Fq(c0_final), Fq(c1_final))
     }
 
-    // General case: c1 != 0
+    // General case: c1 != 0, by the complex method as arkworks does it, so the root is the one
+    // compressed points' sign flags are relative to. The exponent chain is two Fq square roots,
+    // (c0² + c1²)^((p+1)/4) and δ^((p+1)/4), one Legendre symbol δ^((p-1)/2) and one inverse.
+    // Also returns a wire set iff `a` is a quadratic residue, i.e. iff the root squares back to
+    // `a`; for a non-residue the root is meaningless.
+    pub fn sqrt_general_montgomery<C: CircuitContext>(circuit: &mut C, a: &Fq2) -> (Fq2, WireId) {
+        let root = Self::sqrt_general_root_montgomery(circuit, a);
+        let square = Self::square_montgomery(circuit, &root);
+        let is_qr = Self::equal(circuit, &square, a);
+        (root, is_qr)
+    }
+
+    // The root itself, assuming that it exists
     #[component]
-    pub fn sqrt_general_montgomery<C: CircuitContext>(circuit: &mut C, a: &Fq2) -> Fq2 {
+    fn sqrt_general_root_montgomery<C: CircuitContext>(circuit: &mut C, a: &Fq2) -> Fq2 {
         let alpha = Self::norm_montgomery(circuit, a.c0(), a.c1()); // c0² + c1²
         let alpha_sqrt = Fq::sqrt_montgomery(circuit, &alpha); // sqrt(norm)
 
@@ -456,12 +554,12 @@ mod tests {
     use super::*;
     use crate::{
         circuit::{
-            CircuitInput, CircuitOutput, EncodeInput,
+            CircuitInput, CircuitOutput, EncodeInput, FALSE_WIRE, TRUE_WIRE,
             modes::{CircuitMode, ExecuteMode},
         },
         gadgets::{
             bigint::{BigUint as BigUintOutput, bits_from_biguint_with_len},
-            bn254::fp254impl::Fp254Impl,
+            field::FpImpl,
         },
         test_utils::trng,
     };
@@ -567,6 +665,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fq2_is_zero_matches_equal_constant() {
+        let a = random();
+        let values = [
+            ark_bn254::Fq2::ZERO,
+            ark_bn254::Fq2::new(a.c0, ark_bn254::Fq::ZERO),
+            ark_bn254::Fq2::new(ark_bn254::Fq::ZERO, a.c1),
+            a,
+        ];
+
+        for value in values {
+            let run = |nor_tree: bool| {
+                let result = crate::circuit::CircuitBuilder::streaming_execute::<_, _, bool>(
+                    Fq2Input::new([value]),
+                    10_000,
+                    |ctx, input| {
+                        let [a] = input;
+                        if nor_tree {
+                            Fq2::is_zero(ctx, a)
+                        } else {
+                            Fq2::equal_constant(ctx, a, &ark_bn254::Fq2::ZERO)
+                        }
+                    },
+                );
+                (result.output_value, result.gate_count.nonfree_gate_count())
+            };
+
+            let (is_zero, nor_gates) = run(true);
+            let (equal, equal_gates) = run(false);
+            assert_eq!(is_zero, value.is_zero(), "{value}");
+            assert_eq!(is_zero, equal, "{value}");
+            // One AND per bit but the last. Zero already takes `equal_zero` per component, so
+            // joining the halves with a third AND costs the same.
+            assert_eq!(nor_gates, 2 * Fq::N_BITS as u64 - 1);
+            assert_eq!(nor_gates, equal_gates);
+        }
+    }
+
+    #[test]
+    fn test_fq2_conditional_select() {
+        let a = random();
+        let b = random();
+
+        for (flag, expected) in [(TRUE_WIRE, a), (FALSE_WIRE, b)] {
+            let input = Fq2Input::new([a, b]);
+            let result = crate::circuit::CircuitBuilder::streaming_execute::<_, _, Fq2Output>(
+                input,
+                10_000,
+                |ctx, input| {
+                    let [a, b] = input;
+                    Fq2::conditional_select(ctx, a, b, flag)
+                },
+            );
+
+            assert_eq!(result.output_value.value, expected);
+        }
+    }
+
     #[test]
     fn test_fq2_add() {
         let a = random();
@@ -604,6 +760,23 @@ mod tests {
         assert_eq!(result.output_value.value, expected);
     }
 
+    #[test]
+    fn test_fq2_decode_output() {
+        let a = random();
+
+        let input = Fq2Input::new([Fq2::as_montgomery(a)]);
+        let result = crate::circuit::CircuitBuilder::streaming_execute::<_, _, ark_bn254::Fq2>(
+            input,
+            10_000,
+            |ctx, input| {
+                let [a] = input;
+                Fq2::neg(ctx, a.clone())
+            },
+        );
+
+        assert_eq!(result.output_value, -a);
+    }
+
     #[test]
     fn test_fq2_sub() {
         let a = random();
@@ -850,6 +1023,55 @@ mod tests {
         assert_eq!(result.output_value.value, expected);
     }
 
+    #[test]
+    fn test_fq2_pow_by_constant_montgomery() {
+        let mut rng = trng();
+        let a = Fq2::random(&mut rng);
+        let exps = [
+            BigUint::ZERO,
+            BigUint::from(1u32),
+            BigUint::from(2u32),
+            BigUint::from(0b1011_0110u32),
+            BigUint::from(rng.r#gen::<u64>()),
+        ];
+
+        for exp in exps {
+            let expected = Fq2::as_montgomery(a.pow(exp.to_u64_digits()));
+
+            let input = Fq2Input::new([Fq2::as_montgomery(a)]);
+            let result = crate::circuit::CircuitBuilder::streaming_execute::<_, _, Fq2Output>(
+                input,
+                10_000,
+                |ctx, input| {
+                    let [a] = input;
+                    Fq2::pow_by_constant_montgomery(ctx, a, &exp)
+                },
+            );
+
+            assert_eq!(result.output_value.value, expected, "exponent {exp}");
+        }
+    }
+
+    #[test]
+    fn test_fq2_pow_by_constant_montgomery_fermat_inverse() {
+        let a = random();
+        let p = BigUint::from(ark_bn254::Fq::MODULUS);
+        let expected = Fq2::as_montgomery(a.inverse().unwrap());
+
+        let input = Fq2Input::new([Fq2::as_montgomery(a)]);
+        let result = crate::circuit::CircuitBuilder::streaming_execute::<_, _, Fq2Output>(
+            input,
+            10_000,
+            |ctx, input| {
+                let [a] = input;
+                // a^(p² - 2) = a^-1 in the multiplicative group of order p² - 1
+                Fq2::pow_by_constant_montgomery(ctx, a, &(&p * &p - 2u32))
+            },
+        );
+
+        assert_eq!(result.output_value.value, expected);
+    }
+
     #[test]
     fn test_fq2_frobenius_montgomery() {
         let a_val = random();
@@ -1004,20 +1226,51 @@ mod tests {
 
     #[test]
     fn test_fq2_sqrt_general_montgomery() {
-        let r = random();
-        let rr = r * r;
-        let expected = rr.sqrt().unwrap();
+        let mut rng = trng();
+        for _ in 0..4 {
+            let r = Fq2::random(&mut rng);
+            let rr = r * r;
+            let expected = rr.sqrt().unwrap();
 
-        let input = Fq2Input::new([Fq2::as_montgomery(rr)]);
-        let result = crate::circuit::CircuitBuilder::streaming_execute::<_, _, Fq2Output>(
-            input,
-            10_000,
-            |ctx, input| {
-                let [a] = input;
-                Fq2::sqrt_general_montgomery(ctx, a)
-            },
-        );
+            let input = Fq2Input::new([Fq2::as_montgomery(rr)]);
+            let result = crate::circuit::CircuitBuilder::streaming_execute::<_, _, Fq2Output>(
+                input,
+                10_000,
+                |ctx, input| {
+                    let [a] = input;
+                    Fq2::sqrt_general_montgomery(ctx, a).0
+                },
+            );
+
+            // Same root as arkworks, which compressed points' y flags are relative to
+            assert_eq!(result.output_value.value, Fq2::as_montgomery(expected));
+        }
+    }
+
+    #[test]
+    fn test_fq2_sqrt_general_montgomery_validity() {
+        let mut rng = trng();
+        let r = Fq2::random(&mut rng);
+        let residue = r * r;
+        let non_residue = loop {
+            let v = Fq2::random(&mut rng);
+            if v.legendre().is_qnr() {
+                break v;
+            }
+        };
 
-        assert_eq!(result.output_value.value, Fq2::as_montgomery(expected));
+        for (value, expected) in [(residue, true), (non_residue, false)] {
+            let input = Fq2Input::new([Fq2::as_montgomery(value)]);
+            let result = crate::circuit::CircuitBuilder::streaming_execute::<_, _, bool>(
+                input,
+                10_000,
+                |ctx, input| {
+                    let [a] = input;
+                    Fq2::sqrt_general_montgomery(ctx, a).1
+                },
+            );
+
+            assert_eq!(result.output_value, expected, "{value}");
+        }
     }
 }
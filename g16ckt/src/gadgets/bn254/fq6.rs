@@ -3,9 +3,13 @@ use rand::Rng;
 
 use super::fq2::Pair;
 use crate::{
-    CircuitContext, Gate, WireId,
-    circuit::{FromWires, WiresObject},
-    gadgets::bn254::fq2::Fq2,
+    CircuitContext, WireId,
+    circuit::{CircuitMode, CircuitOutput, ExecuteMode, FromWires, WiresObject, lookup_bits},
+    gadgets::{
+        bigint,
+        bn254::{fq::Fq, fq2::Fq2},
+        field::FpImpl,
+    },
 };
 
 pub type Fq6Components<T> = [Pair<T>; 3];
@@ -15,12 +19,17 @@ pub struct Fq6(pub [Fq2; 3]);
 
 impl WiresObject for Fq6 {
     fn to_wires_vec(&self) -> Vec<WireId> {
-        self.0[0]
-            .to_wires_vec()
-            .into_iter()
-            .chain(self.0[1].to_wires_vec())
-            .chain(self.0[2].to_wires_vec())
-            .collect()
+        let mut wires = Vec::with_capacity(self.wire_count());
+        self.for_each_wire(|wire| wires.push(wire));
+        wires
+    }
+
+    fn for_each_wire(&self, mut f: impl FnMut(WireId)) {
+        self.0.iter().for_each(|c| c.for_each_wire(&mut f))
+    }
+
+    fn wire_count(&self) -> usize {
+        self.0.iter().map(Fq2::wire_count).sum()
     }
 
     fn clone_from(&self, mut wire_gen: &mut impl FnMut() -> WireId) -> Self {
@@ -45,6 +54,20 @@ impl FromWires for Fq6 {
     }
 }
 
+/// Reads back an `Fq6` held in Montgomery form, coefficient by coefficient as [`Fq6::from_bits`]
+/// lays them out, returning it in standard form.
+impl CircuitOutput<ExecuteMode> for ark_bn254::Fq6 {
+    type WireRepr = Fq6;
+
+    fn decode(wires: Self::WireRepr, cache: &mut ExecuteMode) -> Self {
+        let bits = lookup_bits(&wires, cache);
+
+        let mut fqs = bits.chunks(Fq::N_BITS).map(<[bool]>::to_vec);
+        let components = std::array::from_fn(|_| (fqs.next().unwrap(), fqs.next().unwrap()));
+        Fq6::from_montgomery(Fq6::from_bits(components))
+    }
+}
+
 impl Fq6 {
     pub const N_BITS: usize = 3 * Fq2::N_BITS;
 
@@ -144,11 +167,7 @@ impl Fq6 {
         let u = Fq2::equal_constant(circuit, a.c0(), &b.c0);
         let v = Fq2::equal_constant(circuit, a.c1(), &b.c1);
         let w = Fq2::equal_constant(circuit, a.c2(), &b.c2);
-        let x = circuit.issue_wire();
-        let y = circuit.issue_wire();
-        circuit.add_gate(Gate::and(u, v, x));
-        circuit.add_gate(Gate::and(x, w, y));
-        y
+        bigint::reduce_and(circuit, &[u, v, w])
     }
 
     pub fn add<C: CircuitContext>(circuit: &mut C, a: &Fq6, b: &Fq6) -> Fq6 {
@@ -536,7 +555,7 @@ mod tests {
         },
         gadgets::{
             bigint::{BigUint as BigUintOutput, bits_from_biguint_with_len},
-            bn254::fp254impl::Fp254Impl,
+            field::FpImpl,
         },
         test_utils::trng,
     };
@@ -970,6 +989,20 @@ mod tests {
         assert_eq!(result.output_value.value, expected);
     }
 
+    #[test]
+    fn test_fq6_output_decodes_standard_form() {
+        let mut rng = trng();
+        let (a, b) = (Fq6::random(&mut rng), Fq6::random(&mut rng));
+
+        let input = Fq6Input::new([Fq6::as_montgomery(a), Fq6::as_montgomery(b)]);
+        let result = CircuitBuilder::streaming_execute::<_, _, ark_bn254::Fq6>(
+            input,
+            10_000,
+            |ctx, [a, b]| Fq6::mul_montgomery(ctx, a, b),
+        );
+        assert_eq!(result.output_value, a * b);
+    }
+
     #[test]
     fn test_fq6_inverse_montgomery() {
         let a = random();
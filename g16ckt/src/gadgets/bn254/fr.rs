@@ -9,19 +9,22 @@
 
 use std::ops::{Deref, DerefMut};
 
-use ark_ff::PrimeField;
+use ark_ff::{BigInteger, PrimeField};
 use num_bigint::BigUint;
 
-use super::super::bn254::fp254impl::Fp254Impl;
+use super::super::field::FpImpl;
 use crate::{
-    CircuitContext, WireId,
-    circuit::WiresObject,
+    CircuitContext, Gate, WireId,
+    circuit::{CircuitMode, CircuitOutput, ExecuteMode, FALSE_WIRE, WiresObject, lookup_bits},
     gadgets::{
         self,
         bigint::{self, BigIntWires, Error},
     },
 };
 
+/// Wires in a 32-byte little-endian scalar encoding
+pub const LE_BYTES_BITS: usize = 32 * 8;
+
 /// BN254 scalar field Fr implementation
 ///
 /// Represents elements in the scalar field Fr of the BN254 elliptic curve.
@@ -48,6 +51,14 @@ impl WiresObject for Fr {
         self.0.iter().copied().collect()
     }
 
+    fn for_each_wire(&self, f: impl FnMut(WireId)) {
+        self.0.iter().copied().for_each(f)
+    }
+
+    fn wire_count(&self) -> usize {
+        self.0.len()
+    }
+
     fn clone_from(&self, wire_gen: &mut impl FnMut() -> WireId) -> Self {
         Self(self.0.clone_from(wire_gen))
     }
@@ -61,7 +72,9 @@ impl crate::circuit::FromWires for Fr {
     }
 }
 
-impl Fp254Impl for Fr {
+impl FpImpl for Fr {
+    type Field = ark_bn254::Fr;
+
     const MODULUS: &'static str =
         "21888242871839275222246405745257275088548364400416034343698204186575808495617";
     const MONTGOMERY_M_INVERSE: &'static str =
@@ -82,6 +95,21 @@ impl Fp254Impl for Fr {
     }
 }
 
+/// Reads back an `Fr` held in Montgomery form, as the wires of [`Fr::as_montgomery`] inputs and
+/// of the Montgomery gadgets are, returning it in standard form.
+///
+/// Scalars fed by [`Fr::encode`], such as the groth16 public inputs, are in standard form and
+/// must be read with [`Fr::from_bits`] instead.
+impl CircuitOutput<ExecuteMode> for ark_bn254::Fr {
+    type WireRepr = Fr;
+
+    fn decode(wires: Self::WireRepr, cache: &mut ExecuteMode) -> Self {
+        let bits = lookup_bits(&wires, cache);
+
+        Fr::from_montgomery(Fr::from_bits(bits))
+    }
+}
+
 impl Fr {
     /// Create constant field element wires from a known value
     ///
@@ -106,6 +134,20 @@ impl Fr {
         Fr(BigIntWires::new(issue, Self::N_BITS))
     }
 
+    /// See [`FpImpl::from_bits_checked`]
+    pub fn from_bits_checked<C: CircuitContext>(
+        circuit: &mut C,
+        bits: impl IntoIterator<Item = WireId>,
+    ) -> (Fr, WireId) {
+        let (a, valid) = <Self as FpImpl>::from_bits_checked(circuit, bits);
+        (Fr(a), valid)
+    }
+
+    /// See [`FpImpl::is_reduced`]
+    pub fn is_reduced<C: CircuitContext>(circuit: &mut C, a: &Fr) -> WireId {
+        <Self as FpImpl>::is_reduced(circuit, &a.0)
+    }
+
     pub fn get_wire_bits_fn(
         wires: &Fr,
         value: &ark_bn254::Fr,
@@ -115,6 +157,21 @@ impl Fr {
             .get_wire_bits_fn(&BigUint::from(value.into_bigint()))
     }
 
+    /// Feed every wire of `wires` with its bit of `value`, as an input encoder does.
+    ///
+    /// Bits are read straight off the limbs, without the lookup [`Fr::get_wire_bits_fn`] builds,
+    /// so encoding thousands of public inputs doesn't allocate per element.
+    pub fn encode<M: CircuitMode<WireValue = bool>>(
+        wires: &Fr,
+        value: &ark_bn254::Fr,
+        cache: &mut M,
+    ) {
+        let value = value.into_bigint();
+        for (i, &wire_id) in wires.iter().enumerate() {
+            cache.feed_wire(wire_id, value.get_bit(i));
+        }
+    }
+
     /// Convert field element wires to a bitmask string for debugging
     ///
     /// # Arguments
@@ -176,9 +233,9 @@ impl Fr {
         ark_bn254::Fr::from(u)
     }
 
-    // Field arithmetic methods (directly using Fp254Impl trait methods)
+    // Field arithmetic methods (directly using FpImpl trait methods)
     pub fn add(circuit: &mut impl crate::CircuitContext, a: &Fr, b: &Fr) -> Fr {
-        Fr(<Self as Fp254Impl>::add(circuit, &a.0, &b.0))
+        Fr(<Self as FpImpl>::add(circuit, &a.0, &b.0))
     }
 
     pub fn add_constant(circuit: &mut impl crate::CircuitContext, a: &Fr, b: &ark_bn254::Fr) -> Fr {
@@ -190,35 +247,35 @@ impl Fr {
     }
 
     pub fn sub(circuit: &mut impl crate::CircuitContext, a: &Fr, b: &Fr) -> Fr {
-        Fr(<Self as Fp254Impl>::sub(circuit, &a.0, &b.0))
+        Fr(<Self as FpImpl>::sub(circuit, &a.0, &b.0))
     }
 
     pub fn neg(circuit: &mut impl crate::CircuitContext, a: &Fr) -> Fr {
-        Fr(<Self as Fp254Impl>::neg(circuit, &a.0))
+        Fr(<Self as FpImpl>::neg(circuit, &a.0))
     }
 
     pub fn double(circuit: &mut impl crate::CircuitContext, a: &Fr) -> Fr {
-        Fr(<Self as Fp254Impl>::double(circuit, &a.0))
+        Fr(<Self as FpImpl>::double(circuit, &a.0))
     }
 
     pub fn half(circuit: &mut impl crate::CircuitContext, a: &Fr) -> Fr {
-        Fr(<Self as Fp254Impl>::half(circuit, &a.0))
+        Fr(<Self as FpImpl>::half(circuit, &a.0))
     }
 
     pub fn triple(circuit: &mut impl crate::CircuitContext, a: &Fr) -> Fr {
-        Fr(<Self as Fp254Impl>::triple(circuit, &a.0))
+        Fr(<Self as FpImpl>::triple(circuit, &a.0))
     }
 
     pub fn div6(circuit: &mut impl crate::CircuitContext, a: &Fr) -> Fr {
-        Fr(<Self as Fp254Impl>::div6(circuit, &a.0))
+        Fr(<Self as FpImpl>::div6(circuit, &a.0))
     }
 
     pub fn inverse(circuit: &mut impl crate::CircuitContext, a: &Fr) -> Fr {
-        Fr(<Self as Fp254Impl>::inverse(circuit, &a.0))
+        Fr(<Self as FpImpl>::inverse(circuit, &a.0))
     }
 
     pub fn mul_montgomery(circuit: &mut impl crate::CircuitContext, a: &Fr, b: &Fr) -> Fr {
-        Fr(<Self as Fp254Impl>::mul_montgomery(circuit, &a.0, &b.0))
+        Fr(<Self as FpImpl>::mul_montgomery(circuit, &a.0, &b.0))
     }
 
     pub fn mul_by_constant_montgomery(
@@ -229,19 +286,19 @@ impl Fr {
         let b_mont = Self::as_montgomery(*b);
         let b_wires =
             BigIntWires::new_constant(Self::N_BITS, &BigUint::from(b_mont.into_bigint())).unwrap();
-        Fr(<Self as Fp254Impl>::mul_montgomery(circuit, &a.0, &b_wires))
+        Fr(<Self as FpImpl>::mul_montgomery(circuit, &a.0, &b_wires))
     }
 
     pub fn square_montgomery(circuit: &mut impl crate::CircuitContext, a: &Fr) -> Fr {
-        Fr(<Self as Fp254Impl>::square_montgomery(circuit, &a.0))
+        Fr(<Self as FpImpl>::square_montgomery(circuit, &a.0))
     }
 
     pub fn montgomery_reduce(circuit: &mut impl crate::CircuitContext, x: &BigIntWires) -> Fr {
-        Fr(<Self as Fp254Impl>::montgomery_reduce(circuit, x))
+        Fr(<Self as FpImpl>::montgomery_reduce(circuit, x))
     }
 
     pub fn inverse_montgomery(circuit: &mut impl crate::CircuitContext, a: &Fr) -> Fr {
-        Fr(<Self as Fp254Impl>::inverse_montgomery(circuit, &a.0))
+        Fr(<Self as FpImpl>::inverse_montgomery(circuit, &a.0))
     }
 
     pub fn exp_by_constant_montgomery(
@@ -249,7 +306,7 @@ impl Fr {
         a: &Fr,
         exp: &BigUint,
     ) -> Fr {
-        Fr(<Self as Fp254Impl>::exp_by_constant_montgomery(
+        Fr(<Self as FpImpl>::exp_by_constant_montgomery(
             circuit, &a.0, exp,
         ))
     }
@@ -261,12 +318,7 @@ impl Fr {
         w: usize,
     ) -> Fr {
         let bigint_array: Vec<BigIntWires> = a.iter().map(|fr| fr.0.clone()).collect();
-        Fr(<Self as Fp254Impl>::multiplexer(
-            circuit,
-            &bigint_array,
-            s,
-            w,
-        ))
+        Fr(<Self as FpImpl>::multiplexer(circuit, &bigint_array, s, w))
     }
 
     pub fn equal_constant(
@@ -276,15 +328,150 @@ impl Fr {
     ) -> WireId {
         bigint::equal_constant(circuit, &a.0, &BigUint::from(b.into_bigint()))
     }
+
+    /// Reinterpret a 32-byte little-endian encoding as a scalar in standard form, without gates.
+    ///
+    /// Bit `j` of byte `i` is wire `8 * i + j`, so the wires run from the least significant bit,
+    /// as [`Fr`]'s own bits do. The two top bits are dropped, so only use this when the encoding
+    /// is known to be canonical; otherwise see [`Fr::from_le_bytes_checked`].
+    pub fn from_le_bytes(bytes: &[WireId; LE_BYTES_BITS]) -> Fr {
+        Fr(BigIntWires::from_bits(
+            bytes[..Self::N_BITS].iter().copied(),
+        ))
+    }
+
+    /// Like [`Fr::from_le_bytes`], also returning a wire that is true iff the bytes encode a value
+    /// below the modulus
+    pub fn from_le_bytes_checked<C: CircuitContext>(
+        circuit: &mut C,
+        bytes: &[WireId; LE_BYTES_BITS],
+    ) -> (Fr, WireId) {
+        let fr = Self::from_le_bytes(bytes);
+
+        let high_zero = circuit.issue_wire();
+        circuit.add_gate(Gate::nor(
+            bytes[Self::N_BITS],
+            bytes[Self::N_BITS + 1],
+            high_zero,
+        ));
+        let below_modulus = Self::is_reduced(circuit, &fr);
+        let valid = circuit.issue_wire();
+        circuit.add_gate(Gate::and(high_zero, below_modulus, valid));
+
+        (fr, valid)
+    }
+
+    /// 32-byte little-endian encoding of `a`, the inverse of [`Fr::from_le_bytes`]
+    pub fn to_le_bytes(a: &Fr) -> [WireId; LE_BYTES_BITS] {
+        assert_eq!(a.len(), Self::N_BITS);
+
+        let mut bytes = [FALSE_WIRE; LE_BYTES_BITS];
+        for (byte_bit, bit) in bytes.iter_mut().zip(a.iter()) {
+            *byte_bit = *bit;
+        }
+        bytes
+    }
+
+    /// Signed fixed-window recoding of the integer held in `s` (its bits as they are, so the scalar
+    /// itself rather than its Montgomery form), for window-based scalar multiplication.
+    ///
+    /// Digits are `(negative, magnitude)` pairs, least significant first, with `w`-bit magnitudes
+    /// in `[0, 2^(w-1)]`, so that `s = sum((-1)^negative * magnitude * 2^(w * i))`. A table of
+    /// `2^(w-1) + 1` multiples and a conditional negation is then enough per window.
+    ///
+    /// A window is made negative when its value plus the incoming carry exceeds `2^(w-1)`, which
+    /// carries one into the next window. The top window only needs its own digit when it's
+    /// narrower than `w`; otherwise the final carry becomes one extra digit.
+    pub fn recode_wnaf<C: CircuitContext>(
+        circuit: &mut C,
+        s: &Fr,
+        w: usize,
+    ) -> Vec<(WireId, Vec<WireId>)> {
+        assert!(w >= 2, "window size must be at least 2");
+        assert_eq!(s.len(), Self::N_BITS);
+
+        let bits = s.iter().copied().collect::<Vec<_>>();
+        let mut digits = Vec::with_capacity(Self::N_BITS.div_ceil(w) + 1);
+        let mut carry = FALSE_WIRE;
+
+        for window in bits.chunks(w) {
+            let (mut value, carry_out) = Self::add_bit(circuit, window, carry);
+
+            if window.len() < w {
+                // At most 2^(w-1): nonnegative, and nothing is carried out
+                value.push(carry_out);
+                value.resize(w, FALSE_WIRE);
+                digits.push((FALSE_WIRE, value));
+                carry = FALSE_WIRE;
+                continue;
+            }
+
+            // value > 2^(w-1) iff it overflowed w bits, or bit w-1 is set along with a lower one
+            let lower_set = bigint::reduce_or(circuit, &value[..w - 1]);
+            let above_half = circuit.issue_wire();
+            circuit.add_gate(Gate::and(value[w - 1], lower_set, above_half));
+            let negative = circuit.issue_wire();
+            circuit.add_gate(Gate::or(carry_out, above_half, negative));
+
+            // 2^w - value, as the two's complement of the low w bits, when negative
+            let flipped = value
+                .iter()
+                .map(|&bit| {
+                    let flipped = circuit.issue_wire();
+                    circuit.add_gate(Gate::xor(bit, negative, flipped));
+                    flipped
+                })
+                .collect::<Vec<_>>();
+            let (magnitude, _) = Self::add_bit(circuit, &flipped, negative);
+
+            digits.push((negative, magnitude));
+            carry = negative;
+        }
+
+        if Self::N_BITS % w == 0 {
+            let mut magnitude = vec![FALSE_WIRE; w];
+            magnitude[0] = carry;
+            digits.push((FALSE_WIRE, magnitude));
+        }
+
+        digits
+    }
+
+    /// `bits + bit` as a ripple of half adders, returning the sum and the carry out
+    fn add_bit<C: CircuitContext>(
+        circuit: &mut C,
+        bits: &[WireId],
+        mut carry: WireId,
+    ) -> (Vec<WireId>, WireId) {
+        if carry == FALSE_WIRE {
+            return (bits.to_vec(), FALSE_WIRE);
+        }
+
+        let sum = bits
+            .iter()
+            .map(|&bit| {
+                let sum = circuit.issue_wire();
+                circuit.add_gate(Gate::xor(bit, carry, sum));
+                let carry_out = circuit.issue_wire();
+                circuit.add_gate(Gate::and(bit, carry, carry_out));
+                carry = carry_out;
+                sum
+            })
+            .collect();
+        (sum, carry)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use ark_ff::Field;
+    use ark_ff::{AdditiveGroup, Field};
     use rand::Rng;
 
     use super::*;
-    use crate::test_utils::trng;
+    use crate::{
+        circuit::{CircuitBuilder, CircuitInput, EncodeInput},
+        test_utils::trng,
+    };
 
     fn rnd() -> ark_bn254::Fr {
         loop {
@@ -303,4 +490,152 @@ mod tests {
         println!("v: {v:?}");
         assert_eq!(u, v);
     }
+
+    struct FrInput(ark_bn254::Fr);
+
+    impl CircuitInput for FrInput {
+        type WireRepr = Fr;
+
+        fn allocate(&self, issue: impl FnMut() -> WireId) -> Self::WireRepr {
+            Fr::new(issue)
+        }
+
+        fn collect_wire_ids(repr: &Self::WireRepr) -> Vec<WireId> {
+            repr.to_wires_vec()
+        }
+    }
+
+    impl<M: CircuitMode<WireValue = bool>> EncodeInput<M> for FrInput {
+        fn encode(&self, repr: &Self::WireRepr, cache: &mut M) {
+            for (wire, bit) in repr.iter().zip(Fr::to_bits(Fr::as_montgomery(self.0))) {
+                cache.feed_wire(*wire, bit);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fr_decode_output() {
+        let u = rnd();
+
+        let result = CircuitBuilder::streaming_execute::<_, _, ark_bn254::Fr>(
+            FrInput(u),
+            10_000,
+            |ctx, a| Fr::add(ctx, a, a),
+        );
+
+        assert_eq!(result.output_value, u + u);
+    }
+
+    fn le_bits(bytes: &[u8]) -> [bool; LE_BYTES_BITS] {
+        std::array::from_fn(|i| (bytes[i / 8] >> (i % 8)) & 1 == 1)
+    }
+
+    #[test]
+    fn test_fr_le_bytes_round_trip() {
+        use ark_ff::{BigInteger, UniformRand};
+
+        let mut rng = trng();
+        let modulus = Fr::modulus_as_biguint().to_bytes_le();
+        let mut cases = (0..4)
+            .map(|_| {
+                (
+                    ark_bn254::Fr::rand(&mut rng).into_bigint().to_bytes_le(),
+                    true,
+                )
+            })
+            .collect::<Vec<_>>();
+        cases.push((modulus, false));
+        cases.push((vec![0xff; 32], false));
+
+        for (bytes, valid) in cases {
+            let bits = le_bits(&bytes);
+            let result =
+                CircuitBuilder::streaming_execute::<_, _, Vec<bool>>(bits, 10_000, |ctx, bytes| {
+                    let (fr, valid) = Fr::from_le_bytes_checked(ctx, bytes);
+                    let mut out = Fr::to_le_bytes(&fr).to_vec();
+                    out.push(valid);
+                    out
+                });
+
+            let (out_bits, out_valid) = result.output_value.split_at(LE_BYTES_BITS);
+            assert_eq!(out_valid, [valid]);
+            if valid {
+                assert_eq!(out_bits, bits);
+                assert_eq!(
+                    Fr::from_bits(out_bits[..Fr::N_BITS].to_vec())
+                        .into_bigint()
+                        .to_bytes_le(),
+                    bytes
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_fr_from_bits_checked() {
+        let modulus = Fr::modulus_as_biguint();
+        let cases = [
+            (BigUint::from(rnd().into_bigint()), true),
+            (&modulus - 1u8, true),
+            (modulus.clone(), false),
+            ((BigUint::from(1u8) << Fr::N_BITS) - 1u8, false),
+        ];
+
+        for (value, valid) in cases {
+            let input: [bool; Fr::N_BITS] = std::array::from_fn(|i| value.bit(i as u64));
+
+            let result =
+                CircuitBuilder::streaming_execute::<_, _, Vec<bool>>(input, 10_000, |ctx, bits| {
+                    let (fr, valid) = Fr::from_bits_checked(ctx, bits.iter().copied());
+                    let mut out = vec![valid];
+                    out.extend(fr.iter());
+                    out
+                });
+
+            assert_eq!(result.output_value[0], valid, "value {value}");
+            assert_eq!(result.output_value[1..], input);
+        }
+    }
+
+    #[test]
+    fn test_fr_recode_wnaf_reconstructs_scalar() {
+        use num_bigint::BigInt;
+
+        let mut scalars = (0..16).map(|_| rnd()).collect::<Vec<_>>();
+        scalars.extend([ark_bn254::Fr::ZERO, ark_bn254::Fr::ONE, -ark_bn254::Fr::ONE]);
+
+        for w in [2, 3, 4, 5, 6] {
+            for &u in &scalars {
+                let bits: [bool; Fr::N_BITS] = Fr::to_bits(u).try_into().unwrap();
+                let result =
+                    CircuitBuilder::streaming_execute::<_, _, Vec<bool>>(bits, 10_000, |ctx, s| {
+                        let s = Fr(BigIntWires::from_bits(s.iter().copied()));
+                        Fr::recode_wnaf(ctx, &s, w)
+                            .into_iter()
+                            .flat_map(|(negative, magnitude)| {
+                                std::iter::once(negative).chain(magnitude)
+                            })
+                            .collect()
+                    });
+
+                let digits = result.output_value.chunks(w + 1).map(|digit| {
+                    let magnitude = digit[1..]
+                        .iter()
+                        .rev()
+                        .fold(BigInt::ZERO, |acc, &bit| (acc << 1) + u8::from(bit));
+                    assert!(magnitude <= BigInt::from(1u64 << (w - 1)), "w = {w}");
+                    if digit[0] { -magnitude } else { magnitude }
+                });
+                let reconstructed = digits
+                    .rev()
+                    .fold(BigInt::ZERO, |acc, digit| (acc << w) + digit);
+
+                assert_eq!(
+                    reconstructed,
+                    BigInt::from(BigUint::from(u.into_bigint())),
+                    "w = {w}"
+                );
+            }
+        }
+    }
 }
@@ -1,12 +1,22 @@
 use std::{cmp::min, collections::HashMap, iter};
 
-use ark_ff::Zero;
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ff::{Field, PrimeField, Zero};
 use circuit_component_macro::component;
+use num_bigint::BigUint;
 
 use crate::{
-    CircuitContext, WireId,
-    circuit::{FromWires, WiresObject},
-    gadgets::bn254::{fp254impl::Fp254Impl, fq::Fq, fr::Fr},
+    CircuitContext, Gate, WireId,
+    circuit::{
+        CircuitMode, CircuitOutput, ExecuteMode, FromWires, FromWiresError, WireFields,
+        WiresObject, lookup_bits,
+    },
+    gadgets::{
+        bigint,
+        bn254::{fq::Fq, fr::Fr},
+        field::FpImpl,
+        groth16::projective_to_affine_montgomery,
+    },
 };
 
 #[derive(Clone, Debug)]
@@ -18,13 +28,21 @@ pub struct G1Projective {
 
 impl WiresObject for G1Projective {
     fn to_wires_vec(&self) -> Vec<WireId> {
-        let mut wires = Vec::new();
-        wires.extend(self.x.to_wires_vec());
-        wires.extend(self.y.to_wires_vec());
-        wires.extend(self.z.to_wires_vec());
+        let mut wires = Vec::with_capacity(self.wire_count());
+        self.for_each_wire(|wire| wires.push(wire));
         wires
     }
 
+    fn for_each_wire(&self, mut f: impl FnMut(WireId)) {
+        self.x.for_each_wire(&mut f);
+        self.y.for_each_wire(&mut f);
+        self.z.for_each_wire(&mut f);
+    }
+
+    fn wire_count(&self) -> usize {
+        self.x.wire_count() + self.y.wire_count() + self.z.wire_count()
+    }
+
     fn clone_from(&self, mut wire_gen: &mut impl FnMut() -> WireId) -> Self {
         let Self { x, y, z } = self;
 
@@ -38,13 +56,33 @@ impl WiresObject for G1Projective {
 
 impl FromWires for G1Projective {
     fn from_wires(wires: &[WireId]) -> Option<Self> {
-        let len = wires.len() / 3;
-        let mut chunks = wires.chunks(len);
-        Some(Self {
-            x: Fq::from_wires(chunks.next()?)?,
-            y: Fq::from_wires(chunks.next()?)?,
-            z: Fq::from_wires(chunks.next()?)?,
-        })
+        Self::try_from_wires(wires).ok()
+    }
+
+    fn try_from_wires(wires: &[WireId]) -> Result<Self, FromWiresError> {
+        let mut fields = WireFields::new("G1Projective", wires);
+        let point = Self {
+            x: fields.field("x", Fq::N_BITS)?,
+            y: fields.field("y", Fq::N_BITS)?,
+            z: fields.field("z", Fq::N_BITS)?,
+        };
+        fields.finish()?;
+
+        Ok(point)
+    }
+}
+
+/// Reads back a projective G1 point whose coordinates are in Montgomery form, returning it with
+/// standard-form coordinates. The point is not checked to be on the curve.
+// Same type as `ark_bn254::G1Projective`, spelled without the associated-type alias so
+// coherence can tell the G1 and G2 impls apart.
+impl CircuitOutput<ExecuteMode> for Projective<ark_bn254::g1::Config> {
+    type WireRepr = G1Projective;
+
+    fn decode(wires: Self::WireRepr, cache: &mut ExecuteMode) -> Self {
+        let bits = lookup_bits(&wires, cache);
+
+        G1Projective::from_montgomery(G1Projective::from_bits_unchecked(bits))
     }
 }
 
@@ -152,9 +190,39 @@ impl G1Projective {
 
         Ok(move |wire_id: WireId| bits.get(&wire_id).copied())
     }
+
+    /// Feed every wire of `wires` with its bit of `value`, as an input encoder does
+    pub fn encode<M: CircuitMode<WireValue = bool>>(
+        wires: &G1Projective,
+        value: &ark_bn254::G1Projective,
+        cache: &mut M,
+    ) {
+        let bits = Self::get_wire_bits_fn(wires, value).unwrap();
+        wires.for_each_wire(|wire_id| {
+            if let Some(bit) = bits(wire_id) {
+                cache.feed_wire(wire_id, bit);
+            }
+        });
+    }
 }
 
 impl G1Projective {
+    /// Whether `p` is the point at infinity, i.e. `z == 0`
+    pub fn is_infinity<C: CircuitContext>(circuit: &mut C, p: &G1Projective) -> WireId {
+        bigint::equal_zero(circuit, &p.z.0)
+    }
+
+    /// `x^3 + b` in Montgomery form, the `y^2` of any affine point with this `x`
+    pub fn y_square_montgomery<C: CircuitContext>(circuit: &mut C, x: &Fq) -> Fq {
+        let x2 = Fq::square_montgomery(circuit, x);
+        let x3 = Fq::mul_montgomery(circuit, &x2, x);
+        Fq::add_constant(
+            circuit,
+            &x3,
+            &Fq::as_montgomery(ark_bn254::g1::Config::COEFF_B),
+        )
+    }
+
     // http://koclab.cs.ucsb.edu/teaching/ccs130h/2018/09projective.pdf
     #[component]
     pub fn add_montgomery<C: CircuitContext>(
@@ -205,33 +273,27 @@ impl G1Projective {
         let z1z2 = Fq::mul_montgomery(circuit, z1, z2);
         let z3 = Fq::mul_montgomery(circuit, &z1z2, &h);
 
-        let z1_0 = Fq::equal_constant(circuit, z1, &ark_bn254::Fq::zero());
-        let z2_0 = Fq::equal_constant(circuit, z2, &ark_bn254::Fq::zero());
+        let z1_0 = Self::is_infinity(circuit, p);
+        let z2_0 = Self::is_infinity(circuit, q);
 
         let zero = Fq::new_constant(&ark_bn254::Fq::zero()).unwrap();
+        let sum = G1Projective {
+            x: x3,
+            y: y3,
+            z: z3,
+        };
+        let infinity = G1Projective {
+            x: zero.clone(),
+            y: zero.clone(),
+            z: zero,
+        };
 
-        let s = [z1_0, z2_0];
-
-        let x = Fq::multiplexer(
-            circuit,
-            &[x3.clone(), x2.clone(), x1.clone(), zero.clone()],
-            &s,
-            2,
-        );
-        let y = Fq::multiplexer(
+        Self::multiplexer(
             circuit,
-            &[y3.clone(), y2.clone(), y1.clone(), zero.clone()],
-            &s,
+            &[sum, q.clone(), p.clone(), infinity],
+            &[z1_0, z2_0],
             2,
-        );
-        let z = Fq::multiplexer(
-            circuit,
-            &[z3.clone(), z2.clone(), z1.clone(), zero.clone()],
-            &s,
-            2,
-        );
-
-        G1Projective { x, y, z }
+        )
     }
 
     pub fn double_montgomery<C: CircuitContext>(circuit: &mut C, p: &G1Projective) -> G1Projective {
@@ -264,7 +326,7 @@ impl G1Projective {
         let yz = Fq::mul_montgomery(circuit, y1, z1);
         let zr = Fq::double(circuit, &yz);
 
-        let z_0 = Fq::equal_constant(circuit, z1, &ark_bn254::Fq::zero()); //equal _zero _function ?
+        let z_0 = Self::is_infinity(circuit, p);
         let zero = Fq::new_constant(&ark_bn254::Fq::zero()).unwrap();
         // let z = Fq::multiplexer(circuit, &[&x3, x2, x1, &zero], &s, 1);
         let z = Fq::multiplexer(circuit, &[zr.clone(), zero.clone()], &[z_0], 1);
@@ -272,6 +334,20 @@ impl G1Projective {
         G1Projective { x: xr, y: yr, z }
     }
 
+    /// Select `a` if `flag` is set, `b` otherwise
+    pub fn conditional_select<C: CircuitContext>(
+        circuit: &mut C,
+        a: &G1Projective,
+        b: &G1Projective,
+        flag: WireId,
+    ) -> G1Projective {
+        G1Projective {
+            x: Fq(bigint::select(circuit, &a.x.0, &b.x.0, flag)),
+            y: Fq(bigint::select(circuit, &a.y.0, &b.y.0, flag)),
+            z: Fq(bigint::select(circuit, &a.z.0, &b.z.0, flag)),
+        }
+    }
+
     #[component(offcircuit_args = "w")]
     pub fn multiplexer<C: CircuitContext>(
         circuit: &mut C,
@@ -305,14 +381,25 @@ impl G1Projective {
         }
     }
 
-    #[component(offcircuit_args = "base")]
     pub fn scalar_mul_by_constant_base_montgomery<const W: usize, C: CircuitContext>(
         circuit: &mut C,
         s: &Fr,
         base: &ark_bn254::G1Projective,
+    ) -> G1Projective {
+        Self::scalar_mul_by_constant_base_montgomery_dyn(circuit, s, base, W)
+    }
+
+    /// Same as [`Self::scalar_mul_by_constant_base_montgomery`], with the window size chosen at
+    /// runtime.
+    #[component(offcircuit_args = "base, w")]
+    pub fn scalar_mul_by_constant_base_montgomery_dyn<C: CircuitContext>(
+        circuit: &mut C,
+        s: &Fr,
+        base: &ark_bn254::G1Projective,
+        w: usize,
     ) -> G1Projective {
         assert_eq!(s.len(), Fr::N_BITS);
-        let n = 2_usize.pow(W as u32);
+        let n = 2_usize.pow(w as u32);
 
         let mut bases = Vec::new();
         let mut p = ark_bn254::G1Projective::default();
@@ -334,16 +421,21 @@ impl G1Projective {
 
         let mut index = 0;
         while index < Fr::N_BITS {
-            let w = min(W, Fr::N_BITS - index);
-            let m = 2_usize.pow(w as u32);
-            let selector = s.iter().skip(index).take(w).copied().collect::<Vec<_>>();
-            let result = Self::multiplexer(circuit, &bases_wires[0..m], &selector, w);
+            let window = min(w, Fr::N_BITS - index);
+            let m = 2_usize.pow(window as u32);
+            let selector = s
+                .iter()
+                .skip(index)
+                .take(window)
+                .copied()
+                .collect::<Vec<_>>();
+            let result = Self::multiplexer(circuit, &bases_wires[0..m], &selector, window);
             to_be_added.push(result);
-            index += W;
+            index += w;
             let mut new_bases = Vec::new();
             for b in bases {
                 let mut new_b = b;
-                for _ in 0..w {
+                for _ in 0..window {
                     new_b = new_b + new_b;
                 }
                 new_bases.push(new_b);
@@ -409,16 +501,140 @@ impl G1Projective {
     }
 }
 
+/// gnark's compressed encoding of G1 points: the big-endian bytes of the affine x, with the flags
+/// in the two top bits of the first byte, which x never reaches as it is below `2^254`.
+///
+/// In wires, bit `j` of byte `i` (counting from the least significant bit) is at `8 * i + j`.
+impl G1Projective {
+    pub const COMPRESSED_BITS: usize = 32 * 8;
+
+    /// Set on every compressed point but infinity
+    const COMPRESSED_FLAG: usize = 7;
+    /// On a finite point, set iff `y` is the lexicographically largest of `±y`, i.e. above
+    /// `(p - 1) / 2`; alone, it marks infinity, whose x bytes are all zero.
+    const LARGEST_FLAG: usize = 6;
+
+    /// Position of bit `k` of x among the compressed bits
+    fn compressed_x_bit(k: usize) -> usize {
+        8 * (31 - k / 8) + k % 8
+    }
+
+    /// Whether `y`, in standard form, is at most `(p - 1) / 2`
+    fn is_lexicographically_smallest<C: CircuitContext>(circuit: &mut C, y: &Fq) -> WireId {
+        let half = BigUint::from(ark_bn254::Fq::MODULUS_MINUS_ONE_DIV_TWO) + 1u32;
+        bigint::less_than_constant(circuit, &y.0, &half)
+    }
+
+    /// Compress `p`, in Montgomery form, to gnark's 32-byte layout
+    pub fn serialize_compressed<C: CircuitContext>(
+        circuit: &mut C,
+        p: &G1Projective,
+    ) -> [WireId; Self::COMPRESSED_BITS] {
+        let infinity = Self::is_infinity(circuit, p);
+        let affine = projective_to_affine_montgomery(circuit, p);
+        let x = Fq::mul_by_constant_montgomery(circuit, &affine.x, &ark_bn254::Fq::ONE);
+        let y = Fq::mul_by_constant_montgomery(circuit, &affine.y, &ark_bn254::Fq::ONE);
+
+        // Infinity has no affine form, so its x is zeroed whatever the inverse of z gave
+        let x = bigint::self_or_zero_inv(circuit, &x.0, infinity);
+        let smallest = Self::is_lexicographically_smallest(circuit, &y);
+
+        let mut bytes = [WireId::UNREACHABLE; Self::COMPRESSED_BITS];
+        for (k, bit) in x.iter().enumerate() {
+            bytes[Self::compressed_x_bit(k)] = *bit;
+        }
+
+        let compressed = circuit.issue_wire();
+        circuit.add_gate(Gate::not_with_xor(infinity, compressed));
+        bytes[Self::COMPRESSED_FLAG] = compressed;
+
+        // infinity || !smallest
+        let largest = circuit.issue_wire();
+        circuit.add_gate(Gate::and_variant(
+            infinity,
+            smallest,
+            largest,
+            [true, false, true],
+        ));
+        bytes[Self::LARGEST_FLAG] = largest;
+
+        bytes
+    }
+
+    /// Decompress a point in gnark's 32-byte layout to Montgomery form, along with a wire that is
+    /// true iff the encoding is valid: a compressed x below the modulus whose point is on the curve,
+    /// or the encoding of infinity. G1 has prime order, so such a point is also in the subgroup.
+    pub fn deserialize_checked<C: CircuitContext>(
+        circuit: &mut C,
+        bytes: &[WireId; Self::COMPRESSED_BITS],
+    ) -> (G1Projective, WireId) {
+        let compressed = bytes[Self::COMPRESSED_FLAG];
+        let largest = bytes[Self::LARGEST_FLAG];
+
+        let (x, x_valid) = Fq::from_bits_checked(
+            circuit,
+            (0..Fq::N_BITS).map(|k| bytes[Self::compressed_x_bit(k)]),
+        );
+        let x_zero = bigint::equal_zero(circuit, &x.0);
+
+        // x * R^2 / R
+        let r_square = Fq::as_montgomery(Fq::as_montgomery(ark_bn254::Fq::ONE));
+        let x_m = Fq::mul_by_constant_montgomery(circuit, &x, &r_square);
+
+        let rhs = Self::y_square_montgomery(circuit, &x_m);
+        let (y_m, on_curve) = Fq::sqrt_checked_montgomery(circuit, &rhs);
+
+        // Take -y when the root's sign disagrees with the flag
+        let y = Fq::mul_by_constant_montgomery(circuit, &y_m, &ark_bn254::Fq::ONE);
+        let smallest = Self::is_lexicographically_smallest(circuit, &y);
+        let negate = circuit.issue_wire();
+        circuit.add_gate(Gate::xnor(smallest, largest, negate));
+        let neg_y_m = Fq::neg(circuit, &y_m);
+        let y_m = Fq(bigint::select(circuit, &neg_y_m.0, &y_m.0, negate));
+
+        let finite = G1Projective {
+            x: x_m,
+            y: y_m,
+            z: Fq::new_constant(&Fq::as_montgomery(ark_bn254::Fq::ONE)).unwrap(),
+        };
+        let infinity =
+            G1Projective::new_constant(&Self::as_montgomery(ark_bn254::G1Projective::zero()));
+
+        // !compressed && largest && x == 0
+        let infinity_flags = circuit.issue_wire();
+        circuit.add_gate(Gate::and_variant(
+            compressed,
+            largest,
+            infinity_flags,
+            [true, false, false],
+        ));
+        let is_infinity = circuit.issue_wire();
+        circuit.add_gate(Gate::and(infinity_flags, x_zero, is_infinity));
+
+        let finite_checks = circuit.issue_wire();
+        circuit.add_gate(Gate::and(x_valid, on_curve, finite_checks));
+        let finite_valid = circuit.issue_wire();
+        circuit.add_gate(Gate::and(compressed, finite_checks, finite_valid));
+        let valid = circuit.issue_wire();
+        circuit.add_gate(Gate::or(finite_valid, is_infinity, valid));
+
+        let point = Self::conditional_select(circuit, &infinity, &finite, is_infinity);
+        (point, valid)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use ark_ec::{CurveGroup, VariableBaseMSM};
-    use ark_ff::UniformRand;
+    use ark_ec::{CurveGroup, PrimeGroup, VariableBaseMSM};
+    use ark_ff::{BigInteger, UniformRand};
     use rand::{Rng, SeedableRng};
     use rand_chacha::ChaCha20Rng;
 
     use super::*;
     use crate::{
-        circuit::{CircuitBuilder, CircuitInput, EncodeInput, modes::CircuitMode},
+        circuit::{
+            CircuitBuilder, CircuitInput, EncodeInput, FALSE_WIRE, TRUE_WIRE, modes::CircuitMode,
+        },
         test_utils::trng,
     };
 
@@ -428,7 +644,7 @@ mod tests {
     }
 
     pub fn rnd_g1(rng: &mut impl Rng) -> ark_bn254::G1Projective {
-        ark_bn254::G1Projective::default() * rnd_fr(rng)
+        ark_bn254::G1Projective::generator() * rnd_fr(rng)
     }
 
     // Standardized input/output structures for G1 tests
@@ -461,23 +677,38 @@ mod tests {
     impl<const N: usize, M: CircuitMode<WireValue = bool>> EncodeInput<M> for G1Input<N> {
         fn encode(&self, repr: &Self::WireRepr, cache: &mut M) {
             for (point_wire, point_val) in repr.points.iter().zip(self.points.iter()) {
-                let point_fn = G1Projective::get_wire_bits_fn(point_wire, point_val).unwrap();
-                for &wire_id in point_wire
-                    .x
-                    .iter()
-                    .chain(point_wire.y.iter())
-                    .chain(point_wire.z.iter())
-                {
-                    if let Some(bit) = point_fn(wire_id) {
-                        cache.feed_wire(wire_id, bit);
-                    }
-                }
+                G1Projective::encode(point_wire, point_val, cache);
             }
         }
     }
 
+    pub struct ScalarInput {
+        pub s: ark_bn254::Fr,
+    }
+
+    pub struct ScalarInputWire {
+        pub s: Fr,
+    }
+
+    impl CircuitInput for ScalarInput {
+        type WireRepr = ScalarInputWire;
+
+        fn allocate(&self, issue: impl FnMut() -> WireId) -> Self::WireRepr {
+            ScalarInputWire { s: Fr::new(issue) }
+        }
+
+        fn collect_wire_ids(repr: &Self::WireRepr) -> Vec<WireId> {
+            repr.s.iter().cloned().collect()
+        }
+    }
+
+    impl<M: CircuitMode<WireValue = bool>> EncodeInput<M> for ScalarInput {
+        fn encode(&self, repr: &ScalarInputWire, cache: &mut M) {
+            Fr::encode(&repr.s, &self.s, cache);
+        }
+    }
+
     fn rnd() -> ark_bn254::G1Projective {
-        use ark_ec::PrimeGroup;
         let g1 = ark_bn254::G1Projective::generator();
         g1.mul_bigint(<rand::rngs::StdRng as SeedableRng>::seed_from_u64(1).r#gen::<[u64; 4]>())
     }
@@ -485,8 +716,9 @@ mod tests {
     #[test]
     fn test_g1p_add_montgomery() {
         // Generate random G1 points
-        let a = rnd_g1(&mut trng());
-        let b = rnd_g1(&mut trng());
+        let mut rng = trng();
+        let a = rnd_g1(&mut rng);
+        let b = rnd_g1(&mut rng);
         let c = a + b;
 
         // Convert to Montgomery form
@@ -524,30 +756,8 @@ mod tests {
         }
         impl<M: CircuitMode<WireValue = bool>> EncodeInput<M> for TwoG1Inputs {
             fn encode(&self, repr: &TwoG1InputsWire, cache: &mut M) {
-                let a_fn = G1Projective::get_wire_bits_fn(&repr.a, &self.a).unwrap();
-                let b_fn = G1Projective::get_wire_bits_fn(&repr.b, &self.b).unwrap();
-                for &wire_id in repr
-                    .a
-                    .x
-                    .iter()
-                    .chain(repr.a.y.iter())
-                    .chain(repr.a.z.iter())
-                {
-                    if let Some(bit) = a_fn(wire_id) {
-                        cache.feed_wire(wire_id, bit);
-                    }
-                }
-                for &wire_id in repr
-                    .b
-                    .x
-                    .iter()
-                    .chain(repr.b.y.iter())
-                    .chain(repr.b.z.iter())
-                {
-                    if let Some(bit) = b_fn(wire_id) {
-                        cache.feed_wire(wire_id, bit);
-                    }
-                }
+                G1Projective::encode(&repr.a, &self.a, cache);
+                G1Projective::encode(&repr.b, &self.b, cache);
             }
         }
 
@@ -604,18 +814,7 @@ mod tests {
         }
         impl<M: CircuitMode<WireValue = bool>> EncodeInput<M> for OneG1Input {
             fn encode(&self, repr: &OneG1InputWire, cache: &mut M) {
-                let a_fn = G1Projective::get_wire_bits_fn(&repr.a, &self.a).unwrap();
-                for &wire_id in repr
-                    .a
-                    .x
-                    .iter()
-                    .chain(repr.a.y.iter())
-                    .chain(repr.a.z.iter())
-                {
-                    if let Some(bit) = a_fn(wire_id) {
-                        cache.feed_wire(wire_id, bit);
-                    }
-                }
+                G1Projective::encode(&repr.a, &self.a, cache);
             }
         }
 
@@ -634,14 +833,106 @@ mod tests {
         assert_eq!(actual_result, c_mont);
     }
 
+    /// `add_montgomery` of two input points, in Montgomery form
+    fn add_in_circuit(
+        a: ark_bn254::G1Projective,
+        b: ark_bn254::G1Projective,
+    ) -> ark_bn254::G1Projective {
+        let inputs = G1Input {
+            points: [
+                G1Projective::as_montgomery(a),
+                G1Projective::as_montgomery(b),
+            ],
+        };
+        let result: crate::circuit::StreamingResult<_, _, Vec<bool>> =
+            CircuitBuilder::streaming_execute(inputs, 10_000, |root, inputs_wire| {
+                let [a, b] = &inputs_wire.points;
+                G1Projective::add_montgomery(root, a, b).to_wires_vec()
+            });
+        G1Projective::from_bits_unchecked(result.output_value)
+    }
+
+    #[test]
+    fn test_g1p_add_montgomery_infinity() {
+        let a = rnd_g1(&mut trng());
+        let zero = ark_bn254::G1Projective::zero();
+
+        for (p, q) in [(a, zero), (zero, a), (zero, zero), (a, -a)] {
+            assert_eq!(
+                add_in_circuit(p, q),
+                G1Projective::as_montgomery(p + q),
+                "{p} + {q}"
+            );
+        }
+    }
+
+    /// The addition formula doesn't cover doubling: with `p == q` both `h` and `r` vanish and the
+    /// result is the point at infinity. Callers that may see equal points use `double_montgomery`.
+    #[test]
+    fn test_g1p_add_montgomery_equal_points() {
+        let a = rnd_g1(&mut trng());
+
+        assert!(add_in_circuit(a, a).is_zero());
+    }
+
+    #[test]
+    fn test_g1p_double_montgomery_infinity() {
+        let inputs = G1Input {
+            points: [G1Projective::as_montgomery(ark_bn254::G1Projective::zero())],
+        };
+        let result: crate::circuit::StreamingResult<_, _, Vec<bool>> =
+            CircuitBuilder::streaming_execute(inputs, 10_000, |root, inputs_wire| {
+                G1Projective::double_montgomery(root, &inputs_wire.points[0]).to_wires_vec()
+            });
+
+        assert!(G1Projective::from_bits_unchecked(result.output_value).is_zero());
+    }
+
+    #[test]
+    fn test_g1p_is_infinity() {
+        let generator = ark_bn254::G1Projective::generator();
+        let identity = ark_bn254::G1Projective::zero();
+
+        for (point, expected) in [(generator, false), (identity, true)] {
+            let inputs = G1Input {
+                points: [G1Projective::as_montgomery(point)],
+            };
+            let result: crate::circuit::StreamingResult<_, _, bool> =
+                CircuitBuilder::streaming_execute(inputs, 10_000, |root, inputs_wire| {
+                    G1Projective::is_infinity(root, &inputs_wire.points[0])
+                });
+
+            assert_eq!(result.output_value, expected);
+        }
+    }
+
+    #[test]
+    fn test_g1p_conditional_select() {
+        let a = G1Projective::as_montgomery(rnd());
+        let b = G1Projective::as_montgomery(rnd() + rnd());
+
+        for (flag, expected) in [(TRUE_WIRE, a), (FALSE_WIRE, b)] {
+            let inputs = G1Input { points: [a, b] };
+            let result: crate::circuit::StreamingResult<_, _, Vec<bool>> =
+                CircuitBuilder::streaming_execute(inputs, 10_000, |root, inputs_wire| {
+                    let [a, b] = &inputs_wire.points;
+                    G1Projective::conditional_select(root, a, b, flag).to_wires_vec()
+                });
+
+            let actual_result = G1Projective::from_bits_unchecked(result.output_value.clone());
+            assert_eq!(actual_result, expected);
+        }
+    }
+
     #[test]
     fn test_g1p_multiplexer() {
         let w = 2;
         let n = 2_usize.pow(w as u32);
+        let mut rng = trng();
         let a_val = (0..n)
-            .map(|_| G1Projective::as_montgomery(rnd_g1(&mut trng())))
+            .map(|_| G1Projective::as_montgomery(rnd_g1(&mut rng)))
             .collect::<Vec<_>>();
-        let s_val = (0..w).map(|_| trng().r#gen()).collect::<Vec<_>>();
+        let s_val = (0..w).map(|_| rng.r#gen()).collect::<Vec<_>>();
 
         let mut u = 0;
         for i in s_val.iter().rev() {
@@ -682,17 +973,7 @@ mod tests {
         impl<M: CircuitMode<WireValue = bool>> EncodeInput<M> for MultiplexerInputs {
             fn encode(&self, repr: &MultiplexerInputsWire, cache: &mut M) {
                 for (g1_wire, g1_val) in repr.a.iter().zip(self.a.iter()) {
-                    let g1_fn = G1Projective::get_wire_bits_fn(g1_wire, g1_val).unwrap();
-                    for &wire_id in g1_wire
-                        .x
-                        .iter()
-                        .chain(g1_wire.y.iter())
-                        .chain(g1_wire.z.iter())
-                    {
-                        if let Some(bit) = g1_fn(wire_id) {
-                            cache.feed_wire(wire_id, bit);
-                        }
-                    }
+                    G1Projective::encode(g1_wire, g1_val, cache);
                 }
                 for (&wire_id, &bit) in repr.s.iter().zip(self.s.iter()) {
                     cache.feed_wire(wire_id, bit);
@@ -718,37 +999,11 @@ mod tests {
 
     #[test]
     fn test_g1p_scalar_mul_with_constant_base_montgomery() {
-        let s = rnd_fr(&mut trng());
-        let p = rnd_g1(&mut trng());
+        let mut rng = trng();
+        let s = rnd_fr(&mut rng);
+        let p = rnd_g1(&mut rng);
         let result = p * s;
 
-        // Define input structure
-        struct ScalarInput {
-            s: ark_bn254::Fr,
-        }
-        struct ScalarInputWire {
-            s: Fr,
-        }
-        impl crate::circuit::CircuitInput for ScalarInput {
-            type WireRepr = ScalarInputWire;
-            fn allocate(&self, issue: impl FnMut() -> WireId) -> Self::WireRepr {
-                ScalarInputWire { s: Fr::new(issue) }
-            }
-            fn collect_wire_ids(repr: &Self::WireRepr) -> Vec<WireId> {
-                repr.s.iter().cloned().collect()
-            }
-        }
-        impl<M: CircuitMode<WireValue = bool>> EncodeInput<M> for ScalarInput {
-            fn encode(&self, repr: &ScalarInputWire, cache: &mut M) {
-                let s_fn = Fr::get_wire_bits_fn(&repr.s, &self.s).unwrap();
-                for &wire_id in repr.s.iter() {
-                    if let Some(bit) = s_fn(wire_id) {
-                        cache.feed_wire(wire_id, bit);
-                    }
-                }
-            }
-        }
-
         let inputs = ScalarInput { s };
         let circuit_result: crate::circuit::StreamingResult<_, _, Vec<bool>> =
             CircuitBuilder::streaming_execute(inputs, 10_000, |root, inputs_wire| {
@@ -768,11 +1023,62 @@ mod tests {
         assert_eq!(actual_result, G1Projective::as_montgomery(result));
     }
 
+    #[test]
+    fn test_g1p_scalar_mul_boundary_scalars() {
+        use ark_ff::{AdditiveGroup, Field};
+
+        let p = rnd();
+
+        // Zero selects the identity in every window, so every addition has infinite operands
+        for s in [ark_bn254::Fr::ZERO, ark_bn254::Fr::ONE, -ark_bn254::Fr::ONE] {
+            let inputs = ScalarInput { s };
+            let circuit_result: crate::circuit::StreamingResult<_, _, Vec<bool>> =
+                CircuitBuilder::streaming_execute(inputs, 10_000, |root, inputs_wire| {
+                    G1Projective::scalar_mul_by_constant_base_montgomery::<10, _>(
+                        root,
+                        &inputs_wire.s,
+                        &p,
+                    )
+                    .to_wires_vec()
+                });
+
+            let actual_result =
+                G1Projective::from_bits_unchecked(circuit_result.output_value.clone());
+            assert_eq!(actual_result, G1Projective::as_montgomery(p * s), "s = {s}");
+        }
+    }
+
+    #[test]
+    fn test_g1p_scalar_mul_window_sizes_agree() {
+        let s = rnd_fr(&mut trng());
+        let p = rnd();
+        let expected = G1Projective::as_montgomery(p * s);
+
+        for w in 2..=12 {
+            let inputs = ScalarInput { s };
+            let circuit_result: crate::circuit::StreamingResult<_, _, Vec<bool>> =
+                CircuitBuilder::streaming_execute(inputs, 10_000, |root, inputs_wire| {
+                    G1Projective::scalar_mul_by_constant_base_montgomery_dyn(
+                        root,
+                        &inputs_wire.s,
+                        &p,
+                        w,
+                    )
+                    .to_wires_vec()
+                });
+
+            let actual_result =
+                G1Projective::from_bits_unchecked(circuit_result.output_value.clone());
+            assert_eq!(actual_result, expected, "w = {w}");
+        }
+    }
+
     #[test]
     fn test_msm_with_constant_bases_montgomery() {
-        let n = 1;
-        let scalars = (0..n).map(|_| rnd_fr(&mut trng())).collect::<Vec<_>>();
-        let bases = (0..n).map(|_| rnd_g1(&mut trng())).collect::<Vec<_>>();
+        let n = 3;
+        let mut rng = trng();
+        let scalars = (0..n).map(|_| rnd_fr(&mut rng)).collect::<Vec<_>>();
+        let bases = (0..n).map(|_| rnd_g1(&mut rng)).collect::<Vec<_>>();
         let bases_affine = bases.iter().map(|g| g.into_affine()).collect::<Vec<_>>();
         let result = ark_bn254::G1Projective::msm(&bases_affine, &scalars).unwrap();
 
@@ -802,12 +1108,7 @@ mod tests {
         impl<M: CircuitMode<WireValue = bool>> EncodeInput<M> for MsmInputs {
             fn encode(&self, repr: &MsmInputsWire, cache: &mut M) {
                 for (fr_wire, fr_val) in repr.scalars.iter().zip(self.scalars.iter()) {
-                    let fr_fn = Fr::get_wire_bits_fn(fr_wire, fr_val).unwrap();
-                    for &wire_id in fr_wire.iter() {
-                        if let Some(bit) = fr_fn(wire_id) {
-                            cache.feed_wire(wire_id, bit);
-                        }
-                    }
+                    Fr::encode(fr_wire, fr_val, cache);
                 }
             }
         }
@@ -831,6 +1132,143 @@ mod tests {
         assert_eq!(actual_result, G1Projective::as_montgomery(result));
     }
 
+    #[test]
+    fn test_g1p_decode_output() {
+        let a = rnd_g1(&mut trng());
+        let inputs = G1Input {
+            points: [G1Projective::as_montgomery(a)],
+        };
+
+        let result = CircuitBuilder::streaming_execute::<_, _, ark_bn254::G1Projective>(
+            inputs,
+            10_000,
+            |root, inputs_wire| G1Projective::neg(root, &inputs_wire.points[0]),
+        );
+
+        assert_eq!(result.output_value, -a);
+    }
+
+    /// gnark's compressed encoding of `p`, in the bit order of
+    /// [`G1Projective::serialize_compressed`]
+    fn gnark_compressed(p: ark_bn254::G1Projective) -> [bool; G1Projective::COMPRESSED_BITS] {
+        let p = p.into_affine();
+        let mut bytes = [0u8; 32];
+        if p.infinity {
+            bytes[0] = 0b01 << 6;
+        } else {
+            bytes.copy_from_slice(&p.x.into_bigint().to_bytes_be());
+            bytes[0] |= if p.y > -p.y { 0b11 << 6 } else { 0b10 << 6 };
+        }
+        std::array::from_fn(|i| bytes[i / 8] >> (i % 8) & 1 == 1)
+    }
+
+    /// Validity wire and point [`G1Projective::deserialize_checked`] decodes from `bits`
+    fn deserialize(bits: [bool; G1Projective::COMPRESSED_BITS]) -> (bool, ark_bn254::G1Projective) {
+        let result: crate::circuit::StreamingResult<_, _, Vec<bool>> =
+            CircuitBuilder::streaming_execute(bits, 10_000, |ctx, bytes| {
+                let (point, valid) = G1Projective::deserialize_checked(ctx, bytes);
+                iter::once(valid).chain(point.to_wires_vec()).collect()
+            });
+        let (valid, point) = result.output_value.split_first().unwrap();
+        let point = G1Projective::from_bits_unchecked(point.to_vec());
+        (*valid, G1Projective::from_montgomery(point))
+    }
+
+    #[test]
+    fn test_g1p_serialize_compressed_matches_gnark() {
+        let a = rnd_g1(&mut trng());
+        for p in [a, -a, ark_bn254::G1Projective::zero()] {
+            let inputs = G1Input {
+                points: [G1Projective::as_montgomery(p)],
+            };
+            let result: crate::circuit::StreamingResult<_, _, Vec<bool>> =
+                CircuitBuilder::streaming_execute(inputs, 10_000, |ctx, inputs_wire| {
+                    G1Projective::serialize_compressed(ctx, &inputs_wire.points[0]).to_vec()
+                });
+
+            assert_eq!(result.output_value, gnark_compressed(p), "{p}");
+        }
+    }
+
+    #[test]
+    fn test_g1p_compressed_round_trip() {
+        let mut rng = trng();
+        for p in [
+            rnd_g1(&mut rng),
+            rnd_g1(&mut rng),
+            ark_bn254::G1Projective::zero(),
+        ] {
+            let (valid, decoded) = deserialize(gnark_compressed(p));
+            assert!(valid, "{p}");
+            assert_eq!(decoded, p);
+        }
+    }
+
+    #[test]
+    fn test_g1p_deserialize_rejects_invalid_encodings() {
+        let a = gnark_compressed(rnd_g1(&mut trng()));
+
+        let mut uncompressed = a;
+        uncompressed[7] = false;
+        uncompressed[6] = false;
+
+        // x = p, with the flags of a compressed point
+        let modulus = ark_bn254::Fq::MODULUS.to_bytes_be();
+        let mut unreduced: [bool; G1Projective::COMPRESSED_BITS] =
+            std::array::from_fn(|i| modulus[i / 8] >> (i % 8) & 1 == 1);
+        unreduced[7] = true;
+
+        // The smallest x with no point on the curve
+        let off_curve_x = (1u64..)
+            .map(ark_bn254::Fq::from)
+            .find(|x| {
+                (x.square() * x + ark_bn254::g1::Config::COEFF_B)
+                    .sqrt()
+                    .is_none()
+            })
+            .unwrap();
+        let x = off_curve_x.into_bigint().to_bytes_be();
+        let mut off_curve: [bool; G1Projective::COMPRESSED_BITS] =
+            std::array::from_fn(|i| x[i / 8] >> (i % 8) & 1 == 1);
+        off_curve[7] = true;
+
+        let mut infinity_with_x = a;
+        infinity_with_x[7] = false;
+        infinity_with_x[6] = true;
+
+        for (name, bits) in [
+            ("uncompressed", uncompressed),
+            ("unreduced", unreduced),
+            ("off curve", off_curve),
+            ("infinity with x", infinity_with_x),
+        ] {
+            assert!(!deserialize(bits).0, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_g1p_deserialize_rejects_tampered_x() {
+        let p = rnd_g1(&mut trng()).into_affine();
+        let bits = gnark_compressed(p.into());
+        assert_eq!(deserialize(bits), (true, p.into()));
+
+        // Flip the lowest bit of x that takes it off the curve
+        let k = (0..Fq::N_BITS)
+            .find(|&k| {
+                let mut x = p.x.into_bigint();
+                x.0[k / 64] ^= 1 << (k % 64);
+                ark_bn254::Fq::from_bigint(x).is_some_and(|x| {
+                    (x.square() * x + ark_bn254::g1::Config::COEFF_B)
+                        .sqrt()
+                        .is_none()
+                })
+            })
+            .unwrap();
+        let mut tampered = bits;
+        tampered[G1Projective::compressed_x_bit(k)] ^= true;
+        assert!(!deserialize(tampered).0, "bit {k}");
+    }
+
     #[test]
     fn test_g1p_neg() {
         // Generate random G1 point
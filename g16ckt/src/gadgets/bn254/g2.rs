@@ -1,14 +1,19 @@
 use std::{cmp::min, collections::HashMap, iter::zip};
 
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
 use ark_ff::Zero;
 use circuit_component_macro::component;
 
 use crate::{
-    CircuitContext, WireId,
-    circuit::{FromWires, WiresObject},
+    CircuitContext, Gate, WireId,
+    circuit::{
+        CircuitMode, CircuitOutput, ExecuteMode, FromWires, FromWiresError, OffCircuitParam,
+        WireFields, WiresObject, lookup_bits,
+    },
     gadgets::{
         bigint::Error,
-        bn254::{fp254impl::Fp254Impl, fq::Fq, fq2::Fq2, fr::Fr},
+        bn254::{fq::Fq, fq2::Fq2, fr::Fr},
+        field::FpImpl,
     },
 };
 
@@ -21,13 +26,21 @@ pub struct G2Projective {
 
 impl WiresObject for G2Projective {
     fn to_wires_vec(&self) -> Vec<WireId> {
-        let mut wires = Vec::new();
-        wires.extend(self.x.to_wires_vec());
-        wires.extend(self.y.to_wires_vec());
-        wires.extend(self.z.to_wires_vec());
+        let mut wires = Vec::with_capacity(self.wire_count());
+        self.for_each_wire(|wire| wires.push(wire));
         wires
     }
 
+    fn for_each_wire(&self, mut f: impl FnMut(WireId)) {
+        self.x.for_each_wire(&mut f);
+        self.y.for_each_wire(&mut f);
+        self.z.for_each_wire(&mut f);
+    }
+
+    fn wire_count(&self) -> usize {
+        self.x.wire_count() + self.y.wire_count() + self.z.wire_count()
+    }
+
     fn clone_from(&self, wire_gen: &mut impl FnMut() -> WireId) -> Self {
         let Self { x, y, z } = self;
 
@@ -41,13 +54,33 @@ impl WiresObject for G2Projective {
 
 impl FromWires for G2Projective {
     fn from_wires(wires: &[WireId]) -> Option<Self> {
-        let len = wires.len() / 3;
-        let mut chunks = wires.chunks(len);
-        Some(Self {
-            x: Fq2::from_wires(chunks.next()?)?,
-            y: Fq2::from_wires(chunks.next()?)?,
-            z: Fq2::from_wires(chunks.next()?)?,
-        })
+        Self::try_from_wires(wires).ok()
+    }
+
+    fn try_from_wires(wires: &[WireId]) -> Result<Self, FromWiresError> {
+        let mut fields = WireFields::new("G2Projective", wires);
+        let point = Self {
+            x: fields.field("x", Fq2::N_BITS)?,
+            y: fields.field("y", Fq2::N_BITS)?,
+            z: fields.field("z", Fq2::N_BITS)?,
+        };
+        fields.finish()?;
+
+        Ok(point)
+    }
+}
+
+/// Reads back a projective G2 point whose coordinates are in Montgomery form, returning it with
+/// standard-form coordinates. The point is not checked to be on the curve.
+// Same type as `ark_bn254::G2Projective`, spelled without the associated-type alias so
+// coherence can tell the G1 and G2 impls apart.
+impl CircuitOutput<ExecuteMode> for Projective<ark_bn254::g2::Config> {
+    type WireRepr = G2Projective;
+
+    fn decode(wires: Self::WireRepr, cache: &mut ExecuteMode) -> Self {
+        let bits = lookup_bits(&wires, cache);
+
+        G2Projective::from_montgomery(G2Projective::from_bits_unchecked(bits))
     }
 }
 
@@ -203,16 +236,91 @@ impl G2Projective {
 
         Ok(move |wire_id: WireId| bits.get(&wire_id).copied())
     }
+
+    /// Feed every wire of `wires` with its bit of `value`, as an input encoder does
+    pub fn encode<M: CircuitMode<WireValue = bool>>(
+        wires: &G2Projective,
+        value: &ark_bn254::G2Projective,
+        cache: &mut M,
+    ) {
+        let bits = Self::get_wire_bits_fn(wires, value).unwrap();
+        wires.for_each_wire(|wire_id| {
+            if let Some(bit) = bits(wire_id) {
+                cache.feed_wire(wire_id, bit);
+            }
+        });
+    }
+}
+
+/// What [`G2Projective::add_montgomery`] needs to tell `p == q` apart from a regular sum, when
+/// both are finite
+struct AddDegeneracy {
+    /// `u1 - u2`, zero iff `p` and `q` have the same affine x
+    h: Fq2,
+    /// `s1 - s2`, zero iff they also have the same affine y
+    r: Fq2,
+    p_inf: WireId,
+    q_inf: WireId,
 }
 
 impl G2Projective {
-    // http://koclab.cs.ucsb.edu/teaching/ccs130h/2018/09projective.pdf
+    /// Whether `p` is the point at infinity, i.e. `z == 0`
+    pub fn is_infinity<C: CircuitContext>(circuit: &mut C, p: &G2Projective) -> WireId {
+        Fq2::is_zero(circuit, &p.z)
+    }
+
+    /// `x^3 + b` in Montgomery form, the `y^2` of any affine point with this `x`
+    pub fn y_square_montgomery<C: CircuitContext>(circuit: &mut C, x: &Fq2) -> Fq2 {
+        let x2 = Fq2::square_montgomery(circuit, x);
+        let x3 = Fq2::mul_montgomery(circuit, &x2, x);
+        Fq2::add_constant(
+            circuit,
+            &x3,
+            &Fq2::as_montgomery(ark_bn254::g2::Config::COEFF_B),
+        )
+    }
+
+    /// `p + q`, where `p != q` unless either is the point at infinity: the formula doesn't cover
+    /// doubling, and adding a point to itself gives the point at infinity.
     #[component]
     pub fn add_montgomery<C: CircuitContext>(
         circuit: &mut C,
         p: &G2Projective,
         q: &G2Projective,
     ) -> G2Projective {
+        Self::add_unchecked(circuit, p, q).0
+    }
+
+    /// Same as [`Self::add_montgomery`], but `p != q` is asserted through
+    /// [`CircuitContext::assert_true`] instead of assumed, so a violation fails the validity
+    /// output rather than silently giving a wrong sum. Cheaper than handling the doubling case.
+    pub fn add_montgomery_asserting<C: CircuitContext>(
+        circuit: &mut C,
+        p: &G2Projective,
+        q: &G2Projective,
+    ) -> G2Projective {
+        let (sum, AddDegeneracy { h, r, p_inf, q_inf }) = Self::add_unchecked(circuit, p, q);
+
+        // p == q for finite points iff both u1 == u2 and s1 == s2
+        let same_x = Fq2::is_zero(circuit, &h);
+        let same_y = Fq2::is_zero(circuit, &r);
+        let same = circuit.issue_wire();
+        circuit.add_gate(Gate::and(same_x, same_y, same));
+        let finite = circuit.issue_wire();
+        circuit.add_gate(Gate::nor(p_inf, q_inf, finite));
+        let distinct = circuit.issue_wire();
+        circuit.add_gate(Gate::nand(same, finite, distinct));
+
+        circuit.assert_true(distinct);
+        sum
+    }
+
+    // http://koclab.cs.ucsb.edu/teaching/ccs130h/2018/09projective.pdf
+    fn add_unchecked<C: CircuitContext>(
+        circuit: &mut C,
+        p: &G2Projective,
+        q: &G2Projective,
+    ) -> (G2Projective, AddDegeneracy) {
         assert_eq!(p.x.c0().len() + p.x.c1().len(), Fq2::N_BITS);
         assert_eq!(p.y.c0().len() + p.y.c1().len(), Fq2::N_BITS);
         assert_eq!(p.z.c0().len() + p.z.c1().len(), Fq2::N_BITS);
@@ -256,90 +364,37 @@ impl G2Projective {
         let z1z2 = Fq2::mul_montgomery(circuit, z1, z2);
         let z3 = Fq2::mul_montgomery(circuit, &z1z2, &h);
 
-        let z1_0 = Fq2::equal_constant(circuit, z1, &ark_bn254::Fq2::zero());
-        let z2_0 = Fq2::equal_constant(circuit, z2, &ark_bn254::Fq2::zero());
+        let z1_0 = Self::is_infinity(circuit, p);
+        let z2_0 = Self::is_infinity(circuit, q);
 
         let zero = Fq2::from_components(
             Fq::new_constant(&ark_bn254::Fq::zero()).unwrap(),
             Fq::new_constant(&ark_bn254::Fq::zero()).unwrap(),
         );
+        let sum = G2Projective {
+            x: x3,
+            y: y3,
+            z: z3,
+        };
+        let infinity = G2Projective {
+            x: zero.clone(),
+            y: zero.clone(),
+            z: zero,
+        };
 
-        let s = [z1_0, z2_0];
-
-        // Implement multiplexer for Fq2 by multiplexing each component
-        let x_c0 = Fq::multiplexer(
-            circuit,
-            &[
-                x3.c0().clone(),
-                x2.c0().clone(),
-                x1.c0().clone(),
-                zero.c0().clone(),
-            ],
-            &s,
-            2,
-        );
-        let x_c1 = Fq::multiplexer(
-            circuit,
-            &[
-                x3.c1().clone(),
-                x2.c1().clone(),
-                x1.c1().clone(),
-                zero.c1().clone(),
-            ],
-            &s,
-            2,
-        );
-        let x = Fq2::from_components(x_c0, x_c1);
-
-        let y_c0 = Fq::multiplexer(
-            circuit,
-            &[
-                y3.c0().clone(),
-                y2.c0().clone(),
-                y1.c0().clone(),
-                zero.c0().clone(),
-            ],
-            &s,
-            2,
-        );
-        let y_c1 = Fq::multiplexer(
-            circuit,
-            &[
-                y3.c1().clone(),
-                y2.c1().clone(),
-                y1.c1().clone(),
-                zero.c1().clone(),
-            ],
-            &s,
-            2,
-        );
-        let y = Fq2::from_components(y_c0, y_c1);
-
-        let z_c0 = Fq::multiplexer(
-            circuit,
-            &[
-                z3.c0().clone(),
-                z2.c0().clone(),
-                z1.c0().clone(),
-                zero.c0().clone(),
-            ],
-            &s,
-            2,
-        );
-        let z_c1 = Fq::multiplexer(
+        let sum = Self::multiplexer(
             circuit,
-            &[
-                z3.c1().clone(),
-                z2.c1().clone(),
-                z1.c1().clone(),
-                zero.c1().clone(),
-            ],
-            &s,
+            &[sum, q.clone(), p.clone(), infinity],
+            &[z1_0, z2_0],
             2,
         );
-        let z = Fq2::from_components(z_c0, z_c1);
-
-        G2Projective { x, y, z }
+        let degeneracy = AddDegeneracy {
+            h,
+            r,
+            p_inf: z1_0,
+            q_inf: z2_0,
+        };
+        (sum, degeneracy)
     }
 
     #[component]
@@ -373,7 +428,7 @@ impl G2Projective {
         let yz = Fq2::mul_montgomery(circuit, y1, z1);
         let zr = Fq2::double(circuit, &yz);
 
-        let z_0 = Fq2::equal_constant(circuit, z1, &ark_bn254::Fq2::zero());
+        let z_0 = Self::is_infinity(circuit, p);
         let zero = Fq2::from_components(
             Fq::new_constant(&ark_bn254::Fq::zero()).unwrap(),
             Fq::new_constant(&ark_bn254::Fq::zero()).unwrap(),
@@ -385,6 +440,20 @@ impl G2Projective {
         G2Projective { x: xr, y: yr, z }
     }
 
+    /// Select `a` if `flag` is set, `b` otherwise
+    pub fn conditional_select<C: CircuitContext>(
+        circuit: &mut C,
+        a: &G2Projective,
+        b: &G2Projective,
+        flag: WireId,
+    ) -> G2Projective {
+        G2Projective {
+            x: Fq2::conditional_select(circuit, &a.x, &b.x, flag),
+            y: Fq2::conditional_select(circuit, &a.y, &b.y, flag),
+            z: Fq2::conditional_select(circuit, &a.z, &b.z, flag),
+        }
+    }
+
     #[component(offcircuit_args = "w")]
     pub fn multiplexer<C: CircuitContext>(
         circuit: &mut C,
@@ -440,51 +509,53 @@ impl G2Projective {
         }
     }
 
-    #[component(offcircuit_args = "base")]
     pub fn scalar_mul_by_constant_base_montgomery<C: CircuitContext, const W: usize>(
         circuit: &mut C,
         s: &Fr,
         base: &ark_bn254::G2Projective,
     ) -> G2Projective {
-        assert_eq!(s.len(), Fr::N_BITS);
-        let n = 2_usize.pow(W as u32);
+        Self::scalar_mul_by_constant_base_montgomery_dyn(circuit, s, base, W)
+    }
 
-        let mut bases = Vec::new();
-        let mut p = ark_bn254::G2Projective::default();
+    /// Same as [`Self::scalar_mul_by_constant_base_montgomery`], with the window size chosen at
+    /// runtime.
+    #[component(offcircuit_args = "base, w")]
+    pub fn scalar_mul_by_constant_base_montgomery_dyn<C: CircuitContext>(
+        circuit: &mut C,
+        s: &Fr,
+        base: &ark_bn254::G2Projective,
+        w: usize,
+    ) -> G2Projective {
+        Self::scalar_mul_by_windows(circuit, s, &G2ConstTables::new(base, w))
+    }
 
-        for _ in 0..n {
-            bases.push(p);
-            p += base;
-        }
+    /// Same as [`Self::scalar_mul_by_constant_base_montgomery`], with the constant tables of the
+    /// base already built.
+    #[component(offcircuit_args = "tables")]
+    pub fn scalar_mul_by_const_tables_montgomery<C: CircuitContext>(
+        circuit: &mut C,
+        s: &Fr,
+        tables: &G2ConstTables,
+    ) -> G2Projective {
+        Self::scalar_mul_by_windows(circuit, s, tables)
+    }
 
-        let mut bases_wires = bases
-            .iter()
-            .map(|p| G2Projective::new_constant(p).unwrap())
-            .collect::<Vec<_>>();
+    fn scalar_mul_by_windows<C: CircuitContext>(
+        circuit: &mut C,
+        s: &Fr,
+        tables: &G2ConstTables,
+    ) -> G2Projective {
+        assert_eq!(s.len(), Fr::N_BITS);
 
         let mut to_be_added = Vec::new();
 
         let mut index = 0;
-        while index < Fr::N_BITS {
-            let w = min(W, Fr::N_BITS - index);
-            let m = 2_usize.pow(w as u32);
+        for window in &tables.windows {
+            let w = min(tables.w, Fr::N_BITS - index);
             let selector: Vec<WireId> = s.iter().skip(index).take(w).copied().collect();
-            let result = Self::multiplexer(circuit, &bases_wires[0..m], &selector, w);
+            let result = Self::multiplexer(circuit, window, &selector, w);
             to_be_added.push(result);
-            index += W;
-            let mut new_bases = Vec::new();
-            for b in bases {
-                let mut new_b = b;
-                for _ in 0..w {
-                    new_b = new_b + new_b;
-                }
-                new_bases.push(new_b);
-            }
-            bases = new_bases;
-            bases_wires = bases
-                .iter()
-                .map(|p| G2Projective::new_constant(p).unwrap())
-                .collect::<Vec<_>>();
+            index += tables.w;
         }
 
         let mut acc = to_be_added[0].clone();
@@ -498,13 +569,28 @@ impl G2Projective {
 
     pub fn msm_with_constant_bases_montgomery<const W: usize, C: CircuitContext>(
         circuit: &mut C,
-        scalars: &Vec<Fr>,
-        bases: &Vec<ark_bn254::G2Projective>,
+        scalars: &[Fr],
+        bases: &[ark_bn254::G2Projective],
+    ) -> G2Projective {
+        let context = G2MsmContext::new(bases, W);
+        Self::msm_with_context_montgomery(circuit, scalars, bases, &context)
+    }
+
+    /// MSM over constant bases whose tables are taken from `context`, so a base repeated across
+    /// terms (or across MSMs sharing the context) only has its tables built once.
+    pub fn msm_with_context_montgomery<C: CircuitContext>(
+        circuit: &mut C,
+        scalars: &[Fr],
+        bases: &[ark_bn254::G2Projective],
+        context: &G2MsmContext,
     ) -> G2Projective {
         assert_eq!(scalars.len(), bases.len());
         let mut to_be_added = Vec::new();
         for (s, base) in zip(scalars, bases) {
-            let result = Self::scalar_mul_by_constant_base_montgomery::<_, W>(circuit, s, base);
+            let tables = context
+                .tables(base)
+                .expect("MSM base missing from the context");
+            let result = Self::scalar_mul_by_const_tables_montgomery(circuit, s, tables);
             to_be_added.push(result);
         }
 
@@ -526,16 +612,130 @@ impl G2Projective {
     }
 }
 
+/// Constant multiples of a fixed G2 base, for windowed scalar multiplication.
+///
+/// Window `i` holds `j * 2^(w * i) * base` for every `j` selectable by that window's scalar bits,
+/// already as constant wires. The last window only covers the scalar bits left over.
+#[derive(Clone, Debug)]
+pub struct G2ConstTables {
+    base: ark_bn254::G2Projective,
+    w: usize,
+    windows: Vec<Vec<G2Projective>>,
+}
+
+impl G2ConstTables {
+    pub fn new(base: &ark_bn254::G2Projective, w: usize) -> Self {
+        let n = 2_usize.pow(w as u32);
+
+        let mut bases = Vec::new();
+        let mut p = ark_bn254::G2Projective::default();
+
+        for _ in 0..n {
+            bases.push(p);
+            p += base;
+        }
+
+        let mut windows = Vec::new();
+
+        let mut index = 0;
+        while index < Fr::N_BITS {
+            let m = 2_usize.pow(min(w, Fr::N_BITS - index) as u32);
+            windows.push(
+                bases[0..m]
+                    .iter()
+                    .map(|p| G2Projective::new_constant(&G2Projective::as_montgomery(*p)).unwrap())
+                    .collect(),
+            );
+            index += w;
+            for b in &mut bases {
+                for _ in 0..w {
+                    *b = *b + *b;
+                }
+            }
+        }
+
+        Self {
+            base: *base,
+            w,
+            windows,
+        }
+    }
+
+    pub fn base(&self) -> &ark_bn254::G2Projective {
+        &self.base
+    }
+
+    pub fn window_size(&self) -> usize {
+        self.w
+    }
+}
+
+impl OffCircuitParam for G2ConstTables {
+    fn to_key_bytes(&self) -> Vec<u8> {
+        // The tables are fully determined by the base and the window size
+        let mut bytes = self.base.to_key_bytes();
+        bytes.extend_from_slice(&self.w.to_le_bytes());
+        bytes
+    }
+}
+
+/// Constant tables for the bases of a G2 MSM, built once per distinct base and shared by every
+/// scalar multiplied against it.
+#[derive(Clone, Debug)]
+pub struct G2MsmContext {
+    w: usize,
+    tables: Vec<G2ConstTables>,
+    by_base: HashMap<ark_bn254::G2Projective, usize>,
+}
+
+impl G2MsmContext {
+    pub fn new(bases: &[ark_bn254::G2Projective], w: usize) -> Self {
+        let mut context = Self {
+            w,
+            tables: Vec::new(),
+            by_base: HashMap::new(),
+        };
+        for base in bases {
+            context.insert(base);
+        }
+        context
+    }
+
+    /// Build the tables for `base` unless they're already present
+    pub fn insert(&mut self, base: &ark_bn254::G2Projective) -> &G2ConstTables {
+        let index = *self.by_base.entry(*base).or_insert_with(|| {
+            self.tables.push(G2ConstTables::new(base, self.w));
+            self.tables.len() - 1
+        });
+        &self.tables[index]
+    }
+
+    pub fn tables(&self, base: &ark_bn254::G2Projective) -> Option<&G2ConstTables> {
+        self.by_base.get(base).map(|index| &self.tables[*index])
+    }
+
+    /// Number of bases with tables built
+    pub fn distinct_bases(&self) -> usize {
+        self.tables.len()
+    }
+
+    pub fn window_size(&self) -> usize {
+        self.w
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use ark_ec::{CurveGroup, VariableBaseMSM};
+    use ark_ec::{CurveGroup, PrimeGroup, VariableBaseMSM};
     use ark_ff::UniformRand;
     use rand::{Rng, SeedableRng};
     use rand_chacha::ChaCha20Rng;
 
     use super::*;
     use crate::{
-        circuit::{CircuitBuilder, CircuitInput, EncodeInput, modes::CircuitMode},
+        circuit::{
+            CircuitBuilder, CircuitInput, EncodeInput, FALSE_WIRE, TRUE_WIRE, modes::CircuitMode,
+        },
         test_utils::trng,
     };
 
@@ -545,7 +745,7 @@ mod tests {
     }
 
     pub fn rnd_g2(rng: &mut impl Rng) -> ark_bn254::G2Projective {
-        ark_bn254::G2Projective::default() * rnd_fr(rng)
+        ark_bn254::G2Projective::generator() * rnd_fr(rng)
     }
 
     // Standardized input/output structures for G2 tests
@@ -578,21 +778,7 @@ mod tests {
     impl<const N: usize, M: CircuitMode<WireValue = bool>> EncodeInput<M> for G2Input<N> {
         fn encode(&self, repr: &Self::WireRepr, cache: &mut M) {
             for (point_wire, point_val) in repr.points.iter().zip(self.points.iter()) {
-                let point_fn = G2Projective::get_wire_bits_fn(point_wire, point_val).unwrap();
-                for &wire_id in point_wire
-                    .x
-                    .c0()
-                    .iter()
-                    .chain(point_wire.x.c1().iter())
-                    .chain(point_wire.y.c0().iter())
-                    .chain(point_wire.y.c1().iter())
-                    .chain(point_wire.z.c0().iter())
-                    .chain(point_wire.z.c1().iter())
-                {
-                    if let Some(bit) = point_fn(wire_id) {
-                        cache.feed_wire(wire_id, bit);
-                    }
-                }
+                G2Projective::encode(point_wire, point_val, cache);
             }
         }
     }
@@ -626,27 +812,43 @@ mod tests {
     impl<const N: usize, M: CircuitMode<WireValue = bool>> EncodeInput<M> for ScalarInput<N> {
         fn encode(&self, repr: &Self::WireRepr, cache: &mut M) {
             for (scalar_wire, scalar_val) in repr.scalars.iter().zip(self.scalars.iter()) {
-                let scalar_fn = Fr::get_wire_bits_fn(scalar_wire, scalar_val).unwrap();
-                for &wire_id in scalar_wire.iter() {
-                    if let Some(bit) = scalar_fn(wire_id) {
-                        cache.feed_wire(wire_id, bit);
-                    }
-                }
+                Fr::encode(scalar_wire, scalar_val, cache);
             }
         }
     }
 
-    fn rnd() -> ark_bn254::G2Projective {
-        use ark_ec::PrimeGroup;
-        let g2 = ark_bn254::G2Projective::generator();
-        g2.mul_bigint(<rand::rngs::StdRng as SeedableRng>::seed_from_u64(1).r#gen::<[u64; 4]>())
+    #[test]
+    fn test_g2_try_from_wires_reports_field() {
+        let wires = (2..2 + G2Projective::N_BITS)
+            .map(WireId)
+            .collect::<Vec<_>>();
+
+        assert!(G2Projective::try_from_wires(&wires).is_ok());
+
+        // Drop one wire of z.c1
+        let err = G2Projective::try_from_wires(&wires[..wires.len() - 1]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "G2Projective.z: Fq2.c1: Fq: expected 254 wires, got 253"
+        );
+
+        let err = G2Projective::try_from_wires(&[wires.clone(), wires].concat()).unwrap_err();
+        assert_eq!(
+            err,
+            FromWiresError::Length {
+                ty: "G2Projective",
+                expected: G2Projective::N_BITS,
+                actual: 2 * G2Projective::N_BITS,
+            }
+        );
     }
 
     #[test]
     fn test_g2p_add_montgomery() {
         // Generate random G2 points
-        let a = rnd_g2(&mut trng());
-        let b = rnd_g2(&mut trng());
+        let mut rng = trng();
+        let a = rnd_g2(&mut rng);
+        let b = rnd_g2(&mut rng);
         let c = a + b;
 
         // Convert to Montgomery form
@@ -671,10 +873,46 @@ mod tests {
         assert_eq!(actual_result, c_mont);
     }
 
+    #[test]
+    fn test_g2p_add_montgomery_asserting() {
+        let mut rng = trng();
+        let (a, b) = (rnd_g2(&mut rng), rnd_g2(&mut rng));
+        let zero = ark_bn254::G2Projective::zero();
+
+        for (p, q, distinct) in [
+            (a, b, true),
+            (a, zero, true),
+            (zero, zero, true),
+            (a, a, false),
+        ] {
+            let inputs = G2Input {
+                points: [
+                    G2Projective::as_montgomery(p),
+                    G2Projective::as_montgomery(q),
+                ],
+            };
+            let result: crate::circuit::StreamingResult<_, _, Vec<bool>> =
+                CircuitBuilder::streaming_execute(inputs, 10_000, |root, inputs_wire| {
+                    let [p, q] = &inputs_wire.points;
+                    let mut wires =
+                        G2Projective::add_montgomery_asserting(root, p, q).to_wires_vec();
+                    wires.push(root.validity_output());
+                    wires
+                });
+
+            let (sum, validity) = result.output_value.split_at(G2Projective::N_BITS);
+            assert_eq!(validity, [distinct]);
+            if distinct {
+                let sum = G2Projective::from_bits_unchecked(sum.to_vec());
+                assert_eq!(sum, G2Projective::as_montgomery(p + q));
+            }
+        }
+    }
+
     #[test]
     fn test_g2p_double_montgomery() {
         // Generate random G2 point
-        let a = rnd();
+        let a = rnd_g2(&mut trng());
         let c = a + a;
 
         // Convert to Montgomery form
@@ -692,6 +930,49 @@ mod tests {
         assert_eq!(actual_result, c_mont);
     }
 
+    #[test]
+    fn test_g2p_decode_output() {
+        let a = rnd_g2(&mut trng());
+        let inputs = G2Input {
+            points: [G2Projective::as_montgomery(a)],
+        };
+
+        let result = CircuitBuilder::streaming_execute::<_, _, ark_bn254::G2Projective>(
+            inputs,
+            10_000,
+            |root, inputs_wire| G2Projective::neg(root, &inputs_wire.points[0]),
+        );
+
+        assert_eq!(result.output_value, -a);
+    }
+
+    #[test]
+    fn test_encode_round_trips_through_decode() {
+        let mut rng = trng();
+        let point = rnd_g2(&mut rng);
+        let scalar = rnd_fr(&mut rng);
+
+        let inputs = G2Input {
+            points: [G2Projective::as_montgomery(point)],
+        };
+        let result = CircuitBuilder::streaming_execute::<_, _, ark_bn254::G2Projective>(
+            inputs,
+            10_000,
+            |_, inputs_wire| inputs_wire.points[0].clone(),
+        );
+        assert_eq!(result.output_value, point);
+
+        let inputs = ScalarInput {
+            scalars: [Fr::as_montgomery(scalar)],
+        };
+        let result = CircuitBuilder::streaming_execute::<_, _, ark_bn254::Fr>(
+            inputs,
+            10_000,
+            |_, inputs_wire| inputs_wire.scalars[0].clone(),
+        );
+        assert_eq!(result.output_value, scalar);
+    }
+
     #[test]
     fn test_g2p_neg() {
         // Generate random G2 point
@@ -713,14 +994,52 @@ mod tests {
         assert_eq!(actual_result, neg_a_mont);
     }
 
+    #[test]
+    fn test_g2p_is_infinity() {
+        let generator = ark_bn254::G2Projective::generator();
+        let identity = ark_bn254::G2Projective::zero();
+
+        for (point, expected) in [(generator, false), (identity, true)] {
+            let inputs = G2Input {
+                points: [G2Projective::as_montgomery(point)],
+            };
+            let result: crate::circuit::StreamingResult<_, _, bool> =
+                CircuitBuilder::streaming_execute(inputs, 10_000, |root, inputs_wire| {
+                    G2Projective::is_infinity(root, &inputs_wire.points[0])
+                });
+
+            assert_eq!(result.output_value, expected);
+        }
+    }
+
+    #[test]
+    fn test_g2p_conditional_select() {
+        let mut rng = trng();
+        let a = G2Projective::as_montgomery(rnd_g2(&mut rng));
+        let b = G2Projective::as_montgomery(rnd_g2(&mut rng));
+
+        for (flag, expected) in [(TRUE_WIRE, a), (FALSE_WIRE, b)] {
+            let inputs = G2Input { points: [a, b] };
+            let result: crate::circuit::StreamingResult<_, _, Vec<bool>> =
+                CircuitBuilder::streaming_execute(inputs, 10_000, |root, inputs_wire| {
+                    let [a, b] = &inputs_wire.points;
+                    G2Projective::conditional_select(root, a, b, flag).to_wires_vec()
+                });
+
+            let actual_result = G2Projective::from_bits_unchecked(result.output_value.clone());
+            assert_eq!(actual_result, expected);
+        }
+    }
+
     #[test]
     fn test_g2p_multiplexer() {
         let w = 2;
         let n = 2_usize.pow(w as u32);
+        let mut rng = trng();
         let a_val = (0..n)
-            .map(|_| G2Projective::as_montgomery(rnd_g2(&mut trng())))
+            .map(|_| G2Projective::as_montgomery(rnd_g2(&mut rng)))
             .collect::<Vec<_>>();
-        let s_val = (0..w).map(|_| trng().r#gen()).collect::<Vec<_>>();
+        let s_val = (0..w).map(|_| rng.r#gen()).collect::<Vec<_>>();
 
         let mut u = 0;
         for i in s_val.iter().rev() {
@@ -759,21 +1078,7 @@ mod tests {
         impl<M: CircuitMode<WireValue = bool>> EncodeInput<M> for MultiplexerInputs {
             fn encode(&self, repr: &MultiplexerInputsWire, cache: &mut M) {
                 for (g2_wire, g2_val) in repr.a.iter().zip(self.a.iter()) {
-                    let g2_fn = G2Projective::get_wire_bits_fn(g2_wire, g2_val).unwrap();
-                    for &wire_id in g2_wire
-                        .x
-                        .c0()
-                        .iter()
-                        .chain(g2_wire.x.c1().iter())
-                        .chain(g2_wire.y.c0().iter())
-                        .chain(g2_wire.y.c1().iter())
-                        .chain(g2_wire.z.c0().iter())
-                        .chain(g2_wire.z.c1().iter())
-                    {
-                        if let Some(bit) = g2_fn(wire_id) {
-                            cache.feed_wire(wire_id, bit);
-                        }
-                    }
+                    G2Projective::encode(g2_wire, g2_val, cache);
                 }
                 for (&wire_id, &bit) in repr.s.iter().zip(self.s.iter()) {
                     cache.feed_wire(wire_id, bit);
@@ -795,8 +1100,9 @@ mod tests {
 
     #[test]
     fn test_g2p_scalar_mul_with_constant_base_montgomery() {
-        let s = rnd_fr(&mut trng());
-        let p = rnd_g2(&mut trng());
+        let mut rng = trng();
+        let s = rnd_fr(&mut rng);
+        let p = rnd_g2(&mut rng);
         let result = p * s;
 
         let inputs = ScalarInput { scalars: [s] };
@@ -814,11 +1120,112 @@ mod tests {
         assert_eq!(actual_result, G2Projective::as_montgomery(result));
     }
 
+    #[test]
+    fn test_g2p_scalar_mul_boundary_scalars() {
+        use ark_ff::{AdditiveGroup, Field};
+
+        let p = rnd_g2(&mut trng());
+
+        // Zero selects the identity in every window, so every addition has infinite operands
+        for s in [ark_bn254::Fr::ZERO, ark_bn254::Fr::ONE, -ark_bn254::Fr::ONE] {
+            let inputs = ScalarInput { scalars: [s] };
+            let circuit_result: crate::circuit::StreamingResult<_, _, Vec<bool>> =
+                CircuitBuilder::streaming_execute(inputs, 10_000, |root, inputs_wire| {
+                    G2Projective::scalar_mul_by_constant_base_montgomery::<_, 10>(
+                        root,
+                        &inputs_wire.scalars[0],
+                        &p,
+                    )
+                    .to_wires_vec()
+                });
+
+            let actual_result =
+                G2Projective::from_bits_unchecked(circuit_result.output_value.clone());
+            assert_eq!(actual_result, G2Projective::as_montgomery(p * s), "s = {s}");
+        }
+    }
+
+    #[test]
+    fn test_g2p_scalar_mul_window_sizes_agree() {
+        let mut rng = trng();
+        let s = rnd_fr(&mut rng);
+        let p = rnd_g2(&mut rng);
+        let expected = G2Projective::as_montgomery(p * s);
+
+        // G2 additions are expensive, so only sample the range covered for G1
+        for w in [2, 7, 12] {
+            let inputs = ScalarInput { scalars: [s] };
+            let circuit_result: crate::circuit::StreamingResult<_, _, Vec<bool>> =
+                CircuitBuilder::streaming_execute(inputs, 10_000, |root, inputs_wire| {
+                    G2Projective::scalar_mul_by_constant_base_montgomery_dyn(
+                        root,
+                        &inputs_wire.scalars[0],
+                        &p,
+                        w,
+                    )
+                    .to_wires_vec()
+                });
+
+            let actual_result =
+                G2Projective::from_bits_unchecked(circuit_result.output_value.clone());
+            assert_eq!(actual_result, expected, "w = {w}");
+        }
+    }
+
+    #[test]
+    fn test_g2p_scalar_mul_by_tables_matches_inline() {
+        const W: usize = 10;
+
+        let mut rng = trng();
+        let s = rnd_fr(&mut rng);
+        let p = rnd_g2(&mut rng);
+        let tables = G2ConstTables::new(&p, W);
+
+        let inline: crate::circuit::StreamingResult<_, _, Vec<bool>> =
+            CircuitBuilder::streaming_execute(
+                ScalarInput { scalars: [s] },
+                10_000,
+                |root, inputs_wire| {
+                    G2Projective::scalar_mul_by_constant_base_montgomery::<_, W>(
+                        root,
+                        &inputs_wire.scalars[0],
+                        &p,
+                    )
+                    .to_wires_vec()
+                },
+            );
+        let tabled: crate::circuit::StreamingResult<_, _, Vec<bool>> =
+            CircuitBuilder::streaming_execute(
+                ScalarInput { scalars: [s] },
+                10_000,
+                |root, inputs_wire| {
+                    G2Projective::scalar_mul_by_const_tables_montgomery(
+                        root,
+                        &inputs_wire.scalars[0],
+                        &tables,
+                    )
+                    .to_wires_vec()
+                },
+            );
+
+        // Precomputing the tables only moves work off-circuit
+        assert_eq!(tabled.output_value, inline.output_value);
+        assert_eq!(
+            tabled.gate_count.total_gate_count(),
+            inline.gate_count.total_gate_count()
+        );
+        assert_eq!(
+            G2Projective::from_bits_unchecked(tabled.output_value),
+            G2Projective::as_montgomery(p * s)
+        );
+    }
+
     #[test]
     fn test_msm_with_constant_bases_montgomery() {
-        let n = 1;
-        let scalars = (0..n).map(|_| rnd_fr(&mut trng())).collect::<Vec<_>>();
-        let bases = (0..n).map(|_| rnd_g2(&mut trng())).collect::<Vec<_>>();
+        let n = 2;
+        let mut rng = trng();
+        let scalars = (0..n).map(|_| rnd_fr(&mut rng)).collect::<Vec<_>>();
+        let bases = (0..n).map(|_| rnd_g2(&mut rng)).collect::<Vec<_>>();
         let bases_affine = bases.iter().map(|g| g.into_affine()).collect::<Vec<_>>();
         let result = ark_bn254::G2Projective::msm(&bases_affine, &scalars).unwrap();
 
@@ -849,12 +1256,7 @@ mod tests {
         impl<M: CircuitMode<WireValue = bool>> EncodeInput<M> for MsmInputs {
             fn encode(&self, repr: &MsmInputsWire, cache: &mut M) {
                 for (fr_wire, fr_val) in repr.scalars.iter().zip(self.scalars.iter()) {
-                    let fr_fn = Fr::get_wire_bits_fn(fr_wire, fr_val).unwrap();
-                    for &wire_id in fr_wire.iter() {
-                        if let Some(bit) = fr_fn(wire_id) {
-                            cache.feed_wire(wire_id, bit);
-                        }
-                    }
+                    Fr::encode(fr_wire, fr_val, cache);
                 }
             }
         }
@@ -873,4 +1275,44 @@ mod tests {
         let actual_result = G2Projective::from_bits_unchecked(circuit_result.output_value.clone());
         assert_eq!(actual_result, G2Projective::as_montgomery(result));
     }
+
+    #[test]
+    fn test_msm_with_repeated_bases_builds_tables_once() {
+        const W: usize = 10;
+
+        let mut rng = trng();
+        let p = rnd_g2(&mut rng);
+        let q = rnd_g2(&mut rng);
+        let bases = vec![p, q, p];
+        let scalars = [(); 3].map(|_| rnd_fr(&mut rng));
+        let bases_affine = bases.iter().map(|g| g.into_affine()).collect::<Vec<_>>();
+        let expected = ark_bn254::G2Projective::msm(&bases_affine, &scalars).unwrap();
+
+        let start = std::time::Instant::now();
+        for base in &bases {
+            G2ConstTables::new(base, W);
+        }
+        let per_term = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let context = G2MsmContext::new(&bases, W);
+        let shared = start.elapsed();
+        tracing::info!("tables built per term in {per_term:?}, shared in {shared:?}");
+        assert_eq!(context.distinct_bases(), 2);
+
+        let inputs = ScalarInput { scalars };
+        let circuit_result: crate::circuit::StreamingResult<_, _, Vec<bool>> =
+            CircuitBuilder::streaming_execute(inputs, 10_000, |root, inputs_wire| {
+                G2Projective::msm_with_context_montgomery(
+                    root,
+                    &inputs_wire.scalars,
+                    &bases,
+                    &context,
+                )
+                .to_wires_vec()
+            });
+
+        let actual_result = G2Projective::from_bits_unchecked(circuit_result.output_value.clone());
+        assert_eq!(actual_result, G2Projective::as_montgomery(expected));
+    }
 }
@@ -4,7 +4,6 @@
 //! for the BN254 (alt_bn128) elliptic curve, commonly used in zero-knowledge proofs.
 
 pub mod final_exponentiation;
-pub mod fp254impl;
 pub mod fq;
 pub mod fq12;
 pub mod fq2;
@@ -14,7 +13,6 @@ pub mod g1;
 pub mod g2;
 pub mod montgomery;
 pub mod pairing;
-pub use fp254impl::Fp254Impl;
 pub use fq::Fq;
 //pub use fq2::Fq2;
 //pub use fq6::Fq6;
@@ -25,5 +23,5 @@ pub use g2::G2Projective;
 pub use montgomery::Montgomery;
 pub use pairing::{
     ell_coeffs, miller_loop_const_q, multi_miller_loop_const_q, multi_pairing_const_q,
-    pairing_const_q,
+    multi_pairing_montgomery, pairing_const_q, pairing_montgomery,
 };
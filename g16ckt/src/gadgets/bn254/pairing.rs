@@ -19,7 +19,7 @@ use ark_ff::{AdditiveGroup, Field};
 use circuit_component_macro::component;
 
 use crate::{
-    CircuitContext, Fp254Impl,
+    CircuitContext, FpImpl,
     circuit::{FromWires, OffCircuitParam, WiresArity, WiresObject},
     gadgets::bn254::{
         final_exponentiation::final_exponentiation_montgomery, fq::Fq, fq2::Fq2, fq6::Fq6,
@@ -888,6 +888,47 @@ pub fn pairing_const_q<C: CircuitContext>(
     final_exponentiation_montgomery(circuit, &f)
 }
 
+/// Full pairing `e(P, Q)` with both points as wires, in Montgomery form: normalizes them to affine,
+/// then runs the Miller loop and the final exponentiation.
+///
+/// Neither point may be the point at infinity, whose `z` has no inverse.
+pub fn pairing_montgomery<C: CircuitContext>(
+    circuit: &mut C,
+    p: &G1Projective,
+    q: &G2Projective,
+) -> Fq12 {
+    let p = g1_normalize_to_affine(circuit, p);
+    let q = g2_normalize_to_affine(circuit, q);
+    let f = miller_loop_montgomery_fast(circuit, &p, &q);
+    final_exponentiation_montgomery(circuit, &f)
+}
+
+/// Product of pairings `e(P_1, Q_1) * ... * e(P_n, Q_n)` with every point as wires, in Montgomery
+/// form. The Miller loops share one final exponentiation, so each extra pair costs one loop only.
+///
+/// No point may be the point at infinity; no pairs at all give one.
+pub fn multi_pairing_montgomery<C: CircuitContext>(
+    circuit: &mut C,
+    ps: &[G1Projective],
+    qs: &[G2Projective],
+) -> Fq12 {
+    assert_eq!(ps.len(), qs.len());
+    if ps.is_empty() {
+        return new_fq12_constant_montgomery(ark_bn254::Fq12::ONE);
+    }
+
+    let ps = ps
+        .iter()
+        .map(|p| g1_normalize_to_affine(circuit, p))
+        .collect::<Vec<_>>();
+    let qs = qs
+        .iter()
+        .map(|q| g2_normalize_to_affine(circuit, q))
+        .collect::<Vec<_>>();
+    let f = multi_miller_loop_montgomery_fast(circuit, &ps, &qs);
+    final_exponentiation_montgomery(circuit, &f)
+}
+
 /// Multi-pairing aggregation with constant `Q_i` and variable `P_i`.
 #[component(offcircuit_args = "qs")]
 pub fn multi_pairing_const_q<C: CircuitContext>(
@@ -1024,7 +1065,8 @@ mod tests {
         },
         gadgets::{
             bigint::{BigUint as BigUintOutput, bits_from_biguint_with_len},
-            bn254::{fp254impl::Fp254Impl, g2::G2Projective as G2Wires},
+            bn254::g2::G2Projective as G2Wires,
+            field::FpImpl,
         },
     };
 
@@ -2178,6 +2220,103 @@ mod tests {
         assert_eq!(result.output_value.value, expected_m);
     }
 
+    #[test]
+    fn test_pairing_montgomery_matches_ark() {
+        use ark_ec::pairing::Pairing;
+
+        let mut rng = ChaCha20Rng::seed_from_u64(0xE9);
+        // Scalar multiples come out with z != 1, so normalization is exercised too
+        let p = ark_bn254::G1Projective::generator() * rnd_fr(&mut rng);
+        let q = ark_bn254::G2Projective::generator() * rnd_fr(&mut rng);
+        let expected = ark_bn254::Bn254::pairing(p, q);
+
+        struct In {
+            p: ark_bn254::G1Projective,
+            q: ark_bn254::G2Projective,
+        }
+        struct W {
+            p: G1Projective,
+            q: G2Wires,
+        }
+        impl CircuitInput for In {
+            type WireRepr = W;
+            fn allocate(&self, mut issue: impl FnMut() -> WireId) -> Self::WireRepr {
+                W {
+                    p: G1Projective::new(&mut issue),
+                    q: G2Wires::new(issue),
+                }
+            }
+            fn collect_wire_ids(repr: &Self::WireRepr) -> Vec<WireId> {
+                [repr.p.to_wires_vec(), repr.q.to_wires_vec()].concat()
+            }
+        }
+        impl<M: CircuitMode<WireValue = bool>> EncodeInput<M> for In {
+            fn encode(&self, repr: &W, cache: &mut M) {
+                G1Projective::encode(&repr.p, &G1Projective::as_montgomery(self.p), cache);
+                G2Wires::encode(&repr.q, &G2Wires::as_montgomery(self.q), cache);
+            }
+        }
+
+        let result = CircuitBuilder::streaming_execute::<_, _, Fq12Output>(
+            In { p, q },
+            10_000,
+            |ctx, input| pairing_montgomery(ctx, &input.p, &input.q),
+        );
+
+        assert_eq!(result.output_value.value, Fq12::as_montgomery(expected.0));
+    }
+
+    #[test]
+    fn test_multi_pairing_montgomery_matches_ark() {
+        use ark_ec::pairing::Pairing;
+
+        let mut rng = ChaCha20Rng::seed_from_u64(0xE10);
+        let ps = [(); 2].map(|_| ark_bn254::G1Projective::generator() * rnd_fr(&mut rng));
+        let qs = [(); 2].map(|_| ark_bn254::G2Projective::generator() * rnd_fr(&mut rng));
+        let expected = ark_bn254::Bn254::multi_pairing(ps, qs);
+
+        struct In {
+            ps: [ark_bn254::G1Projective; 2],
+            qs: [ark_bn254::G2Projective; 2],
+        }
+        struct W {
+            ps: [G1Projective; 2],
+            qs: [G2Wires; 2],
+        }
+        impl CircuitInput for In {
+            type WireRepr = W;
+            fn allocate(&self, mut issue: impl FnMut() -> WireId) -> Self::WireRepr {
+                W {
+                    ps: std::array::from_fn(|_| G1Projective::new(&mut issue)),
+                    qs: std::array::from_fn(|_| G2Wires::new(&mut issue)),
+                }
+            }
+            fn collect_wire_ids(repr: &Self::WireRepr) -> Vec<WireId> {
+                let ps = repr.ps.iter().flat_map(|p| p.to_wires_vec());
+                let qs = repr.qs.iter().flat_map(|q| q.to_wires_vec());
+                ps.chain(qs).collect()
+            }
+        }
+        impl<M: CircuitMode<WireValue = bool>> EncodeInput<M> for In {
+            fn encode(&self, repr: &W, cache: &mut M) {
+                for (wires, p) in repr.ps.iter().zip(self.ps) {
+                    G1Projective::encode(wires, &G1Projective::as_montgomery(p), cache);
+                }
+                for (wires, q) in repr.qs.iter().zip(self.qs) {
+                    G2Wires::encode(wires, &G2Wires::as_montgomery(q), cache);
+                }
+            }
+        }
+
+        let result = CircuitBuilder::streaming_execute::<_, _, Fq12Output>(
+            In { ps, qs },
+            10_000,
+            |ctx, input| multi_pairing_montgomery(ctx, &input.ps, &input.qs),
+        );
+
+        assert_eq!(result.output_value.value, Fq12::as_montgomery(expected.0));
+    }
+
     #[test]
     fn test_multi_pairing_const_q_matches_ark_n3() {
         use ark_ec::pairing::Pairing;
@@ -1,3 +1,5 @@
+//! Prime field arithmetic over wires, shared by the BN254 and BLS12-381 fields
+
 use std::str::FromStr;
 
 use ark_ff::{AdditiveGroup, Field, PrimeField};
@@ -6,23 +8,52 @@ use itertools::Itertools;
 use num_bigint::BigUint;
 use num_traits::{One, Zero};
 
-use super::super::bigint::{self, BigIntWires};
+use super::bigint::{self, BigIntWires};
 use crate::{
     CircuitContext, Gate, WireId,
-    circuit::{FALSE_WIRE, FromWires, TRUE_WIRE, WiresObject},
+    circuit::{FALSE_WIRE, FromWires, OffCircuitParam, TRUE_WIRE, WiresObject},
     math::montgomery::calculate_montgomery_constants,
 };
 
-/// Core trait for BN254 field implementation with 254-bit prime field arithmetic
+/// Most doublings plus additions [`FpImpl::mul_by_constant_montgomery`] spends on a shift-add
+/// chain before falling back to a full multiplication. A step costs about 1.5k AND gates against
+/// about 80k for the multiplication, so a chain stays well under half of it.
+const MAX_SHIFT_ADD_STEPS: u64 = 32;
+
+/// Doublings plus additions a shift-add chain multiplying by `factor` takes
+fn shift_add_steps(factor: &BigUint) -> u64 {
+    (factor.bits() - 1) + (factor.count_ones() - 1)
+}
+
+/// `a * factor mod p` by doubling, from the most significant bit of `factor` down, and adding `a`
+/// at each set bit
+fn mul_by_shift_add<F: FpImpl + ?Sized, C: CircuitContext>(
+    circuit: &mut C,
+    a: &BigIntWires,
+    factor: &BigUint,
+) -> BigIntWires {
+    let mut acc = a.clone();
+    for i in (0..factor.bits() - 1).rev() {
+        acc = F::double(circuit, &acc);
+        if factor.bit(i) {
+            acc = F::add(circuit, &acc, a);
+        }
+    }
+    acc
+}
+
+/// Core trait for prime field arithmetic over `N_BITS`-wide wires
 /// Provides constants and operations for field elements in Montgomery form
-pub trait Fp254Impl {
+///
+/// Written for the 254-bit BN254 fields; anything else only has to supply its modulus, width and
+/// off-circuit field, like the BLS12-381 base field does.
+pub trait FpImpl {
+    /// Off-circuit field the wires hold elements of, used for constants
+    type Field: PrimeField + OffCircuitParam;
+
     /// The prime modulus for the field
     const MODULUS: &'static str;
 
-    /// Montgomery constant R = 2^254
-    const MONTGOMERY_R: &'static str =
-        "28948022309329048855892746252171976963317496166410141009864396001978282409984";
-
     /// MODULUS^-1 modulo R  
     const MONTGOMERY_M_INVERSE: &'static str;
 
@@ -32,18 +63,14 @@ pub trait Fp254Impl {
     /// Number of bits in field elements
     const N_BITS: usize;
 
-    /// (MODULUS+1)/4 for square root operations
-    const MODULUS_ADD_1_DIV_4: &'static str =
-        "5472060717959818805561601436314318772174077789324455915672259473661306552146";
-
     /// Convert modulus string to BigUint
     fn modulus_as_biguint() -> BigUint {
         BigUint::from_str(Self::MODULUS).unwrap()
     }
 
-    /// Convert Montgomery R to BigUint
+    /// Montgomery constant R = 2^N_BITS
     fn montgomery_r_as_biguint() -> BigUint {
-        BigUint::from_str(Self::MONTGOMERY_R).unwrap()
+        BigUint::one() << Self::N_BITS
     }
 
     /// Convert Montgomery modulus inverse to BigUint
@@ -87,9 +114,29 @@ pub trait Fp254Impl {
     fn equal_constant<C: CircuitContext>(
         circuit: &mut C,
         a: &BigIntWires,
-        b: &ark_bn254::Fq,
+        b: &Self::Field,
     ) -> WireId {
-        bigint::equal_constant(circuit, a, &BigUint::from(b.into_bigint()))
+        bigint::equal_constant(circuit, a, &(*b).into())
+    }
+
+    /// Wire that is true iff `a` is the canonical encoding of an element, i.e. below the modulus
+    fn is_reduced<C: CircuitContext>(circuit: &mut C, a: &BigIntWires) -> WireId {
+        bigint::less_than_constant(circuit, a, &Self::modulus_as_biguint())
+    }
+
+    /// Element over `bits`, along with its [`FpImpl::is_reduced`] wire. Untrusted inputs can
+    /// encode anything up to `2^N_BITS - 1`, so they should be taken through here rather than
+    /// allocated unchecked, and the validity wire folded into the circuit result, e.g. with
+    /// [`CircuitContext::assert_true`].
+    fn from_bits_checked<C: CircuitContext>(
+        circuit: &mut C,
+        bits: impl IntoIterator<Item = WireId>,
+    ) -> (BigIntWires, WireId) {
+        let a = BigIntWires::from_bits(bits);
+        assert_eq!(a.len(), Self::N_BITS);
+
+        let valid = Self::is_reduced(circuit, &a);
+        (a, valid)
     }
 
     #[bn_component(arity = "Self::N_BITS")]
@@ -116,14 +163,14 @@ pub trait Fp254Impl {
     fn add_constant<C: CircuitContext>(
         circuit: &mut C,
         a: &BigIntWires,
-        b: &ark_bn254::Fq,
+        b: &Self::Field,
     ) -> BigIntWires {
         assert_eq!(a.len(), Self::N_BITS);
         if b.is_zero() {
             return (*a).clone();
         }
 
-        let mut wires_1 = bigint::add_constant(circuit, a, &BigUint::from(*b));
+        let mut wires_1 = bigint::add_constant(circuit, a, &(*b).into());
         let u = wires_1.pop().unwrap();
         let mut wires_2 = bigint::add_constant(circuit, &wires_1, &Self::not_modulus_as_biguint());
 
@@ -161,7 +208,7 @@ pub trait Fp254Impl {
         Self::add_constant(
             circuit,
             &not_a,
-            &(ark_bn254::Fq::from(1) - ark_bn254::Fq::from(Self::not_modulus_as_biguint())),
+            &(Self::Field::ONE - Self::Field::from(Self::not_modulus_as_biguint())),
         )
     }
 
@@ -232,8 +279,8 @@ pub trait Fp254Impl {
     ///
     /// # Returns
     /// Field element in Montgomery form (a * R mod p)
-    fn as_montgomery(a: ark_bn254::Fq) -> ark_bn254::Fq {
-        a * ark_bn254::Fq::from(Self::montgomery_r_as_biguint())
+    fn as_montgomery(a: Self::Field) -> Self::Field {
+        a * Self::Field::from(Self::montgomery_r_as_biguint())
     }
 
     /// Montgomery multiplication by constant for circuit wires
@@ -249,23 +296,36 @@ pub trait Fp254Impl {
     ///
     /// # Returns
     /// Product in Montgomery form
+    ///
+    /// When `b * R^-1`, the factor `a` ends up multiplied by, or its negation is small, this is
+    /// a chain of field doublings and additions instead of a full multiplication.
     #[bn_component(arity = "Self::N_BITS", offcircuit_args = "b")]
     fn mul_by_constant_montgomery<C: CircuitContext>(
         circuit: &mut C,
         a: &BigIntWires,
-        b: &ark_bn254::Fq,
+        b: &Self::Field,
     ) -> BigIntWires {
         assert_eq!(a.len(), Self::N_BITS);
 
-        if b == &ark_bn254::Fq::ZERO {
+        let modulus = Self::modulus_as_biguint();
+        let b: BigUint = (*b).into();
+        let factor = &b * Self::montgomery_r_inverse_as_biguint() % &modulus;
+        if factor.is_zero() {
             return BigIntWires::new_constant(a.len(), &BigUint::zero()).unwrap();
         }
 
-        if b == &Self::as_montgomery(ark_bn254::Fq::ONE) {
-            return (*a).clone();
+        if shift_add_steps(&factor) <= MAX_SHIFT_ADD_STEPS {
+            return mul_by_shift_add::<Self, _>(circuit, a, &factor);
         }
 
-        let mul_circuit = bigint::mul_by_constant(circuit, a, &b.into_bigint().into());
+        // Negating costs about one more step
+        let negated = &modulus - &factor;
+        if shift_add_steps(&negated) < MAX_SHIFT_ADD_STEPS {
+            let product = mul_by_shift_add::<Self, _>(circuit, a, &negated);
+            return Self::neg(circuit, &product);
+        }
+
+        let mul_circuit = bigint::mul_by_constant(circuit, a, &b);
 
         Self::montgomery_reduce(circuit, &mul_circuit)
     }
@@ -289,34 +349,34 @@ pub trait Fp254Impl {
     /// Montgomery reduction for circuit wires
     ///
     /// Reduces a double-width product to single-width Montgomery form.
-    /// Takes a 508-bit result from multiplication and reduces it to 254-bit Montgomery form
-    /// using the Montgomery reduction algorithm: `x * R^-1 mod p`.
+    /// Takes a `2 * N_BITS`-bit result from multiplication and reduces it to `N_BITS`-bit
+    /// Montgomery form using the Montgomery reduction algorithm: `x * R^-1 mod p`.
     ///
     /// This is the core operation that enables efficient Montgomery multiplication.
     ///
     /// # Arguments
     /// * `circuit` - Circuit to add gates to
-    /// * `x` - Double-width (508-bit) multiplication result
+    /// * `x` - Double-width (`2 * N_BITS`-bit) multiplication result
     ///
     /// # Returns
-    /// Single-width (254-bit) result in Montgomery form
+    /// Single-width (`N_BITS`-bit) result in Montgomery form
     #[bn_component(arity = "Self::N_BITS")]
     fn montgomery_reduce<C: CircuitContext>(circuit: &mut C, x: &BigIntWires) -> BigIntWires {
         assert_eq!(x.len(), 2 * Self::N_BITS);
 
-        let (x_low, x_high) = (*x).clone().split_at(254);
+        let (x_low, x_high) = (*x).clone().split_at(Self::N_BITS);
 
         let q = bigint::mul_by_constant_modulo_power_two(
             circuit,
             &x_low,
             &Self::montgomery_m_inverse_as_biguint(),
-            254,
+            Self::N_BITS,
         );
 
         let sub = bigint::mul_by_constant(circuit, &q, &Self::modulus_as_biguint())
-            .split_at(254)
+            .split_at(Self::N_BITS)
             .1
-            .truncate(254);
+            .truncate(Self::N_BITS);
 
         let bound_check = bigint::greater_than(circuit, &sub, &x_high);
 
@@ -389,15 +449,11 @@ pub trait Fp254Impl {
         let u = bigint::half(circuit, &neg_odd_part);
         let v = odd_part;
 
-        let k = BigIntWires::new_constant(a.len(), &BigUint::from(ark_bn254::Fq::ONE)).unwrap();
+        let k = BigIntWires::new_constant(a.len(), &BigUint::one()).unwrap();
 
-        let r = BigIntWires::new_constant(a.len(), &BigUint::from(ark_bn254::Fq::ONE)).unwrap();
+        let r = BigIntWires::new_constant(a.len(), &BigUint::one()).unwrap();
 
-        let s = BigIntWires::new_constant(
-            a.len(),
-            &BigUint::from(ark_bn254::Fq::ONE + ark_bn254::Fq::ONE),
-        )
-        .unwrap();
+        let s = BigIntWires::new_constant(a.len(), &BigUint::from(2u8)).unwrap();
 
         let mut input = IterationContext { u, v, r, s, k };
 
@@ -406,8 +462,10 @@ pub trait Fp254Impl {
         for chunk in (0..2 * Self::N_BITS).chunks(PER_CHUNK).into_iter() {
             let chunk = chunk.into_iter().collect::<Vec<_>>();
 
+            let name = "inverse_iteration";
             input = circuit.with_named_child(
-                crate::component_key!("inverse_iteration", 5 * Self::N_BITS, 5 * Self::N_BITS),
+                name,
+                crate::component_key!(name, 5 * Self::N_BITS, 5 * Self::N_BITS),
                 input,
                 |circuit, input| {
                     let IterationContext {
@@ -544,12 +602,10 @@ pub trait Fp254Impl {
 
         let IterationContext { s, k, .. } = input;
 
+        let name = "inverse::divide_result_by_even_part";
         let s = circuit.with_named_child(
-            crate::component_key!(
-                "inverse::divide_result_by_even_part",
-                2 * Self::N_BITS,
-                s.len() + even_part.len()
-            ),
+            name,
+            crate::component_key!(name, 2 * Self::N_BITS, s.len() + even_part.len()),
             (s, even_part),
             |circuit, inputs| {
                 let (s, even_part) = inputs;
@@ -564,9 +620,11 @@ pub trait Fp254Impl {
 
                     let chunk_idx_bytes = chunk_idx.to_le_bytes();
 
+                    let name = "inverse::divide_result_by_even_part::chunk";
                     let (new_s, new_even_part) = circuit.with_named_child(
+                        name,
                         crate::component_key!(
-                            "inverse::divide_result_by_even_part::chunk",
+                            name,
                             chunk_idx = &chunk_idx_bytes[..] ;
                             2 * Self::N_BITS,
                             s.len() + even_part.len()
@@ -609,12 +667,10 @@ pub trait Fp254Impl {
 
         let input_wires = [s.clone().to_wires_vec(), k.clone().to_wires_vec()].concat();
         let input_wires_len = input_wires.len();
+        let name = "inverse::divide_result_by_2^k";
         circuit.with_named_child(
-            crate::component_key!(
-                "inverse::divide_result_by_2^k",
-                2 * Self::N_BITS,
-                input_wires_len
-            ),
+            name,
+            crate::component_key!(name, 2 * Self::N_BITS, input_wires_len),
             (s.clone(), k.clone()),
             |circuit, inputs| {
                 let (mut s, mut k) = inputs.clone();
@@ -623,23 +679,20 @@ pub trait Fp254Impl {
                 for chunk in (0..2 * Self::N_BITS).chunks(PER_CHUNK).into_iter() {
                     let chunk = chunk.collect::<Vec<_>>();
 
+                    let name = "inverse::divide_result_by_2^k::chunk";
                     let (new_s, new_k) = circuit.with_named_child(
-                        crate::component_key!(
-                            "inverse::divide_result_by_2^k::chunk",
-                            2 * Self::N_BITS,
-                            s.len() + k.len()
-                        ),
+                        name,
+                        crate::component_key!(name, 2 * Self::N_BITS, s.len() + k.len()),
                         (s, k),
                         |circuit, inputs| {
                             let (mut s, mut k) = inputs.clone();
 
                             for _ in chunk.iter() {
                                 let updated_s = Self::half(circuit, &s);
-                                let updated_k =
-                                    Self::add_constant(circuit, &k, &ark_bn254::Fq::from(-1));
+                                let updated_k = Self::add_constant(circuit, &k, &-Self::Field::ONE);
 
                                 let selector =
-                                    Self::equal_constant(circuit, &k, &ark_bn254::Fq::ZERO);
+                                    Self::equal_constant(circuit, &k, &Self::Field::ZERO);
 
                                 s = bigint::select(circuit, &s, &updated_s, selector);
                                 k = bigint::select(circuit, &k, &updated_k, selector);
@@ -683,8 +736,8 @@ pub trait Fp254Impl {
         Self::mul_by_constant_montgomery(
             circuit,
             &b,
-            &(ark_bn254::Fq::from(Self::montgomery_r_as_biguint()).square()
-                * ark_bn254::Fq::from(Self::montgomery_r_as_biguint())),
+            &(Self::Field::from(Self::montgomery_r_as_biguint()).square()
+                * Self::Field::from(Self::montgomery_r_as_biguint())),
         )
     }
 
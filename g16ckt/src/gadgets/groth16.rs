@@ -5,14 +5,18 @@
 //! where `msm = vk.gamma_abc_g1[0] + sum_i(public[i] * vk.gamma_abc_g1[i+1])`.
 
 use ark_bn254::Bn254;
-use ark_ec::{AffineRepr, CurveGroup, models::short_weierstrass::SWCurveConfig, pairing::Pairing};
+use ark_ec::{
+    AffineRepr, CurveGroup,
+    models::short_weierstrass::{Affine, SWCurveConfig},
+    pairing::Pairing,
+};
 use ark_ff::{AdditiveGroup, Field};
 use ark_groth16::VerifyingKey;
 use circuit_component_macro::component;
 
 use crate::{
-    CircuitContext, Fq2Wire, WireId,
-    circuit::{CircuitInput, CircuitMode, EncodeInput, WiresObject},
+    CircuitContext, Fq2Wire, Gate, WireId,
+    circuit::{CircuitInput, CircuitMode, EncodeInput, TRUE_WIRE, WiresObject},
     gadgets::{
         bigint,
         bn254::{
@@ -46,6 +50,37 @@ pub fn projective_to_affine_montgomery<C: CircuitContext>(
     }
 }
 
+/// Result wires of [`groth16_verify`], telling a malformed input apart from a proof that doesn't
+/// verify.
+#[derive(Clone, Copy, Debug)]
+pub struct Groth16Result {
+    /// True iff the pairing equation holds
+    pub pairing_ok: WireId,
+    /// True iff every public input is below the scalar field modulus and no assertion made through
+    /// [`CircuitContext::assert_true`] failed
+    pub inputs_valid: WireId,
+    /// `pairing_ok AND inputs_valid`
+    pub ok: WireId,
+}
+
+impl WiresObject for Groth16Result {
+    fn to_wires_vec(&self) -> Vec<WireId> {
+        vec![self.pairing_ok, self.inputs_valid, self.ok]
+    }
+
+    fn wire_count(&self) -> usize {
+        3
+    }
+
+    fn clone_from(&self, wire_gen: &mut impl FnMut() -> WireId) -> Self {
+        Self {
+            pairing_ok: self.pairing_ok.clone_from(wire_gen),
+            inputs_valid: self.inputs_valid.clone_from(wire_gen),
+            ok: self.ok.clone_from(wire_gen),
+        }
+    }
+}
+
 /// Verify Groth16 proof for BN254 using streaming gadgets.
 ///
 /// - `public`: public inputs as Fr wires (bit-wires, Montgomery ops inside gadgets).
@@ -53,11 +88,15 @@ pub fn projective_to_affine_montgomery<C: CircuitContext>(
 /// - `proof_b`: proof G2 point as host constant (affine).
 /// - `vk`: verifying key with constant elements (host-provided arkworks types).
 ///
-/// Returns a boolean wire that is 1 iff the proof verifies.
+/// Takes the validity accumulator ([`CircuitContext::validity_output`]) for
+/// [`Groth16Result::inputs_valid`], so it must be called at the root of the circuit.
+///
+/// Panics if `vk` doesn't have one `gamma_abc_g1` point per public input plus the constant term,
+/// see [`check_gamma_abc`].
 pub fn groth16_verify<C: CircuitContext>(
     circuit: &mut C,
     input: &Groth16VerifyInputWires,
-) -> WireId {
+) -> Groth16Result {
     let Groth16VerifyInputWires {
         public,
         a,
@@ -66,6 +105,15 @@ pub fn groth16_verify<C: CircuitContext>(
         vk,
     } = input;
 
+    if let Err(e) = check_gamma_abc(vk, public.len()) {
+        panic!("{e}");
+    }
+
+    for scalar in public {
+        let below_modulus = Fr::is_reduced(circuit, scalar);
+        circuit.assert_true(below_modulus);
+    }
+
     // Standard verification with public inputs
     // MSM: sum_i public[i] * gamma_abc_g1[i+1]
     let bases: Vec<ark_bn254::G1Projective> = vk
@@ -106,12 +154,90 @@ pub fn groth16_verify<C: CircuitContext>(
 
     let f = final_exponentiation_montgomery(circuit, &f);
 
-    Fq12::equal_constant(circuit, &f, &Fq12::as_montgomery(alpha_beta))
+    let pairing_ok = Fq12::equal_constant(circuit, &f, &Fq12::as_montgomery(alpha_beta));
+    // The accumulator can only be read once, while `inputs_valid` is read by `ok` and the caller
+    let validity = circuit.validity_output();
+    let inputs_valid = circuit.issue_wire();
+    circuit.add_gate(Gate::and(validity, TRUE_WIRE, inputs_valid));
+    let ok = circuit.issue_wire();
+    circuit.add_gate(Gate::and(pairing_ok, inputs_valid, ok));
+
+    Groth16Result {
+        pairing_ok,
+        inputs_valid,
+        ok,
+    }
+}
+
+/// Hash of the public input `preimage` that [`groth16_verify_with_public_hash`] verifies
+/// against: `sum_i (i + 1) * preimage[i] mod r`, in standard form.
+///
+/// This linear combination only binds the order and values of the preimage; it is not collision
+/// resistant, and stands in for a real hash such as Poseidon. Every preimage scalar is asserted
+/// below the modulus through [`CircuitContext::assert_true`].
+pub fn public_hash<C: CircuitContext>(circuit: &mut C, preimage: &[Fr]) -> Fr {
+    // With suffix sums `s_j = sum_{i >= j} preimage[i]`, the hash is `sum_j s_j`: two additions
+    // per scalar and no multiplication
+    let mut suffix: Option<Fr> = None;
+    let mut hash: Option<Fr> = None;
+    for scalar in preimage.iter().rev() {
+        let below_modulus = Fr::is_reduced(circuit, scalar);
+        circuit.assert_true(below_modulus);
+
+        let s = match &suffix {
+            Some(suffix) => Fr::add(circuit, suffix, scalar),
+            None => scalar.clone(),
+        };
+        hash = Some(match &hash {
+            Some(hash) => Fr::add(circuit, hash, &s),
+            None => s.clone(),
+        });
+        suffix = Some(s);
+    }
+
+    hash.unwrap_or_else(|| Fr::new_constant(&ark_bn254::Fr::ZERO).unwrap())
+}
+
+/// Off-circuit [`public_hash`], the public input to prove against
+pub fn public_hash_native(preimage: &[ark_bn254::Fr]) -> ark_bn254::Fr {
+    preimage
+        .iter()
+        .zip(1u64..)
+        .map(|(scalar, i)| *scalar * ark_bn254::Fr::from(i))
+        .sum()
+}
+
+/// Verify a proof whose only public input is the [`public_hash`] of `input.public`, for
+/// deployments that pass many public inputs as the preimage of a single one.
+///
+/// `input.vk` is the key of the circuit with the single hashed input. Also returns the hash
+/// wires, so the caller can bind them to the hash published elsewhere. Like [`groth16_verify`],
+/// must be called at the root of the circuit.
+pub fn groth16_verify_with_public_hash<C: CircuitContext>(
+    circuit: &mut C,
+    input: &Groth16VerifyInputWires,
+) -> (Groth16Result, Fr) {
+    let hash = public_hash(circuit, &input.public);
+
+    let result = groth16_verify(
+        circuit,
+        &Groth16VerifyInputWires {
+            public: vec![hash.clone()],
+            a: input.a.clone(),
+            b: input.b.clone(),
+            c: input.c.clone(),
+            vk: input.vk.clone(),
+        },
+    );
+    (result, hash)
 }
 
 /// Decompress a compressed G1 point (x, sign bit) into projective wires with z = 1 (Montgomery domain).
 /// - `x_m`: x-coordinate in Montgomery form wires
 /// - `y_flag`: boolean wire selecting the correct sqrt branch for y
+///
+/// An `x` with no point on the curve is asserted against through [`CircuitContext::assert_true`],
+/// as in [`decompress_g2_from_compressed`].
 #[component]
 pub fn decompress_g1_from_compressed<C: CircuitContext>(
     circuit: &mut C,
@@ -119,14 +245,12 @@ pub fn decompress_g1_from_compressed<C: CircuitContext>(
 ) -> G1Projective {
     let CompressedG1Wires { x_m, y_flag } = compressed.clone();
 
-    // rhs = x^3 + b (Montgomery domain)
-    let x2 = Fq::square_montgomery(circuit, &x_m);
-    let x3 = Fq::mul_montgomery(circuit, &x2, &x_m);
-    let b_m = Fq::as_montgomery(ark_bn254::g1::Config::COEFF_B);
-    let rhs = Fq::add_constant(circuit, &x3, &b_m);
+    let rhs = G1Projective::y_square_montgomery(circuit, &x_m);
 
     // sy = sqrt(rhs) in Montgomery domain
-    let sy = Fq::sqrt_montgomery(circuit, &rhs);
+    let (sy, on_curve) = Fq::sqrt_checked_montgomery(circuit, &rhs);
+    circuit.assert_true(on_curve);
+
     let sy_neg = Fq::neg(circuit, &sy);
     let y_bits = bigint::select(circuit, &sy.0, &sy_neg.0, y_flag);
     let y = Fq(y_bits);
@@ -142,6 +266,10 @@ pub fn decompress_g1_from_compressed<C: CircuitContext>(
     }
 }
 
+/// Decompress a compressed G2 point (x, sign bit) into projective wires with z = 1 (Montgomery domain).
+///
+/// An `x` with no point on the curve is asserted against through [`CircuitContext::assert_true`],
+/// rather than decompressed to a meaningless `y`.
 #[component]
 pub fn decompress_g2_from_compressed<C: CircuitContext>(
     circuit: &mut C,
@@ -149,22 +277,14 @@ pub fn decompress_g2_from_compressed<C: CircuitContext>(
 ) -> G2Projective {
     let CompressedG2Wires { p: x, y_flag } = compressed;
 
-    let x2 = Fq2Wire::square_montgomery(circuit, x);
+    let y2 = G2Projective::y_square_montgomery(circuit, x);
 
-    let x3 = Fq2Wire::mul_montgomery(circuit, &x2, x);
-
-    let y2 = Fq2Wire::add_constant(
-        circuit,
-        &x3,
-        &Fq2Wire::as_montgomery(ark_bn254::g2::Config::COEFF_B),
-    );
-
-    let y = Fq2Wire::sqrt_general_montgomery(circuit, &y2);
+    let (y, is_qr) = Fq2Wire::sqrt_general_montgomery(circuit, &y2);
+    circuit.assert_true(is_qr);
 
     let neg_y = Fq2Wire::neg(circuit, y.clone());
 
-    let final_y_0 = bigint::select(circuit, y.c0(), neg_y.c0(), *y_flag);
-    let final_y_1 = bigint::select(circuit, y.c1(), neg_y.c1(), *y_flag);
+    let final_y = Fq2Wire::conditional_select(circuit, &y, &neg_y, *y_flag);
 
     // z = 1 in Montgomery
     let one_m = Fq::as_montgomery(ark_bn254::Fq::ONE);
@@ -172,7 +292,7 @@ pub fn decompress_g2_from_compressed<C: CircuitContext>(
 
     G2Projective {
         x: x.clone(),
-        y: Fq2Wire([Fq(final_y_0), Fq(final_y_1)]),
+        y: final_y,
         // In Fq2, ONE is (c0=1, c1=0). Use Montgomery representation.
         z: Fq2Wire([
             Fq::new_constant(&one_m).unwrap(),
@@ -205,6 +325,10 @@ impl WiresObject for CompressedG1Wires {
         v
     }
 
+    fn wire_count(&self) -> usize {
+        self.x_m.wire_count() + 1
+    }
+
     fn clone_from(&self, wire_gen: &mut impl FnMut() -> WireId) -> Self {
         Self {
             x_m: self.x_m.clone_from(wire_gen),
@@ -237,6 +361,10 @@ impl WiresObject for CompressedG2Wires {
         v
     }
 
+    fn wire_count(&self) -> usize {
+        self.p.wire_count() + 1
+    }
+
     fn clone_from(&self, wire_gen: &mut impl FnMut() -> WireId) -> Self {
         Self {
             p: self.p.clone_from(wire_gen),
@@ -250,7 +378,7 @@ impl WiresObject for CompressedG2Wires {
 pub fn groth16_verify_compressed<C: CircuitContext>(
     circuit: &mut C,
     input: &Groth16VerifyCompressedInputWires,
-) -> crate::WireId {
+) -> Groth16Result {
     let a = decompress_g1_from_compressed(circuit, &input.a);
     let b = decompress_g2_from_compressed(circuit, &input.b);
     let c = decompress_g1_from_compressed(circuit, &input.c);
@@ -267,7 +395,7 @@ pub fn groth16_verify_compressed<C: CircuitContext>(
     )
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Groth16VerifyInput {
     pub public: Vec<ark_bn254::Fr>,
     pub a: ark_bn254::G1Projective,
@@ -313,13 +441,7 @@ impl<M: CircuitMode<WireValue = bool>> EncodeInput<M> for Groth16VerifyInput {
     fn encode(&self, repr: &Groth16VerifyInputWires, cache: &mut M) {
         // Encode public scalars
         for (w, v) in repr.public.iter().zip(self.public.iter()) {
-            let fr_fn = Fr::get_wire_bits_fn(w, v).unwrap();
-
-            for &wire in w.iter() {
-                if let Some(bit) = fr_fn(wire) {
-                    cache.feed_wire(wire, bit);
-                }
-            }
+            Fr::encode(w, v, cache);
         }
 
         // Encode G1 points (Montgomery coordinates)
@@ -368,14 +490,86 @@ impl<M: CircuitMode<WireValue = bool>> EncodeInput<M> for Groth16VerifyInput {
     }
 }
 
+/// A verifying key that doesn't fit the public inputs it is used with.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum VkMismatch {
+    #[error(
+        "verifying key has {gamma_abc} gamma_abc_g1 points, but {public} public inputs need {}",
+        public + 1
+    )]
+    GammaAbcLength { gamma_abc: usize, public: usize },
+}
+
+/// Check that `vk` has one `gamma_abc_g1` point per public input, plus the constant term.
+pub fn check_gamma_abc(vk: &VerifyingKey<Bn254>, public: usize) -> Result<(), VkMismatch> {
+    if vk.gamma_abc_g1.len() != public + 1 {
+        return Err(VkMismatch::GammaAbcLength {
+            gamma_abc: vk.gamma_abc_g1.len(),
+            public,
+        });
+    }
+    Ok(())
+}
+
 impl Groth16VerifyInput {
-    pub fn compress(self) -> Groth16VerifyCompressedInput {
-        Groth16VerifyCompressedInput(self)
+    /// Compress the proof points, once the verifying key is checked to fit the public inputs.
+    pub fn compress(self) -> Result<Groth16VerifyCompressedInput, VkMismatch> {
+        check_gamma_abc(&self.vk, self.public.len())?;
+        Ok(Groth16VerifyCompressedInput(self))
     }
 }
 
 pub struct Groth16VerifyCompressedInput(pub Groth16VerifyInput);
 
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum DecompressError {
+    #[error("compressed proof point {0} is not on the curve")]
+    NotOnCurve(&'static str),
+}
+
+/// Sign flag of a compressed point: whether `y` is the root [`Field::sqrt`] returns for `y^2`,
+/// which is the branch [`decompress_g1_from_compressed`] and [`decompress_g2_from_compressed`]
+/// select when the flag wire is set.
+///
+/// This is not the sign bit of gnark's or arkworks' compressed encodings, which mark the
+/// lexicographically largest of `±y` (in Fq2, comparing c1 first and c0 only on a tie). Neither
+/// bit determines the other, so a point compressed elsewhere has to be decompressed off-circuit
+/// before its flag can be computed.
+fn y_flag<F: Field>(y: F) -> bool {
+    y.square().sqrt().expect("y^2 must be QR") == y
+}
+
+/// Off-circuit counterpart of the in-circuit decompression: `y = ±sqrt(x^3 + a*x + b)`.
+fn decompress_point<P: SWCurveConfig>(x: P::BaseField, y_flag: bool) -> Option<Affine<P>> {
+    let y = (x.square() * x + P::COEFF_A * x + P::COEFF_B).sqrt()?;
+    let point = Affine::new_unchecked(x, if y_flag { y } else { -y });
+    point.is_on_curve().then_some(point)
+}
+
+impl Groth16VerifyCompressedInput {
+    /// Recover the uncompressed input from the x-coordinates and sign flags of the proof points,
+    /// exactly as the circuit sees them.
+    pub fn decompress(&self) -> Result<Groth16VerifyInput, DecompressError> {
+        let a = self.0.a.into_affine();
+        let b = self.0.b.into_affine();
+        let c = self.0.c.into_affine();
+
+        Ok(Groth16VerifyInput {
+            public: self.0.public.clone(),
+            a: decompress_point(a.x, y_flag(a.y))
+                .ok_or(DecompressError::NotOnCurve("A"))?
+                .into_group(),
+            b: decompress_point(b.x, y_flag(b.y))
+                .ok_or(DecompressError::NotOnCurve("B"))?
+                .into_group(),
+            c: decompress_point(c.x, y_flag(c.y))
+                .ok_or(DecompressError::NotOnCurve("C"))?
+                .into_group(),
+            vk: self.0.vk.clone(),
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct Groth16VerifyCompressedInputWires {
     pub public: Vec<Fr>,
@@ -390,6 +584,13 @@ impl WiresObject for Groth16VerifyCompressedInputWires {
         Groth16VerifyCompressedInput::collect_wire_ids(self)
     }
 
+    fn wire_count(&self) -> usize {
+        self.public.iter().map(Fr::wire_count).sum::<usize>()
+            + self.a.wire_count()
+            + self.b.wire_count()
+            + self.c.wire_count()
+    }
+
     fn clone_from(&self, mut issue: &mut impl FnMut() -> WireId) -> Self {
         Groth16VerifyCompressedInputWires {
             public: self.public.iter().map(|_| Fr::new(&mut issue)).collect(),
@@ -423,19 +624,17 @@ impl CircuitInput for Groth16VerifyCompressedInput {
         ids.extend(repr.c.to_wires_vec());
         ids
     }
+
+    fn input_wire_count(repr: &Self::WireRepr) -> usize {
+        repr.wire_count()
+    }
 }
 
 impl<M: CircuitMode<WireValue = bool>> EncodeInput<M> for Groth16VerifyCompressedInput {
     fn encode(&self, repr: &Groth16VerifyCompressedInputWires, cache: &mut M) {
         // Encode public scalars
         for (w, v) in repr.public.iter().zip(self.0.public.iter()) {
-            let fr_fn = Fr::get_wire_bits_fn(w, v).unwrap();
-
-            for &wire in w.iter() {
-                if let Some(bit) = fr_fn(wire) {
-                    cache.feed_wire(wire, bit);
-                }
-            }
+            Fr::encode(w, v, cache);
         }
 
         // Compute compression from standard affine coords; feed Montgomery x + flag
@@ -443,18 +642,9 @@ impl<M: CircuitMode<WireValue = bool>> EncodeInput<M> for Groth16VerifyCompresse
         let b_aff_std = self.0.b.into_affine();
         let c_aff_std = self.0.c.into_affine();
 
-        let a_flag = (a_aff_std.y.square())
-            .sqrt()
-            .expect("y^2 must be QR")
-            .eq(&a_aff_std.y);
-        let b_flag = (b_aff_std.y.square())
-            .sqrt()
-            .expect("y^2 must be QR in Fq2")
-            .eq(&b_aff_std.y);
-        let c_flag = (c_aff_std.y.square())
-            .sqrt()
-            .expect("y^2 must be QR")
-            .eq(&c_aff_std.y);
+        let a_flag = y_flag(a_aff_std.y);
+        let b_flag = y_flag(b_aff_std.y);
+        let c_flag = y_flag(c_aff_std.y);
 
         let a_x_m = Fq::as_montgomery(a_aff_std.x);
         let b_x_m = Fq2Wire::as_montgomery(b_aff_std.x);
@@ -491,6 +681,8 @@ impl<M: CircuitMode<WireValue = bool>> EncodeInput<M> for Groth16VerifyCompresse
 
 #[cfg(test)]
 mod tests {
+    use std::{cmp::Ordering, collections::HashSet, iter};
+
     use ark_ec::{AffineRepr, CurveGroup, PrimeGroup};
     use ark_ff::UniformRand;
     use ark_groth16::Groth16;
@@ -498,13 +690,17 @@ mod tests {
         lc,
         r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
     };
+    use ark_serialize::CanonicalSerialize;
     use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
     use rand::SeedableRng;
     use rand_chacha::ChaCha20Rng;
     use test_log::test;
 
     use super::*;
-    use crate::circuit::{CircuitBuilder, CircuitMode, EncodeInput, StreamingResult};
+    use crate::circuit::{
+        CircuitBuilder, CircuitMode, EncodeInput, StreamingResult,
+        modes::{Divergence, ReferenceCheckMode},
+    };
 
     // Helper to reduce duplication across bitflip tests for A, B, and C
     fn run_false_bitflip_test(seed: u64, mutate: impl FnOnce(&mut Groth16VerifyInput)) {
@@ -532,7 +728,9 @@ mod tests {
         mutate(&mut inputs);
 
         let out: StreamingResult<_, _, bool> =
-            CircuitBuilder::streaming_execute(inputs, 10_000, groth16_verify);
+            CircuitBuilder::streaming_execute(inputs, 10_000, |ctx, input| {
+                groth16_verify(ctx, input).ok
+            });
 
         assert!(!out.output_value);
     }
@@ -602,6 +800,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_wire_count_matches_wires_vec() {
+        let mut next = 0;
+        let mut issue = || {
+            next += 1;
+            WireId(next)
+        };
+
+        let point = G2Projective::new(&mut issue);
+        assert_eq!(point.wire_count(), point.to_wires_vec().len());
+
+        let inputs = Groth16VerifyCompressedInput(Groth16VerifyInput {
+            public: vec![ark_bn254::Fr::ONE; 3],
+            a: ark_bn254::G1Projective::generator(),
+            b: ark_bn254::G2Projective::generator(),
+            c: ark_bn254::G1Projective::generator(),
+            vk: VerifyingKey::default(),
+        });
+        let repr = inputs.allocate(issue);
+        assert_eq!(repr.wire_count(), repr.to_wires_vec().len());
+        assert_eq!(
+            Groth16VerifyCompressedInput::input_wire_count(&repr),
+            Groth16VerifyCompressedInput::collect_wire_ids(&repr).len()
+        );
+    }
+
     #[test]
     fn test_groth16_verify_true() {
         let k = 6;
@@ -626,11 +850,64 @@ mod tests {
         };
 
         let out: StreamingResult<_, _, bool> =
-            CircuitBuilder::streaming_execute(inputs, 40_000, groth16_verify);
+            CircuitBuilder::streaming_execute(inputs, 40_000, |ctx, input| {
+                groth16_verify(ctx, input).ok
+            });
 
         assert!(out.output_value);
     }
 
+    #[test]
+    fn test_public_hash_of_known_preimage() {
+        let preimage = [1u64, 2, 3].map(ark_bn254::Fr::from);
+        assert_eq!(public_hash_native(&preimage), ark_bn254::Fr::from(14u64));
+        assert_eq!(public_hash_native(&[]), ark_bn254::Fr::ZERO);
+    }
+
+    #[test]
+    fn test_groth16_verify_with_public_hash() {
+        let mut rng = ChaCha20Rng::seed_from_u64(777);
+        let preimage = [5u64, 7, 11, 13].map(ark_bn254::Fr::from).to_vec();
+        let hash = public_hash_native(&preimage);
+
+        // The dummy circuit's public input is `a * b`, so pick `b` to make it the hash
+        let a = ark_bn254::Fr::rand(&mut rng);
+        let circuit = DummyCircuit::<ark_bn254::Fr> {
+            a: Some(a),
+            b: Some(hash / a),
+            num_variables: 10,
+            num_constraints: 1 << 6,
+        };
+        let (pk, vk) = Groth16::<ark_bn254::Bn254>::setup(circuit, &mut rng).unwrap();
+        let proof = Groth16::<ark_bn254::Bn254>::prove(&pk, circuit, &mut rng).unwrap();
+
+        for (tampered, expected) in [(false, true), (true, false)] {
+            let mut public = preimage.clone();
+            if tampered {
+                public.swap(0, 1);
+            }
+            let inputs = Groth16VerifyInput {
+                public,
+                a: proof.a.into_group(),
+                b: proof.b.into_group(),
+                c: proof.c.into_group(),
+                vk: vk.clone(),
+            };
+
+            let out: StreamingResult<_, _, Vec<bool>> =
+                CircuitBuilder::streaming_execute(inputs, 40_000, |ctx, input| {
+                    let (result, hash) = groth16_verify_with_public_hash(ctx, input);
+                    iter::once(result.ok).chain(hash.iter().copied()).collect()
+                });
+
+            let (ok, hash_bits) = out.output_value.split_first().unwrap();
+            assert_eq!(*ok, expected, "tampered {tampered}");
+            if !tampered {
+                assert_eq!(Fr::from_bits(hash_bits.to_vec()), hash);
+            }
+        }
+    }
+
     #[test]
     fn test_groth16_verify_false_bitflip_a() {
         run_false_bitflip_test(54321, |inputs| {
@@ -677,7 +954,9 @@ mod tests {
         };
 
         let out: StreamingResult<_, _, bool> =
-            CircuitBuilder::streaming_execute(inputs, 10_000, groth16_verify);
+            CircuitBuilder::streaming_execute(inputs, 10_000, |ctx, input| {
+                groth16_verify(ctx, input).ok
+            });
 
         assert!(
             out.output_value,
@@ -712,7 +991,9 @@ mod tests {
         inputs.a = ark_bn254::G1Projective::rand(&mut rng);
 
         let out: StreamingResult<_, _, bool> =
-            CircuitBuilder::streaming_execute(inputs, 10_000, groth16_verify);
+            CircuitBuilder::streaming_execute(inputs, 10_000, |ctx, input| {
+                groth16_verify(ctx, input).ok
+            });
 
         assert!(
             !out.output_value,
@@ -747,7 +1028,9 @@ mod tests {
         inputs.b = ark_bn254::G2Projective::rand(&mut rng);
 
         let out: StreamingResult<_, _, bool> =
-            CircuitBuilder::streaming_execute(inputs, 10_000, groth16_verify);
+            CircuitBuilder::streaming_execute(inputs, 10_000, |ctx, input| {
+                groth16_verify(ctx, input).ok
+            });
 
         assert!(
             !out.output_value,
@@ -782,7 +1065,9 @@ mod tests {
         inputs.c = ark_bn254::G1Projective::rand(&mut rng);
 
         let out: StreamingResult<_, _, bool> =
-            CircuitBuilder::streaming_execute(inputs, 10_000, groth16_verify);
+            CircuitBuilder::streaming_execute(inputs, 10_000, |ctx, input| {
+                groth16_verify(ctx, input).ok
+            });
 
         assert!(
             !out.output_value,
@@ -816,7 +1101,9 @@ mod tests {
         };
 
         let out: crate::circuit::StreamingResult<_, _, bool> =
-            CircuitBuilder::streaming_execute(inputs, 10_000, groth16_verify);
+            CircuitBuilder::streaming_execute(inputs, 10_000, |ctx, input| {
+                groth16_verify(ctx, input).ok
+            });
 
         assert!(!out.output_value);
     }
@@ -877,6 +1164,73 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_groth16_result_separates_pairing_from_input_validity() {
+        let k = 4;
+        let mut rng = ChaCha20Rng::seed_from_u64(4242);
+        let circuit = DummyCircuit::<ark_bn254::Fr> {
+            a: Some(ark_bn254::Fr::rand(&mut rng)),
+            b: Some(ark_bn254::Fr::rand(&mut rng)),
+            num_variables: 8,
+            num_constraints: 1 << k,
+        };
+        let (pk, vk) = Groth16::<ark_bn254::Bn254>::setup(circuit, &mut rng).unwrap();
+        let proof = Groth16::<ark_bn254::Bn254>::prove(&pk, circuit, &mut rng).unwrap();
+
+        // A genuine proof, checked against the wrong statement
+        let inputs = Groth16VerifyInput {
+            public: vec![circuit.a.unwrap() * circuit.b.unwrap() + ark_bn254::Fr::ONE],
+            a: proof.a.into_group(),
+            b: proof.b.into_group(),
+            c: proof.c.into_group(),
+            vk,
+        };
+
+        let out: StreamingResult<_, _, Vec<bool>> =
+            CircuitBuilder::streaming_execute(inputs, 40_000, |ctx, input| {
+                groth16_verify(ctx, input).to_wires_vec()
+            });
+
+        let [pairing_ok, inputs_valid, ok] = out.output_value[..] else {
+            unreachable!()
+        };
+        assert!(!pairing_ok);
+        assert!(inputs_valid);
+        assert!(!ok);
+    }
+
+    /// Sign bit of gnark's and arkworks' compressed encodings: whether `y` is larger than `-y`,
+    /// comparing c1 first and c0 only when the c1s are equal
+    fn lexicographically_largest(y: ark_bn254::Fq2) -> bool {
+        match y.c1.cmp(&-y.c1) {
+            Ordering::Equal => y.c0 > -y.c0,
+            ordering => ordering == Ordering::Greater,
+        }
+    }
+
+    #[test]
+    fn test_g2_y_flag_is_not_the_lexicographic_sign() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1609);
+        let points = iter::once(ark_bn254::G2Affine::generator())
+            .chain((0..32).map(|_| ark_bn254::G2Projective::rand(&mut rng).into_affine()));
+
+        let mut seen = HashSet::new();
+        for p in points {
+            // arkworks sets the top bit of its encoding for the lexicographically largest y
+            let mut bytes = Vec::new();
+            p.serialize_compressed(&mut bytes).unwrap();
+            let largest = bytes.last().unwrap() & 0x80 != 0;
+            assert_eq!(largest, lexicographically_largest(p.y));
+
+            // The circuit's flag recovers y, whichever root it is
+            assert_eq!(decompress_point(p.x, y_flag(p.y)), Some(p));
+            seen.insert((largest, y_flag(p.y)));
+        }
+
+        // Every combination occurs, so one bit can't stand in for the other
+        assert_eq!(seen.len(), 4);
+    }
+
     #[test]
     fn test_g1_compress_decompress_matches() {
         let mut rng = ChaCha20Rng::seed_from_u64(111);
@@ -903,6 +1257,32 @@ mod tests {
         assert!(out.output_value.iter().all(|&b| b));
     }
 
+    #[test]
+    fn test_g1_decompress_asserts_on_curve() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1614);
+        let p = ark_bn254::G1Projective::rand(&mut rng).into_affine();
+
+        let mut x = p.x;
+        while ark_bn254::G1Affine::get_point_from_x_unchecked(x, true).is_some() {
+            x += ark_bn254::Fq::ONE;
+        }
+        let off_curve = ark_bn254::G1Affine::new_unchecked(x, ark_bn254::Fq::ONE);
+
+        for (point, on_curve) in [(p, true), (off_curve, false)] {
+            let out: crate::circuit::StreamingResult<_, _, Vec<bool>> =
+                CircuitBuilder::streaming_execute(
+                    OnlyCompressedG1Input(point),
+                    10_000,
+                    |ctx, wires| {
+                        decompress_g1_from_compressed(ctx, wires);
+                        vec![ctx.validity_output()]
+                    },
+                );
+
+            assert_eq!(out.output_value, [on_curve]);
+        }
+    }
+
     #[test]
     fn test_g2_compress_decompress_matches() {
         let mut rng = ChaCha20Rng::seed_from_u64(222);
@@ -980,6 +1360,90 @@ mod tests {
         assert!(out.output_value.iter().all(|&b| b));
     }
 
+    #[test]
+    fn test_groth16_compress_decompress_roundtrip() {
+        let mut rng = ChaCha20Rng::seed_from_u64(44444);
+        let circuit = DummyCircuit::<ark_bn254::Fr> {
+            a: Some(ark_bn254::Fr::rand(&mut rng)),
+            b: Some(ark_bn254::Fr::rand(&mut rng)),
+            num_variables: 8,
+            num_constraints: 1 << 4,
+        };
+        let (pk, vk) = Groth16::<ark_bn254::Bn254>::setup(circuit, &mut rng).unwrap();
+
+        for _ in 0..4 {
+            let proof = Groth16::<ark_bn254::Bn254>::prove(&pk, circuit, &mut rng).unwrap();
+            let input = Groth16VerifyInput {
+                public: vec![circuit.a.unwrap() * circuit.b.unwrap()],
+                a: proof.a.into_group(),
+                b: proof.b.into_group(),
+                c: proof.c.into_group(),
+                vk: vk.clone(),
+            };
+
+            assert_eq!(input.clone().compress().unwrap().decompress(), Ok(input));
+        }
+    }
+
+    #[test]
+    fn test_groth16_decompress_rejects_off_curve_x() {
+        let mut rng = ChaCha20Rng::seed_from_u64(55555);
+        let mut input = Groth16VerifyInput {
+            public: vec![],
+            a: ark_bn254::G1Projective::rand(&mut rng),
+            b: ark_bn254::G2Projective::rand(&mut rng),
+            c: ark_bn254::G1Projective::rand(&mut rng),
+            vk: VerifyingKey::default(),
+        };
+
+        // About half of all x have no point above them
+        let mut c = input.c.into_affine();
+        while ark_bn254::G1Affine::get_point_from_x_unchecked(c.x, true).is_some() {
+            c.x += ark_bn254::Fq::ONE;
+        }
+        input.c = c.into();
+
+        assert_eq!(
+            Groth16VerifyCompressedInput(input).decompress(),
+            Err(DecompressError::NotOnCurve("C"))
+        );
+    }
+
+    #[test]
+    fn test_groth16_compress_rejects_gamma_abc_mismatch() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1622);
+        let vk = VerifyingKey::<ark_bn254::Bn254> {
+            gamma_abc_g1: vec![ark_bn254::G1Affine::rand(&mut rng); 2],
+            ..Default::default()
+        };
+        let mut input = |public: usize| Groth16VerifyInput {
+            public: vec![ark_bn254::Fr::ONE; public],
+            a: ark_bn254::G1Projective::rand(&mut rng),
+            b: ark_bn254::G2Projective::rand(&mut rng),
+            c: ark_bn254::G1Projective::rand(&mut rng),
+            vk: vk.clone(),
+        };
+
+        assert!(input(1).compress().is_ok());
+        for public in [0, 2] {
+            let err = input(public).compress().err().unwrap();
+            assert_eq!(
+                err,
+                VkMismatch::GammaAbcLength {
+                    gamma_abc: 2,
+                    public
+                }
+            );
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "verifying key has 2 gamma_abc_g1 points, but {public} public inputs need {}",
+                    public + 1
+                )
+            );
+        }
+    }
+
     // Full end-to-end compressed Groth16 verification. This is heavy because it
     // runs Miller loop + final exponentiation in-circuit. Kept for completeness
     // but ignored by default; run explicitly when needed.
@@ -1004,14 +1468,94 @@ mod tests {
             c: proof.c.into_group(),
             vk,
         }
-        .compress();
+        .compress()
+        .unwrap();
 
         let out: crate::circuit::StreamingResult<_, _, bool> =
-            CircuitBuilder::streaming_execute(inputs, 80_000, groth16_verify_compressed);
+            CircuitBuilder::streaming_execute(inputs, 80_000, |ctx, input| {
+                groth16_verify_compressed(ctx, input).ok
+            });
 
         assert!(out.output_value);
     }
 
+    /// Decompresses the proof points of `inputs` with each checked against ark's affine point,
+    /// after inverting the sign flag of B if `flip_b` is set
+    fn decompress_against_reference(
+        inputs: Groth16VerifyCompressedInput,
+        flip_b: bool,
+    ) -> Option<Divergence> {
+        // Decompression gives affine points, with z = 1
+        let a = G1Projective::as_montgomery(inputs.0.a.into_affine().into_group());
+        let b = G2Projective::as_montgomery(inputs.0.b.into_affine().into_group());
+        let c = G1Projective::as_montgomery(inputs.0.c.into_affine().into_group());
+
+        let result: StreamingResult<_, _, Vec<bool>> = CircuitBuilder::run_streaming(
+            inputs,
+            ReferenceCheckMode::with_capacity(80_000),
+            |ctx, input| {
+                let mut compressed_b = input.b.clone();
+                if flip_b {
+                    compressed_b.y_flag = ctx.issue_wire();
+                    ctx.add_gate(Gate::xor(input.b.y_flag, TRUE_WIRE, compressed_b.y_flag));
+                }
+
+                let a_wires = decompress_g1_from_compressed(ctx, &input.a);
+                let b_wires = decompress_g2_from_compressed(ctx, &compressed_b);
+                let c_wires = decompress_g1_from_compressed(ctx, &input.c);
+
+                if let Some(mode) = ctx.get_mut_mode() {
+                    let a_bits = G1Projective::get_wire_bits_fn(&a_wires, &a).unwrap();
+                    mode.check("A", &a_wires.to_wires_vec(), a_bits);
+                    let b_bits = G2Projective::get_wire_bits_fn(&b_wires, &b).unwrap();
+                    mode.check("B", &b_wires.to_wires_vec(), b_bits);
+                    let c_bits = G1Projective::get_wire_bits_fn(&c_wires, &c).unwrap();
+                    mode.check("C", &c_wires.to_wires_vec(), c_bits);
+                }
+
+                [
+                    a_wires.to_wires_vec(),
+                    b_wires.to_wires_vec(),
+                    c_wires.to_wires_vec(),
+                ]
+                .concat()
+            },
+        );
+        result.ciphertext_handler_result
+    }
+
+    #[test]
+    fn test_reference_check_localizes_corrupted_decompression() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1603);
+        let input = Groth16VerifyInput {
+            public: vec![],
+            a: ark_bn254::G1Projective::rand(&mut rng),
+            b: ark_bn254::G2Projective::rand(&mut rng),
+            c: ark_bn254::G1Projective::rand(&mut rng),
+            vk: VerifyingKey::default(),
+        };
+        let b = input.b.into_affine();
+
+        assert_eq!(
+            decompress_against_reference(Groth16VerifyCompressedInput(input.clone()), false),
+            None
+        );
+
+        // With the flag flipped, B comes out as -B: x still matches, and the first wrong wire is
+        // the lowest bit where the Montgomery forms of y and -y differ
+        let (y_c0, y_c1) = Fq2Wire::to_bits(Fq2Wire::as_montgomery(b.y));
+        let (neg_y_c0, neg_y_c1) = Fq2Wire::to_bits(Fq2Wire::as_montgomery(-b.y));
+        let (y, neg_y) = ([y_c0, y_c1].concat(), [neg_y_c0, neg_y_c1].concat());
+        let first = (0..y.len()).find(|&i| y[i] != neg_y[i]).unwrap();
+
+        let divergence =
+            decompress_against_reference(Groth16VerifyCompressedInput(input), true).unwrap();
+        assert_eq!(divergence.label, "B");
+        assert_eq!(divergence.index, Fq2Wire::N_BITS + first);
+        assert_eq!(divergence.expected, y[first]);
+        assert_eq!(divergence.actual, Some(neg_y[first]));
+    }
+
     // Unified small verifier runner to avoid duplication across flows and bitflips
     #[derive(Copy, Clone)]
     enum VerifyFlow {
@@ -1048,15 +1592,17 @@ mod tests {
         match flow {
             VerifyFlow::Uncompressed => {
                 let out: StreamingResult<_, _, bool> =
-                    CircuitBuilder::streaming_execute(inputs, 40_000, groth16_verify);
+                    CircuitBuilder::streaming_execute(inputs, 40_000, |ctx, input| {
+                        groth16_verify(ctx, input).ok
+                    });
 
                 out.output_value
             }
             VerifyFlow::Compressed => {
                 let out: StreamingResult<_, _, bool> = CircuitBuilder::streaming_execute(
-                    inputs.compress(),
+                    inputs.compress().unwrap(),
                     80_000,
-                    groth16_verify_compressed,
+                    |ctx, input| groth16_verify_compressed(ctx, input).ok,
                 );
 
                 out.output_value
@@ -1,6 +1,8 @@
 pub mod basic;
 pub mod bigint;
+pub mod bls12_381;
 pub mod bn254;
+pub mod field;
 pub mod groth16;
 
 pub use groth16::{groth16_verify, groth16_verify_compressed};
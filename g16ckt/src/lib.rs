@@ -6,7 +6,13 @@ mod math;
 pub mod storage;
 
 // Re-export the procedural macro
-pub use core::{delta::Delta, gate::Gate, gate_type::GateType, s::S, wire::WireId};
+pub use core::{
+    delta::Delta,
+    gate::Gate,
+    gate_type::{GateCount, GateType},
+    s::S,
+    wire::WireId,
+};
 
 pub use circuit::CircuitContext;
 pub use circuit_component_macro::component;
@@ -14,10 +20,11 @@ pub use circuit_component_macro::component;
 pub use gadgets::{
     bits_from_biguint_with_len,
     bn254::{
-        Fp254Impl, fq::Fq as FqWire, fq2::Fq2 as Fq2Wire, fr::Fr as FrWire,
-        g1::G1Projective as G1Wire, g2::G2Projective as G2Wire,
+        fq::Fq as FqWire, fq2::Fq2 as Fq2Wire, fq6::Fq6 as Fq6Wire, fq12::Fq12 as Fq12Wire,
+        fr::Fr as FrWire, g1::G1Projective as G1Wire, g2::G2Projective as G2Wire,
     },
-    groth16::{Groth16VerifyInput, Groth16VerifyInputWires},
+    field::FpImpl,
+    groth16::{Groth16Result, Groth16VerifyInput, Groth16VerifyInputWires},
     groth16_verify, groth16_verify_compressed,
 };
 pub use logging::init_tracing;
@@ -25,11 +32,50 @@ pub use math::*;
 
 #[cfg(test)]
 pub mod test_utils {
+    use std::env::{self, VarError};
+
     use rand::SeedableRng;
     use rand_chacha::ChaCha20Rng;
 
+    /// Environment variable overriding the seed of [`trng`], to replay a failing run with the seed
+    /// it logged
+    pub const SEED_VAR: &str = "G16_TEST_SEED";
+
+    /// Deterministic RNG, seeded from [`SEED_VAR`] or with 0 when it is unset
     pub fn trng() -> ChaCha20Rng {
-        ChaCha20Rng::seed_from_u64(0)
+        ChaCha20Rng::seed_from_u64(seed_from(env::var(SEED_VAR)))
+    }
+
+    fn seed_from(var: Result<String, VarError>) -> u64 {
+        let seed = match var {
+            Ok(seed) => seed
+                .parse()
+                .unwrap_or_else(|e| panic!("{SEED_VAR}={seed:?} is not a u64 seed: {e}")),
+            Err(VarError::NotPresent) => 0,
+            Err(e) => panic!("{SEED_VAR}: {e}"),
+        };
+        tracing::debug!("seeding trng with {SEED_VAR}={seed}");
+        seed
+    }
+
+    #[test]
+    fn test_same_seed_replays_same_points() {
+        use ark_ff::UniformRand;
+
+        let points = |var: Result<String, VarError>| {
+            let mut rng = ChaCha20Rng::seed_from_u64(seed_from(var));
+            (0..4)
+                .map(|_| ark_bn254::G2Projective::rand(&mut rng))
+                .collect::<Vec<_>>()
+        };
+
+        let replayed = points(Ok("1623".to_string()));
+        assert_eq!(replayed, points(Ok("1623".to_string())));
+        assert_ne!(replayed, points(Ok("1624".to_string())));
+        assert_eq!(
+            points(Err(VarError::NotPresent)),
+            points(Ok("0".to_string()))
+        );
     }
 }
 
@@ -37,7 +83,9 @@ pub mod test_utils {
 pub mod ark {
     // Field traits and RNG utilities
     // Curve types and configs used by examples
-    pub use ark_bn254::{Bn254, Fq, Fq2, Fq12, Fr, G1Projective, G2Affine, G2Projective, g1, g2};
+    pub use ark_bn254::{
+        Bn254, Fq, Fq2, Fq6, Fq12, Fr, G1Projective, G2Affine, G2Projective, g1, g2,
+    };
     // EC traits
     pub use ark_ec::{AffineRepr, CurveGroup, PrimeGroup, short_weierstrass::SWCurveConfig};
     pub use ark_ff::{PrimeField, UniformRand, fields::Field};
@@ -29,6 +29,78 @@ pub enum Error {
 /// - Typical fanout counts are small; `u16` is sufficient here.
 pub type Credits = u16;
 
+/// Bit width of a credit counter kept outside of [`Storage`], e.g. when collecting credits for a
+/// circuit file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CreditWidth {
+    U16,
+    U24,
+    #[default]
+    U32,
+}
+
+impl CreditWidth {
+    pub const fn max(self) -> u32 {
+        match self {
+            CreditWidth::U16 => u16::MAX as u32,
+            CreditWidth::U24 => (1 << 24) - 1,
+            CreditWidth::U32 => u32::MAX,
+        }
+    }
+
+    /// `value` as a counter of this width, or `None` if it doesn't fit.
+    pub fn checked(self, value: u64) -> Option<u32> {
+        u32::try_from(value)
+            .ok()
+            .filter(|value| *value <= self.max())
+    }
+
+    /// `value` as a counter of this width, clamped at [`CreditWidth::max`].
+    pub fn saturating(self, value: u64) -> u32 {
+        self.checked(value).unwrap_or(self.max())
+    }
+}
+
+/// What to do when a credit counter would exceed its [`CreditWidth`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CreditOverflow {
+    #[default]
+    Panic,
+    /// Cap the counter at the width maximum.
+    Saturate,
+}
+
+/// Width and overflow policy for collected credit counters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CreditConfig {
+    pub width: CreditWidth,
+    pub overflow: CreditOverflow,
+}
+
+impl CreditConfig {
+    /// Add `credits` to `counter`, returning `true` if the result had to be capped.
+    ///
+    /// Panics on overflow under [`CreditOverflow::Panic`].
+    pub fn add(self, counter: &mut u32, credits: u32) -> bool {
+        let max = self.width.max();
+        match counter.checked_add(credits).filter(|sum| *sum <= max) {
+            Some(sum) => {
+                *counter = sum;
+                false
+            }
+            None => match self.overflow {
+                CreditOverflow::Panic => {
+                    panic!("credit counter overflows {:?}", self.width)
+                }
+                CreditOverflow::Saturate => {
+                    *counter = max;
+                    true
+                }
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 struct Entry<T: Default> {
     credits: NonZero<Credits>,
@@ -39,6 +111,8 @@ struct Entry<T: Default> {
 pub struct Storage<K: From<usize>, T: Default> {
     data: Slab<Entry<T>>,
     index_offset: usize,
+    /// Whether outgrowing the preallocated slab is logged as an error
+    report_growth: bool,
     _p: PhantomData<K>,
 }
 
@@ -73,10 +147,20 @@ impl<K: Debug + Into<usize> + From<usize>, T: Default> Storage<K, T> {
         Self {
             data: Slab::with_capacity(capacity),
             index_offset: 2,
+            report_growth: true,
             _p: PhantomData,
         }
     }
 
+    /// Storage without preallocated slots that grows silently, for runs that measure how many
+    /// slots are needed
+    pub fn growable() -> Self {
+        Self {
+            report_growth: false,
+            ..Self::new(0)
+        }
+    }
+
     pub fn len(&self) -> usize {
         self.data.len()
     }
@@ -97,6 +181,12 @@ impl<K: Debug + Into<usize> + From<usize>, T: Default> Storage<K, T> {
         self.data.get(index).is_some()
     }
 
+    /// Value under `key`, if it is still live, without consuming a credit
+    pub fn peek(&self, key: K) -> Option<&T> {
+        let index = self.to_index(key);
+        self.data.get(index).map(|entry| &entry.data)
+    }
+
     fn to_key(&self, index: usize) -> K {
         K::from(index + self.index_offset)
     }
@@ -122,7 +212,7 @@ impl<K: Debug + Into<usize> + From<usize>, T: Default> Storage<K, T> {
             let index = self.data.insert(Entry { data, credits });
             let after = self.data.capacity();
 
-            if before != after {
+            if self.report_growth && before != after {
                 error!(" Capacity has grown from {before} to {after}");
             }
 
@@ -283,4 +373,53 @@ mod tests {
         assert_eq!(st.set(fake, |_| ()), Err(Error::NotFound { key: 123 }));
         assert_eq!(st.add_credits(fake, 1), Err(Error::NotFound { key: 123 }));
     }
+
+    #[test]
+    fn credit_config_saturates() {
+        let config = CreditConfig {
+            width: CreditWidth::U16,
+            overflow: CreditOverflow::Saturate,
+        };
+
+        let mut counter = u16::MAX as u32 - 1;
+        assert!(!config.add(&mut counter, 1));
+        assert_eq!(counter, u16::MAX as u32);
+        assert!(config.add(&mut counter, 1));
+        assert_eq!(counter, u16::MAX as u32);
+    }
+
+    #[test]
+    fn credit_width_u24_boundary() {
+        let width = CreditWidth::U24;
+
+        assert_eq!(width.checked(0xFF_FFFF), Some(0xFF_FFFF));
+        assert_eq!(width.checked(0x100_0000), None);
+        assert_eq!(width.checked(u64::MAX), None);
+
+        assert_eq!(width.saturating(0xFF_FFFF), 0xFF_FFFF);
+        assert_eq!(width.saturating(0x100_0000), 0xFF_FFFF);
+        assert_eq!(width.saturating(0xFF_FFFF * 0xFF_FFFF), 0xFF_FFFF);
+
+        let config = CreditConfig {
+            width,
+            overflow: CreditOverflow::Saturate,
+        };
+        let mut counter = 0xFF_FFFE;
+        assert!(!config.add(&mut counter, 1));
+        assert_eq!(counter, 0xFF_FFFF);
+        assert!(config.add(&mut counter, 0xFF_FFFF));
+        assert_eq!(counter, 0xFF_FFFF);
+    }
+
+    #[test]
+    #[should_panic(expected = "credit counter overflows U24")]
+    fn credit_config_panics_on_overflow() {
+        let config = CreditConfig {
+            width: CreditWidth::U24,
+            overflow: CreditOverflow::Panic,
+        };
+
+        let mut counter = CreditWidth::U24.max();
+        config.add(&mut counter, 1);
+    }
 }
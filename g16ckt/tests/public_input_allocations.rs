@@ -0,0 +1,89 @@
+// Encoding the public inputs of a Groth16 verifier shouldn't allocate per element. Kept in its own
+// test binary, since counting allocations takes over the global allocator.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use g16ckt::{
+    FpImpl, FrWire as Fr, Groth16VerifyInput,
+    ark::{self, UniformRand},
+    circuit::{CircuitInput, CircuitMode, EncodeInput, ExecuteMode},
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+const PUBLIC_INPUTS: usize = 2000;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts the allocations of the current thread
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Allocations made by `f` on this thread
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn test_public_inputs_encode_without_per_element_allocations() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let input = Groth16VerifyInput {
+        public: (0..PUBLIC_INPUTS)
+            .map(|_| ark::Fr::rand(&mut rng))
+            .collect(),
+        a: ark::G1Projective::rand(&mut rng),
+        b: ark::G2Projective::rand(&mut rng),
+        c: ark::G1Projective::rand(&mut rng),
+        vk: Default::default(),
+    };
+
+    let mut mode = ExecuteMode::with_capacity(PUBLIC_INPUTS * Fr::N_BITS + 10_000);
+    let repr = input.allocate(|| mode.allocate_wire(1));
+
+    // What encoding each element through a bit lookup, as it used to be done, costs
+    let per_element = allocations(|| {
+        for (wires, value) in repr.public.iter().zip(&input.public) {
+            let bits = Fr::get_wire_bits_fn(wires, value).unwrap();
+            for &wire in wires.iter() {
+                mode.feed_wire(wire, bits(wire).unwrap());
+            }
+        }
+    });
+    let batched = allocations(|| input.encode(&repr, &mut mode));
+
+    // The proof points alone, which are still encoded through lookups
+    let proof_only = Groth16VerifyInput {
+        public: Vec::new(),
+        ..input.clone()
+    };
+    let proof_repr = proof_only.allocate(|| mode.allocate_wire(1));
+    let proof_points = allocations(|| proof_only.encode(&proof_repr, &mut mode));
+
+    assert!(per_element >= PUBLIC_INPUTS, "{per_element} allocations");
+    assert_eq!(batched, proof_points);
+
+    for (wires, value) in repr.public.iter().zip(&input.public) {
+        let bits = wires.iter().map(|&wire| mode.peek_wire(wire).unwrap());
+        assert!(bits.eq(Fr::to_bits(*value)), "{value}");
+    }
+}
@@ -1,89 +1,355 @@
 use std::{
-    fs::OpenOptions,
-    io::{BufReader, BufWriter, Read, Write},
+    fmt,
+    fs::{self, OpenOptions},
+    hash::{DefaultHasher, Hasher},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
 };
 
-use g16ckt::WireId;
+use g16ckt::{
+    WireId,
+    ark::{self, ark_serialize::CanonicalSerialize},
+};
 
-const FANOUT_FILE: &str = "fanout.cache";
-const OUTPUT_WIRES_FILE: &str = "outputs.cache";
+const CACHE_MAGIC: [u8; 4] = *b"G16C";
+/// Bump whenever the cache layout or the credits computation changes
+const CACHE_VERSION: u32 = 3;
 
-/// Try to load cached fanout and output wires from files
-pub fn try_load_cache() -> Option<(Vec<u16>, Vec<WireId>)> {
-    let fanout = load_fanout()?;
-    let output_wires = load_output_wires()?;
-    Some((fanout, output_wires))
+/// Identifies the circuit a cache was computed for; written as the cache file header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CacheKey {
+    pub k: u64,
+    pub primary_inputs: u64,
+    /// Hash of the verifying key, whose constants are baked into the circuit
+    pub circuit_hash: u64,
 }
 
-/// Load fanout from cache file
-fn load_fanout() -> Option<Vec<u16>> {
-    let file = OpenOptions::new().read(true).open(FANOUT_FILE).ok()?;
-    let mut reader = BufReader::new(file);
-    let mut fanout = Vec::new();
+impl CacheKey {
+    pub fn new(k: usize, primary_inputs: usize, vk: &ark::VerifyingKey<ark::Bn254>) -> Self {
+        let mut bytes = Vec::new();
+        vk.serialize_compressed(&mut bytes)
+            .expect("serializing into a Vec can't fail");
+        let mut hasher = DefaultHasher::new();
+        hasher.write(&bytes);
+
+        Self {
+            k: k as u64,
+            primary_inputs: primary_inputs as u64,
+            circuit_hash: hasher.finish(),
+        }
+    }
+
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(&CACHE_MAGIC)?;
+        writer.write_all(&CACHE_VERSION.to_le_bytes())?;
+        writer.write_all(&self.k.to_le_bytes())?;
+        writer.write_all(&self.primary_inputs.to_le_bytes())?;
+        writer.write_all(&self.circuit_hash.to_le_bytes())
+    }
+
+    /// Read a header and check it against `self`
+    fn check(&self, reader: &mut impl Read) -> Result<(), CacheError> {
+        let mut magic = [0u8; 4];
+        let mut version = [0u8; 4];
+        let mut fields = [[0u8; 8]; 3];
+        reader.read_exact(&mut magic)?;
+        reader.read_exact(&mut version)?;
+        for field in &mut fields {
+            reader.read_exact(field)?;
+        }
 
-    loop {
-        let mut buf = [0u8; 2];
-        if reader.read_exact(&mut buf).is_err() {
-            break;
+        if magic != CACHE_MAGIC {
+            return Err(CacheError::Stale("not a g16gen cache file".to_string()));
         }
-        fanout.push(u16::from_le_bytes(buf));
+        let version = u32::from_le_bytes(version);
+        if version != CACHE_VERSION {
+            return Err(CacheError::Stale(format!(
+                "format version {version}, expected {CACHE_VERSION}"
+            )));
+        }
+
+        let [k, primary_inputs, circuit_hash] = fields.map(u64::from_le_bytes);
+        if k != self.k {
+            return Err(CacheError::Stale(format!("k={k}, expected k={}", self.k)));
+        }
+        if primary_inputs != self.primary_inputs {
+            return Err(CacheError::Stale(format!(
+                "{primary_inputs} primary inputs, expected {}",
+                self.primary_inputs
+            )));
+        }
+        if circuit_hash != self.circuit_hash {
+            return Err(CacheError::Stale(
+                "computed for a different verifying key".to_string(),
+            ));
+        }
+
+        Ok(())
     }
+}
 
-    Some(fanout)
+/// Why a cache couldn't be used
+#[derive(Debug)]
+pub enum CacheError {
+    /// No cache file present
+    Absent,
+    /// Cache file written for a different circuit, by an incompatible version, or cut short
+    Stale(String),
+    Io(io::Error),
 }
 
-/// Load output wires from cache file
-fn load_output_wires() -> Option<Vec<WireId>> {
-    let file = OpenOptions::new().read(true).open(OUTPUT_WIRES_FILE).ok()?;
-    let mut reader = BufReader::new(file);
-    let mut output_wires = Vec::new();
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::Absent => write!(f, "no cache found"),
+            CacheError::Stale(reason) => write!(f, "stale cache: {reason}"),
+            CacheError::Io(e) => write!(f, "failed to read cache: {e}"),
+        }
+    }
+}
 
-    loop {
-        let mut buf = [0u8; 8];
-        if reader.read_exact(&mut buf).is_err() {
-            break;
+impl From<io::Error> for CacheError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::NotFound => CacheError::Absent,
+            io::ErrorKind::UnexpectedEof => CacheError::Stale("truncated file".to_string()),
+            _ => CacheError::Io(e),
         }
-        output_wires.push(WireId(usize::from_le_bytes(buf)));
     }
+}
 
-    Some(output_wires)
+/// Cache file for the circuit written to `output`: next to it, with a `.cache` extension, so
+/// circuits written to different paths keep their own caches.
+pub fn cache_path(output: &Path) -> PathBuf {
+    output.with_extension("cache")
 }
 
-/// Save fanout to cache file
-pub fn save_fanout(fanout: &[u16]) -> std::io::Result<()> {
-    let file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(FANOUT_FILE)?;
+/// File a cache is written to before being renamed to `path`
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    PathBuf::from(tmp)
+}
 
-    let mut writer = BufWriter::new(file);
-    for fanout in fanout {
-        writer.write_all(&fanout.to_le_bytes())?;
+/// Try to load cached fanout and output wires computed for `key` from the cache file at `path`
+pub fn try_load_cache(path: &Path, key: &CacheKey) -> Result<(Vec<u32>, Vec<WireId>), CacheError> {
+    let file = OpenOptions::new().read(true).open(path)?;
+    let mut reader = BufReader::new(file);
+    key.check(&mut reader)?;
+
+    let output_wires = read_section(&mut reader, |buf: [u8; 8]| {
+        WireId(u64::from_le_bytes(buf) as usize)
+    })?;
+    let fanout = read_section(&mut reader, u32::from_le_bytes)?;
+
+    if reader.read(&mut [0u8])? != 0 {
+        return Err(CacheError::Stale("trailing data".to_string()));
     }
-    writer.flush()?;
-    Ok(())
+
+    Ok((fanout, output_wires))
+}
+
+/// Read a length-prefixed section of fixed-size little-endian entries, such as one written by
+/// [`write_credits`], in a single read.
+///
+/// The buffer only grows with the bytes actually read, so a corrupt length can't trigger a huge
+/// allocation before the truncation is noticed.
+fn read_section<const N: usize, T>(
+    reader: &mut impl Read,
+    decode: impl Fn([u8; N]) -> T,
+) -> Result<Vec<T>, CacheError> {
+    let mut len = [0u8; 8];
+    reader.read_exact(&mut len)?;
+    let len = u64::from_le_bytes(len)
+        .checked_mul(N as u64)
+        .ok_or_else(|| CacheError::Stale("section too long".to_string()))?;
+
+    let mut bytes = Vec::new();
+    reader.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(CacheError::Stale("truncated file".to_string()));
+    }
+
+    Ok(bytes
+        .chunks_exact(N)
+        .map(|entry| decode(entry.try_into().unwrap()))
+        .collect())
 }
 
-/// Save output wires to cache file
-pub fn save_output_wires(output_wires: &[WireId]) -> std::io::Result<()> {
+/// Write a length-prefixed section of credits, encoded into one buffer and written in a single
+/// call rather than one small write per wire
+fn write_credits(writer: &mut impl Write, credits: &[u32]) -> io::Result<()> {
+    let mut bytes = Vec::with_capacity(8 + credits.len() * 4);
+    bytes.extend_from_slice(&(credits.len() as u64).to_le_bytes());
+    bytes.extend(credits.iter().flat_map(|credits| credits.to_le_bytes()));
+    writer.write_all(&bytes)
+}
+
+/// Save both credits and output wires to the cache file at `path`.
+///
+/// The cache is written next to its final location and renamed into place, so an interrupted
+/// write never leaves a partial cache file behind.
+pub fn save_cache(
+    path: &Path,
+    key: &CacheKey,
+    credits: &[u32],
+    output_wires: &[WireId],
+) -> io::Result<()> {
+    let tmp_path = tmp_path(path);
     let file = OpenOptions::new()
         .write(true)
         .create(true)
         .truncate(true)
-        .open(OUTPUT_WIRES_FILE)?;
+        .open(&tmp_path)?;
 
     let mut writer = BufWriter::new(file);
+    key.write(&mut writer)?;
+
+    writer.write_all(&(output_wires.len() as u64).to_le_bytes())?;
     for output_wire in output_wires {
-        writer.write_all(&output_wire.0.to_le_bytes())?;
+        writer.write_all(&(output_wire.0 as u64).to_le_bytes())?;
     }
-    writer.flush()?;
-    Ok(())
+
+    write_credits(&mut writer, credits)?;
+
+    writer.into_inner()?.sync_all()?;
+    fs::rename(tmp_path, path)
 }
 
-/// Save both credits and output wires to cache files
-pub fn save_cache(credits: &[u16], output_wires: &[WireId]) -> std::io::Result<()> {
-    save_fanout(credits)?;
-    save_output_wires(output_wires)?;
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(k: u64) -> CacheKey {
+        CacheKey {
+            k,
+            primary_inputs: 1273,
+            circuit_hash: 0xdead_beef,
+        }
+    }
+
+    fn test_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_changing_k_invalidates_cache() {
+        let dir = test_dir("g16gen_cache_test");
+        let path = cache_path(&dir.join("g16.ckt"));
+
+        assert!(matches!(
+            try_load_cache(&path, &key(6)),
+            Err(CacheError::Absent)
+        ));
+
+        let credits = vec![0, 3, 1, u32::MAX];
+        let output_wires = vec![WireId(42)];
+        save_cache(&path, &key(6), &credits, &output_wires).unwrap();
+
+        let (loaded_credits, loaded_outputs) = try_load_cache(&path, &key(6)).unwrap();
+        assert_eq!(loaded_credits, credits);
+        assert_eq!(loaded_outputs, output_wires);
+
+        assert!(matches!(
+            try_load_cache(&path, &key(7)),
+            Err(CacheError::Stale(_))
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_partial_write_is_not_loaded() {
+        let dir = test_dir("g16gen_cache_partial_test");
+        let path = cache_path(&dir.join("g16.ckt"));
+
+        let credits = (0..100).collect::<Vec<u32>>();
+        let output_wires = vec![WireId(7), WireId(9)];
+        save_cache(&path, &key(6), &credits, &output_wires).unwrap();
+        let bytes = fs::read(&path).unwrap();
+
+        // Interrupted save: only a truncated temp file was written
+        fs::remove_file(&path).unwrap();
+        fs::write(tmp_path(&path), &bytes[..bytes.len() / 2]).unwrap();
+        assert!(matches!(
+            try_load_cache(&path, &key(6)),
+            Err(CacheError::Absent)
+        ));
+
+        // Even a truncated cache file is rejected instead of yielding partial sections
+        fs::rename(tmp_path(&path), &path).unwrap();
+        assert!(matches!(
+            try_load_cache(&path, &key(6)),
+            Err(CacheError::Stale(_))
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_bulk_credits_round_trip() {
+        let credits = (0..1u32 << 22)
+            .map(|i| i.wrapping_mul(0x9e37_79b9))
+            .collect::<Vec<u32>>();
+
+        let mut bytes = Vec::new();
+        write_credits(&mut bytes, &credits).unwrap();
+
+        // Same layout as writing the length and then every entry on its own
+        let mut looped = (credits.len() as u64).to_le_bytes().to_vec();
+        for credits in &credits {
+            looped.extend_from_slice(&credits.to_le_bytes());
+        }
+        assert_eq!(bytes, looped);
+
+        assert_eq!(
+            read_section(&mut bytes.as_slice(), u32::from_le_bytes).unwrap(),
+            credits
+        );
+        assert!(matches!(
+            read_section(&mut &bytes[..bytes.len() - 1], u32::from_le_bytes),
+            Err(CacheError::Stale(_))
+        ));
+
+        // A corrupt length is reported as stale instead of being allocated up front
+        let mut corrupt = (u64::MAX / 8).to_le_bytes().to_vec();
+        corrupt.extend_from_slice(&bytes[8..]);
+        assert!(matches!(
+            read_section(&mut corrupt.as_slice(), u32::from_le_bytes),
+            Err(CacheError::Stale(_))
+        ));
+    }
+
+    #[test]
+    fn test_corrupt_section_length_is_stale() {
+        let mut bytes = u64::MAX.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&42u64.to_le_bytes());
+
+        assert!(matches!(
+            read_section(&mut bytes.as_slice(), u64::from_le_bytes),
+            Err(CacheError::Stale(_))
+        ));
+
+        bytes[..8].copy_from_slice(&(u64::MAX / 16).to_le_bytes());
+        assert!(matches!(
+            read_section(&mut bytes.as_slice(), u64::from_le_bytes),
+            Err(CacheError::Stale(_))
+        ));
+    }
+
+    #[test]
+    fn test_cache_follows_output_path() {
+        assert_eq!(cache_path(Path::new("g16.ckt")), Path::new("g16.cache"));
+        assert_eq!(
+            cache_path(Path::new("circuits/k12.ckt")),
+            Path::new("circuits/k12.cache")
+        );
+        assert_eq!(
+            tmp_path(&cache_path(Path::new("circuits/k12.ckt"))),
+            Path::new("circuits/k12.cache.tmp")
+        );
+    }
 }
@@ -1,31 +1,59 @@
 use g16ckt::ark::{self, ConstraintSystemRef, PrimeField, SynthesisError};
 
 /// Circuit for generating test proofs
+///
+/// The public inputs are the chain of products `c_1 = a * b`, `c_{i+1} = c_i * b`, so
+/// `num_public` must be at least one. The chain takes `num_public` constraints of its own, so with
+/// more public inputs than `num_constraints` the circuit gets those instead.
 #[derive(Copy, Clone)]
 pub struct DummyCircuit<F: PrimeField> {
     pub a: Option<F>,
     pub b: Option<F>,
     pub num_variables: usize,
     pub num_constraints: usize,
+    pub num_public: usize,
+}
+
+impl<F: PrimeField> DummyCircuit<F> {
+    /// Values of the public inputs, in allocation order
+    pub fn public_inputs(&self) -> Option<Vec<F>> {
+        let (a, b) = (self.a?, self.b?);
+        Some(
+            std::iter::successors(Some(a * b), |c| Some(*c * b))
+                .take(self.num_public)
+                .collect(),
+        )
+    }
 }
 
 impl<F: PrimeField> ark::ConstraintSynthesizer<F> for DummyCircuit<F> {
     fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        assert!(self.num_public >= 1, "DummyCircuit needs a public input");
+
         let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
         let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
-        let c = cs.new_input_variable(|| {
-            let a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
-            let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
-            Ok(a * b)
-        })?;
+        let public = (0..self.num_public)
+            .map(|i| {
+                cs.new_input_variable(|| {
+                    let values = self
+                        .public_inputs()
+                        .ok_or(SynthesisError::AssignmentMissing)?;
+                    Ok(values[i])
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let c = public[0];
 
-        for _ in 0..(self.num_variables - 3) {
+        for _ in 0..self.num_variables.saturating_sub(2 + self.num_public) {
             let _ = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
         }
 
-        for _ in 0..self.num_constraints - 1 {
+        for _ in 0..self.num_constraints.saturating_sub(self.num_public) {
             cs.enforce_constraint(ark::lc!() + a, ark::lc!() + b, ark::lc!() + c)?;
         }
+        for link in public.windows(2) {
+            cs.enforce_constraint(ark::lc!() + link[0], ark::lc!() + b, ark::lc!() + link[1])?;
+        }
 
         cs.enforce_constraint(ark::lc!(), ark::lc!(), ark::lc!())?;
         Ok(())
@@ -0,0 +1,197 @@
+use std::{
+    io,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use g16ckt::{
+    WireId, circuit::CircuitInput, gadgets::groth16::Groth16VerifyCompressedInput,
+    storage::CreditConfig,
+};
+use tracing::{info, warn};
+
+use crate::{
+    cache::{CacheError, CacheKey, cache_path, save_cache, try_load_cache},
+    modes::decompose::BasisConfig,
+    passes::{
+        credits::{Groth16Verifier, run_credits_pass, run_credits_pass_parallel},
+        translation::run_translation_pass,
+    },
+};
+
+/// How [`generate_circuit`] runs the passes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GenConfig {
+    /// Constraint size the inputs were generated for, part of the credits cache key
+    pub k: usize,
+    /// Threads of the credits pass, which runs serially with 1
+    pub threads: usize,
+    /// Checkpoint the translation so an interrupted run can pick up where it stopped
+    pub resumable: bool,
+    /// Also write the component map next to the circuit
+    pub components: bool,
+}
+
+/// What [`generate_circuit`] produced and how long it took
+#[derive(Clone, Copy, Debug)]
+pub struct CircuitStats {
+    pub primary_inputs: usize,
+    pub outputs: usize,
+    pub gates: u64,
+    /// Whether the credits were loaded from the cache rather than computed
+    pub cached_credits: bool,
+    /// Time to compute or load the credits
+    pub credits_time: Duration,
+    pub translation_time: Duration,
+}
+
+/// Generate the `.ckt` circuit of the Groth16 verifier for `inputs` at `output_path`.
+///
+/// Runs the whole pipeline: the credits pass, unless the credits cache next to `output_path`
+/// is usable, and then the translation pass. Freshly computed credits are saved to the cache.
+/// A cache that turns out not to match the circuit fails the translation, and the error names
+/// the cache file to remove.
+pub async fn generate_circuit(
+    inputs: &Groth16VerifyCompressedInput,
+    output_path: &Path,
+    config: GenConfig,
+) -> io::Result<CircuitStats> {
+    let output = output_path.to_str().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("output path {} is not UTF-8", output_path.display()),
+        )
+    })?;
+
+    let input_wires = inputs.allocate(|| WireId(0)); // Dummy wire generator
+    let primary_inputs = Groth16VerifyCompressedInput::input_wire_count(&input_wires);
+    println!("Primary input count: {}", primary_inputs);
+
+    // Try to load credits and output wires from cache, or compute them
+    let credits_start = Instant::now();
+    let cache_key = CacheKey::new(config.k, primary_inputs, &inputs.0.vk);
+    let cache_path = cache_path(output_path);
+    let cached = match try_load_cache(&cache_path, &cache_key) {
+        Ok(cached) => Some(cached),
+        Err(CacheError::Absent) => None,
+        Err(e) => {
+            warn!("Ignoring cache: {}", e);
+            None
+        }
+    };
+    let cached_credits = cached.is_some();
+    let (credits, output_wires) = if let Some((credits, output_wires)) = cached {
+        info!("Loaded credits and output wires from cache");
+        (credits, output_wires)
+    } else {
+        info!("Running credits pass...");
+        let (credits, output_wires) = if config.threads > 1 {
+            run_credits_pass_parallel(
+                inputs,
+                primary_inputs,
+                BasisConfig::default(),
+                CreditConfig::default(),
+                config.threads,
+                &Groth16Verifier,
+            )
+        } else {
+            run_credits_pass(
+                inputs,
+                primary_inputs,
+                BasisConfig::default(),
+                CreditConfig::default(),
+                &Groth16Verifier,
+            )
+        };
+
+        if let Err(e) = save_cache(&cache_path, &cache_key, &credits, &output_wires) {
+            eprintln!("Warning: Failed to save cache: {}", e);
+        } else {
+            info!("Saved credits and output wires to cache");
+        }
+
+        (credits, output_wires)
+    };
+    let credits_time = credits_start.elapsed();
+    let outputs = output_wires.len();
+
+    info!("Running translation pass...");
+    let translation_start = Instant::now();
+    let gates = run_translation_pass(
+        inputs,
+        primary_inputs,
+        credits,
+        output_wires,
+        BasisConfig::default(),
+        output,
+        config.resumable,
+        config.components,
+        &Groth16Verifier,
+    )
+    .await
+    .map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "{e}; remove {} to recompute the credits",
+                cache_path.display()
+            ),
+        )
+    })?;
+
+    Ok(CircuitStats {
+        primary_inputs,
+        outputs,
+        gates,
+        cached_credits,
+        credits_time,
+        translation_time: translation_start.elapsed(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use monoio::{FusionDriver, RuntimeBuilder};
+
+    use super::*;
+    use crate::{
+        passes::{input_bits::input_bits, verify::run_verify},
+        proof_setup::generate_test_proof,
+    };
+
+    #[test]
+    fn test_generated_circuit_accepts_its_proof() {
+        let dir = std::env::temp_dir().join("g16gen_generate_test");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let output = dir.join("g16.ckt");
+
+        let inputs = generate_test_proof(1 << 4, 1);
+        let config = GenConfig {
+            k: 4,
+            threads: 1,
+            resumable: false,
+            components: false,
+        };
+
+        RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let stats = generate_circuit(&inputs, &output, config).await.unwrap();
+                assert!(!stats.cached_credits);
+                assert_eq!(stats.outputs, 1);
+                assert!(stats.gates > 0);
+
+                let input_bits = input_bits(&inputs);
+                assert_eq!(input_bits.len(), stats.primary_inputs);
+                assert!(run_verify(output.to_str().unwrap(), &input_bits).await);
+            });
+
+        assert!(cache_path(&output).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,55 +1,173 @@
+use std::{ops::RangeInclusive, path::Path};
+
 use g16ckt::{WireId, circuit::CircuitInput, gadgets::groth16::Groth16VerifyCompressedInput};
-use tracing::info;
+use tracing::{info, warn};
 
 mod cache;
 mod dummy_circuit;
+mod generate;
 mod modes;
 mod passes;
 mod proof_setup;
 
-use cache::{save_cache, try_load_cache};
+use cache::{CacheError, CacheKey, cache_path, try_load_cache};
+use generate::{GenConfig, generate_circuit};
 use passes::{
-    credits::run_credits_pass, input_bits::write_input_bits, translation::run_translation_pass,
+    aiger::run_aiger_pass,
+    bristol::run_bristol_pass,
+    gate_estimate::{groth16_verify_gate_estimate, groth16_verify_peak_live_wires},
+    input_bits::{INPUT_BITS_FILE, InputBitsFormat, read_input_bits, write_input_bits},
+    translation::DEFAULT_OUTPUT_FILE,
+    verify::run_verify,
 };
-use proof_setup::generate_test_proof;
+use proof_setup::{generate_test_proof, load_proof};
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 enum Command {
-    Generate { constraint_size: usize },
-    WriteInputBits { constraint_size: usize },
+    Generate {
+        constraint_size: usize,
+        threads: usize,
+        resumable: bool,
+        components: bool,
+        output: String,
+    },
+    GenerateBristol {
+        constraint_size: usize,
+    },
+    GenerateAiger {
+        constraint_size: usize,
+    },
+    WriteInputBits {
+        constraint_size: usize,
+        proof: Option<String>,
+        format: InputBitsFormat,
+    },
+    Estimate {
+        constraint_size: usize,
+    },
+    Verify {
+        inputs: String,
+    },
     Help,
 }
 
-fn parse_args() -> Command {
-    let args: Vec<String> = std::env::args().collect();
+/// Constraint size used when `k` isn't given
+const DEFAULT_K: usize = 6;
+/// Accepted values of `k`; beyond 2^28 constraints the setup alone exhausts memory
+const K_RANGE: RangeInclusive<usize> = 1..=28;
+
+/// Positional argument at `index`, if given. Positional arguments come right after the command,
+/// so anything after the first flag is a flag or a flag's value.
+fn positional(args: &[String], index: usize) -> Option<&String> {
+    args.get(2..=index)?
+        .iter()
+        .all(|arg| !arg.starts_with("--"))
+        .then_some(&args[index])
+}
+
+/// Constraint size given as the positional argument at `index`, or [`DEFAULT_K`] if there is none
+fn parse_k(args: &[String], index: usize) -> Result<usize, String> {
+    let Some(k) = positional(args, index) else {
+        return Ok(DEFAULT_K);
+    };
+    let k = k
+        .parse()
+        .map_err(|e| format!("invalid constraint size k={k:?}: {e}"))?;
+    if !K_RANGE.contains(&k) {
+        return Err(format!(
+            "constraint size k={k} out of range {}..={}",
+            K_RANGE.start(),
+            K_RANGE.end()
+        ));
+    }
+    Ok(k)
+}
+
+/// Credits pass thread count given as the positional argument at `index`, or 1 if there is none
+fn parse_threads(args: &[String], index: usize) -> Result<usize, String> {
+    let Some(threads) = positional(args, index) else {
+        return Ok(1);
+    };
+    let threads = threads
+        .parse()
+        .map_err(|e| format!("invalid thread count {threads:?}: {e}"))?;
+    if threads == 0 {
+        return Err("thread count must be at least 1".to_string());
+    }
+    Ok(threads)
+}
 
+/// Value following `flag`, if the flag is given. A flag at the end of the line, or followed by
+/// another flag, is missing its value.
+fn flag_value(args: &[String], flag: &str) -> Result<Option<String>, String> {
+    let Some(i) = args.iter().position(|arg| arg == flag) else {
+        return Ok(None);
+    };
+    match args.get(i + 1) {
+        Some(value) if !value.starts_with("--") => Ok(Some(value.clone())),
+        _ => Err(format!("{flag} needs a value")),
+    }
+}
+
+/// Parse the full command line, program name included
+fn parse_args(args: &[String]) -> Result<Command, String> {
     if args.len() < 2 {
-        return Command::Generate { constraint_size: 6 };
+        return Ok(Command::Generate {
+            constraint_size: DEFAULT_K,
+            threads: 1,
+            resumable: false,
+            components: false,
+            output: DEFAULT_OUTPUT_FILE.to_string(),
+        });
     }
 
-    match args[1].as_str() {
+    let command = match args[1].as_str() {
         "generate" => {
-            let constraint_size = if args.len() > 2 {
-                args[2].parse().unwrap_or(6)
-            } else {
-                6
-            };
-            Command::Generate { constraint_size }
+            let constraint_size = parse_k(args, 2)?;
+            let threads = parse_threads(args, 3)?;
+            let resumable = args.iter().any(|arg| arg == "--resumable");
+            let components = args.iter().any(|arg| arg == "--components");
+            let output =
+                flag_value(args, "--output")?.unwrap_or_else(|| DEFAULT_OUTPUT_FILE.to_string());
+            Command::Generate {
+                constraint_size,
+                threads,
+                resumable,
+                components,
+                output,
+            }
         }
+        "generate-bristol" => Command::GenerateBristol {
+            constraint_size: parse_k(args, 2)?,
+        },
+        "generate-aiger" => Command::GenerateAiger {
+            constraint_size: parse_k(args, 2)?,
+        },
         "write-input-bits" => {
-            let constraint_size = if args.len() > 2 {
-                args[2].parse().unwrap_or(6)
-            } else {
-                6
+            let proof = flag_value(args, "--proof")?;
+            let format = match flag_value(args, "--format")? {
+                Some(format) => format.parse()?,
+                None => InputBitsFormat::default(),
             };
-            Command::WriteInputBits { constraint_size }
+            Command::WriteInputBits {
+                constraint_size: parse_k(args, 2)?,
+                proof,
+                format,
+            }
         }
+        "estimate" => Command::Estimate {
+            constraint_size: parse_k(args, 2)?,
+        },
+        "verify" => Command::Verify {
+            inputs: flag_value(args, "--inputs")?.unwrap_or_else(|| INPUT_BITS_FILE.to_string()),
+        },
         "help" | "--help" | "-h" => Command::Help,
         _ => {
             eprintln!("Unknown command: {}", args[1]);
             Command::Help
         }
-    }
+    };
+    Ok(command)
 }
 
 fn print_help() {
@@ -61,88 +179,409 @@ fn print_help() {
     println!("    g16gen <COMMAND> [OPTIONS]");
     println!();
     println!("COMMANDS:");
-    println!("    generate [k]           Generate boolean circuit file encoding Groth16 verifier");
+    println!("    generate [k] [threads] Generate boolean circuit file encoding Groth16 verifier");
+    println!(
+        "                           (default: k=6, creates verifier for 2^k constraint proofs;"
+    );
+    println!("                           threads > 1 parallelizes the credits pass;");
+    println!("                           --resumable checkpoints translation so a rerun resumes;");
+    println!("                           --output <path> writes the circuit to <path> instead of");
+    println!("                           g16.ckt, with its cache next to it;");
+    println!("                           --components also writes <path>.components, mapping");
+    println!("                           wires to the gadgets that emitted them)");
+    println!("    generate-bristol [k]   Generate the same verifier in Bristol Fashion format");
+    println!("                           (default: k=6, outputs circuit to g16.bristol)");
+    println!("    generate-aiger [k]     Generate the same verifier in binary AIGER format");
+    println!("                           (default: k=6, outputs circuit to g16.aig)");
+    println!("    write-input-bits [k] [--proof <path>] [--format ascii|bin]");
+    println!("                           Extract boolean input bits for a specific Groth16 proof");
+    println!("                           (default: k=6, outputs bits to inputs.txt; --proof");
+    println!("                           reads an arkworks-serialized (proof, vk, public inputs);");
+    println!("                           --format bin packs them into inputs.bin instead)");
     println!(
-        "                           (default: k=6, creates verifier for 2^k constraint proofs)"
+        "    estimate [k]           Count the verifier's gates without generating the circuit"
     );
-    println!("    write-input-bits [k]   Extract boolean input bits for a specific Groth16 proof");
-    println!("                           (default: k=6, outputs bits to input_bits.txt)");
+    println!("                           (default: k=6; with the credits cache of a previous");
+    println!("                           generate, also the peak number of live wires)");
+    println!("    verify [--inputs <path>]");
+    println!("                           Evaluate g16.ckt on inputs.txt, or the bits at <path> in");
+    println!("                           either format, and check that it outputs ok");
     println!("    help                   Print this help message");
     println!();
+    println!(
+        "k must lie in {}..={} for every command taking it.",
+        K_RANGE.start(),
+        K_RANGE.end()
+    );
+    println!();
     println!("EXAMPLES:");
     println!(
         "    g16gen generate 8             # Generate verifier circuit for 2^8 constraint proofs"
     );
+    println!("    g16gen generate 8 4           # Same, counting credits on 4 threads");
+    println!("    g16gen generate 12 --resumable  # Resume translation after an interruption");
+    println!("    g16gen generate 8 --output circuits/k8.ckt  # Write the circuit under circuits/");
+    println!("    g16gen generate 8 --components  # Also map wires to gadgets for g16check");
     println!("    g16gen write-input-bits 6     # Extract input bits for a specific proof");
+    println!(
+        "    g16gen write-input-bits --proof proof.bin  # Extract input bits for a proof file"
+    );
+    println!("    g16gen write-input-bits 6 --format bin  # Same, packed into inputs.bin");
+    println!("    g16gen verify                 # Run the generated circuit on the extracted bits");
+    println!("    g16gen verify --inputs inputs.bin  # Same, on the packed bits");
+}
+
+async fn run_generate(k: usize, threads: usize, resumable: bool, components: bool, output: &str) {
+    info!("Generating test proof with 2^{} constraints", k);
+    let inputs = generate_test_proof(1 << k, 1);
+
+    let config = GenConfig {
+        k,
+        threads,
+        resumable,
+        components,
+    };
+    match generate_circuit(&inputs, Path::new(output), config).await {
+        Ok(stats) => info!(
+            "Circuit generation complete! {} gates, {} primary inputs and {} outputs in {:?} (credits {:?}{})",
+            stats.gates,
+            stats.primary_inputs,
+            stats.outputs,
+            stats.credits_time + stats.translation_time,
+            stats.credits_time,
+            if stats.cached_credits { ", cached" } else { "" }
+        ),
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+    }
 }
 
-async fn run_generate(k: usize) {
+fn run_generate_bristol(k: usize) {
     info!("Generating test proof with 2^{} constraints", k);
-    let inputs = generate_test_proof(1 << k);
+    let inputs = generate_test_proof(1 << k, 1);
 
     let input_wires = inputs.allocate(|| WireId(0)); // Dummy wire generator
-    let primary_input_count = Groth16VerifyCompressedInput::collect_wire_ids(&input_wires).len();
+    let primary_input_count = Groth16VerifyCompressedInput::input_wire_count(&input_wires);
     println!("Primary input count: {}", primary_input_count);
 
-    // Try to load credits and output wires from cache, or compute them
-    let (credits, output_wires) = if let Some((credits, output_wires)) = try_load_cache() {
-        info!("Loaded credits and output wires from cache");
-        (credits, output_wires)
-    } else {
-        info!("Running credits pass...");
-        let (credits, output_wires) = run_credits_pass(&inputs, primary_input_count);
+    info!("Running Bristol translation pass...");
+    run_bristol_pass(&inputs, primary_input_count);
+    info!("Bristol circuit generation complete!");
+}
 
-        if let Err(e) = save_cache(&credits, &output_wires) {
-            eprintln!("Warning: Failed to save cache: {}", e);
-        } else {
-            info!("Saved credits and output wires to cache");
-        }
+fn run_generate_aiger(k: usize) {
+    info!("Generating test proof with 2^{} constraints", k);
+    let inputs = generate_test_proof(1 << k, 1);
 
-        (credits, output_wires)
-    };
+    let input_wires = inputs.allocate(|| WireId(0)); // Dummy wire generator
+    let primary_input_count = Groth16VerifyCompressedInput::input_wire_count(&input_wires);
+    println!("Primary input count: {}", primary_input_count);
 
-    // Run translation pass
-    info!("Running translation pass...");
-    run_translation_pass(&inputs, primary_input_count, credits, output_wires).await;
-    info!("Circuit generation complete!");
+    info!("Running AIGER translation pass...");
+    run_aiger_pass(&inputs, primary_input_count);
+    info!("AIGER circuit generation complete!");
 }
 
-async fn run_write_input_bits(k: usize) {
-    info!("Generating test proof with 2^{} constraints", k);
-    let inputs = generate_test_proof(1 << k);
+async fn run_write_input_bits(k: usize, proof: Option<String>, format: InputBitsFormat) {
+    let inputs = match proof {
+        Some(path) => {
+            info!("Loading proof from {}", path);
+            match load_proof(&path) {
+                Ok(inputs) => inputs,
+                Err(e) => {
+                    eprintln!("Error loading proof: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => {
+            info!("Generating test proof with 2^{} constraints", k);
+            generate_test_proof(1 << k, 1)
+        }
+    };
 
     let input_wires = inputs.allocate(|| WireId(0)); // Dummy wire generator
-    let primary_input_count = Groth16VerifyCompressedInput::collect_wire_ids(&input_wires).len();
+    let primary_input_count = Groth16VerifyCompressedInput::input_wire_count(&input_wires);
     println!("Primary input count: {}", primary_input_count);
 
     info!("Writing input bits to file...");
-    if let Err(e) = write_input_bits(&inputs) {
+    if let Err(e) = write_input_bits(&inputs, format) {
         eprintln!("Error writing input bits: {}", e);
         std::process::exit(1);
     }
     info!("Input bits written successfully!");
 }
 
+fn run_estimate(k: usize) {
+    info!("Generating test proof with 2^{} constraints", k);
+    let inputs = generate_test_proof(1 << k, 1);
+
+    // The test proof's key always fits its public inputs
+    let counts = groth16_verify_gate_estimate(&inputs.0.vk).unwrap();
+    println!("AND gates: {}", counts.and);
+    println!("XOR gates: {}", counts.xor);
+    println!("Total gates: {}", counts.total());
+    println!();
+    println!("Source gates by type:");
+    print!("{}", counts.source);
+
+    // Liveness needs the credits, which only a previous `generate` has computed
+    let input_wires = inputs.allocate(|| WireId(0)); // Dummy wire generator
+    let primary_input_count = Groth16VerifyCompressedInput::input_wire_count(&input_wires);
+    let cache_key = CacheKey::new(k, primary_input_count, &inputs.0.vk);
+    match try_load_cache(&cache_path(Path::new(DEFAULT_OUTPUT_FILE)), &cache_key) {
+        Ok((credits, _)) => {
+            let peak =
+                groth16_verify_peak_live_wires(&inputs.0.vk, credits, primary_input_count).unwrap();
+            println!();
+            println!("Peak live wires: {}", peak);
+        }
+        Err(CacheError::Absent) => {
+            info!("No credits cache, run generate first to also trace the peak live wires")
+        }
+        Err(e) => warn!("Ignoring cache: {}", e),
+    }
+}
+
+async fn run_verify_command(inputs: &str) {
+    let input_bits = match read_input_bits(inputs) {
+        Ok(input_bits) => input_bits,
+        Err(e) => {
+            eprintln!("Error reading input bits: {}", e);
+            std::process::exit(1);
+        }
+    };
+    println!("Input bit count: {}", input_bits.len());
+
+    info!("Evaluating circuit...");
+    if run_verify(DEFAULT_OUTPUT_FILE, &input_bits).await {
+        println!("Verification succeeded: circuit output is ok = true");
+    } else {
+        println!("Verification failed: circuit output is not ok = true");
+        std::process::exit(1);
+    }
+}
+
 #[monoio::main]
 async fn main() {
     tracing_subscriber::fmt::init();
 
-    let command = parse_args();
+    let args: Vec<String> = std::env::args().collect();
+    let command = match parse_args(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            print_help();
+            std::process::exit(2);
+        }
+    };
 
     match command {
-        Command::Generate { constraint_size } => {
-            info!("Running generate command with k={}", constraint_size);
-            run_generate(constraint_size).await;
+        Command::Generate {
+            constraint_size,
+            threads,
+            resumable,
+            components,
+            output,
+        } => {
+            info!(
+                "Running generate command with k={} threads={} resumable={} components={} output={}",
+                constraint_size, threads, resumable, components, output
+            );
+            run_generate(constraint_size, threads, resumable, components, &output).await;
         }
-        Command::WriteInputBits { constraint_size } => {
+        Command::GenerateBristol { constraint_size } => {
             info!(
-                "Running write-input-bits command with k={}",
+                "Running generate-bristol command with k={}",
                 constraint_size
             );
-            run_write_input_bits(constraint_size).await;
+            run_generate_bristol(constraint_size);
+        }
+        Command::GenerateAiger { constraint_size } => {
+            info!("Running generate-aiger command with k={}", constraint_size);
+            run_generate_aiger(constraint_size);
+        }
+        Command::WriteInputBits {
+            constraint_size,
+            proof,
+            format,
+        } => {
+            info!(
+                "Running write-input-bits command with k={} format={}",
+                constraint_size, format
+            );
+            run_write_input_bits(constraint_size, proof, format).await;
+        }
+        Command::Estimate { constraint_size } => {
+            info!("Running estimate command with k={}", constraint_size);
+            run_estimate(constraint_size);
+        }
+        Command::Verify { inputs } => {
+            info!("Running verify command on {}", inputs);
+            run_verify_command(&inputs).await;
         }
         Command::Help => {
             print_help();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Command, String> {
+        let args = std::iter::once("g16gen")
+            .chain(args.iter().copied())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        parse_args(&args)
+    }
+
+    #[test]
+    fn test_parse_k() {
+        assert_eq!(
+            args(&["estimate"]),
+            Ok(Command::Estimate {
+                constraint_size: DEFAULT_K
+            })
+        );
+        assert_eq!(
+            args(&["generate", "12", "4", "--resumable"]),
+            Ok(Command::Generate {
+                constraint_size: 12,
+                threads: 4,
+                resumable: true,
+                components: false,
+                output: DEFAULT_OUTPUT_FILE.to_string(),
+            })
+        );
+        assert_eq!(
+            args(&["generate", "8", "--components"]),
+            Ok(Command::Generate {
+                constraint_size: 8,
+                threads: 1,
+                resumable: false,
+                components: true,
+                output: DEFAULT_OUTPUT_FILE.to_string(),
+            })
+        );
+        // Flags aren't mistaken for k
+        assert_eq!(
+            args(&["write-input-bits", "--proof", "proof.bin"]),
+            Ok(Command::WriteInputBits {
+                constraint_size: DEFAULT_K,
+                proof: Some("proof.bin".to_string()),
+                format: InputBitsFormat::Ascii,
+            })
+        );
+        assert_eq!(
+            args(&["generate-bristol", "28"]),
+            Ok(Command::GenerateBristol {
+                constraint_size: 28
+            })
+        );
+        assert_eq!(
+            args(&["generate-aiger"]),
+            Ok(Command::GenerateAiger {
+                constraint_size: DEFAULT_K
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_k_is_rejected() {
+        for command in [
+            "generate",
+            "generate-bristol",
+            "generate-aiger",
+            "write-input-bits",
+            "estimate",
+        ] {
+            assert!(args(&[command, "abc"]).unwrap_err().contains("invalid"));
+            assert!(args(&[command, "-3"]).unwrap_err().contains("invalid"));
+            assert!(args(&[command, "0"]).unwrap_err().contains("out of range"));
+            assert!(args(&[command, "29"]).unwrap_err().contains("out of range"));
+            assert!(args(&[command, "40"]).unwrap_err().contains("out of range"));
+        }
+    }
+
+    #[test]
+    fn test_parse_threads() {
+        let generate = |threads, output: &str| {
+            Ok(Command::Generate {
+                constraint_size: 6,
+                threads,
+                resumable: false,
+                components: false,
+                output: output.to_string(),
+            })
+        };
+
+        assert_eq!(
+            args(&["generate", "6", "3"]),
+            generate(3, DEFAULT_OUTPUT_FILE)
+        );
+        assert_eq!(args(&["generate", "6"]), generate(1, DEFAULT_OUTPUT_FILE));
+        // Flags and their values aren't mistaken for the thread count
+        assert_eq!(
+            args(&["generate", "6", "--output", "x.ckt"]),
+            generate(1, "x.ckt")
+        );
+        assert_eq!(
+            args(&["generate", "--output", "x.ckt"]),
+            generate(1, "x.ckt")
+        );
+
+        assert!(
+            args(&["generate", "6", "abc"])
+                .unwrap_err()
+                .contains("invalid")
+        );
+        assert!(
+            args(&["generate", "6", "-2"])
+                .unwrap_err()
+                .contains("invalid")
+        );
+        assert!(args(&["generate", "6", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_input_bits_format() {
+        assert_eq!(
+            args(&["write-input-bits", "6", "--format", "bin"]),
+            Ok(Command::WriteInputBits {
+                constraint_size: 6,
+                proof: None,
+                format: InputBitsFormat::Binary,
+            })
+        );
+        assert!(args(&["write-input-bits", "--format", "hex"]).is_err());
+        assert!(args(&["write-input-bits", "--format"]).is_err());
+        assert_eq!(
+            args(&["verify", "--inputs", "inputs.bin"]),
+            Ok(Command::Verify {
+                inputs: "inputs.bin".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_flag_without_value_is_rejected() {
+        for (line, flag) in [
+            (&["generate", "--output"][..], "--output"),
+            (&["generate", "--output", "--resumable"], "--output"),
+            (&["write-input-bits", "--proof"], "--proof"),
+            (
+                &["write-input-bits", "--proof", "--format", "bin"],
+                "--proof",
+            ),
+            (&["write-input-bits", "--format"], "--format"),
+            (&["verify", "--inputs"], "--inputs"),
+        ] {
+            assert_eq!(args(line), Err(format!("{flag} needs a value")));
+        }
+    }
+}
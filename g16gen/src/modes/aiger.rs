@@ -0,0 +1,297 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    num::NonZero,
+    path::PathBuf,
+};
+
+use ckt_lvl::types::CompactWireId;
+use g16ckt::{Gate as SourceGate, WireId, circuit::CircuitMode, storage::Credits as SourceCredits};
+
+use crate::modes::decompose::{BasisConfig, GateSink, Primitive, translate_gate};
+
+/// Translation backend that writes binary AIGER (`.aig`) instead of `.ckt` v5a.
+///
+/// Gates are decomposed into the AND/XOR basis exactly as in `TranslationMode`. An and-inverter
+/// graph has nothing but AND gates with optionally inverted inputs, so every XOR becomes three
+/// ANDs, `a ^ b = !(!(a & !b) & !(!a & b))`, and XOR with a constant is just an inverted edge.
+/// Primary inputs are the AIGER inputs `1..=n` in order, and FALSE/TRUE are the constant
+/// literals 0/1.
+///
+/// AND gates are numbered as they are emitted, so the literal of every normalized ID is kept in
+/// memory for the whole circuit. The gates stream into a temporary body file; the header and the
+/// outputs, which precede them in the file, get prepended by [`AigerTranslationMode::finish`].
+#[derive(Debug)]
+pub struct AigerTranslationMode {
+    next_normalized_id: u64,
+    primary_inputs: u64,
+    /// AIGER literal by normalized ID
+    literals: Vec<u64>,
+    and_count: u64,
+    path: PathBuf,
+    body_path: PathBuf,
+    body: BufWriter<File>,
+}
+
+impl CircuitMode for AigerTranslationMode {
+    type WireValue = bool; // We don't store values, just translate
+    type CiphertextAcc = ();
+
+    fn false_value(&self) -> Self::WireValue {
+        false
+    }
+    fn true_value(&self) -> Self::WireValue {
+        true
+    }
+
+    fn allocate_wire(&mut self, _credits: SourceCredits) -> WireId {
+        let normalized_id = self.allocate_normalized_id();
+        WireId(normalized_id as usize)
+    }
+
+    fn lookup_wire(&mut self, _wire: WireId) -> Option<Self::WireValue> {
+        Some(false) // Always return dummy value
+    }
+
+    fn feed_wire(&mut self, _wire: WireId, _value: Self::WireValue) {
+        // No-op for translation
+    }
+
+    fn add_credits(&mut self, _wires: &[WireId], _credits: NonZero<SourceCredits>) {}
+
+    fn evaluate_gate(&mut self, gate: &SourceGate) {
+        translate_gate(self, BasisConfig::AndXor, gate);
+    }
+}
+
+impl AigerTranslationMode {
+    pub fn new(path: &str, primary_inputs: u64) -> io::Result<Self> {
+        let path = PathBuf::from(path);
+        let mut body_path = OsString::from(&path);
+        body_path.push(".body");
+        let body_path = PathBuf::from(body_path);
+        let body = BufWriter::new(File::create(&body_path)?);
+
+        // FALSE and TRUE are the constant literals, input `i` is variable `i + 1`
+        let literals = [0, 1]
+            .into_iter()
+            .chain((1..=primary_inputs).map(|variable| 2 * variable))
+            .collect();
+
+        Ok(Self {
+            // IDs 0 and 1 are the FALSE and TRUE constants, primary inputs get allocated next
+            next_normalized_id: 2,
+            primary_inputs,
+            literals,
+            and_count: 0,
+            path,
+            body_path,
+            body,
+        })
+    }
+
+    /// Write the final file: header, `outputs` and the AND gates.
+    pub fn finish(&mut self, outputs: &[WireId]) -> io::Result<()> {
+        self.body.flush()?;
+
+        let mut file = BufWriter::new(File::create(&self.path)?);
+        // M I L O A, without latches
+        writeln!(
+            file,
+            "aig {} {} 0 {} {}",
+            self.primary_inputs + self.and_count,
+            self.primary_inputs,
+            outputs.len(),
+            self.and_count
+        )?;
+        for wire in outputs {
+            writeln!(file, "{}", self.literals[wire.0])?;
+        }
+        io::copy(&mut File::open(&self.body_path)?, &mut file)?;
+        file.flush()?;
+
+        fs::remove_file(&self.body_path)
+    }
+
+    /// Literal of the AND of literals `a` and `b`, emitting a gate unless a constant decides it.
+    fn and(&mut self, a: u64, b: u64) -> io::Result<u64> {
+        match (a.min(b), a.max(b)) {
+            (0, _) => return Ok(0),
+            (1, other) => return Ok(other),
+            _ => {}
+        }
+
+        // Binary AIGER numbers the gates implicitly and stores each as two deltas, the first from
+        // its own literal to the larger input
+        self.and_count += 1;
+        let lhs = 2 * (self.primary_inputs + self.and_count);
+        let (rhs0, rhs1) = (a.max(b), a.min(b));
+        write_delta(&mut self.body, lhs - rhs0)?;
+        write_delta(&mut self.body, rhs0 - rhs1)?;
+        Ok(lhs)
+    }
+
+    /// Literal of the XOR of literals `a` and `b`
+    fn xor(&mut self, a: u64, b: u64) -> io::Result<u64> {
+        // With a constant, this only inverts the other side or not
+        if a < 2 || b < 2 {
+            return Ok(a ^ b);
+        }
+
+        let a_not_b = self.and(a, b ^ 1)?;
+        let b_not_a = self.and(a ^ 1, b)?;
+        Ok(self.and(a_not_b ^ 1, b_not_a ^ 1)? ^ 1)
+    }
+}
+
+/// Write `delta` as AIGER's variable-length integer: seven bits per byte, least significant
+/// first, with the high bit set on all but the last byte.
+fn write_delta(writer: &mut impl Write, mut delta: u64) -> io::Result<()> {
+    while delta >= 0x80 {
+        writer.write_all(&[(delta & 0x7f) as u8 | 0x80])?;
+        delta >>= 7;
+    }
+    writer.write_all(&[delta as u8])
+}
+
+impl GateSink for AigerTranslationMode {
+    fn allocate_normalized_id(&mut self) -> u64 {
+        let id = self.next_normalized_id;
+        self.next_normalized_id += 1;
+        id
+    }
+
+    fn true_wire_id(&self) -> CompactWireId {
+        CompactWireId::from_u64(1)
+    }
+
+    fn write_gate(
+        &mut self,
+        gate_type: Primitive,
+        in1: CompactWireId,
+        in2: CompactWireId,
+        out: CompactWireId,
+    ) {
+        let a = self.literals[in1.to_u64() as usize];
+        let b = self.literals[in2.to_u64() as usize];
+
+        let c = match gate_type {
+            Primitive::And => self.and(a, b),
+            Primitive::Xor => self.xor(a, b),
+            Primitive::Xnor | Primitive::Nand | Primitive::Or => {
+                unreachable!("AIGER output uses the AND/XOR basis")
+            }
+        }
+        .unwrap();
+
+        let out = out.to_u64() as usize;
+        if out >= self.literals.len() {
+            self.literals.resize(out + 1, 0);
+        }
+        self.literals[out] = c;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use g16ckt::circuit::{CircuitBuilder, StreamingMode, component_meta::ComponentMetaBuilder};
+
+    use super::*;
+    use crate::modes::bristol::tests::gadget;
+
+    fn translate(path: &str) {
+        let inputs = [false; 3];
+        let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(&inputs);
+        let mut metadata_mode = StreamingMode::<AigerTranslationMode>::MetadataPass(root_meta);
+        let meta_output_wires = gadget(&mut metadata_mode, &allocated_inputs);
+
+        let (mut ctx, allocated_inputs) = metadata_mode.to_root_ctx(
+            AigerTranslationMode::new(path, inputs.len() as u64).unwrap(),
+            &inputs,
+            &meta_output_wires,
+        );
+        let output_wires = gadget(&mut ctx, &allocated_inputs);
+        ctx.get_mut_mode().unwrap().finish(&output_wires).unwrap();
+    }
+
+    /// Minimal binary AIGER evaluator for a combinational circuit.
+    fn simulate(bytes: &[u8], inputs: &[bool]) -> Vec<bool> {
+        let mut rest = bytes;
+        let mut line = || {
+            let end = rest.iter().position(|&byte| byte == b'\n').unwrap();
+            let line = std::str::from_utf8(&rest[..end]).unwrap().to_string();
+            rest = &rest[end + 1..];
+            line
+        };
+
+        let header = line();
+        let header = header.split_whitespace().collect::<Vec<_>>();
+        assert_eq!(header[0], "aig");
+        let [m, i, l, o, a] = [1, 2, 3, 4, 5].map(|k| header[k].parse::<u64>().unwrap());
+        assert_eq!((i, l, m), (inputs.len() as u64, 0, i + a));
+        let outputs = (0..o)
+            .map(|_| line().parse::<u64>().unwrap())
+            .collect::<Vec<_>>();
+
+        let mut variables = vec![false; m as usize + 1];
+        variables[1..=inputs.len()].copy_from_slice(inputs);
+        let value = |variables: &[bool], literal: u64| {
+            variables[(literal / 2) as usize] ^ (literal & 1 == 1)
+        };
+
+        let mut deltas = rest.iter();
+        let mut delta = || {
+            let (mut delta, mut shift) = (0u64, 0);
+            loop {
+                let byte = *deltas.next().expect("truncated AND section");
+                delta |= u64::from(byte & 0x7f) << shift;
+                if byte & 0x80 == 0 {
+                    return delta;
+                }
+                shift += 7;
+            }
+        };
+        for gate in 1..=a {
+            let lhs = 2 * (i + gate);
+            let rhs0 = lhs - delta();
+            let rhs1 = rhs0 - delta();
+            variables[(lhs / 2) as usize] = value(&variables, rhs0) & value(&variables, rhs1);
+        }
+        assert!(
+            deltas.next().is_none(),
+            "trailing bytes after the AND section"
+        );
+
+        outputs
+            .into_iter()
+            .map(|literal| value(&variables, literal))
+            .collect()
+    }
+
+    #[test]
+    fn test_aiger_resimulation_matches_execution() {
+        let path = std::env::temp_dir().join("g16gen_aiger_test.aig");
+        translate(path.to_str().unwrap());
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        for bits in 0..8u8 {
+            let inputs: [bool; 3] = std::array::from_fn(|i| (bits >> i) & 1 == 1);
+            let expected =
+                CircuitBuilder::streaming_execute::<_, _, Vec<bool>>(inputs, 10_000, gadget)
+                    .output_value;
+
+            assert_eq!(simulate(&bytes, &inputs), expected, "inputs {inputs:?}");
+        }
+    }
+
+    #[test]
+    fn test_deltas_use_seven_bit_groups() {
+        let mut bytes = Vec::new();
+        for delta in [0, 0x7f, 0x80, 16387] {
+            write_delta(&mut bytes, delta).unwrap();
+        }
+        assert_eq!(bytes, [0x00, 0x7f, 0x80, 0x01, 0x83, 0x80, 0x01]);
+    }
+}
@@ -0,0 +1,291 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    num::NonZero,
+    path::PathBuf,
+};
+
+use ckt_lvl::types::CompactWireId;
+use g16ckt::{Gate as SourceGate, WireId, circuit::CircuitMode, storage::Credits as SourceCredits};
+
+use crate::modes::decompose::{BasisConfig, GateSink, Primitive, translate_gate};
+
+/// Translation backend that writes Bristol Fashion instead of `.ckt` v5a.
+///
+/// Gates are decomposed into the AND/XOR basis exactly as in `TranslationMode` and streamed into a
+/// temporary body file; the header (gate/wire/input/output counts) is only known at the end and
+/// gets prepended by [`BristolTranslationMode::finish`].
+///
+/// Bristol has no constant wires, so normalized IDs are permuted: primary inputs take `0..n`,
+/// FALSE/TRUE follow at `n`/`n + 1` (derived as `x0 XOR x0` and its INV), and every other wire
+/// keeps its normalized ID. Outputs are copied onto the last wires, as the format requires.
+#[derive(Debug)]
+pub struct BristolTranslationMode {
+    next_normalized_id: u64,
+    primary_inputs: u64,
+    gate_count: u64,
+    path: PathBuf,
+    body_path: PathBuf,
+    body: BufWriter<File>,
+}
+
+impl CircuitMode for BristolTranslationMode {
+    type WireValue = bool; // We don't store values, just translate
+    type CiphertextAcc = ();
+
+    fn false_value(&self) -> Self::WireValue {
+        false
+    }
+    fn true_value(&self) -> Self::WireValue {
+        true
+    }
+
+    fn allocate_wire(&mut self, _credits: SourceCredits) -> WireId {
+        let normalized_id = self.allocate_normalized_id();
+        WireId(normalized_id as usize)
+    }
+
+    fn lookup_wire(&mut self, _wire: WireId) -> Option<Self::WireValue> {
+        Some(false) // Always return dummy value
+    }
+
+    fn feed_wire(&mut self, _wire: WireId, _value: Self::WireValue) {
+        // No-op for translation
+    }
+
+    fn add_credits(&mut self, _wires: &[WireId], _credits: NonZero<SourceCredits>) {}
+
+    fn evaluate_gate(&mut self, gate: &SourceGate) {
+        translate_gate(self, BasisConfig::AndXor, gate);
+    }
+}
+
+impl BristolTranslationMode {
+    pub fn new(path: &str, primary_inputs: u64) -> io::Result<Self> {
+        assert!(
+            primary_inputs > 0,
+            "Bristol constants are derived from the first primary input"
+        );
+
+        let path = PathBuf::from(path);
+        let mut body_path = OsString::from(&path);
+        body_path.push(".body");
+        let body_path = PathBuf::from(body_path);
+        let body = BufWriter::new(File::create(&body_path)?);
+
+        let mut mode = Self {
+            next_normalized_id: 0,
+            primary_inputs,
+            gate_count: 0,
+            path,
+            body_path,
+            body,
+        };
+
+        // Reserve normalized IDs for constants
+        let false_wire = mode.allocate_normalized_id(); // ID 0 = FALSE
+        let true_wire = mode.allocate_normalized_id(); // ID 1 = TRUE (ONE wire)
+
+        let (x0, false_wire, true_wire) = (
+            mode.to_bristol(2),
+            mode.to_bristol(false_wire),
+            mode.to_bristol(true_wire),
+        );
+        writeln!(mode.body, "2 1 {x0} {x0} {false_wire} XOR")?;
+        writeln!(mode.body, "1 1 {false_wire} {true_wire} INV")?;
+        mode.gate_count += 2;
+
+        Ok(mode)
+    }
+
+    /// Copy `outputs` onto the last wires and write the final file (header followed by gates).
+    pub fn finish(&mut self, outputs: &[WireId]) -> io::Result<()> {
+        let false_wire = self.to_bristol(0);
+        for wire in outputs {
+            let out = self.allocate_normalized_id();
+            let wire = self.to_bristol(wire.0 as u64);
+            writeln!(self.body, "2 1 {wire} {false_wire} {out} XOR")?;
+            self.gate_count += 1;
+        }
+        self.body.flush()?;
+
+        let mut file = BufWriter::new(File::create(&self.path)?);
+        writeln!(file, "{} {}", self.gate_count, self.next_normalized_id)?;
+        writeln!(file, "1 {}", self.primary_inputs)?;
+        writeln!(file, "1 {}", outputs.len())?;
+        writeln!(file)?;
+        io::copy(&mut File::open(&self.body_path)?, &mut file)?;
+        file.flush()?;
+
+        fs::remove_file(&self.body_path)
+    }
+
+    /// Map a normalized ID onto its Bristol wire index.
+    fn to_bristol(&self, id: u64) -> u64 {
+        match id {
+            0 | 1 => self.primary_inputs + id,
+            id if id < self.primary_inputs + 2 => id - 2,
+            id => id,
+        }
+    }
+}
+
+impl GateSink for BristolTranslationMode {
+    fn allocate_normalized_id(&mut self) -> u64 {
+        let id = self.next_normalized_id;
+        self.next_normalized_id += 1;
+        id
+    }
+
+    fn true_wire_id(&self) -> CompactWireId {
+        CompactWireId::from_u64(1)
+    }
+
+    fn write_gate(
+        &mut self,
+        gate_type: Primitive,
+        in1: CompactWireId,
+        in2: CompactWireId,
+        out: CompactWireId,
+    ) {
+        let one = self.true_wire_id().to_u64();
+        let (in1, in2) = (in1.to_u64(), in2.to_u64());
+        let [a, b, c] = [in1, in2, out.to_u64()].map(|id| self.to_bristol(id));
+
+        match gate_type {
+            // XOR with ONE is how the decomposition spells NOT
+            Primitive::Xor if in2 == one => writeln!(self.body, "1 1 {a} {c} INV"),
+            Primitive::Xor if in1 == one => writeln!(self.body, "1 1 {b} {c} INV"),
+            Primitive::Xor => writeln!(self.body, "2 1 {a} {b} {c} XOR"),
+            Primitive::And => writeln!(self.body, "2 1 {a} {b} {c} AND"),
+            Primitive::Xnor | Primitive::Nand | Primitive::Or => {
+                unreachable!("Bristol output uses the AND/XOR basis")
+            }
+        }
+        .unwrap();
+        self.gate_count += 1;
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use g16ckt::{
+        CircuitContext, GateType as SourceGateType,
+        circuit::{CircuitBuilder, StreamingMode, TRUE_WIRE, component_meta::ComponentMetaBuilder},
+        gadgets::basic::full_adder,
+    };
+
+    use super::*;
+
+    const GATE_TYPES: [SourceGateType; 10] = [
+        SourceGateType::And,
+        SourceGateType::Nand,
+        SourceGateType::Nimp,
+        SourceGateType::Imp,
+        SourceGateType::Ncimp,
+        SourceGateType::Cimp,
+        SourceGateType::Nor,
+        SourceGateType::Or,
+        SourceGateType::Xor,
+        SourceGateType::Xnor,
+    ];
+
+    /// Every source gate type, plus gates on constants and a full adder
+    pub(crate) fn gadget<C: CircuitContext>(ctx: &mut C, inputs: &[WireId; 3]) -> Vec<WireId> {
+        let [a, b, c] = *inputs;
+
+        let mut outputs = GATE_TYPES
+            .iter()
+            .map(|&gate_type| {
+                let out = ctx.issue_wire();
+                ctx.add_gate(SourceGate::new(gate_type, a, b, out));
+                out
+            })
+            .collect::<Vec<_>>();
+
+        let with_constant = ctx.issue_wire();
+        ctx.add_gate(SourceGate::nand(c, TRUE_WIRE, with_constant));
+        outputs.push(with_constant);
+
+        let (sum, carry) = full_adder(ctx, a, b, c);
+        outputs.extend([sum, carry]);
+
+        outputs
+    }
+
+    fn translate(path: &str) {
+        let inputs = [false; 3];
+        let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(&inputs);
+        let mut metadata_mode = StreamingMode::<BristolTranslationMode>::MetadataPass(root_meta);
+        let meta_output_wires = gadget(&mut metadata_mode, &allocated_inputs);
+
+        let (mut ctx, allocated_inputs) = metadata_mode.to_root_ctx(
+            BristolTranslationMode::new(path, inputs.len() as u64).unwrap(),
+            &inputs,
+            &meta_output_wires,
+        );
+        let output_wires = gadget(&mut ctx, &allocated_inputs);
+        ctx.get_mut_mode().unwrap().finish(&output_wires).unwrap();
+    }
+
+    /// Minimal Bristol Fashion evaluator for a single input and a single output value.
+    fn simulate(text: &str, inputs: &[bool]) -> Vec<bool> {
+        let parse = |line: &str| {
+            line.split_whitespace()
+                .map(|token| token.parse::<usize>().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        let mut lines = text.lines();
+        let header = parse(lines.next().unwrap());
+        let (gate_count, wire_count) = (header[0], header[1]);
+        assert_eq!(parse(lines.next().unwrap()), [1, inputs.len()]);
+        let output_count = parse(lines.next().unwrap())[1];
+
+        let mut wires = vec![None; wire_count];
+        for (wire, value) in wires.iter_mut().zip(inputs) {
+            *wire = Some(*value);
+        }
+        let read = |wires: &[Option<bool>], index: &str| {
+            wires[index.parse::<usize>().unwrap()].expect("wire read before being written")
+        };
+
+        let gates = lines.filter(|line| !line.is_empty()).collect::<Vec<_>>();
+        assert_eq!(gates.len(), gate_count);
+        for gate in gates {
+            let tokens = gate.split_whitespace().collect::<Vec<_>>();
+            let value = match tokens[tokens.len() - 1] {
+                "AND" => read(&wires, tokens[2]) & read(&wires, tokens[3]),
+                "XOR" => read(&wires, tokens[2]) ^ read(&wires, tokens[3]),
+                "INV" => !read(&wires, tokens[2]),
+                other => panic!("unexpected gate {other}"),
+            };
+            let out = tokens[tokens.len() - 2].parse::<usize>().unwrap();
+            assert!(wires[out].is_none(), "wire {out} written twice");
+            wires[out] = Some(value);
+        }
+
+        wires[wire_count - output_count..]
+            .iter()
+            .map(|wire| wire.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_bristol_resimulation_matches_execution() {
+        let path = std::env::temp_dir().join("g16gen_bristol_test.txt");
+        translate(path.to_str().unwrap());
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        for bits in 0..8u8 {
+            let inputs: [bool; 3] = std::array::from_fn(|i| (bits >> i) & 1 == 1);
+            let expected =
+                CircuitBuilder::streaming_execute::<_, _, Vec<bool>>(inputs, 10_000, gadget)
+                    .output_value;
+
+            assert_eq!(simulate(&text, &inputs), expected, "inputs {inputs:?}");
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// Sidecar of the circuit file at `ckt_path` mapping its wires to components
+pub fn sidecar_path(ckt_path: &str) -> PathBuf {
+    PathBuf::from(format!("{ckt_path}.components"))
+}
+
+/// Writes the sidecar of a translated circuit: one `<first> <end> <component>` line per component
+/// call that allocated wires, covering the IDs `first..end` allocated from its start to its end.
+///
+/// Calls nest, so the ranges do too, and a line is written once the call ends: nested calls come
+/// before the one containing them. The innermost range containing a wire is the component whose
+/// gate wrote it. Wires allocated outside of any component, like the primary inputs, aren't
+/// covered.
+#[derive(Debug)]
+pub struct ComponentMapWriter {
+    out: BufWriter<File>,
+    /// Names of the components being executed, innermost last, with the first wire of each
+    stack: Vec<(String, u64)>,
+}
+
+impl ComponentMapWriter {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            out: BufWriter::new(File::create(path)?),
+            stack: Vec::new(),
+        })
+    }
+
+    /// A call of the component `name` starts, and `next_wire` is the next wire ID to be allocated
+    pub fn enter(&mut self, name: &str, next_wire: u64) {
+        self.stack.push((name.to_string(), next_wire));
+    }
+
+    /// The innermost component ends, and `next_wire` is the next wire ID to be allocated
+    pub fn exit(&mut self, next_wire: u64) -> io::Result<()> {
+        let (name, first) = self
+            .stack
+            .pop()
+            .expect("exiting a component that was never entered");
+        if next_wire == first {
+            return Ok(());
+        }
+
+        writeln!(self.out, "{first} {next_wire} {name}")
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        assert!(self.stack.is_empty(), "components left unfinished");
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_nested_calls_are_written_innermost_first() {
+        let path = std::env::temp_dir().join("g16gen_component_map_test.components");
+        let (outer, inner) = ("test::outer", "test::inner");
+
+        // Wires 0..4 are inputs; outer allocates 4..10, of which inner 6..9
+        let mut writer = ComponentMapWriter::create(&path).unwrap();
+        writer.enter(outer, 4);
+        writer.enter(inner, 6);
+        writer.exit(9).unwrap();
+        // Nothing allocated: no line
+        writer.enter(inner, 9);
+        writer.exit(9).unwrap();
+        writer.exit(10).unwrap();
+        writer.finish().unwrap();
+
+        let written = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(written, "6 9 test::inner\n4 10 test::outer\n");
+    }
+}
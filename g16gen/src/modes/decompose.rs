@@ -0,0 +1,445 @@
+use ckt_lvl::types::CompactWireId;
+use g16ckt::{Gate as SourceGate, GateType as SourceGateType};
+
+/// Primitive gates a source gate can be decomposed into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Primitive {
+    And,
+    Xor,
+    Xnor,
+    Nand,
+    Or,
+}
+
+/// Primitive gate set targeted by [`translate_gate`].
+///
+/// NOT is never a primitive of its own: it is expressed against the constant ONE wire (XOR with ONE,
+/// or NAND with ONE for the NAND-only basis).
+///
+/// Only bases some writer can encode are available outside tests; the others stay test-only until
+/// a writer encodes their primitives.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BasisConfig {
+    /// AND/XOR, the basis of the `.ckt` v5a format.
+    #[default]
+    AndXor,
+    /// AND/XOR with XNOR kept native.
+    #[cfg(test)]
+    AndXorXnor,
+    /// AND/XOR with XNOR and OR kept native, which also shortens NOR, IMP and CIMP.
+    #[cfg(test)]
+    AndXorXnorOr,
+    /// NAND only.
+    #[cfg(test)]
+    Nand,
+}
+
+impl BasisConfig {
+    pub fn primitives(self) -> &'static [Primitive] {
+        match self {
+            BasisConfig::AndXor => &[Primitive::And, Primitive::Xor],
+            #[cfg(test)]
+            BasisConfig::AndXorXnor => &[Primitive::And, Primitive::Xor, Primitive::Xnor],
+            #[cfg(test)]
+            BasisConfig::AndXorXnorOr => &[
+                Primitive::And,
+                Primitive::Xor,
+                Primitive::Xnor,
+                Primitive::Or,
+            ],
+            #[cfg(test)]
+            BasisConfig::Nand => &[Primitive::Nand],
+        }
+    }
+
+    pub fn allows(self, primitive: Primitive) -> bool {
+        self.primitives().contains(&primitive)
+    }
+}
+
+/// Output side of [`translate_gate`]: receives the primitive gates a source gate decomposes into.
+pub trait GateSink {
+    /// Allocate a fresh normalized ID for a temporary wire.
+    fn allocate_normalized_id(&mut self) -> u64;
+
+    /// Normalized ID of the constant ONE wire.
+    fn true_wire_id(&self) -> CompactWireId;
+
+    fn write_gate(
+        &mut self,
+        gate_type: Primitive,
+        in1: CompactWireId,
+        in2: CompactWireId,
+        out: CompactWireId,
+    );
+}
+
+/// Decompose a source gate into the primitives of `basis`.
+///
+/// Every temporary wire is allocated through the sink, so a sink that only counts wire uses (see
+/// `FanoutCounter`) sees exactly the wires a writing sink will emit.
+pub fn translate_gate(sink: &mut impl GateSink, basis: BasisConfig, gate: &SourceGate) {
+    #[cfg(test)]
+    if basis == BasisConfig::Nand {
+        return translate_nand(sink, gate);
+    }
+    translate_and_xor(sink, basis, gate)
+}
+
+fn allocate_id(sink: &mut impl GateSink) -> CompactWireId {
+    CompactWireId::from_u64(sink.allocate_normalized_id())
+}
+
+fn translate_and_xor(sink: &mut impl GateSink, basis: BasisConfig, gate: &SourceGate) {
+    let in1 = CompactWireId::from_u64(gate.wire_a.0 as u64);
+    let in2 = CompactWireId::from_u64(gate.wire_b.0 as u64);
+    let out = CompactWireId::from_u64(gate.wire_c.0 as u64);
+    let one = sink.true_wire_id();
+
+    use SourceGateType::*;
+    match gate.gate_type {
+        // Direct mappings
+        And => sink.write_gate(Primitive::And, in1, in2, out),
+        Xor => sink.write_gate(Primitive::Xor, in1, in2, out),
+        Xnor if basis.allows(Primitive::Xnor) => sink.write_gate(Primitive::Xnor, in1, in2, out),
+        Or if basis.allows(Primitive::Or) => sink.write_gate(Primitive::Or, in1, in2, out),
+
+        // With native OR, the rest of its family is OR with one side negated
+        Nor if basis.allows(Primitive::Or) => {
+            let temp = allocate_id(sink);
+            sink.write_gate(Primitive::Or, in1, in2, temp);
+            sink.write_gate(Primitive::Xor, temp, one, out);
+        }
+        Imp if basis.allows(Primitive::Or) => {
+            let temp = allocate_id(sink);
+            sink.write_gate(Primitive::Xor, in1, one, temp); // NOT a
+            sink.write_gate(Primitive::Or, temp, in2, out);
+        }
+        Cimp if basis.allows(Primitive::Or) => {
+            let temp = allocate_id(sink);
+            sink.write_gate(Primitive::Xor, in2, one, temp); // NOT b
+            sink.write_gate(Primitive::Or, in1, temp, out);
+        }
+
+        // Negated versions - XOR result with ONE
+        Nand => {
+            let temp = allocate_id(sink);
+            sink.write_gate(Primitive::And, in1, in2, temp);
+            sink.write_gate(Primitive::Xor, temp, one, out);
+        }
+
+        Xnor => {
+            let temp = allocate_id(sink);
+            sink.write_gate(Primitive::Xor, in1, in2, temp);
+            sink.write_gate(Primitive::Xor, temp, one, out);
+        }
+
+        // NOT: XOR with ONE
+        Not => sink.write_gate(Primitive::Xor, in1, one, out),
+
+        // OR = XOR(XOR(AND(a,b), a), b)
+        Or => {
+            let temp1 = allocate_id(sink);
+            let temp2 = allocate_id(sink);
+            sink.write_gate(Primitive::And, in1, in2, temp1);
+            sink.write_gate(Primitive::Xor, temp1, in1, temp2);
+            sink.write_gate(Primitive::Xor, temp2, in2, out);
+        }
+
+        // NOR = XOR(OR(a,b), ONE)
+        Nor => {
+            let temp1 = allocate_id(sink);
+            let temp2 = allocate_id(sink);
+            let temp3 = allocate_id(sink);
+            // First compute OR
+            sink.write_gate(Primitive::And, in1, in2, temp1);
+            sink.write_gate(Primitive::Xor, temp1, in1, temp2);
+            sink.write_gate(Primitive::Xor, temp2, in2, temp3);
+            // Then negate with ONE
+            sink.write_gate(Primitive::Xor, temp3, one, out);
+        }
+
+        // NIMP: a AND NOT b = AND(a, XOR(b, ONE))
+        Nimp => {
+            let temp = allocate_id(sink);
+            sink.write_gate(Primitive::Xor, in2, one, temp); // NOT b
+            sink.write_gate(Primitive::And, in1, temp, out); // a AND (NOT b)
+        }
+
+        // NCIMP: NOT a AND b = AND(XOR(a, ONE), b)
+        Ncimp => {
+            let temp = allocate_id(sink);
+            sink.write_gate(Primitive::Xor, in1, one, temp); // NOT a
+            sink.write_gate(Primitive::And, temp, in2, out); // (NOT a) AND b
+        }
+
+        // IMP: a => b = NOT a OR b
+        Imp => {
+            let temp1 = allocate_id(sink);
+            let temp2 = allocate_id(sink);
+            let temp3 = allocate_id(sink);
+
+            // NOT a
+            sink.write_gate(Primitive::Xor, in1, one, temp1);
+            // OR(NOT a, b) = XOR(XOR(AND(NOT a, b), NOT a), b)
+            sink.write_gate(Primitive::And, temp1, in2, temp2);
+            sink.write_gate(Primitive::Xor, temp2, temp1, temp3);
+            sink.write_gate(Primitive::Xor, temp3, in2, out);
+        }
+
+        // CIMP: b => a (swap inputs for IMP)
+        Cimp => {
+            let temp1 = allocate_id(sink);
+            let temp2 = allocate_id(sink);
+            let temp3 = allocate_id(sink);
+
+            // NOT b
+            sink.write_gate(Primitive::Xor, in2, one, temp1);
+            // OR(NOT b, a)
+            sink.write_gate(Primitive::And, temp1, in1, temp2);
+            sink.write_gate(Primitive::Xor, temp2, temp1, temp3);
+            sink.write_gate(Primitive::Xor, temp3, in1, out);
+        }
+    }
+}
+
+#[cfg(test)]
+fn translate_nand(sink: &mut impl GateSink, gate: &SourceGate) {
+    let in1 = CompactWireId::from_u64(gate.wire_a.0 as u64);
+    let in2 = CompactWireId::from_u64(gate.wire_b.0 as u64);
+    let out = CompactWireId::from_u64(gate.wire_c.0 as u64);
+    let one = sink.true_wire_id();
+
+    use SourceGateType::*;
+    match gate.gate_type {
+        Nand => sink.write_gate(Primitive::Nand, in1, in2, out),
+
+        // NOT: NAND with ONE
+        Not => sink.write_gate(Primitive::Nand, in1, one, out),
+
+        // AND = NOT(NAND(a,b))
+        And => {
+            let temp = allocate_id(sink);
+            sink.write_gate(Primitive::Nand, in1, in2, temp);
+            sink.write_gate(Primitive::Nand, temp, one, out);
+        }
+
+        // OR = NAND(NOT a, NOT b)
+        Or => {
+            let temp1 = allocate_id(sink);
+            let temp2 = allocate_id(sink);
+            sink.write_gate(Primitive::Nand, in1, one, temp1);
+            sink.write_gate(Primitive::Nand, in2, one, temp2);
+            sink.write_gate(Primitive::Nand, temp1, temp2, out);
+        }
+
+        // NOR = NOT(OR(a,b))
+        Nor => {
+            let temp1 = allocate_id(sink);
+            let temp2 = allocate_id(sink);
+            let temp3 = allocate_id(sink);
+            sink.write_gate(Primitive::Nand, in1, one, temp1);
+            sink.write_gate(Primitive::Nand, in2, one, temp2);
+            sink.write_gate(Primitive::Nand, temp1, temp2, temp3);
+            sink.write_gate(Primitive::Nand, temp3, one, out);
+        }
+
+        // XOR: the classic four-NAND construction
+        Xor => {
+            let temp1 = allocate_id(sink);
+            let temp2 = allocate_id(sink);
+            let temp3 = allocate_id(sink);
+            sink.write_gate(Primitive::Nand, in1, in2, temp1);
+            sink.write_gate(Primitive::Nand, in1, temp1, temp2);
+            sink.write_gate(Primitive::Nand, in2, temp1, temp3);
+            sink.write_gate(Primitive::Nand, temp2, temp3, out);
+        }
+
+        // XNOR = NOT(XOR(a,b))
+        Xnor => {
+            let temp1 = allocate_id(sink);
+            let temp2 = allocate_id(sink);
+            let temp3 = allocate_id(sink);
+            let temp4 = allocate_id(sink);
+            sink.write_gate(Primitive::Nand, in1, in2, temp1);
+            sink.write_gate(Primitive::Nand, in1, temp1, temp2);
+            sink.write_gate(Primitive::Nand, in2, temp1, temp3);
+            sink.write_gate(Primitive::Nand, temp2, temp3, temp4);
+            sink.write_gate(Primitive::Nand, temp4, one, out);
+        }
+
+        // NIMP: a AND NOT b = NOT(NAND(a, NOT b))
+        Nimp => {
+            let temp1 = allocate_id(sink);
+            let temp2 = allocate_id(sink);
+            sink.write_gate(Primitive::Nand, in2, one, temp1); // NOT b
+            sink.write_gate(Primitive::Nand, in1, temp1, temp2);
+            sink.write_gate(Primitive::Nand, temp2, one, out);
+        }
+
+        // NCIMP: NOT a AND b = NOT(NAND(NOT a, b))
+        Ncimp => {
+            let temp1 = allocate_id(sink);
+            let temp2 = allocate_id(sink);
+            sink.write_gate(Primitive::Nand, in1, one, temp1); // NOT a
+            sink.write_gate(Primitive::Nand, temp1, in2, temp2);
+            sink.write_gate(Primitive::Nand, temp2, one, out);
+        }
+
+        // IMP: a => b = NAND(a, NOT b)
+        Imp => {
+            let temp = allocate_id(sink);
+            sink.write_gate(Primitive::Nand, in2, one, temp); // NOT b
+            sink.write_gate(Primitive::Nand, in1, temp, out);
+        }
+
+        // CIMP: b => a = NAND(NOT a, b)
+        Cimp => {
+            let temp = allocate_id(sink);
+            sink.write_gate(Primitive::Nand, in1, one, temp); // NOT a
+            sink.write_gate(Primitive::Nand, temp, in2, out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use g16ckt::WireId;
+
+    use super::*;
+
+    const GATE_TYPES: [SourceGateType; 11] = [
+        SourceGateType::And,
+        SourceGateType::Nand,
+        SourceGateType::Nimp,
+        SourceGateType::Imp,
+        SourceGateType::Ncimp,
+        SourceGateType::Cimp,
+        SourceGateType::Nor,
+        SourceGateType::Or,
+        SourceGateType::Xor,
+        SourceGateType::Xnor,
+        SourceGateType::Not,
+    ];
+
+    const BASES: [BasisConfig; 4] = [
+        BasisConfig::AndXor,
+        BasisConfig::AndXorXnor,
+        BasisConfig::AndXorXnorOr,
+        BasisConfig::Nand,
+    ];
+
+    /// Records emitted primitives; wire 0 is FALSE, 1 is TRUE, 2/3 are the source inputs.
+    struct Recorder {
+        next_normalized_id: u64,
+        gates: Vec<(Primitive, u64, u64, u64)>,
+    }
+
+    impl GateSink for Recorder {
+        fn allocate_normalized_id(&mut self) -> u64 {
+            let id = self.next_normalized_id;
+            self.next_normalized_id += 1;
+            id
+        }
+
+        fn true_wire_id(&self) -> CompactWireId {
+            CompactWireId::from_u64(1)
+        }
+
+        fn write_gate(
+            &mut self,
+            gate_type: Primitive,
+            in1: CompactWireId,
+            in2: CompactWireId,
+            out: CompactWireId,
+        ) {
+            self.gates
+                .push((gate_type, in1.to_u64(), in2.to_u64(), out.to_u64()));
+        }
+    }
+
+    fn evaluate(gates: &[(Primitive, u64, u64, u64)], a: bool, b: bool) -> HashMap<u64, bool> {
+        let mut wires = HashMap::from([(0, false), (1, true), (2, a), (3, b)]);
+        for &(gate_type, in1, in2, out) in gates {
+            let (x, y) = (wires[&in1], wires[&in2]);
+            let value = match gate_type {
+                Primitive::And => x & y,
+                Primitive::Xor => x ^ y,
+                Primitive::Xnor => !(x ^ y),
+                Primitive::Nand => !(x & y),
+                Primitive::Or => x | y,
+            };
+            wires.insert(out, value);
+        }
+        wires
+    }
+
+    #[test]
+    fn test_decomposition_respects_basis() {
+        for basis in BASES {
+            for gate_type in GATE_TYPES {
+                let out = if gate_type == SourceGateType::Not {
+                    2
+                } else {
+                    4
+                };
+                let gate = SourceGate::new(gate_type, WireId(2), WireId(3), WireId(out));
+
+                let mut recorder = Recorder {
+                    next_normalized_id: 5,
+                    gates: Vec::new(),
+                };
+                translate_gate(&mut recorder, basis, &gate);
+
+                for &(primitive, ..) in &recorder.gates {
+                    assert!(
+                        basis.allows(primitive),
+                        "{basis:?}: {gate_type:?} emitted {primitive:?}"
+                    );
+                }
+
+                for (a, b) in [(false, false), (false, true), (true, false), (true, true)] {
+                    let wires = evaluate(&recorder.gates, a, b);
+                    assert_eq!(
+                        wires[&(out as u64)],
+                        gate_type.f()(a, b),
+                        "{basis:?}: {gate_type:?}({a}, {b})"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_native_gates_need_no_temporaries() {
+        let count_temps = |basis, gate_type| {
+            let mut recorder = Recorder {
+                next_normalized_id: 5,
+                gates: Vec::new(),
+            };
+            let gate = SourceGate::new(gate_type, WireId(2), WireId(3), WireId(4));
+            translate_gate(&mut recorder, basis, &gate);
+            recorder.next_normalized_id - 5
+        };
+
+        assert_eq!(count_temps(BasisConfig::AndXor, SourceGateType::Xnor), 1);
+        assert_eq!(
+            count_temps(BasisConfig::AndXorXnor, SourceGateType::Xnor),
+            0
+        );
+        assert_eq!(count_temps(BasisConfig::AndXor, SourceGateType::Or), 2);
+        assert_eq!(
+            count_temps(BasisConfig::AndXorXnorOr, SourceGateType::Or),
+            0
+        );
+        assert_eq!(count_temps(BasisConfig::AndXor, SourceGateType::Imp), 3);
+        assert_eq!(
+            count_temps(BasisConfig::AndXorXnorOr, SourceGateType::Imp),
+            1
+        );
+        assert_eq!(count_temps(BasisConfig::Nand, SourceGateType::Nand), 0);
+        assert_eq!(count_temps(BasisConfig::Nand, SourceGateType::Xor), 3);
+    }
+}
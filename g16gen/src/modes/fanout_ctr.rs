@@ -1,16 +1,99 @@
-use std::num::NonZero;
+use std::{collections::HashMap, num::NonZero, ops::Range};
 
+use ckt_lvl::types::CompactWireId;
 use g16ckt::{
-    Gate as SourceGate, GateType, WireId, circuit::CircuitMode, storage::Credits as SourceCredits,
+    Gate as SourceGate, WireId,
+    circuit::{CircuitMode, ComponentKey},
+    storage::{CreditConfig, Credits as SourceCredits},
 };
 use indicatif::ProgressBar;
+use tracing::warn;
+
+use crate::modes::decompose::{BasisConfig, GateSink, Primitive, translate_gate};
+
+/// What a component contributes to the circuit, children excluded
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ComponentStats {
+    /// Counted source gates the component executes itself, over all of its invocations
+    pub gates: u64,
+    /// Largest fanout among the wires the component produces
+    pub max_fanout: u32,
+}
+
+/// Statistics by innermost component, `None` standing for gates and wires outside any component
+pub type ComponentSummary = HashMap<Option<ComponentKey>, ComponentStats>;
+
+/// Attribution of gates and normalized IDs to the component executing them
+#[derive(Debug)]
+struct ComponentTracker {
+    /// Components currently executing, innermost last
+    stack: Vec<ComponentKey>,
+    /// From each start ID on, normalized IDs are produced by that component, up to the next start
+    wire_ranges: Vec<(u64, Option<ComponentKey>)>,
+    gates: HashMap<Option<ComponentKey>, u64>,
+}
+
+impl ComponentTracker {
+    fn new() -> Self {
+        Self {
+            stack: Vec::new(),
+            wire_ranges: vec![(0, None)],
+            gates: HashMap::new(),
+        }
+    }
+
+    /// The innermost component produces everything from `next_id` on
+    fn switch(&mut self, next_id: u64) {
+        let current = self.stack.last().copied();
+        match self.wire_ranges.last_mut() {
+            Some((start, key)) if *start == next_id => *key = current,
+            _ => self.wire_ranges.push((next_id, current)),
+        }
+    }
+
+    fn summarize(&self, fanout: &[u32]) -> ComponentSummary {
+        let mut summary = self
+            .gates
+            .iter()
+            .map(|(key, &gates)| {
+                let stats = ComponentStats {
+                    gates,
+                    max_fanout: 0,
+                };
+                (*key, stats)
+            })
+            .collect::<ComponentSummary>();
+
+        let ends = self.wire_ranges.iter().skip(1).map(|(start, _)| *start);
+        for (&(start, key), end) in self
+            .wire_ranges
+            .iter()
+            .zip(ends.chain([fanout.len() as u64]))
+        {
+            let range = (start as usize).min(fanout.len())..(end as usize).min(fanout.len());
+            let max_fanout = fanout[range].iter().copied().max().unwrap_or(0);
+            let stats = summary.entry(key).or_default();
+            stats.max_fanout = stats.max_fanout.max(max_fanout);
+        }
+
+        summary
+    }
+}
 
 #[derive(Debug)]
 pub struct FanoutCounter {
-    fanout: Option<Vec<u16>>, // Original -> Normalized IDs
+    fanout: Option<Vec<u32>>, // Original -> Normalized IDs
     next_normalized_id: u64,
+    basis: BasisConfig,
+    credit_config: CreditConfig,
+    saturated: usize,
+    /// Gates (by position in the source stream) whose wire uses are counted
+    gate_range: Range<u64>,
+    gate_index: u64,
     primary_inputs: usize,
     biggest_fanout_seen: usize,
+    components: Option<ComponentTracker>,
+    histogram: bool,
     spinner: ProgressBar,
 }
 
@@ -30,6 +113,14 @@ impl CircuitMode for FanoutCounter {
         WireId(normalized_id)
     }
 
+    fn allocate_wires(&mut self, credits: &[SourceCredits]) -> Vec<WireId> {
+        let start = self.next_normalized_id;
+        self.next_normalized_id += credits.len() as u64;
+        (start..self.next_normalized_id)
+            .map(|id| WireId(id as usize))
+            .collect()
+    }
+
     fn lookup_wire(&mut self, _wire: WireId) -> Option<Self::WireValue> {
         Some(false) // Always return dummy value
     }
@@ -43,135 +134,85 @@ impl CircuitMode for FanoutCounter {
     fn evaluate_gate(&mut self, gate: &SourceGate) {
         self.spinner.inc(1);
 
-        let resize = |fanout: &mut Vec<u16>, max_wire_produced: usize| {
-            if max_wire_produced >= fanout.len() {
-                fanout.resize(max_wire_produced + 1, 0);
-            }
-        };
+        if let Some(components) = &mut self.components
+            && self.gate_range.contains(&self.gate_index)
+        {
+            *components
+                .gates
+                .entry(components.stack.last().copied())
+                .or_default() += 1;
+        }
 
-        // handle additional wires for translation
-        match gate.gate_type {
-            // no translation
-            GateType::And => {
-                resize(self.fanout.as_mut().unwrap(), gate.wire_c.0);
-                self.wire_used(gate.wire_a);
-                self.wire_used(gate.wire_b);
-            }
-            // no translation
-            GateType::Xor => {
-                resize(self.fanout.as_mut().unwrap(), gate.wire_c.0);
-                self.wire_used(gate.wire_a);
-                self.wire_used(gate.wire_b);
-            }
-            GateType::Not => {
-                self.wire_used(gate.wire_a);
-                // ONE is constant, don't count
-            }
-            // XOR(a, ONE)
-            GateType::Nand => {
-                let temp = self.allocate_normalized_id();
-                resize(self.fanout.as_mut().unwrap(), temp as usize);
-                self.wire_used(gate.wire_a);
-                self.wire_used(gate.wire_b);
-                self.wire_used(WireId(temp as usize));
-                // ONE is constant, don't count
-            }
-            //  XOR(XOR(a, b), ONE)
-            GateType::Xnor => {
-                let temp = self.allocate_normalized_id();
-                resize(self.fanout.as_mut().unwrap(), temp as usize);
-                self.wire_used(gate.wire_a);
-                self.wire_used(gate.wire_b);
-                self.wire_used(WireId(temp as usize));
-                // ONE is constant, don't count
-            }
-            // XOR(XOR(AND(a, b), a), b)
-            GateType::Or => {
-                let temp1 = self.allocate_normalized_id();
-                let temp2 = self.allocate_normalized_id();
-                resize(self.fanout.as_mut().unwrap(), temp2 as usize);
-                self.wire_used(gate.wire_a);
-                self.wire_used(gate.wire_b);
-                self.wire_used(WireId(temp1 as usize));
-                self.wire_used(gate.wire_a);
-                self.wire_used(WireId(temp2 as usize));
-                self.wire_used(gate.wire_b);
-            }
-            // XOR(XOR(XOR(AND(a, b), a), b), ONE)
-            GateType::Nor => {
-                let temp1 = self.allocate_normalized_id();
-                let temp2 = self.allocate_normalized_id();
-                let temp3 = self.allocate_normalized_id();
-                resize(self.fanout.as_mut().unwrap(), temp3 as usize);
-                self.wire_used(gate.wire_a);
-                self.wire_used(gate.wire_b);
-                self.wire_used(WireId(temp1 as usize));
-                self.wire_used(gate.wire_a);
-                self.wire_used(WireId(temp2 as usize));
-                self.wire_used(gate.wire_b);
-                self.wire_used(WireId(temp3 as usize));
-                // ONE is constant, don't count
-            }
-            // AND(a, XOR(b, ONE))
-            GateType::Nimp => {
-                let temp = self.allocate_normalized_id();
-                resize(self.fanout.as_mut().unwrap(), temp as usize);
-                self.wire_used(gate.wire_b);
-                // ONE is constant, don't count
-                self.wire_used(gate.wire_a);
-                self.wire_used(WireId(temp as usize));
-            }
-            // AND(XOR(a, ONE), b)
-            GateType::Ncimp => {
-                let temp = self.allocate_normalized_id();
-                resize(self.fanout.as_mut().unwrap(), temp as usize);
-                self.wire_used(gate.wire_a);
-                // ONE is constant, don't count
-                self.wire_used(WireId(temp as usize));
-                self.wire_used(gate.wire_b);
-            }
-            // XOR(XOR(AND(XOR(a, ONE), b), XOR(a, ONE)), b)
-            GateType::Imp => {
-                let temp1 = self.allocate_normalized_id();
-                let temp2 = self.allocate_normalized_id();
-                let temp3 = self.allocate_normalized_id();
-                resize(self.fanout.as_mut().unwrap(), temp3 as usize);
-                self.wire_used(gate.wire_a);
-                // ONE is constant, don't count
-                self.wire_used(WireId(temp1 as usize));
-                self.wire_used(gate.wire_b);
-                self.wire_used(WireId(temp2 as usize));
-                self.wire_used(WireId(temp1 as usize));
-                self.wire_used(WireId(temp3 as usize));
-                self.wire_used(gate.wire_b);
-            }
-            // XOR(XOR(AND(XOR(b, ONE), a), XOR(b, ONE)), a)
-            GateType::Cimp => {
-                let temp1 = self.allocate_normalized_id();
-                let temp2 = self.allocate_normalized_id();
-                let temp3 = self.allocate_normalized_id();
-                resize(self.fanout.as_mut().unwrap(), temp3 as usize);
-                self.wire_used(gate.wire_b);
-                // ONE is constant, don't count
-                self.wire_used(WireId(temp1 as usize));
-                self.wire_used(gate.wire_a);
-                self.wire_used(WireId(temp2 as usize));
-                self.wire_used(WireId(temp1 as usize));
-                self.wire_used(WireId(temp3 as usize));
-                self.wire_used(gate.wire_a);
-            }
+        // Count uses over the exact gates translation will emit, temporaries included.
+        let basis = self.basis;
+        translate_gate(self, basis, gate);
+        self.gate_index += 1;
+    }
+
+    fn enter_component(&mut self, key: ComponentKey, _name: &str) {
+        if let Some(components) = &mut self.components {
+            components.stack.push(key);
+            components.switch(self.next_normalized_id);
+        }
+    }
+
+    fn exit_component(&mut self) {
+        if let Some(components) = &mut self.components {
+            components.stack.pop();
+            components.switch(self.next_normalized_id);
         }
     }
 }
 
+impl GateSink for FanoutCounter {
+    fn allocate_normalized_id(&mut self) -> u64 {
+        let id = self.next_normalized_id;
+        self.next_normalized_id += 1;
+        id
+    }
+
+    fn true_wire_id(&self) -> CompactWireId {
+        CompactWireId::from_u64(1)
+    }
+
+    fn write_gate(
+        &mut self,
+        _gate_type: Primitive,
+        in1: CompactWireId,
+        in2: CompactWireId,
+        out: CompactWireId,
+    ) {
+        if !self.gate_range.contains(&self.gate_index) {
+            return;
+        }
+
+        let fanout = self.fanout.as_mut().unwrap();
+        let out = out.to_u64() as usize;
+        if out >= fanout.len() {
+            fanout.resize(out + 1, 0);
+        }
+
+        // Constants and primary inputs are skipped by `wire_used`
+        self.wire_used(WireId(in1.to_u64() as usize));
+        self.wire_used(WireId(in2.to_u64() as usize));
+    }
+}
+
 impl FanoutCounter {
-    pub fn new(primary_inputs: usize) -> Self {
+    pub fn new(primary_inputs: usize, basis: BasisConfig, credit_config: CreditConfig) -> Self {
         let pb = ProgressBar::no_length();
         let mut mode = Self {
             fanout: Some(Vec::new()),
             next_normalized_id: 0,
+            basis,
+            credit_config,
+            saturated: 0,
+            gate_range: 0..u64::MAX,
+            gate_index: 0,
             primary_inputs,
             biggest_fanout_seen: 0,
+            components: None,
+            histogram: false,
             spinner: pb,
         };
 
@@ -182,25 +223,321 @@ impl FanoutCounter {
         mode
     }
 
-    fn allocate_normalized_id(&mut self) -> u64 {
-        let id = self.next_normalized_id;
-        self.next_normalized_id += 1;
-        id
+    /// Only count wire uses of source gates whose position falls in `gate_range`.
+    ///
+    /// Normalized IDs are still allocated for every gate, so counters over disjoint ranges of the
+    /// same circuit sum up to the full fanout.
+    pub fn with_gate_range(mut self, gate_range: Range<u64>) -> Self {
+        self.gate_range = gate_range;
+        self
+    }
+
+    /// Reserve room for `wires` normalized IDs up front, so counting a circuit of known size never
+    /// grows the fanout vector.
+    pub fn with_capacity(mut self, wires: u64) -> Self {
+        let fanout = self.fanout.as_mut().unwrap();
+        fanout.reserve_exact((wires as usize).saturating_sub(fanout.len()));
+        self
+    }
+
+    /// Attribute counted gates and produced wires to the innermost component executing them, for
+    /// the summary [`FanoutCounter::finish`] returns.
+    pub fn with_component_stats(mut self) -> Self {
+        self.components = Some(ComponentTracker::new());
+        self
+    }
+
+    /// Also tally wires by fanout, for the histogram [`FanoutCounter::finish`] returns.
+    pub fn with_fanout_histogram(mut self) -> Self {
+        self.histogram = true;
+        self
+    }
+
+    /// Whether any gate in `gates` would be counted.
+    pub fn counts_any(&self, gates: Range<u64>) -> bool {
+        gates.start < self.gate_range.end && self.gate_range.start < gates.end
+    }
+
+    /// Position of the next source gate.
+    pub fn gate_index(&self) -> u64 {
+        self.gate_index
+    }
+
+    pub fn next_normalized_id(&self) -> u64 {
+        self.next_normalized_id
+    }
+
+    /// Advance past `gates` uncounted source gates that allocate `wires` normalized IDs.
+    pub fn skip(&mut self, wires: u64, gates: u64) {
+        assert!(!self.counts_any(self.gate_index..self.gate_index + gates));
+
+        self.next_normalized_id += wires;
+        self.gate_index += gates;
+        self.spinner.inc(gates);
+    }
+
+    /// Wires readable any number of times: FALSE, TRUE and the primary inputs.
+    ///
+    /// Uses of these are never counted, so their credits stay zero. This is the same prefix
+    /// `g16check` treats as always available and never decrements.
+    pub fn always_available(&self) -> usize {
+        self.primary_inputs + 2
     }
 
-    fn wire_used(&mut self, wire_id: WireId) -> u16 {
+    fn wire_used(&mut self, wire_id: WireId) -> u32 {
         let wire_id = wire_id.0;
-        if (0..self.primary_inputs + 2).contains(&wire_id) {
+        if wire_id < self.always_available() {
             return 0;
         }
         let fanout = self.fanout.as_mut().unwrap();
+        // With a gate range, the gate producing this wire may not have been counted
+        if wire_id >= fanout.len() {
+            fanout.resize(wire_id + 1, 0);
+        }
+        let fanout = &mut fanout[wire_id];
+
+        if self.credit_config.add(fanout, 1) {
+            self.saturated += 1;
+        }
+        self.biggest_fanout_seen = self.biggest_fanout_seen.max(*fanout as usize);
+        *fanout
+    }
+
+    /// Number of uses dropped because a counter was capped at the configured width.
+    pub fn saturated(&self) -> usize {
+        self.saturated
+    }
+
+    /// Fanout by normalized ID, one entry for each ID allocated, the largest fanout seen and, if
+    /// enabled with [`FanoutCounter::with_component_stats`], the per-component summary.
+    ///
+    /// With [`FanoutCounter::with_fanout_histogram`], also the number of wires with each fanout,
+    /// indexed by fanout. Constants and primary inputs aren't counted, as they take no credits.
+    pub fn finish(&mut self) -> (Vec<u32>, usize, Option<ComponentSummary>, Option<Vec<u64>>) {
+        if self.saturated > 0 {
+            warn!(
+                "{} wire uses exceeded {:?} credits and were saturated",
+                self.saturated, self.credit_config.width
+            );
+        }
+
+        let mut fanout = self.fanout.take().unwrap();
+        // IDs past the last one written or read have no fanout, but still take an entry so the
+        // length is the circuit's wire count
+        fanout.resize(self.next_normalized_id as usize, 0);
+        debug_assert!(
+            fanout.iter().take(self.always_available()).all(|&f| f == 0),
+            "always available wires got credits"
+        );
+        let summary = self
+            .components
+            .as_ref()
+            .map(|components| components.summarize(&fanout));
+        let histogram = self.histogram.then(|| {
+            let mut histogram = vec![0u64; self.biggest_fanout_seen + 1];
+            for &fanout in fanout.iter().skip(self.always_available()) {
+                histogram[fanout as usize] += 1;
+            }
+            histogram
+        });
+        (fanout, self.biggest_fanout_seen, summary, histogram)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use g16ckt::{
+        CircuitContext, GateType as SourceGateType,
+        circuit::{FALSE_WIRE, StreamingMode, TRUE_WIRE, component_meta::ComponentMetaBuilder},
+        storage::{CreditOverflow, CreditWidth},
+    };
+
+    use super::*;
+
+    fn drive_past_u16(credit_config: CreditConfig) -> (Vec<u32>, usize) {
+        let mut counter = FanoutCounter::new(0, BasisConfig::AndXor, credit_config);
+        let hot = counter.allocate_wire(1);
 
-        fanout[wire_id] += 1;
-        fanout[wire_id]
+        // Each gate reads `hot` twice
+        for _ in 0..=u16::MAX as usize / 2 {
+            let out = counter.allocate_wire(1);
+            counter.evaluate_gate(&SourceGate::and(hot, hot, out));
+        }
+
+        let saturated = counter.saturated();
+        (counter.finish().0, saturated)
+    }
+
+    #[test]
+    fn test_saturating_credits_do_not_panic() {
+        let (fanout, saturated) = drive_past_u16(CreditConfig {
+            width: CreditWidth::U16,
+            overflow: CreditOverflow::Saturate,
+        });
+
+        assert_eq!(fanout[2], u16::MAX as u32);
+        assert_eq!(saturated, 1);
+    }
+
+    #[test]
+    fn test_wide_credits_keep_exact_count() {
+        let (fanout, saturated) = drive_past_u16(CreditConfig {
+            width: CreditWidth::U24,
+            overflow: CreditOverflow::Panic,
+        });
+
+        assert_eq!(fanout[2], u16::MAX as u32 + 1);
+        assert_eq!(saturated, 0);
     }
 
-    pub fn finish(&mut self) -> (Vec<u16>, usize) {
-        let fanout = self.fanout.take().unwrap();
-        (fanout, self.biggest_fanout_seen)
+    #[test]
+    fn test_component_summary_attributes_gates() {
+        const MUL: ComponentKey = [1; 8];
+        const SQUARE: ComponentKey = [2; 8];
+
+        fn mul<C: CircuitContext>(ctx: &mut C, a: WireId, b: WireId) -> WireId {
+            ctx.with_named_child(
+                "test::mul",
+                MUL,
+                vec![a, b],
+                |ctx, inputs| {
+                    let [a, b] = [inputs[0], inputs[1]];
+                    let [t, u, v, w] = [(); 4].map(|_| ctx.issue_wire());
+                    // `t` is read three times
+                    ctx.add_gate(SourceGate::and(a, b, t));
+                    ctx.add_gate(SourceGate::xor(t, a, u));
+                    ctx.add_gate(SourceGate::and(t, u, v));
+                    ctx.add_gate(SourceGate::xor(t, v, w));
+                    w
+                },
+                1,
+            )
+        }
+
+        fn square<C: CircuitContext>(ctx: &mut C, a: WireId) -> WireId {
+            ctx.with_named_child(
+                "test::square",
+                SQUARE,
+                vec![a],
+                |ctx, inputs| {
+                    let s = ctx.issue_wire();
+                    ctx.add_gate(SourceGate::and(inputs[0], inputs[0], s));
+                    s
+                },
+                1,
+            )
+        }
+
+        fn circuit<C: CircuitContext>(ctx: &mut C, &[a, b]: &[WireId; 2]) -> Vec<WireId> {
+            let w = mul(ctx, a, b);
+            let s = square(ctx, w);
+            let x = mul(ctx, s, a);
+            let r = ctx.issue_wire();
+            ctx.add_gate(SourceGate::xor(x, b, r));
+            vec![r]
+        }
+
+        let inputs = [false; 2];
+        let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(&inputs);
+        let mut metadata_mode = StreamingMode::<FanoutCounter>::MetadataPass(root_meta);
+        let meta_output_wires = circuit(&mut metadata_mode, &allocated_inputs);
+        let (mut ctx, allocated_inputs) = metadata_mode.to_root_ctx(
+            FanoutCounter::new(2, BasisConfig::AndXor, CreditConfig::default())
+                .with_component_stats(),
+            &inputs,
+            &meta_output_wires,
+        );
+        circuit(&mut ctx, &allocated_inputs);
+        let (_, _, summary, _) = ctx.get_mut_mode().unwrap().finish();
+
+        let stats = |gates, max_fanout| ComponentStats { gates, max_fanout };
+        assert_eq!(
+            summary.unwrap(),
+            ComponentSummary::from([
+                // Both invocations; the second one's output is read once by the root
+                (Some(MUL), stats(8, 3)),
+                // Its output is the second multiplication's first input, read twice
+                (Some(SQUARE), stats(1, 2)),
+                (None, stats(1, 0)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_native_or_counts_fewer_uses() {
+        let count = |basis| {
+            let mut counter = FanoutCounter::new(0, basis, CreditConfig::default());
+            let [x, y] = [(); 2].map(|_| counter.allocate_wire(1));
+            for gate_type in [
+                SourceGateType::Or,
+                SourceGateType::Nor,
+                SourceGateType::Imp,
+                SourceGateType::Cimp,
+            ] {
+                let out = counter.allocate_wire(1);
+                counter.evaluate_gate(&SourceGate::new(gate_type, x, y, out));
+            }
+            counter.finish().0
+        };
+
+        // OR and NOR read each input twice when expanded; IMP and CIMP read their negated input
+        // once and the other twice
+        let expanded = count(BasisConfig::AndXor);
+        assert_eq!((expanded[2], expanded[3]), (7, 7));
+        // Natively, every gate reads each input once
+        let native = count(BasisConfig::AndXorXnorOr);
+        assert_eq!((native[2], native[3]), (4, 4));
+
+        // Constants, inputs and four outputs, plus 11 temporaries expanded but only 3 native: the
+        // negations, each read once
+        assert_eq!(expanded.len(), 2 + 2 + 4 + 11);
+        assert_eq!(native.len(), 2 + 2 + 4 + 3);
+        assert_eq!(native[4..], [0, 0, 1, 0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn test_fanout_histogram_counts_wires_by_fanout() {
+        let mut counter = FanoutCounter::new(1, BasisConfig::AndXor, CreditConfig::default())
+            .with_fanout_histogram();
+        // Wire 2 is the primary input
+        let [input, a] = [(); 2].map(|_| counter.allocate_wire(1));
+        let [b, c, d, e] = [(); 4].map(|_| counter.allocate_wire(1));
+        counter.evaluate_gate(&SourceGate::and(input, a, b));
+        counter.evaluate_gate(&SourceGate::xor(a, b, c));
+        counter.evaluate_gate(&SourceGate::and(a, c, d));
+        counter.evaluate_gate(&SourceGate::xor(d, d, e));
+
+        let (fanout, _, _, histogram) = counter.finish();
+        assert_eq!(fanout[3..], [3, 1, 1, 2, 0]);
+        // `e` is unread, `b` and `c` read once, `d` twice and `a` three times
+        assert_eq!(histogram, Some(vec![1, 2, 1, 1]));
+    }
+
+    #[test]
+    fn test_constant_wires_get_no_credits() {
+        let mut counter = FanoutCounter::new(1, BasisConfig::AndXor, CreditConfig::default());
+        let [input, a, b, c] = [(); 4].map(|_| counter.allocate_wire(1));
+        counter.evaluate_gate(&SourceGate::and(TRUE_WIRE, FALSE_WIRE, a));
+        counter.evaluate_gate(&SourceGate::xor(input, TRUE_WIRE, b));
+        // Decomposes into an AND followed by a XOR with TRUE
+        counter.evaluate_gate(&SourceGate::nand(a, b, c));
+
+        let (fanout, _, _, _) = counter.finish();
+        assert_eq!(counter.always_available(), 3);
+        assert_eq!(fanout[..3], [0, 0, 0]);
+        assert_eq!(fanout[3..5], [1, 1]);
+    }
+
+    #[test]
+    fn test_component_summary_is_opt_in() {
+        let mut counter = FanoutCounter::new(0, BasisConfig::AndXor, CreditConfig::default());
+        counter.enter_component([1; 8], "test::component");
+        let out = counter.allocate_wire(1);
+        counter.evaluate_gate(&SourceGate::and(out, out, out));
+        counter.exit_component();
+
+        let (_, _, summary, histogram) = counter.finish();
+        assert_eq!(summary, None);
+        assert_eq!(histogram, None);
     }
 }
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use g16ckt::{
+    CircuitContext, Gate as SourceGate, WireId,
+    circuit::{CircuitMode, ComponentKey, FALSE_WIRE, FromWires, TRUE_WIRE, WiresObject},
+};
+
+use crate::modes::fanout_ctr::FanoutCounter;
+
+/// Where a component output comes from, relative to the component invocation.
+#[derive(Clone, Debug)]
+enum ShapeOutput {
+    Fixed(WireId),
+    Input(usize),
+    /// Offset from the first normalized ID allocated inside the component
+    Internal(u64),
+}
+
+/// Everything needed to step over a component without running it.
+#[derive(Clone, Debug)]
+pub struct ComponentShape {
+    wires: u64,
+    gates: u64,
+    outputs: Vec<ShapeOutput>,
+}
+
+/// Shapes by component key; `None` marks components whose outputs can't be replayed.
+pub type ComponentShapes = HashMap<ComponentKey, Option<ComponentShape>>;
+
+/// Circuit context that drives a [`FanoutCounter`] directly, without the metadata pass.
+///
+/// Normalized IDs and gate positions advance exactly as in the streaming credits pass. Once a
+/// component's shape has been seen, later invocations with no gate inside the counter's range are
+/// stepped over instead of rebuilt. Like the template pool, this relies on a [`ComponentKey`]
+/// fully determining the component's structure.
+#[derive(Debug)]
+pub struct FanoutShard {
+    counter: FanoutCounter,
+    shapes: ComponentShapes,
+    /// Validity accumulator, driven like the streaming execution pass drives it
+    validity: WireId,
+}
+
+impl FanoutShard {
+    pub fn new(counter: FanoutCounter, shapes: ComponentShapes) -> Self {
+        Self {
+            counter,
+            shapes,
+            validity: TRUE_WIRE,
+        }
+    }
+
+    pub fn into_parts(self) -> (FanoutCounter, ComponentShapes) {
+        (self.counter, self.shapes)
+    }
+}
+
+impl CircuitContext for FanoutShard {
+    type Mode = FanoutCounter;
+
+    fn issue_wire(&mut self) -> WireId {
+        // Credits are what we're computing, the counter doesn't look at them
+        self.counter.allocate_wire(0)
+    }
+
+    fn issue_wires(&mut self, n: usize) -> Vec<WireId> {
+        self.counter.allocate_wires(&vec![0; n])
+    }
+
+    fn add_gate(&mut self, gate: SourceGate) {
+        self.counter.evaluate_gate(&gate);
+    }
+
+    fn assert_true(&mut self, wire: WireId) {
+        if self.validity == TRUE_WIRE {
+            self.validity = wire;
+            return;
+        }
+
+        let validity = self.counter.allocate_wire(1);
+        self.counter
+            .evaluate_gate(&SourceGate::and(self.validity, wire, validity));
+        self.validity = validity;
+    }
+
+    fn validity_output(&mut self) -> WireId {
+        std::mem::replace(&mut self.validity, TRUE_WIRE)
+    }
+
+    fn with_named_child<I: WiresObject, O: FromWires>(
+        &mut self,
+        name: &str,
+        key: ComponentKey,
+        inputs: I,
+        f: impl Fn(&mut Self, &I) -> O,
+        _arity: usize,
+    ) -> O {
+        let first_wire = self.counter.next_normalized_id();
+        let first_gate = self.counter.gate_index();
+        let validity = self.validity;
+
+        if let Some(Some(shape)) = self.shapes.get(&key)
+            && !self
+                .counter
+                .counts_any(first_gate..first_gate + shape.gates)
+        {
+            let input_wires = inputs.to_wires_vec();
+            let outputs = shape
+                .outputs
+                .iter()
+                .map(|output| match *output {
+                    ShapeOutput::Fixed(wire) => wire,
+                    ShapeOutput::Input(position) => input_wires[position],
+                    ShapeOutput::Internal(offset) => WireId((first_wire + offset) as usize),
+                })
+                .collect::<Vec<_>>();
+
+            // Nested components of a skipped one are attributed to it
+            self.counter.enter_component(key, name);
+            self.counter.skip(shape.wires, shape.gates);
+            self.counter.exit_component();
+            return O::from_wires(&outputs).unwrap();
+        }
+
+        self.counter.enter_component(key, name);
+        let output = f(self, &inputs);
+        self.counter.exit_component();
+
+        if !self.shapes.contains_key(&key) {
+            let wires = self.counter.next_normalized_id() - first_wire;
+            let input_wires = inputs.to_wires_vec();
+
+            // An output captured from outside the component can't be expressed by position
+            let outputs = output
+                .to_wires_vec()
+                .into_iter()
+                .map(|wire| match wire {
+                    TRUE_WIRE | FALSE_WIRE | WireId::UNREACHABLE => Some(ShapeOutput::Fixed(wire)),
+                    wire if (first_wire..first_wire + wires).contains(&(wire.0 as u64)) => {
+                        Some(ShapeOutput::Internal(wire.0 as u64 - first_wire))
+                    }
+                    wire => input_wires
+                        .iter()
+                        .position(|input| *input == wire)
+                        .map(ShapeOutput::Input),
+                })
+                .collect::<Option<Vec<_>>>();
+
+            // Assertions thread the accumulator through the component, which a skip can't replay
+            let outputs = outputs.filter(|_| self.validity == validity);
+            let shape = outputs.map(|outputs| ComponentShape {
+                wires,
+                gates: self.counter.gate_index() - first_gate,
+                outputs,
+            });
+            self.shapes.insert(key, shape);
+        }
+
+        output
+    }
+}
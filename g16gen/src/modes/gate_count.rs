@@ -0,0 +1,157 @@
+use std::num::NonZero;
+
+use ckt_lvl::types::CompactWireId;
+use g16ckt::{
+    Gate as SourceGate, GateCount, WireId, circuit::CircuitMode, storage::Credits as SourceCredits,
+};
+
+use crate::modes::decompose::{BasisConfig, GateSink, Primitive, translate_gate};
+
+/// Gates of a circuit, both as built and as translation would emit them.
+#[derive(Clone, Debug, Default)]
+pub struct GateCounts {
+    pub and: u64,
+    pub xor: u64,
+    pub xnor: u64,
+    pub nand: u64,
+    pub or: u64,
+    /// Source gates by type, before decomposition
+    pub source: GateCount,
+}
+
+impl GateCounts {
+    /// Number of primitive gates emitted
+    pub fn total(&self) -> u64 {
+        self.and + self.xor + self.xnor + self.nand + self.or
+    }
+}
+
+/// Counts the primitive gates translation would emit, without credits or any file I/O.
+#[derive(Debug)]
+pub struct GateCountMode {
+    next_normalized_id: u64,
+    basis: BasisConfig,
+    counts: GateCounts,
+}
+
+impl CircuitMode for GateCountMode {
+    type WireValue = bool;
+    type CiphertextAcc = ();
+
+    fn false_value(&self) -> Self::WireValue {
+        false
+    }
+    fn true_value(&self) -> Self::WireValue {
+        true
+    }
+
+    fn allocate_wire(&mut self, _credits: SourceCredits) -> WireId {
+        WireId(self.allocate_normalized_id() as usize)
+    }
+
+    fn lookup_wire(&mut self, _wire: WireId) -> Option<Self::WireValue> {
+        Some(false) // Always return dummy value
+    }
+
+    fn feed_wire(&mut self, _wire: WireId, _value: Self::WireValue) {}
+
+    fn add_credits(&mut self, _wires: &[WireId], _credits: NonZero<SourceCredits>) {}
+
+    fn evaluate_gate(&mut self, gate: &SourceGate) {
+        self.counts.source.handle(gate.gate_type);
+
+        let basis = self.basis;
+        translate_gate(self, basis, gate);
+    }
+}
+
+impl GateSink for GateCountMode {
+    fn allocate_normalized_id(&mut self) -> u64 {
+        let id = self.next_normalized_id;
+        self.next_normalized_id += 1;
+        id
+    }
+
+    fn true_wire_id(&self) -> CompactWireId {
+        CompactWireId::from_u64(1)
+    }
+
+    fn write_gate(
+        &mut self,
+        gate_type: Primitive,
+        _in1: CompactWireId,
+        _in2: CompactWireId,
+        _out: CompactWireId,
+    ) {
+        match gate_type {
+            Primitive::And => self.counts.and += 1,
+            Primitive::Xor => self.counts.xor += 1,
+            Primitive::Xnor => self.counts.xnor += 1,
+            Primitive::Nand => self.counts.nand += 1,
+            Primitive::Or => self.counts.or += 1,
+        }
+    }
+}
+
+impl GateCountMode {
+    pub fn new(basis: BasisConfig) -> Self {
+        Self {
+            // IDs 0 and 1 are the FALSE and TRUE constants
+            next_normalized_id: 2,
+            basis,
+            counts: GateCounts::default(),
+        }
+    }
+
+    pub fn counts(&self) -> &GateCounts {
+        &self.counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use g16ckt::circuit::{
+        StreamingMode,
+        component_meta::ComponentMetaBuilder,
+        modes::{TeeMode, WellFormednessMode},
+    };
+
+    use super::*;
+    use crate::passes::{
+        gate_estimate::estimate_gates,
+        verify::tests::{BITS, gadget},
+    };
+
+    #[test]
+    fn test_teed_counts_match_standalone_run() {
+        let inputs = [false; 2 * BITS];
+        let standalone = estimate_gates(&inputs, BasisConfig::default(), gadget);
+
+        let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(&inputs);
+        let mut metadata_mode = StreamingMode::MetadataPass(root_meta);
+        let meta_output = gadget(&mut metadata_mode, &allocated_inputs);
+
+        let tee = TeeMode::new(
+            GateCountMode::new(BasisConfig::default()),
+            WellFormednessMode::new(),
+        );
+        let (mut ctx, allocated_inputs) = metadata_mode.to_root_ctx(tee, &inputs, &meta_output);
+        gadget(&mut ctx, &allocated_inputs);
+        let teed = ctx.get_mode().unwrap().first().counts();
+
+        assert_eq!(
+            (teed.and, teed.xor, teed.xnor, teed.nand, teed.or),
+            (
+                standalone.and,
+                standalone.xor,
+                standalone.xnor,
+                standalone.nand,
+                standalone.or
+            )
+        );
+        assert_eq!(
+            teed.source.total_gate_count(),
+            standalone.source.total_gate_count()
+        );
+    }
+}
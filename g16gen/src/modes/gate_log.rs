@@ -0,0 +1,146 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use ckt_fmtv5_types::{GateType, v5::a::GateV5a};
+
+const CHECKPOINT_MAGIC: [u8; 4] = *b"G16T";
+/// in1, in2 and out as u64, credits as u32, then the gate type
+const RECORD_SIZE: u64 = 8 * 3 + 4 + 1;
+
+/// How far a resumable translation got; written whenever the gate log is synced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Gates durably in the log
+    pub gates: u64,
+    /// Translation's next normalized ID right after the last of those gates
+    pub next_normalized_id: u64,
+}
+
+impl Checkpoint {
+    /// Read the checkpoint at `path`, or `None` if there is none
+    pub fn read(path: &Path) -> io::Result<Option<Self>> {
+        let mut bytes = [0u8; 20];
+        match File::open(path) {
+            Ok(mut file) => file.read_exact(&mut bytes)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        }
+
+        if bytes[..4] != CHECKPOINT_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a translation checkpoint",
+            ));
+        }
+        Ok(Some(Self {
+            gates: u64::from_le_bytes(bytes[4..12].try_into().unwrap()),
+            next_normalized_id: u64::from_le_bytes(bytes[12..20].try_into().unwrap()),
+        }))
+    }
+
+    /// Replace the checkpoint at `path`, never leaving a partial one behind
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(&CHECKPOINT_MAGIC)?;
+        file.write_all(&self.gates.to_le_bytes())?;
+        file.write_all(&self.next_normalized_id.to_le_bytes())?;
+        file.sync_all()?;
+        fs::rename(tmp_path, path)
+    }
+}
+
+/// Append-only log of translated gates in fixed-size records.
+///
+/// Unlike the `.ckt` writer, the log can be cut back to any checkpoint and appended to again.
+#[derive(Debug)]
+pub struct GateLog {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    gates: u64,
+}
+
+impl GateLog {
+    /// Open the log at `path`, keeping the gates up to `checkpoint` and dropping anything after
+    /// it. Without a checkpoint the log starts out empty.
+    pub fn open(path: &Path, checkpoint: Option<&Checkpoint>) -> io::Result<Self> {
+        let gates = checkpoint.map_or(0, |checkpoint| checkpoint.gates);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        if file.metadata()?.len() < gates * RECORD_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("gate log is shorter than its checkpoint of {gates} gates"),
+            ));
+        }
+        file.set_len(gates * RECORD_SIZE)?;
+        file.seek(SeekFrom::End(0))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: BufWriter::new(file),
+            gates,
+        })
+    }
+
+    pub fn append(&mut self, gate: &GateV5a) -> io::Result<()> {
+        self.writer.write_all(&gate.in1.to_le_bytes())?;
+        self.writer.write_all(&gate.in2.to_le_bytes())?;
+        self.writer.write_all(&gate.out.to_le_bytes())?;
+        self.writer.write_all(&gate.credits.to_le_bytes())?;
+        self.writer.write_all(&[match gate.gate_type {
+            GateType::AND => 0,
+            GateType::XOR => 1,
+        }])?;
+        self.gates += 1;
+        Ok(())
+    }
+
+    /// Make every appended gate durable
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        self.writer.get_ref().sync_data()
+    }
+
+    /// Gates in the log, including those kept from a checkpoint
+    pub fn gates(&self) -> u64 {
+        self.gates
+    }
+
+    /// Read back every gate in the log, in order
+    pub fn replay(&mut self) -> io::Result<impl Iterator<Item = io::Result<GateV5a>>> {
+        self.writer.flush()?;
+        let mut reader = BufReader::new(File::open(&self.path)?);
+
+        Ok((0..self.gates).map(move |_| {
+            let mut record = [0u8; RECORD_SIZE as usize];
+            reader.read_exact(&mut record)?;
+            let word = |i: usize| u64::from_le_bytes(record[i * 8..i * 8 + 8].try_into().unwrap());
+
+            Ok(GateV5a {
+                in1: word(0),
+                in2: word(1),
+                out: word(2),
+                credits: u32::from_le_bytes(record[24..28].try_into().unwrap()),
+                gate_type: match record[28] {
+                    0 => GateType::AND,
+                    1 => GateType::XOR,
+                    t => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("unknown gate type {t} in gate log"),
+                        ));
+                    }
+                },
+            })
+        }))
+    }
+}
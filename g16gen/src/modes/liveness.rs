@@ -0,0 +1,149 @@
+use std::num::NonZero;
+
+use ckt_lvl::types::CompactWireId;
+use g16ckt::{Gate as SourceGate, WireId, circuit::CircuitMode, storage::Credits as SourceCredits};
+
+use crate::modes::decompose::{BasisConfig, GateSink, Primitive, translate_gate};
+
+/// Tracks how many wires hold credits while translation would emit the circuit, given the
+/// credits the credits pass computed for it.
+///
+/// A wire is live from the gate writing it until its last credit is spent, as `g16check` counts
+/// them. Constants and primary inputs are always available and never counted.
+#[derive(Debug)]
+pub struct LivenessMode {
+    next_normalized_id: u64,
+    basis: BasisConfig,
+    always_available: usize,
+    /// Credits left by normalized ID
+    credits: Vec<u32>,
+    live_wires: usize,
+    max_live_wires: usize,
+}
+
+impl CircuitMode for LivenessMode {
+    type WireValue = bool;
+    type CiphertextAcc = ();
+
+    fn false_value(&self) -> Self::WireValue {
+        false
+    }
+    fn true_value(&self) -> Self::WireValue {
+        true
+    }
+
+    fn allocate_wire(&mut self, _credits: SourceCredits) -> WireId {
+        WireId(self.allocate_normalized_id() as usize)
+    }
+
+    fn lookup_wire(&mut self, _wire: WireId) -> Option<Self::WireValue> {
+        Some(false) // Always return dummy value
+    }
+
+    fn feed_wire(&mut self, _wire: WireId, _value: Self::WireValue) {}
+
+    fn add_credits(&mut self, _wires: &[WireId], _credits: NonZero<SourceCredits>) {}
+
+    fn evaluate_gate(&mut self, gate: &SourceGate) {
+        let basis = self.basis;
+        translate_gate(self, basis, gate);
+    }
+}
+
+impl GateSink for LivenessMode {
+    fn allocate_normalized_id(&mut self) -> u64 {
+        let id = self.next_normalized_id;
+        self.next_normalized_id += 1;
+        id
+    }
+
+    fn true_wire_id(&self) -> CompactWireId {
+        CompactWireId::from_u64(1)
+    }
+
+    fn write_gate(
+        &mut self,
+        _gate_type: Primitive,
+        in1: CompactWireId,
+        in2: CompactWireId,
+        out: CompactWireId,
+    ) {
+        self.spend(in1.to_u64() as usize);
+        self.spend(in2.to_u64() as usize);
+
+        let out = out.to_u64() as usize;
+        let credits = *self.credits.get(out).unwrap_or_else(|| {
+            panic!(
+                "no credits for wire {out}: the credits vector has {} entries",
+                self.credits.len()
+            )
+        });
+        if credits > 0 {
+            self.live_wires += 1;
+            self.max_live_wires = self.max_live_wires.max(self.live_wires);
+        }
+    }
+}
+
+impl LivenessMode {
+    pub fn new(credits: Vec<u32>, primary_inputs: usize, basis: BasisConfig) -> Self {
+        Self {
+            // IDs 0 and 1 are the FALSE and TRUE constants
+            next_normalized_id: 2,
+            basis,
+            always_available: primary_inputs + 2,
+            credits,
+            live_wires: 0,
+            max_live_wires: 0,
+        }
+    }
+
+    /// Spend one credit of `wire`, which dies with its last one
+    fn spend(&mut self, wire: usize) {
+        if wire < self.always_available {
+            return;
+        }
+        // Reads beyond the credits are `g16check`'s to report
+        if let Some(credits) = self.credits.get_mut(wire)
+            && *credits > 0
+        {
+            *credits -= 1;
+            if *credits == 0 {
+                self.live_wires -= 1;
+            }
+        }
+    }
+
+    /// Most wires that held credits at once, the storage evaluating the circuit needs
+    pub fn max_live_wires(&self) -> usize {
+        self.max_live_wires
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use g16ckt::circuit::TRUE_WIRE;
+
+    use super::*;
+
+    #[test]
+    fn test_peak_matches_hand_trace() {
+        // One primary input (wire 2), then gates writing wires 3..=7
+        let credits = vec![0, 0, 0, 2, 1, 1, 1, 0];
+        let mut mode = LivenessMode::new(credits, 1, BasisConfig::AndXor);
+        let [input, a, b, c, d, e] = [(); 6].map(|_| mode.allocate_wire(1));
+
+        // live: {a}
+        mode.evaluate_gate(&SourceGate::and(input, TRUE_WIRE, a));
+        // live: {a, b}
+        mode.evaluate_gate(&SourceGate::xor(input, input, b));
+        // a has one credit left; live: {a, b, c}
+        mode.evaluate_gate(&SourceGate::and(a, input, c));
+        // a and b die, then d is written; live: {c, d}
+        mode.evaluate_gate(&SourceGate::xor(a, b, d));
+        // c and d die, e is an output without credits; live: {}
+        mode.evaluate_gate(&SourceGate::and(c, d, e));
+
+        assert_eq!(mode.max_live_wires(), 3);
+    }
+}
@@ -1,2 +1,10 @@
+pub mod aiger;
+pub mod bristol;
+pub mod component_map;
+pub mod decompose;
 pub mod fanout_ctr;
+pub mod fanout_shard;
+pub mod gate_count;
+pub mod gate_log;
+pub mod liveness;
 pub mod translate;
@@ -1,32 +1,101 @@
-use std::{num::NonZero, path::PathBuf, str::FromStr};
+use std::{
+    fmt, fs, io,
+    num::NonZero,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use ckt_fmtv5_types::{
     GateType,
     v5::{
         self,
-        a::{GateV5a, writer::CircuitWriterV5a},
+        a::{GateV5a, reader::CircuitReaderV5a, writer::CircuitWriterV5a},
     },
 };
 use ckt_lvl::types::CompactWireId;
 use cynosure::site_d::ringbuf::{Producer, RingBuf};
 use g16ckt::{
-    Gate as SourceGate, GateType as SourceGateType, WireId, circuit::CircuitMode,
+    Gate as SourceGate, WireId,
+    circuit::{CircuitMode, ComponentKey},
     storage::Credits as SourceCredits,
 };
 use indicatif::ProgressBar;
-use kanal::{Sender, bounded_async};
+use kanal::{Receiver, Sender, bounded_async};
 use monoio::{FusionDriver, RuntimeBuilder, select};
+use tracing::{info, warn};
+
+use crate::modes::{
+    component_map::ComponentMapWriter,
+    decompose::{BasisConfig, GateSink, Primitive, translate_gate},
+    gate_log::{Checkpoint, GateLog},
+};
+
+/// Width of the wire ID fields of a v5a gate.
+///
+/// `ckt_fmtv5_types` exposes no constant for it: the v5a layout in the `ckt` repository packs
+/// `in1`, `in2` and `out` of every gate as 34-bit fields of its blocks. Pinned against the format
+/// crate by `test_max_wire_id_round_trips`.
+const WIRE_ID_BITS: u32 = 34;
+
+/// Highest wire ID a `.ckt` v5a file can hold; a larger one would be truncated when packed
+const MAX_WIRE_ID: u64 = (1 << WIRE_ID_BITS) - 1;
+
+/// The credits vector doesn't have one entry per wire of the circuit being translated, which
+/// happens when it comes from a cache written for another circuit. Caught before translating any
+/// gate, rather than by a missing entry deep into the pass or by credits that silently belong to
+/// other wires.
+#[derive(Debug, PartialEq, Eq)]
+pub struct StaleCredits {
+    /// Wires of the circuit, as [`count_wires`](crate::passes::credits::count_wires) walks them
+    pub expected: usize,
+    pub got: usize,
+}
+
+impl fmt::Display for StaleCredits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "credits cache stale: expected {} entries, got {}",
+            self.expected, self.got
+        )
+    }
+}
+
+impl std::error::Error for StaleCredits {}
+
+/// The credits pass gives every normalized ID of the circuit an entry, so the credits of a
+/// circuit with `wires` of them have exactly that length
+fn check_credits(creds: &[u32], wires: usize) -> Result<(), StaleCredits> {
+    if creds.len() != wires {
+        return Err(StaleCredits {
+            expected: wires,
+            got: creds.len(),
+        });
+    }
+    Ok(())
+}
 
 pub struct TranslationMode {
-    creds: Vec<u16>,
+    creds: Vec<u32>,
     next_normalized_id: u64,
+    basis: BasisConfig,
+    placement: WirePlacement,
 
     // Constants
     _false_wire_id: CompactWireId, // Normalized ID for FALSE
     true_wire_id: CompactWireId,   // Normalized ID for TRUE (our ONE wire)
     pb: ProgressBar,
-    prod: Producer<GateV5a>,
-    stop: Option<Sender<()>>,
+    /// Gates pushed so far; each gate carries its position so the writer can check none is lost
+    next_sequence: u64,
+    /// Gates are translated and counted, but dropped instead of written
+    dry_run: bool,
+    checkpoints: Option<Checkpoints>,
+    /// Sidecar mapping wires to the components emitting them, if requested
+    components: Option<ComponentMapWriter>,
+    flush: Sender<u64>,
+    flushed: Receiver<()>,
+    prod: Producer<(u64, GateV5a)>,
+    stop: Option<Sender<u64>>,
     writer_handle: Option<std::thread::JoinHandle<()>>,
 }
 
@@ -54,6 +123,21 @@ impl CircuitMode for TranslationMode {
         WireId(normalized_id as usize)
     }
 
+    /// Consecutive normalized IDs, checked against the format's limit once for the whole range
+    fn allocate_wires(&mut self, credits: &[SourceCredits]) -> Vec<WireId> {
+        let start = self.next_normalized_id;
+        let end = start + credits.len() as u64;
+        if end > start {
+            let last = self.placement.place(end - 1);
+            assert!(
+                last <= MAX_WIRE_ID,
+                "wire {last} doesn't fit the {WIRE_ID_BITS}-bit wire IDs of the .ckt v5a format"
+            );
+        }
+        self.next_normalized_id = end;
+        (start..end).map(|id| WireId(id as usize)).collect()
+    }
+
     fn lookup_wire(&mut self, _wire: WireId) -> Option<Self::WireValue> {
         Some(false) // Always return dummy value
     }
@@ -66,47 +150,295 @@ impl CircuitMode for TranslationMode {
 
     fn evaluate_gate(&mut self, gate: &SourceGate) {
         // This is where the magic happens - translate instead of execute!
-        self.translate_gate(gate);
+        let basis = self.basis;
+        translate_gate(self, basis, gate);
+    }
+
+    fn enter_component(&mut self, _key: ComponentKey, name: &str) {
+        let next_wire = self.placement.place(self.next_normalized_id);
+        if let Some(components) = &mut self.components {
+            components.enter(name, next_wire);
+        }
+    }
+
+    fn exit_component(&mut self) {
+        let next_wire = self.placement.place(self.next_normalized_id);
+        if let Some(components) = &mut self.components {
+            components.exit(next_wire).unwrap();
+        }
     }
 }
 
 impl TranslationMode {
+    /// Translates into a new circuit file at `path`, once `creds` is checked to hold one entry
+    /// for each of the circuit's `wires`.
     pub async fn new(
-        creds: Vec<u16>,
+        creds: Vec<u32>,
+        wires: usize,
+        path: &str,
+        primary_inputs: u64,
+        outputs: Vec<WireId>,
+        basis: BasisConfig,
+    ) -> Result<Self, StaleCredits> {
+        Self::spawn(
+            creds,
+            wires,
+            path,
+            primary_inputs,
+            outputs,
+            basis,
+            None,
+            None,
+        )
+    }
+
+    /// Runs translation, credit lookups included, without writing anything: gates are dropped
+    /// once translated, and [`Self::gates`] counts them. Catches a credits vector that doesn't
+    /// match the circuit without producing the `.ckt` file.
+    pub fn dry_run(creds: Vec<u32>, basis: BasisConfig) -> Self {
+        assert_encodable(basis);
+
+        // Nothing is ever pushed or flushed, so the writer side is left unconnected
+        let (prod, _) = RingBuf::new(1).split();
+        let (flush, _) = bounded_async::<u64>(1);
+        let (_, flushed) = bounded_async::<()>(1);
+
+        let mut mode = Self {
+            pb: ProgressBar::new(creds.len() as u64),
+            creds,
+            next_normalized_id: 0,
+            basis,
+            placement: WirePlacement {
+                first_gate_wire: 0,
+                offset: 0,
+            },
+            _false_wire_id: CompactWireId::from_u64(0),
+            true_wire_id: CompactWireId::from_u64(1),
+            next_sequence: 0,
+            dry_run: true,
+            checkpoints: None,
+            components: None,
+            flush: flush.to_sync(),
+            flushed: flushed.to_sync(),
+            prod,
+            stop: None,
+            writer_handle: None,
+        };
+
+        mode.allocate_normalized_id(); // ID 0 = FALSE
+        mode.allocate_normalized_id(); // ID 1 = TRUE (ONE wire)
+
+        mode
+    }
+
+    /// Like [`Self::new`], but the gates go after those of the circuit already at `path`, which
+    /// must have the same primary inputs: both sub-circuits read them.
+    ///
+    /// Gate output IDs continue from the prior circuit's highest one and its outputs come first in
+    /// the merged output list. `creds` and `outputs` are numbered as if this circuit were alone,
+    /// as the credits pass produces them. `path` is only replaced once the combined file is
+    /// complete.
+    pub async fn new_appending(
+        creds: Vec<u32>,
+        wires: usize,
+        path: &str,
+        primary_inputs: u64,
+        outputs: Vec<WireId>,
+        basis: BasisConfig,
+    ) -> Result<Self, StaleCredits> {
+        let prior = PriorCircuit::read(path).await;
+        assert_eq!(
+            prior.primary_inputs, primary_inputs,
+            "can only append a circuit with the same primary inputs"
+        );
+        info!(
+            "Appending after {} gates, from wire {}",
+            prior.gates, prior.next_wire
+        );
+
+        Self::spawn(
+            creds,
+            wires,
+            path,
+            primary_inputs,
+            outputs,
+            basis,
+            None,
+            Some(prior),
+        )
+    }
+
+    /// Like [`Self::new`], but gates first go to a log next to `path` that is checkpointed every
+    /// `checkpoint_interval` gates. If a checkpoint from an interrupted run is found, the gates up
+    /// to it are re-translated without being emitted again. `path` itself is only written from the
+    /// log once every gate is in, so it comes out byte-identical to an uninterrupted run.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn new_resumable(
+        creds: Vec<u32>,
+        wires: usize,
+        path: &str,
+        primary_inputs: u64,
+        outputs: Vec<WireId>,
+        basis: BasisConfig,
+        checkpoint_interval: u64,
+    ) -> Result<Self, StaleCredits> {
+        assert!(checkpoint_interval > 0);
+
+        let log_path = PathBuf::from(format!("{path}.gates"));
+        let checkpoint_path = PathBuf::from(format!("{path}.ckpt"));
+
+        let checkpoint = Checkpoint::read(&checkpoint_path).unwrap_or_else(|e| {
+            warn!("Ignoring translation checkpoint: {}", e);
+            None
+        });
+        let (log, checkpoint) = match GateLog::open(&log_path, checkpoint.as_ref()) {
+            Ok(log) => (log, checkpoint.unwrap_or_default()),
+            Err(e) => {
+                warn!("Restarting translation from scratch: {}", e);
+                (
+                    GateLog::open(&log_path, None).unwrap(),
+                    Checkpoint::default(),
+                )
+            }
+        };
+        if checkpoint.gates > 0 {
+            info!("Resuming translation after {} gates", checkpoint.gates);
+        }
+
+        Self::spawn(
+            creds,
+            wires,
+            path,
+            primary_inputs,
+            outputs,
+            basis,
+            Some((log, checkpoint_path, checkpoint, checkpoint_interval)),
+            None,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn spawn(
+        creds: Vec<u32>,
+        wires: usize,
         path: &str,
         primary_inputs: u64,
         outputs: Vec<WireId>,
-    ) -> Self {
+        basis: BasisConfig,
+        resumable: Option<(GateLog, PathBuf, Checkpoint, u64)>,
+        append_to: Option<PriorCircuit>,
+    ) -> Result<Self, StaleCredits> {
+        assert_encodable(basis);
+        check_credits(&creds, wires)?;
+
+        assert!(
+            resumable.is_none() || append_to.is_none(),
+            "resumable translation can't append"
+        );
+
         let (prod, mut cons) = RingBuf::new(2usize.pow(16)).split();
-        let (stop_tx, stop_rx) = bounded_async::<()>(1);
+        // Carries the number of gates pushed, once all of them are
+        let (stop_tx, stop_rx) = bounded_async::<u64>(1);
+        // Asks the writer to sync the log up to a sequence number, and acknowledges it
+        let (flush_tx, flush_rx) = bounded_async::<u64>(1);
+        let (flushed_tx, flushed_rx) = bounded_async::<()>(1);
 
         let pb = ProgressBar::new(creds.len() as u64);
 
+        let (log, checkpoints) = match resumable {
+            Some((log, path, resume_from, interval)) => (
+                Some(log),
+                Some(Checkpoints {
+                    path,
+                    interval,
+                    resume_from,
+                }),
+            ),
+            None => (None, None),
+        };
+
+        let first_gate_wire = 2 + primary_inputs;
+        let placement = WirePlacement {
+            first_gate_wire,
+            offset: append_to
+                .as_ref()
+                .map_or(0, |prior| prior.next_wire - first_gate_wire),
+        };
+
         let path = PathBuf::from_str(path).unwrap();
+        // Appending writes the combined circuit next to the prior one, which it copies from
+        let write_path = match append_to {
+            Some(_) => PathBuf::from(format!("{}.append", path.display())),
+            None => path.clone(),
+        };
+        let outputs = append_to
+            .map(|prior| prior.outputs)
+            .unwrap_or_default()
+            .into_iter()
+            .chain(outputs.into_iter().map(|w| placement.place(w.0 as u64)))
+            .collect::<Vec<_>>();
         let thread_handle = std::thread::spawn(move || {
             RuntimeBuilder::<FusionDriver>::new()
                 .enable_all()
                 .build()
                 .unwrap()
                 .block_on(async move {
-                    let mut writer = CircuitWriterV5a::new(
-                        path,
-                        primary_inputs,
-                        outputs.into_iter().map(|w| w.0 as u64).collect(),
-                    )
-                    .await
-                    .unwrap();
+                    let mut output = match log {
+                        Some(log) => GateOutput::Log(log),
+                        None => GateOutput::Ckt(
+                            CircuitWriterV5a::new(
+                                write_path.clone(),
+                                primary_inputs,
+                                outputs.clone(),
+                            )
+                            .await
+                            .unwrap(),
+                        ),
+                    };
+                    if write_path != path {
+                        output.copy_gates_from(&path).await;
+                    }
 
-                    loop {
+                    let mut written = output.gates();
+                    let pushed = loop {
                         select! {
                             biased; // EXTREMELY IMPORTANT!!!
                             // we risk losing gates in the buffer if we don't check the buffer before the stop signal
-                            gate = cons.pop() => writer.write_gate(gate).await.unwrap(),
-                            _ = stop_rx.recv() => break,
+                            (sequence, gate) = cons.pop() => {
+                                assert_eq!(
+                                    sequence, written,
+                                    "gate {written} missing from the translation buffer"
+                                );
+                                output.write(gate).await;
+                                written += 1;
+                            }
+                            // Same as stopping: the producer waits for us, so the buffer is drained
+                            sequence = flush_rx.recv() => match sequence {
+                                Ok(sequence) => {
+                                    assert_eq!(written, sequence);
+                                    output.sync();
+                                    flushed_tx.send(()).await.unwrap();
+                                }
+                                Err(_) => break None,
+                            },
+                            pushed = stop_rx.recv() => break pushed.ok(),
                         }
-                    }
+                    };
 
-                    writer.finalize().await.unwrap();
+                    match pushed {
+                        Some(pushed) => {
+                            assert_eq!(
+                                written, pushed,
+                                "{pushed} gates translated but only {written} written"
+                            );
+                            output.finalize(&path, primary_inputs, outputs).await;
+                            if write_path != path {
+                                fs::rename(&write_path, &path).unwrap();
+                            }
+                        }
+                        // Dropped before finishing; keep what the log has for a resumed run
+                        None => output.sync(),
+                    }
                 })
         });
 
@@ -114,8 +446,16 @@ impl TranslationMode {
             creds,
             pb,
             next_normalized_id: 0,
+            basis,
+            placement,
             _false_wire_id: CompactWireId::from_u64(0),
             true_wire_id: CompactWireId::from_u64(1),
+            next_sequence: 0,
+            dry_run: false,
+            checkpoints,
+            components: None,
+            flush: flush_tx.to_sync(),
+            flushed: flushed_rx.to_sync(),
             prod,
             stop: Some(stop_tx.to_sync()),
             writer_handle: Some(thread_handle),
@@ -125,134 +465,802 @@ impl TranslationMode {
         mode.allocate_normalized_id(); // ID 0 = FALSE
         mode.allocate_normalized_id(); // ID 1 = TRUE (ONE wire)
 
-        mode
+        Ok(mode)
+    }
+
+    /// Also write the sidecar mapping every wire to the components that allocated it to `path`,
+    /// see [`ComponentMapWriter`]. Must come before translating the first component.
+    pub fn write_component_map(&mut self, path: &Path) -> io::Result<()> {
+        self.components = Some(ComponentMapWriter::create(path)?);
+        Ok(())
     }
 
     pub fn finish(&mut self) {
-        self.stop.take().unwrap().send(()).unwrap();
-        self.writer_handle.take().unwrap().join().unwrap();
+        if let Some(components) = self.components.take() {
+            components.finish().unwrap();
+        }
+        if !self.dry_run {
+            self.stop.take().unwrap().send(self.next_sequence).unwrap();
+            self.writer_handle.take().unwrap().join().unwrap();
+        }
         self.pb.finish();
     }
 
+    /// Gates translated so far
+    pub fn gates(&self) -> u64 {
+        self.next_sequence
+    }
+
+    /// Wait for the writer to sync every gate pushed so far, then record a checkpoint
+    fn checkpoint(&self) {
+        self.flush.send(self.next_sequence).unwrap();
+        self.flushed.recv().unwrap();
+
+        Checkpoint {
+            gates: self.next_sequence,
+            next_normalized_id: self.next_normalized_id,
+        }
+        .write(&self.checkpoints.as_ref().unwrap().path)
+        .unwrap();
+    }
+}
+
+impl Drop for TranslationMode {
+    fn drop(&mut self) {
+        // Not finished: let the writer see the producer is gone, so it syncs and exits
+        self.stop.take();
+        if let Some(handle) = self.writer_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Where translated wires end up in the written file
+#[derive(Clone, Copy, Debug)]
+struct WirePlacement {
+    /// Constants and primary inputs sit below this ID; every wire from it on is a gate output
+    first_gate_wire: u64,
+    /// Added to gate output IDs, so that appended gates follow those of the prior circuit
+    offset: u64,
+}
+
+impl WirePlacement {
+    fn place(self, wire: u64) -> u64 {
+        if wire < self.first_gate_wire {
+            wire
+        } else {
+            wire + self.offset
+        }
+    }
+}
+
+/// The circuit a translation appends to
+#[derive(Debug)]
+struct PriorCircuit {
+    primary_inputs: u64,
+    gates: u64,
+    outputs: Vec<u64>,
+    /// One past the highest wire ID in the circuit
+    next_wire: u64,
+}
+
+impl PriorCircuit {
+    /// Read the header and outputs of the `.ckt` file at `path`, and scan its gates for the highest
+    /// wire ID, which the header doesn't record
+    async fn read(path: &str) -> Self {
+        let mut reader = CircuitReaderV5a::open(path).unwrap();
+        let primary_inputs = reader.header().primary_inputs;
+        let gates = reader.header().total_gates();
+        let outputs = reader.outputs().to_vec();
+
+        let mut next_wire = 2 + primary_inputs;
+        while let Some(block) = reader.next_block_soa().await.unwrap() {
+            for out in &block.out[..block.gates_in_block] {
+                next_wire = next_wire.max(out + 1);
+            }
+        }
+
+        Self {
+            primary_inputs,
+            gates,
+            outputs,
+            next_wire,
+        }
+    }
+}
+
+/// Checkpointing state of a resumable translation
+struct Checkpoints {
+    path: PathBuf,
+    interval: u64,
+    /// Where the interrupted run stopped; gates before it are already in the log
+    resume_from: Checkpoint,
+}
+
+/// Where the writer thread puts gates
+enum GateOutput {
+    Ckt(CircuitWriterV5a),
+    Log(GateLog),
+}
+
+impl GateOutput {
+    fn gates(&self) -> u64 {
+        match self {
+            GateOutput::Ckt(_) => 0,
+            GateOutput::Log(log) => log.gates(),
+        }
+    }
+
+    async fn write(&mut self, gate: GateV5a) {
+        match self {
+            GateOutput::Ckt(writer) => writer.write_gate(gate).await.unwrap(),
+            GateOutput::Log(log) => log.append(&gate).unwrap(),
+        }
+    }
+
+    /// Write every gate of the `.ckt` file at `path`, ahead of the translated ones
+    async fn copy_gates_from(&mut self, path: &Path) {
+        let mut reader = CircuitReaderV5a::open(path.to_str().unwrap()).unwrap();
+        while let Some(block) = reader.next_block_soa().await.unwrap() {
+            for i in 0..block.gates_in_block {
+                let gate = GateV5a {
+                    in1: block.in1[i],
+                    in2: block.in2[i],
+                    out: block.out[i],
+                    credits: block.credits[i],
+                    gate_type: block.gate_types[i],
+                };
+                match self {
+                    GateOutput::Ckt(writer) => writer.write_gate(gate).await.unwrap(),
+                    GateOutput::Log(_) => unreachable!("resumable translation can't append"),
+                }
+            }
+        }
+    }
+
+    fn sync(&mut self) {
+        if let GateOutput::Log(log) = self {
+            log.sync().unwrap();
+        }
+    }
+
+    /// Complete the `.ckt` file, copying the gates over from the log if there is one
+    async fn finalize(self, path: &Path, primary_inputs: u64, outputs: Vec<u64>) {
+        match self {
+            GateOutput::Ckt(writer) => writer.finalize().await.unwrap(),
+            GateOutput::Log(mut log) => {
+                let mut writer = CircuitWriterV5a::new(path.to_path_buf(), primary_inputs, outputs)
+                    .await
+                    .unwrap();
+                for gate in log.replay().unwrap() {
+                    writer.write_gate(gate.unwrap()).await.unwrap();
+                }
+                writer.finalize().await.unwrap();
+
+                fs::remove_file(format!("{}.gates", path.display())).unwrap();
+                fs::remove_file(format!("{}.ckpt", path.display())).unwrap();
+            }
+        }
+    }
+}
+
+impl GateSink for TranslationMode {
     fn allocate_normalized_id(&mut self) -> u64 {
         let id = self.next_normalized_id;
+        assert!(
+            self.placement.place(id) <= MAX_WIRE_ID,
+            "wire {} doesn't fit the {WIRE_ID_BITS}-bit wire IDs of the .ckt v5a format",
+            self.placement.place(id)
+        );
         self.next_normalized_id += 1;
         id
     }
 
+    fn true_wire_id(&self) -> CompactWireId {
+        self.true_wire_id
+    }
+
     fn write_gate(
         &mut self,
-        gate_type: GateType,
+        gate_type: Primitive,
         in1: CompactWireId,
         in2: CompactWireId,
         out: CompactWireId,
     ) {
         let gate = v5::a::GateV5a {
-            in1: in1.to_u64(),
-            in2: in2.to_u64(),
-            out: out.to_u64(),
-            credits: self.creds[out.to_u64() as usize] as u32,
-            gate_type,
+            in1: self.placement.place(in1.to_u64()),
+            in2: self.placement.place(in2.to_u64()),
+            out: self.placement.place(out.to_u64()),
+            credits: *self.creds.get(out.to_u64() as usize).unwrap_or_else(|| {
+                panic!(
+                    "no credits for wire {}: the credits vector has {} entries",
+                    out.to_u64(),
+                    self.creds.len()
+                )
+            }),
+            gate_type: to_v5a(gate_type).unwrap(),
         };
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.pb.inc(1);
+
+        if self.dry_run {
+            return;
+        }
+
+        if let Some(checkpoints) = &self.checkpoints
+            && sequence < checkpoints.resume_from.gates
+        {
+            // Already in the log from the interrupted run
+            if self.next_sequence == checkpoints.resume_from.gates {
+                assert_eq!(
+                    self.next_normalized_id, checkpoints.resume_from.next_normalized_id,
+                    "resumed translation diverged from its checkpoint"
+                );
+            }
+            return;
+        }
+
         loop {
-            if self.prod.try_push(gate).is_ok() {
+            if self.prod.try_push((sequence, gate)).is_ok() {
                 break;
             }
         }
-        self.pb.inc(1);
+
+        if let Some(checkpoints) = &self.checkpoints
+            && self.next_sequence % checkpoints.interval == 0
+        {
+            self.checkpoint();
+        }
     }
+}
 
-    fn translate_gate(&mut self, gate: &SourceGate) {
-        let in1 = CompactWireId::from_u64(gate.wire_a.0 as u64);
-        let in2 = CompactWireId::from_u64(gate.wire_b.0 as u64);
-        let out = CompactWireId::from_u64(gate.wire_c.0 as u64);
-        let allocate_id =
-            |mode: &mut TranslationMode| CompactWireId::from_u64(mode.allocate_normalized_id());
-        use SourceGateType::*;
-        match gate.gate_type {
-            // Direct mappings
-            And => self.write_gate(GateType::AND, in1, in2, out),
-            Xor => self.write_gate(GateType::XOR, in1, in2, out),
-
-            // Negated versions - XOR result with ONE
-            Nand => {
-                let temp = allocate_id(self);
-                self.write_gate(GateType::AND, in1, in2, temp);
-                self.write_gate(GateType::XOR, temp, self.true_wire_id, out);
-            }
+fn assert_encodable(basis: BasisConfig) {
+    assert!(
+        basis.primitives().iter().all(|p| to_v5a(*p).is_some()),
+        "the .ckt v5a format only encodes AND/XOR gates, got {basis:?}"
+    );
+}
 
-            Xnor => {
-                let temp = allocate_id(self);
-                self.write_gate(GateType::XOR, in1, in2, temp);
-                self.write_gate(GateType::XOR, temp, self.true_wire_id, out);
-            }
+/// Gate type encoding `primitive` in the `.ckt` v5a format, if any.
+fn to_v5a(primitive: Primitive) -> Option<GateType> {
+    match primitive {
+        Primitive::And => Some(GateType::AND),
+        Primitive::Xor => Some(GateType::XOR),
+        Primitive::Xnor | Primitive::Nand | Primitive::Or => None,
+    }
+}
 
-            // NOT: XOR with ONE
-            Not => self.write_gate(GateType::XOR, in1, self.true_wire_id, out),
+#[cfg(test)]
+mod tests {
+    use g16ckt::{
+        CircuitContext,
+        circuit::{StreamingMode, component_meta::ComponentMetaBuilder},
+        component_key,
+        gadgets::{
+            bigint::{self, BigIntWires},
+            bn254::Fq,
+            field::FpImpl,
+        },
+        storage::CreditConfig,
+    };
 
-            // OR = XOR(XOR(AND(a,b), a), b)
-            Or => {
-                let temp1 = allocate_id(self);
-                let temp2 = allocate_id(self);
-                self.write_gate(GateType::AND, in1, in2, temp1);
-                self.write_gate(GateType::XOR, temp1, in1, temp2);
-                self.write_gate(GateType::XOR, temp2, in2, out);
-            }
+    use super::*;
+    use crate::{
+        modes::component_map::sidecar_path,
+        passes::{
+            credits::{CreditsGadget, count_wires, run_credits_pass},
+            verify::{simulate_ckt, tests::BITS},
+        },
+    };
 
-            // NOR = XOR(OR(a,b), ONE)
-            Nor => {
-                let temp1 = allocate_id(self);
-                let temp2 = allocate_id(self);
-                let temp3 = allocate_id(self);
-                // First compute OR
-                self.write_gate(GateType::AND, in1, in2, temp1);
-                self.write_gate(GateType::XOR, temp1, in1, temp2);
-                self.write_gate(GateType::XOR, temp2, in2, temp3);
-                // Then negate with ONE
-                self.write_gate(GateType::XOR, temp3, self.true_wire_id, out);
-            }
+    /// Write `gates` gates of a fixed pattern, as translation of the same circuit would
+    fn emit(mode: &mut TranslationMode, gates: u64) {
+        for i in 0..gates {
+            let out = CompactWireId::from_u64(mode.allocate_normalized_id());
+            let primitive = if i % 3 == 0 {
+                Primitive::And
+            } else {
+                Primitive::Xor
+            };
+            mode.write_gate(
+                primitive,
+                CompactWireId::from_u64(i + 1),
+                mode.true_wire_id(),
+                out,
+            );
+        }
+    }
 
-            // NIMP: a AND NOT b = AND(a, XOR(b, ONE))
-            Nimp => {
-                let temp = allocate_id(self);
-                self.write_gate(GateType::XOR, in2, self.true_wire_id, temp); // NOT b
-                self.write_gate(GateType::AND, in1, temp, out); // a AND (NOT b)
-            }
+    const INTERVAL: u64 = 4096;
 
-            // NCIMP: NOT a AND b = AND(XOR(a, ONE), b)
-            Ncimp => {
-                let temp = allocate_id(self);
-                self.write_gate(GateType::XOR, in1, self.true_wire_id, temp); // NOT a
-                self.write_gate(GateType::AND, temp, in2, out); // (NOT a) AND b
-            }
+    async fn resumable(path: &str, gates: u64) -> TranslationMode {
+        TranslationMode::new_resumable(
+            vec![1; gates as usize + 2],
+            gates as usize + 2,
+            path,
+            0,
+            vec![WireId(gates as usize + 1)],
+            BasisConfig::default(),
+            INTERVAL,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resumed_translation_matches_uninterrupted() {
+        const GATES: u64 = 100_000;
+
+        let dir = std::env::temp_dir();
+        let full_path = dir.join("g16gen_translate_full_test.ckt");
+        let resumed_path = dir.join("g16gen_translate_resumed_test.ckt");
+        let (full_path, resumed_path) =
+            (full_path.to_str().unwrap(), resumed_path.to_str().unwrap());
+        let _ = std::fs::remove_file(format!("{resumed_path}.ckpt"));
+
+        RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let mut full = resumable(full_path, GATES).await;
+                emit(&mut full, GATES);
+                full.finish();
+
+                // Interrupted past a checkpoint, with unsynced gates after it
+                let mut interrupted = resumable(resumed_path, GATES).await;
+                emit(&mut interrupted, 10 * INTERVAL + 123);
+                drop(interrupted);
+                let checkpoint = Checkpoint::read(format!("{resumed_path}.ckpt").as_ref())
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(checkpoint.gates, 10 * INTERVAL);
+
+                let mut resumed = resumable(resumed_path, GATES).await;
+                emit(&mut resumed, GATES);
+                resumed.finish();
+            });
+
+        assert_eq!(
+            std::fs::read(full_path).unwrap(),
+            std::fs::read(resumed_path).unwrap()
+        );
+        assert!(!std::path::Path::new(&format!("{resumed_path}.gates")).exists());
+
+        std::fs::remove_file(full_path).unwrap();
+        std::fs::remove_file(resumed_path).unwrap();
+    }
+
+    #[test]
+    fn test_gates_written_in_emission_order() {
+        const GATES: u64 = 4_000_000;
+
+        let path = std::env::temp_dir().join("g16gen_translate_order_test.ckt");
+        let path = path.to_str().unwrap();
+
+        RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let mut mode = TranslationMode::new(
+                    vec![1; GATES as usize + 2],
+                    GATES as usize + 2,
+                    path,
+                    0,
+                    vec![WireId(GATES as usize + 1)],
+                    BasisConfig::default(),
+                )
+                .await
+                .unwrap();
+
+                // Gate i writes wire i + 2, so the output IDs spell out the order
+                emit(&mut mode, GATES);
+                mode.finish();
+
+                let mut reader = CircuitReaderV5a::open(path).unwrap();
+                assert_eq!(reader.header().total_gates(), GATES);
+
+                let mut expected_out = 2;
+                while let Some(block) = reader.next_block_soa().await.unwrap() {
+                    for out in &block.out[..block.gates_in_block] {
+                        assert_eq!(*out, expected_out);
+                        expected_out += 1;
+                    }
+                }
+                assert_eq!(expected_out, GATES + 2);
+            });
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    type Inputs = [bool; 2 * BITS];
+
+    fn operands(inputs: &[WireId; 2 * BITS]) -> (BigIntWires, BigIntWires) {
+        (
+            BigIntWires::from_bits(inputs[..BITS].iter().copied()),
+            BigIntWires::from_bits(inputs[BITS..].iter().copied()),
+        )
+    }
+
+    struct Equal;
+
+    impl CreditsGadget<Inputs> for Equal {
+        fn build<C: CircuitContext>(
+            &self,
+            ctx: &mut C,
+            inputs: &[WireId; 2 * BITS],
+        ) -> Vec<WireId> {
+            let (a, b) = operands(inputs);
+            vec![bigint::equal(ctx, &a, &b)]
+        }
+    }
 
-            // IMP: a => b = NOT a OR b
-            Imp => {
-                let temp1 = allocate_id(self);
-                let temp2 = allocate_id(self);
-                let temp3 = allocate_id(self);
-
-                // NOT a
-                self.write_gate(GateType::XOR, in1, self.true_wire_id, temp1);
-                // OR(NOT a, b) = XOR(XOR(AND(NOT a, b), NOT a), b)
-                self.write_gate(GateType::AND, temp1, in2, temp2);
-                self.write_gate(GateType::XOR, temp2, temp1, temp3);
-                self.write_gate(GateType::XOR, temp3, in2, out);
+    struct GreaterThan;
+
+    impl CreditsGadget<Inputs> for GreaterThan {
+        fn build<C: CircuitContext>(
+            &self,
+            ctx: &mut C,
+            inputs: &[WireId; 2 * BITS],
+        ) -> Vec<WireId> {
+            let (a, b) = operands(inputs);
+            vec![bigint::greater_than(ctx, &a, &b)]
+        }
+    }
+
+    /// Translate `gadget` to `path`, after the circuit already there if `append`
+    async fn translate(path: &str, gadget: &impl CreditsGadget<Inputs>, append: bool) {
+        let inputs = [false; 2 * BITS];
+        let (credits, outputs) = run_credits_pass(
+            &inputs,
+            inputs.len(),
+            BasisConfig::default(),
+            CreditConfig::default(),
+            gadget,
+        );
+
+        let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(&inputs);
+        let mut metadata_mode = StreamingMode::<TranslationMode>::MetadataPass(root_meta);
+        let meta_output = gadget.build(&mut metadata_mode, &allocated_inputs);
+        let (primary_inputs, basis) = (inputs.len() as u64, BasisConfig::default());
+        let wires = count_wires(&inputs, inputs.len(), basis, gadget);
+        let mode = if append {
+            let outputs = outputs.clone();
+            TranslationMode::new_appending(credits, wires, path, primary_inputs, outputs, basis)
+                .await
+        } else {
+            TranslationMode::new(credits, wires, path, primary_inputs, outputs.clone(), basis).await
+        }
+        .unwrap();
+        let (mut ctx, allocated_inputs) = metadata_mode.to_root_ctx(mode, &inputs, &meta_output);
+        assert_eq!(gadget.build(&mut ctx, &allocated_inputs), outputs);
+        ctx.get_mut_mode().unwrap().finish();
+    }
+
+    /// Translate `gadget` without writing it, with the last `missing` credits dropped
+    fn dry_run(gadget: &impl CreditsGadget<Inputs>, missing: usize) -> u64 {
+        let inputs = [false; 2 * BITS];
+        let (mut credits, _) = run_credits_pass(
+            &inputs,
+            inputs.len(),
+            BasisConfig::default(),
+            CreditConfig::default(),
+            gadget,
+        );
+        credits.truncate(credits.len() - missing);
+
+        let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(&inputs);
+        let mut metadata_mode = StreamingMode::<TranslationMode>::MetadataPass(root_meta);
+        let meta_output = gadget.build(&mut metadata_mode, &allocated_inputs);
+        let mode = TranslationMode::dry_run(credits, BasisConfig::default());
+        let (mut ctx, allocated_inputs) = metadata_mode.to_root_ctx(mode, &inputs, &meta_output);
+        gadget.build(&mut ctx, &allocated_inputs);
+
+        let mode = ctx.get_mut_mode().unwrap();
+        mode.finish();
+        mode.gates()
+    }
+
+    #[test]
+    fn test_dry_run_counts_translated_gates() {
+        let path = std::env::temp_dir().join("g16gen_translate_dry_run_test.ckt");
+        let path = path.to_str().unwrap();
+
+        let written = RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                translate(path, &GreaterThan, false).await;
+                CircuitReaderV5a::open(path).unwrap().header().total_gates()
+            });
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(dry_run(&GreaterThan, 0), written);
+    }
+
+    #[test]
+    #[should_panic(expected = "no credits for wire")]
+    fn test_dry_run_short_credits_panics() {
+        dry_run(&GreaterThan, 1);
+    }
+
+    /// Issues a wire, a batch of 254 and one more, each written by a gate reading the inputs
+    struct Batch;
+
+    impl CreditsGadget<Inputs> for Batch {
+        fn build<C: CircuitContext>(
+            &self,
+            ctx: &mut C,
+            inputs: &[WireId; 2 * BITS],
+        ) -> Vec<WireId> {
+            let mut wires = vec![ctx.issue_wire()];
+            wires.extend(ctx.issue_wires(254));
+            wires.push(ctx.issue_wire());
+            for &wire in &wires {
+                ctx.add_gate(SourceGate::and(inputs[0], inputs[1], wire));
             }
+            wires
+        }
+    }
+
+    #[test]
+    fn test_issue_wires_is_contiguous_in_credits_and_translation() {
+        let inputs = [false; 2 * BITS];
+        // The credits pass runs the gadget under the fanout counter
+        let (credits, outputs) = run_credits_pass(
+            &inputs,
+            inputs.len(),
+            BasisConfig::default(),
+            CreditConfig::default(),
+            &Batch,
+        );
+        let first = outputs[0].0;
+        assert_eq!(
+            outputs,
+            (first..first + 256).map(WireId).collect::<Vec<_>>()
+        );
 
-            // CIMP: b => a (swap inputs for IMP)
-            Cimp => {
-                let temp1 = allocate_id(self);
-                let temp2 = allocate_id(self);
-                let temp3 = allocate_id(self);
-
-                // NOT b
-                self.write_gate(GateType::XOR, in2, self.true_wire_id, temp1);
-                // OR(NOT b, a)
-                self.write_gate(GateType::AND, temp1, in1, temp2);
-                self.write_gate(GateType::XOR, temp2, temp1, temp3);
-                self.write_gate(GateType::XOR, temp3, in1, out);
+        let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(&inputs);
+        let mut metadata_mode = StreamingMode::<TranslationMode>::MetadataPass(root_meta);
+        let meta_output = Batch.build(&mut metadata_mode, &allocated_inputs);
+        let mode = TranslationMode::dry_run(credits, BasisConfig::default());
+        let (mut ctx, allocated_inputs) = metadata_mode.to_root_ctx(mode, &inputs, &meta_output);
+        assert_eq!(Batch.build(&mut ctx, &allocated_inputs), outputs);
+
+        let mode = ctx.get_mut_mode().unwrap();
+        mode.finish();
+        assert_eq!(mode.gates(), 256);
+    }
+
+    #[test]
+    fn test_short_credits_are_rejected_upfront() {
+        let path = std::env::temp_dir().join("g16gen_translate_stale_test.ckt");
+        let _ = std::fs::remove_file(&path);
+
+        let error = RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(TranslationMode::new(
+                vec![1; 8],
+                21,
+                path.to_str().unwrap(),
+                4,
+                vec![WireId(6), WireId(20)],
+                BasisConfig::default(),
+            ))
+            .unwrap_err();
+
+        assert_eq!(
+            error,
+            StaleCredits {
+                expected: 21,
+                got: 8
             }
+        );
+        assert_eq!(
+            error.to_string(),
+            "credits cache stale: expected 21 entries, got 8"
+        );
+        assert!(!path.exists());
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit the 34-bit wire IDs")]
+    fn test_wire_id_overflow_panics() {
+        let mut mode = TranslationMode::dry_run(Vec::new(), BasisConfig::default());
+        mode.next_normalized_id = MAX_WIRE_ID;
+
+        assert_eq!(mode.allocate_normalized_id(), MAX_WIRE_ID);
+        mode.allocate_normalized_id();
+    }
+
+    #[test]
+    fn test_max_wire_id_round_trips() {
+        let path = std::env::temp_dir().join("g16gen_max_wire_id.ckt");
+        let gate = GateV5a {
+            in1: 2,
+            in2: MAX_WIRE_ID - 1,
+            out: MAX_WIRE_ID,
+            credits: 0,
+            gate_type: GateType::AND,
+        };
+
+        RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let mut writer = CircuitWriterV5a::new(path.clone(), 1, vec![MAX_WIRE_ID])
+                    .await
+                    .unwrap();
+                writer.write_gate(gate).await.unwrap();
+                writer.finalize().await.unwrap();
+
+                let mut reader = CircuitReaderV5a::open(path.to_str().unwrap()).unwrap();
+                assert_eq!(reader.outputs(), [MAX_WIRE_ID]);
+                let block = reader.next_block_soa().await.unwrap().unwrap();
+                assert_eq!(block.gates_in_block, 1);
+                assert_eq!(
+                    (block.in1[0], block.in2[0], block.out[0]),
+                    (gate.in1, gate.in2, gate.out)
+                );
+            });
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    const FQ_MUL: &str = "test::fq_mul";
+
+    /// An Fq multiplication wrapped in a component of its own, then a gate outside of it
+    struct FqMul;
+
+    impl CreditsGadget<[bool; 2 * Fq::N_BITS]> for FqMul {
+        fn build<C: CircuitContext>(
+            &self,
+            ctx: &mut C,
+            inputs: &[WireId; 2 * Fq::N_BITS],
+        ) -> Vec<WireId> {
+            let a = BigIntWires::from_bits(inputs[..Fq::N_BITS].iter().copied());
+            let b = BigIntWires::from_bits(inputs[Fq::N_BITS..].iter().copied());
+            let key = component_key!(FQ_MUL, Fq::N_BITS, 2 * Fq::N_BITS);
+            let product = ctx.with_named_child(
+                FQ_MUL,
+                key,
+                (a, b),
+                |ctx, (a, b)| <Fq as FpImpl>::mul_montgomery(ctx, a, b),
+                Fq::N_BITS,
+            );
+
+            let out = ctx.issue_wire();
+            ctx.add_gate(SourceGate::xor(product.bits[0], inputs[0], out));
+            vec![out]
         }
     }
+
+    #[test]
+    fn test_component_map_covers_fq_multiply() {
+        let path = std::env::temp_dir().join("g16gen_translate_components_test.ckt");
+        let path = path.to_str().unwrap();
+        let components_path = sidecar_path(path);
+
+        let inputs = [false; 2 * Fq::N_BITS];
+        let (credits, outputs) = run_credits_pass(
+            &inputs,
+            inputs.len(),
+            BasisConfig::default(),
+            CreditConfig::default(),
+            &FqMul,
+        );
+
+        let gate_wires = RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(&inputs);
+                let mut metadata_mode = StreamingMode::<TranslationMode>::MetadataPass(root_meta);
+                let meta_output = FqMul.build(&mut metadata_mode, &allocated_inputs);
+                let mode = TranslationMode::new(
+                    credits,
+                    count_wires(&inputs, inputs.len(), BasisConfig::default(), &FqMul),
+                    path,
+                    inputs.len() as u64,
+                    outputs,
+                    BasisConfig::default(),
+                )
+                .await
+                .unwrap();
+                let (mut ctx, allocated_inputs) =
+                    metadata_mode.to_root_ctx(mode, &inputs, &meta_output);
+                let mode = ctx.get_mut_mode().unwrap();
+                mode.write_component_map(&components_path).unwrap();
+                FqMul.build(&mut ctx, &allocated_inputs);
+                ctx.get_mut_mode().unwrap().finish();
+
+                let mut reader = CircuitReaderV5a::open(path).unwrap();
+                let mut gate_wires = Vec::new();
+                while let Some(block) = reader.next_block_soa().await.unwrap() {
+                    gate_wires.extend_from_slice(&block.out[..block.gates_in_block]);
+                }
+                gate_wires
+            });
+
+        let map = fs::read_to_string(&components_path).unwrap();
+        fs::remove_file(path).unwrap();
+        fs::remove_file(&components_path).unwrap();
+
+        let calls = map
+            .lines()
+            .map(|line| {
+                let mut fields = line.splitn(3, ' ');
+                let [first, end] = [(); 2].map(|_| fields.next().unwrap().parse::<u64>().unwrap());
+                (first, end, fields.next().unwrap().to_string())
+            })
+            .collect::<Vec<_>>();
+
+        // The multiplication encloses everything else, so its line comes last
+        let (first, end, name) = calls.last().unwrap();
+        assert_eq!(name, FQ_MUL);
+        assert_eq!(calls.iter().filter(|(.., name)| name == FQ_MUL).count(), 1);
+
+        // It covers the gates of the multiplication, and not the XOR after it
+        let (last_gate, mul_gates) = gate_wires.split_last().unwrap();
+        assert_eq!(*first, 2 + inputs.len() as u64);
+        assert_eq!(*end, mul_gates.iter().max().unwrap() + 1);
+        assert!(mul_gates.iter().all(|wire| (first..end).contains(&wire)));
+        assert!(last_gate >= end);
+
+        for nested in ["mul_karatsuba", "montgomery_reduce"] {
+            assert!(
+                calls.iter().any(|(.., name)| name.ends_with(nested)),
+                "no {nested} call in {map}"
+            );
+        }
+        assert!(
+            calls
+                .iter()
+                .all(|(f, e, _)| first <= f && e <= end && f < e)
+        );
+    }
+
+    fn bits(value: u8) -> [bool; BITS] {
+        std::array::from_fn(|i| (value >> i) & 1 == 1)
+    }
+
+    #[test]
+    fn test_appended_gadgets_evaluate_together() {
+        let path = std::env::temp_dir().join("g16gen_translate_append_test.ckt");
+        let path = path.to_str().unwrap();
+
+        RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                translate(path, &Equal, false).await;
+                let equal_gates = CircuitReaderV5a::open(path).unwrap().header().total_gates();
+                translate(path, &GreaterThan, true).await;
+
+                let reader = CircuitReaderV5a::open(path).unwrap();
+                assert_eq!(reader.header().primary_inputs, 2 * BITS as u64);
+                assert!(reader.header().total_gates() > equal_gates);
+                assert_eq!(reader.outputs().len(), 2);
+                assert!(!std::path::Path::new(&format!("{path}.append")).exists());
+
+                for (a, b) in [(0x5a, 0x5a), (0x5a, 0x17), (0x17, 0x5a), (0, 0xff)] {
+                    let input_bits = [bits(a), bits(b)].concat();
+                    assert_eq!(
+                        simulate_ckt(path, &input_bits).await,
+                        [a == b, a > b],
+                        "{a} vs {b}"
+                    );
+                }
+            });
+
+        std::fs::remove_file(path).unwrap();
+    }
 }
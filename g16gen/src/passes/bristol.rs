@@ -0,0 +1,40 @@
+use std::time::Instant;
+
+use g16ckt::{
+    circuit::{StreamingMode, component_meta::ComponentMetaBuilder},
+    gadgets::groth16::Groth16VerifyCompressedInput,
+    groth16_verify_compressed,
+};
+use tracing::info;
+
+use crate::modes::bristol::BristolTranslationMode;
+
+const OUTPUT_FILE: &str = "g16.bristol";
+
+/// Run the translation pass writing a Bristol Fashion circuit file
+pub fn run_bristol_pass(inputs: &Groth16VerifyCompressedInput, primary_input_count: usize) {
+    let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(inputs);
+    let mut metadata_mode = StreamingMode::<BristolTranslationMode>::MetadataPass(root_meta);
+
+    let metadata_start = Instant::now();
+    // Run circuit construction in metadata mode
+    let meta_output = groth16_verify_compressed(&mut metadata_mode, &allocated_inputs).ok;
+    let metadata_time = metadata_start.elapsed();
+    println!("Bristol metadata time: {:?}", metadata_time);
+
+    let (mut ctx, allocated_inputs) = metadata_mode.to_root_ctx(
+        BristolTranslationMode::new(OUTPUT_FILE, primary_input_count as u64).unwrap(),
+        inputs,
+        &meta_output,
+    );
+
+    let translation_start = Instant::now();
+    let ok = groth16_verify_compressed(&mut ctx, &allocated_inputs).ok;
+
+    ctx.get_mut_mode().unwrap().finish(&[ok]).unwrap();
+    info!(
+        "Completed Bristol translation pass ({} inputs) in {:?}",
+        allocated_inputs.public.len(),
+        translation_start.elapsed()
+    );
+}
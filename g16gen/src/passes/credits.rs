@@ -1,49 +1,94 @@
-use std::time::Instant;
+use std::{thread, time::Instant};
 
 use g16ckt::{
-    WireId,
-    circuit::{StreamingMode, component_meta::ComponentMetaBuilder},
-    gadgets::groth16::Groth16VerifyCompressedInput,
+    CircuitContext, WireId,
+    circuit::{
+        CircuitInput, EncodeInput, StreamingMode, WiresObject, component_meta::ComponentMetaBuilder,
+    },
+    gadgets::groth16::{Groth16VerifyCompressedInput, Groth16VerifyCompressedInputWires},
     groth16_verify_compressed,
+    storage::CreditConfig,
 };
 use tracing::info;
 
-use crate::modes::fanout_ctr::FanoutCounter;
+use crate::modes::{
+    decompose::BasisConfig,
+    fanout_ctr::FanoutCounter,
+    fanout_shard::{ComponentShapes, FanoutShard},
+};
+
+/// A circuit the credits pass can build with each of the contexts it drives
+pub trait CreditsGadget<I: CircuitInput> {
+    /// Build the circuit on `inputs`, returning its output wires
+    fn build<C: CircuitContext>(&self, ctx: &mut C, inputs: &I::WireRepr) -> Vec<WireId>;
+}
 
-/// Run the credits pass to compute wire credits
-pub fn run_credits_pass(
-    inputs: &Groth16VerifyCompressedInput,
+/// The compressed-input Groth16 verifier, whose only output is its `ok` wire
+#[derive(Debug, Clone, Copy)]
+pub struct Groth16Verifier;
+
+impl CreditsGadget<Groth16VerifyCompressedInput> for Groth16Verifier {
+    fn build<C: CircuitContext>(
+        &self,
+        ctx: &mut C,
+        inputs: &Groth16VerifyCompressedInputWires,
+    ) -> Vec<WireId> {
+        vec![groth16_verify_compressed(ctx, inputs).ok]
+    }
+}
+
+/// Run the credits pass to compute wire credits for the gates `gadget` emits in `basis`, counted
+/// as configured by `credit_config`.
+///
+/// Output wires get one credit on top of their fanout, see [`credit_outputs`].
+pub fn run_credits_pass<I, G>(
+    inputs: &I,
     primary_input_count: usize,
-) -> (Vec<u16>, Vec<WireId>) {
+    basis: BasisConfig,
+    credit_config: CreditConfig,
+    gadget: &G,
+) -> (Vec<u32>, Vec<WireId>)
+where
+    I: CircuitInput + EncodeInput<FanoutCounter>,
+    G: CreditsGadget<I>,
+{
     let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(inputs);
     let mut metadata_mode = StreamingMode::<FanoutCounter>::MetadataPass(root_meta);
 
     let metadata_start = Instant::now();
     // Run circuit construction in metadata mode
-    let meta_output_wires = {
-        let ok = groth16_verify_compressed(&mut metadata_mode, &allocated_inputs);
-        vec![ok]
-    };
+    let meta_output = gadget.build(&mut metadata_mode, &allocated_inputs);
     let metadata_time = metadata_start.elapsed();
     println!("Credits metadata time: {:?}", metadata_time);
 
+    // The shape walk steps over repeated components, so sizing the fanout vector from it is
+    // cheap next to the counting pass
+    let (counter, _, _) = walk_shapes(
+        inputs,
+        primary_input_count,
+        basis,
+        credit_config,
+        |ctx, wires| gadget.build(ctx, wires),
+    );
+    let wires = counter.next_normalized_id();
+
     // Convert to execution mode
     let (mut ctx, allocated_inputs) = metadata_mode.to_root_ctx(
-        FanoutCounter::new(primary_input_count),
+        FanoutCounter::new(primary_input_count, basis, credit_config).with_capacity(wires),
         inputs,
-        &meta_output_wires.to_vec(),
+        &meta_output,
     );
 
     let credits_start = Instant::now();
     // Run the credits pass
-    let real_output_wires = {
-        let ok = groth16_verify_compressed(&mut ctx, &allocated_inputs);
-        vec![ok]
-    };
+    let real_output_wires = gadget.build(&mut ctx, &allocated_inputs);
     println!("Output wires: {:?}", real_output_wires);
 
-    let (mut fanout, biggest_credits_seen) = ctx.get_mut_mode().unwrap().finish();
+    let mode = ctx.get_mut_mode().unwrap();
+    let saturated = mode.saturated();
+    let (mut fanout, biggest_credits_seen, _, _) = mode.finish();
     println!("Biggest credits seen: {}", biggest_credits_seen);
+    println!("Saturated wire uses: {}", saturated);
     let elapsed_credits = credits_start.elapsed();
     info!(
         "Completed credits pass ({} wires) in {:?}",
@@ -51,10 +96,389 @@ pub fn run_credits_pass(
         elapsed_credits
     );
 
-    // Set credits for output wires to 0
-    for output_wire in &real_output_wires {
-        fanout[output_wire.0] = 0;
-    }
+    credit_outputs(&mut fanout, &real_output_wires, credit_config);
 
     (fanout, real_output_wires)
 }
+
+/// Parallel variant of [`run_credits_pass`] producing the same credits.
+///
+/// The gate stream is split into `threads` contiguous ranges, each counted on its own thread.
+/// Every thread holds its own fanout vector, so peak memory grows with `threads`.
+pub fn run_credits_pass_parallel<I, G>(
+    inputs: &I,
+    primary_input_count: usize,
+    basis: BasisConfig,
+    credit_config: CreditConfig,
+    threads: usize,
+    gadget: &G,
+) -> (Vec<u32>, Vec<WireId>)
+where
+    I: CircuitInput + Sync,
+    G: CreditsGadget<I> + Sync,
+{
+    let credits_start = Instant::now();
+    let (fanout, output_wires) = count_fanout_parallel(
+        inputs,
+        primary_input_count,
+        basis,
+        credit_config,
+        threads,
+        |ctx, wires| gadget.build(ctx, wires),
+    );
+    println!("Output wires: {:?}", output_wires);
+    println!(
+        "Biggest credits seen: {}",
+        fanout.iter().max().copied().unwrap_or(0)
+    );
+    info!(
+        "Completed parallel credits pass ({} wires, {} threads) in {:?}",
+        fanout.len(),
+        threads,
+        credits_start.elapsed()
+    );
+
+    (fanout, output_wires)
+}
+
+/// Give every output wire one credit for the read off the finished circuit, on top of the reads
+/// inside it: a wire without credits is never stored, so an output also read by later gates would
+/// otherwise be gone by the end.
+fn credit_outputs(fanout: &mut [u32], output_wires: &[WireId], credit_config: CreditConfig) {
+    for output_wire in output_wires {
+        credit_config.add(&mut fanout[output_wire.0], 1);
+    }
+}
+
+/// Normalized IDs translation allocates for the circuit `gadget` emits in `basis`, which is also
+/// the length of the credits computed for it
+pub fn count_wires<I, G>(
+    inputs: &I,
+    primary_input_count: usize,
+    basis: BasisConfig,
+    gadget: &G,
+) -> usize
+where
+    I: CircuitInput,
+    G: CreditsGadget<I>,
+{
+    let (counter, _, _) = walk_shapes(
+        inputs,
+        primary_input_count,
+        basis,
+        CreditConfig::default(),
+        |ctx, wires| gadget.build(ctx, wires),
+    );
+    counter.next_normalized_id() as usize
+}
+
+/// Walk `circuit` once without counting, to learn component shapes, the total gate count and
+/// the normalized IDs translation will allocate
+fn walk_shapes<I, F>(
+    inputs: &I,
+    primary_input_count: usize,
+    basis: BasisConfig,
+    credit_config: CreditConfig,
+    circuit: F,
+) -> (FanoutCounter, ComponentShapes, Vec<WireId>)
+where
+    I: CircuitInput,
+    F: Fn(&mut FanoutShard, &I::WireRepr) -> Vec<WireId>,
+{
+    let counter =
+        FanoutCounter::new(primary_input_count, basis, credit_config).with_gate_range(0..0);
+    let mut shard = FanoutShard::new(counter, ComponentShapes::new());
+    let input_wires = inputs.allocate(|| shard.issue_wire());
+    let output_wires = circuit(&mut shard, &input_wires);
+    let (counter, shapes) = shard.into_parts();
+    (counter, shapes, output_wires)
+}
+
+fn count_fanout_parallel<I, F>(
+    inputs: &I,
+    primary_input_count: usize,
+    basis: BasisConfig,
+    credit_config: CreditConfig,
+    threads: usize,
+    circuit: F,
+) -> (Vec<u32>, Vec<WireId>)
+where
+    I: CircuitInput + Sync,
+    F: Fn(&mut FanoutShard, &I::WireRepr) -> Vec<WireId> + Sync,
+{
+    // Walk once without counting to learn component shapes and the circuit's size, which also
+    // sizes every shard's fanout vector up front
+    let (counter, shapes, output_wires) =
+        walk_shapes(inputs, primary_input_count, basis, credit_config, &circuit);
+    let total_gates = counter.gate_index();
+    let wires = counter.next_normalized_id();
+    info!(
+        "Shape walk found {} components over {} gates",
+        shapes.len(),
+        total_gates
+    );
+
+    let walk = |gate_range, shapes| {
+        let counter = FanoutCounter::new(primary_input_count, basis, credit_config)
+            .with_gate_range(gate_range)
+            .with_capacity(wires);
+        let mut shard = FanoutShard::new(counter, shapes);
+        let input_wires = inputs.allocate(|| shard.issue_wire());
+        circuit(&mut shard, &input_wires);
+        shard.into_parts()
+    };
+
+    let threads = threads.max(1) as u64;
+    let partials = thread::scope(|scope| {
+        let handles = (0..threads)
+            .map(|i| {
+                let gate_range = total_gates * i / threads..total_gates * (i + 1) / threads;
+                let shapes = shapes.clone();
+                let walk = &walk;
+                scope.spawn(move || {
+                    let (mut counter, _) = walk(gate_range, shapes);
+                    counter.finish().0
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    let mut fanout = Vec::with_capacity(wires as usize);
+    for partial in partials {
+        if partial.len() > fanout.len() {
+            fanout.resize(partial.len(), 0);
+        }
+        for (total, credits) in fanout.iter_mut().zip(partial) {
+            credit_config.add(total, credits);
+        }
+    }
+
+    credit_outputs(&mut fanout, &output_wires, credit_config);
+
+    (fanout, output_wires)
+}
+
+#[cfg(test)]
+mod tests {
+    use g16ckt::{ark, circuit::CircuitMode, gadgets::bn254::fq2::Fq2};
+    use monoio::{FusionDriver, RuntimeBuilder};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+    use crate::passes::{translation::run_translation_pass, verify::simulate_ckt};
+
+    struct Fq2Pair([ark::Fq2; 2]);
+
+    impl CircuitInput for Fq2Pair {
+        type WireRepr = [Fq2; 2];
+
+        fn allocate(&self, mut issue: impl FnMut() -> WireId) -> Self::WireRepr {
+            [Fq2::new(&mut issue), Fq2::new(&mut issue)]
+        }
+
+        fn collect_wire_ids(repr: &Self::WireRepr) -> Vec<WireId> {
+            repr.iter().flat_map(|fq2| fq2.to_wires_vec()).collect()
+        }
+    }
+
+    impl<M: CircuitMode> EncodeInput<M> for Fq2Pair {
+        fn encode(&self, _repr: &Self::WireRepr, _cache: &mut M) {}
+    }
+
+    impl Fq2Pair {
+        /// Values of the primary inputs, in allocation order and Montgomery form
+        fn input_bits(&self) -> Vec<bool> {
+            let mut next_wire = 2;
+            let wires = self.allocate(|| {
+                next_wire += 1;
+                WireId(next_wire - 1)
+            });
+            wires
+                .iter()
+                .zip(self.0)
+                .flat_map(|(wires, value)| {
+                    let bits = Fq2::get_wire_bits_fn(wires, &Fq2::as_montgomery(value)).unwrap();
+                    wires
+                        .to_wires_vec()
+                        .into_iter()
+                        .map(move |wire| bits(wire).unwrap())
+                })
+                .collect()
+        }
+    }
+
+    fn circuit<C: CircuitContext>(ctx: &mut C, [a, b]: &[Fq2; 2]) -> Vec<WireId> {
+        // Repeated Fq multiplications let shards step over already seen components
+        let ab = Fq2::mul_montgomery(ctx, a, b);
+        let aab = Fq2::mul_montgomery(ctx, a, &ab);
+        aab.to_wires_vec()
+    }
+
+    /// Both products of `circuit`, the first of which is also read by the second
+    struct TwoProducts;
+
+    impl CreditsGadget<Fq2Pair> for TwoProducts {
+        fn build<C: CircuitContext>(&self, ctx: &mut C, [a, b]: &[Fq2; 2]) -> Vec<WireId> {
+            let ab = Fq2::mul_montgomery(ctx, a, b);
+            let aab = Fq2::mul_montgomery(ctx, a, &ab);
+            vec![ab.to_wires_vec()[0], aab.to_wires_vec()[0]]
+        }
+    }
+
+    fn inputs() -> (Fq2Pair, usize) {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let inputs = Fq2Pair([Fq2::random(&mut rng), Fq2::random(&mut rng)]);
+        let primary_input_count = Fq2Pair::input_wire_count(&inputs.allocate(|| WireId(0)));
+        (inputs, primary_input_count)
+    }
+
+    /// Streaming credits pass over `circuit`
+    fn count_fanout_sequential(
+        inputs: &Fq2Pair,
+        primary_input_count: usize,
+    ) -> (Vec<u32>, Vec<WireId>) {
+        let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(inputs);
+        let mut metadata_mode = StreamingMode::<FanoutCounter>::MetadataPass(root_meta);
+        let meta_output_wires = circuit(&mut metadata_mode, &allocated_inputs);
+        let (mut ctx, allocated_inputs) = metadata_mode.to_root_ctx(
+            FanoutCounter::new(
+                primary_input_count,
+                BasisConfig::default(),
+                CreditConfig::default(),
+            ),
+            inputs,
+            &meta_output_wires,
+        );
+        let outputs = circuit(&mut ctx, &allocated_inputs);
+        let (fanout, _, _, _) = ctx.get_mut_mode().unwrap().finish();
+        (fanout, outputs)
+    }
+
+    #[test]
+    fn test_parallel_fanout_matches_sequential() {
+        let (inputs, primary_input_count) = inputs();
+
+        let (mut sequential, sequential_outputs) =
+            count_fanout_sequential(&inputs, primary_input_count);
+        credit_outputs(
+            &mut sequential,
+            &sequential_outputs,
+            CreditConfig::default(),
+        );
+
+        for threads in [1, 3, 8] {
+            let (parallel, parallel_outputs) = count_fanout_parallel(
+                &inputs,
+                primary_input_count,
+                BasisConfig::default(),
+                CreditConfig::default(),
+                threads,
+                circuit,
+            );
+
+            assert_eq!(parallel_outputs, sequential_outputs);
+            assert!(
+                parallel == sequential,
+                "fanout differs with {threads} threads"
+            );
+        }
+    }
+
+    #[test]
+    fn test_reserved_fanout_is_not_reallocated() {
+        let (inputs, primary_input_count) = inputs();
+        let (counter, _, _) = walk_shapes(
+            &inputs,
+            primary_input_count,
+            BasisConfig::default(),
+            CreditConfig::default(),
+            |ctx, wires| TwoProducts.build(ctx, wires),
+        );
+        let wires = counter.next_normalized_id();
+
+        let (fanout, _) = run_credits_pass(
+            &inputs,
+            primary_input_count,
+            BasisConfig::default(),
+            CreditConfig::default(),
+            &TwoProducts,
+        );
+
+        // Growing past the reservation would have at least doubled the capacity
+        assert_eq!(fanout.len() as u64, wires);
+        assert_eq!(fanout.capacity() as u64, wires);
+    }
+
+    #[test]
+    fn test_output_wires_keep_their_fanout() {
+        let (inputs, primary_input_count) = inputs();
+        let run = |threads| {
+            if threads > 1 {
+                run_credits_pass_parallel(
+                    &inputs,
+                    primary_input_count,
+                    BasisConfig::default(),
+                    CreditConfig::default(),
+                    threads,
+                    &TwoProducts,
+                )
+            } else {
+                run_credits_pass(
+                    &inputs,
+                    primary_input_count,
+                    BasisConfig::default(),
+                    CreditConfig::default(),
+                    &TwoProducts,
+                )
+            }
+        };
+
+        let (fanout, outputs) = run(1);
+        let [ab, aab] = outputs[..] else {
+            panic!("expected two outputs, got {outputs:?}");
+        };
+        // `ab` is also read by the second multiplication, `aab` only off the finished circuit
+        assert!(fanout[ab.0] > 1, "output {ab:?} lost its fanout");
+        assert_eq!(fanout[aab.0], 1);
+        assert_eq!(run(3), (fanout.clone(), outputs.clone()));
+
+        // Both outputs still evaluate correctly through the written circuit
+        let path = std::env::temp_dir().join("g16gen_two_products.ckt");
+        let path = path.to_str().unwrap();
+        let [a, b] = inputs.0;
+        let low_bit = |value: ark::Fq2| Fq2::to_bits(Fq2::as_montgomery(value)).0[0];
+
+        RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                run_translation_pass(
+                    &inputs,
+                    primary_input_count,
+                    fanout,
+                    outputs,
+                    BasisConfig::default(),
+                    path,
+                    false,
+                    false,
+                    &TwoProducts,
+                )
+                .await
+                .unwrap();
+
+                assert_eq!(
+                    simulate_ckt(path, &inputs.input_bits()).await,
+                    [low_bit(a * b), low_bit(a * a * b)]
+                );
+            });
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
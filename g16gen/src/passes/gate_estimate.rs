@@ -0,0 +1,263 @@
+use std::time::Instant;
+
+use g16ckt::{
+    ark,
+    circuit::{
+        CircuitInput, EncodeInput, StreamingMode, WiresObject, component_meta::ComponentMetaBuilder,
+    },
+    gadgets::groth16::{Groth16VerifyCompressedInput, Groth16VerifyInput, VkMismatch},
+    groth16_verify_compressed,
+};
+use tracing::info;
+
+use crate::modes::{
+    decompose::BasisConfig,
+    gate_count::{GateCountMode, GateCounts},
+    liveness::LivenessMode,
+};
+
+/// Count the gates `circuit` emits in `basis`, running only the metadata and counting passes
+pub fn estimate_gates<I, O>(
+    inputs: &I,
+    basis: BasisConfig,
+    circuit: impl Fn(&mut StreamingMode<GateCountMode>, &I::WireRepr) -> O,
+) -> GateCounts
+where
+    I: CircuitInput + EncodeInput<GateCountMode>,
+    O: WiresObject,
+{
+    let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(inputs);
+    let mut metadata_mode = StreamingMode::<GateCountMode>::MetadataPass(root_meta);
+    let meta_output = circuit(&mut metadata_mode, &allocated_inputs);
+
+    let (mut ctx, allocated_inputs) =
+        metadata_mode.to_root_ctx(GateCountMode::new(basis), inputs, &meta_output);
+    circuit(&mut ctx, &allocated_inputs);
+
+    ctx.get_mode().unwrap().counts().clone()
+}
+
+/// Most wires holding credits at once while `circuit` is evaluated in `basis`, given the
+/// `credits` the credits pass computed for it
+pub fn peak_live_wires<I, O>(
+    inputs: &I,
+    credits: Vec<u32>,
+    primary_input_count: usize,
+    basis: BasisConfig,
+    circuit: impl Fn(&mut StreamingMode<LivenessMode>, &I::WireRepr) -> O,
+) -> usize
+where
+    I: CircuitInput + EncodeInput<LivenessMode>,
+    O: WiresObject,
+{
+    let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(inputs);
+    let mut metadata_mode = StreamingMode::<LivenessMode>::MetadataPass(root_meta);
+    let meta_output = circuit(&mut metadata_mode, &allocated_inputs);
+
+    let mode = LivenessMode::new(credits, primary_input_count, basis);
+    let (mut ctx, allocated_inputs) = metadata_mode.to_root_ctx(mode, inputs, &meta_output);
+    circuit(&mut ctx, &allocated_inputs);
+
+    ctx.get_mode().unwrap().max_live_wires()
+}
+
+/// Any proof for `vk`; the circuit only depends on the verifying key and the number of public
+/// inputs, so every well-formed proof builds the same gates. Fails on a `vk` without even the
+/// constant `gamma_abc_g1` point.
+fn placeholder_inputs(
+    vk: &ark::VerifyingKey<ark::Bn254>,
+) -> Result<Groth16VerifyCompressedInput, VkMismatch> {
+    use ark::PrimeGroup;
+
+    Groth16VerifyInput {
+        public: vec![ark::Fr::from(0u8); vk.gamma_abc_g1.len().saturating_sub(1)],
+        a: ark::G1Projective::generator(),
+        b: ark::G2Projective::generator(),
+        c: ark::G1Projective::generator(),
+        vk: vk.clone(),
+    }
+    .compress()
+}
+
+/// Count the gates of the `.ckt` verifier circuit for `vk` without generating it
+pub fn groth16_verify_gate_estimate(
+    vk: &ark::VerifyingKey<ark::Bn254>,
+) -> Result<GateCounts, VkMismatch> {
+    let inputs = placeholder_inputs(vk)?;
+
+    let start = Instant::now();
+    let counts = estimate_gates(&inputs, BasisConfig::default(), |ctx, inputs| {
+        groth16_verify_compressed(ctx, inputs).ok
+    });
+    info!("Counted {} gates in {:?}", counts.total(), start.elapsed());
+
+    Ok(counts)
+}
+
+/// Peak live wires of the `.ckt` verifier circuit for `vk`, from its cached `credits`
+pub fn groth16_verify_peak_live_wires(
+    vk: &ark::VerifyingKey<ark::Bn254>,
+    credits: Vec<u32>,
+    primary_input_count: usize,
+) -> Result<usize, VkMismatch> {
+    let inputs = placeholder_inputs(vk)?;
+
+    let start = Instant::now();
+    let peak = peak_live_wires(
+        &inputs,
+        credits,
+        primary_input_count,
+        BasisConfig::default(),
+        |ctx, inputs| groth16_verify_compressed(ctx, inputs).ok,
+    );
+    info!("Traced wire liveness in {:?}", start.elapsed());
+
+    Ok(peak)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ckt_fmtv5_types::{GateType, v5::a::reader::CircuitReaderV5a};
+    use g16ckt::{WireId, storage::CreditConfig};
+    use monoio::{FusionDriver, RuntimeBuilder};
+
+    use super::*;
+    use crate::{
+        modes::translate::TranslationMode,
+        passes::{
+            credits::{CreditsGadget, Groth16Verifier, run_credits_pass},
+            verify::tests::{BITS, gadget, generate},
+        },
+        proof_setup::generate_test_proof,
+    };
+
+    #[test]
+    fn test_estimate_matches_translated_circuit() {
+        let path = std::env::temp_dir().join("g16gen_gate_estimate_test.ckt");
+        let path = path.to_str().unwrap();
+
+        let estimate = estimate_gates(&[false; 2 * BITS], BasisConfig::default(), gadget);
+
+        let (and, xor, total) = RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                generate(path).await;
+
+                let mut reader = CircuitReaderV5a::open(path).unwrap();
+                let total = reader.header().total_gates();
+                let (mut and, mut xor) = (0, 0);
+                while let Some(block) = reader.next_block_soa().await.unwrap() {
+                    for gate_type in &block.gate_types[..block.gates_in_block] {
+                        match gate_type {
+                            GateType::AND => and += 1,
+                            GateType::XOR => xor += 1,
+                        }
+                    }
+                }
+                (and, xor, total)
+            });
+
+        assert_eq!((estimate.and, estimate.xor), (and, xor));
+        assert_eq!(estimate.total(), total);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_peak_live_wires_matches_translated_circuit() {
+        let path = std::env::temp_dir().join("g16gen_peak_live_wires_test.ckt");
+        let path = path.to_str().unwrap();
+        let always_available = 2 * BITS as u64 + 2;
+
+        // Replay the written circuit as g16check does, collecting the credits it was given
+        let (credits, peak) = RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                generate(path).await;
+
+                let mut reader = CircuitReaderV5a::open(path).unwrap();
+                let mut credits = vec![0; always_available as usize];
+                let mut live = HashMap::new();
+                let mut peak = 0;
+                while let Some(block) = reader.next_block_soa().await.unwrap() {
+                    for i in 0..block.gates_in_block {
+                        for wire in [block.in1[i], block.in2[i]] {
+                            if wire >= always_available {
+                                let left = live.get_mut(&wire).unwrap();
+                                *left -= 1;
+                                if *left == 0 {
+                                    live.remove(&wire);
+                                }
+                            }
+                        }
+                        let out = block.out[i] as usize;
+                        if out >= credits.len() {
+                            credits.resize(out + 1, 0);
+                        }
+                        credits[out] = block.credits[i];
+                        if block.credits[i] > 0 {
+                            live.insert(block.out[i], block.credits[i]);
+                        }
+                        peak = peak.max(live.len());
+                    }
+                }
+                (credits, peak)
+            });
+
+        let traced = peak_live_wires(
+            &[false; 2 * BITS],
+            credits,
+            2 * BITS,
+            BasisConfig::default(),
+            gadget,
+        );
+        assert!(peak > 0);
+        assert_eq!(traced, peak);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_placeholder_inputs_reject_empty_gamma_abc() {
+        let mut vk = generate_test_proof(1 << 4, 1).0.vk;
+        vk.gamma_abc_g1.clear();
+        assert!(groth16_verify_gate_estimate(&vk).is_err());
+    }
+
+    /// The placeholder proof must build the gates of the real one, so the estimate matches a dry
+    /// translation of the verifier for an actual proof. Both passes run the whole verifier, so
+    /// run with `cargo test --release -p g16gen -- --ignored`.
+    #[test]
+    #[ignore]
+    fn test_groth16_estimate_matches_translated_circuit() {
+        let inputs = generate_test_proof(1 << 4, 1);
+        let estimate = groth16_verify_gate_estimate(&inputs.0.vk).unwrap();
+
+        let primary_input_count =
+            Groth16VerifyCompressedInput::input_wire_count(&inputs.allocate(|| WireId(0)));
+        let (credits, _) = run_credits_pass(
+            &inputs,
+            primary_input_count,
+            BasisConfig::default(),
+            CreditConfig::default(),
+            &Groth16Verifier,
+        );
+
+        let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(&inputs);
+        let mut metadata_mode = StreamingMode::<TranslationMode>::MetadataPass(root_meta);
+        let meta_output = Groth16Verifier.build(&mut metadata_mode, &allocated_inputs);
+        let mode = TranslationMode::dry_run(credits, BasisConfig::default());
+        let (mut ctx, allocated_inputs) = metadata_mode.to_root_ctx(mode, &inputs, &meta_output);
+        Groth16Verifier.build(&mut ctx, &allocated_inputs);
+        let mode = ctx.get_mut_mode().unwrap();
+        mode.finish();
+
+        assert_eq!(estimate.total(), mode.gates());
+    }
+}
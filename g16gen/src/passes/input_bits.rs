@@ -1,8 +1,3 @@
-use std::{
-    fs::OpenOptions,
-    io::{BufWriter, Write},
-};
-
 use g16ckt::{
     Fq2Wire, WireId,
     ark::{CurveGroup, Field},
@@ -12,11 +7,37 @@ use g16ckt::{
         groth16::Groth16VerifyCompressedInput,
     },
 };
+use g16io::input_bits::write_bits;
+pub use g16io::input_bits::{InputBitsFormat, read_input_bits};
+
+pub const INPUT_BITS_FILE: &str = "inputs.txt";
+pub const INPUT_BITS_BIN_FILE: &str = "inputs.bin";
+
+/// File [`write_input_bits`] writes in `format`
+pub fn input_bits_file(format: InputBitsFormat) -> &'static str {
+    match format {
+        InputBitsFormat::Ascii => INPUT_BITS_FILE,
+        InputBitsFormat::Binary => INPUT_BITS_BIN_FILE,
+    }
+}
+
+/// Extract boolean input bits from Groth16VerifyCompressedInput and write them to the file of
+/// `format`
+pub fn write_input_bits(
+    inputs: &Groth16VerifyCompressedInput,
+    format: InputBitsFormat,
+) -> std::io::Result<()> {
+    let path = input_bits_file(format);
+    let bits = input_bits(inputs);
+    write_bits(path, &bits, format)?;
 
-const INPUT_BITS_FILE: &str = "inputs.txt";
+    println!("Wrote {} input bits to {}", bits.len(), path);
 
-/// Extract boolean input bits from Groth16VerifyCompressedInput and write to file
-pub fn write_input_bits(inputs: &Groth16VerifyCompressedInput) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Boolean values of the primary inputs, in the order the circuit allocates them
+pub(crate) fn input_bits(inputs: &Groth16VerifyCompressedInput) -> Vec<bool> {
     let mut next_wire = 2;
     let input_wires = inputs.allocate(|| {
         let w = WireId(next_wire);
@@ -102,20 +123,72 @@ pub fn write_input_bits(inputs: &Groth16VerifyCompressedInput) -> std::io::Resul
         wire_ids.len()
     );
 
-    // Write bits to file as '0' and '1' characters
-    let file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(INPUT_BITS_FILE)?;
+    bits
+}
 
-    let mut writer = BufWriter::new(file);
-    for bit in bits {
-        writer.write_all(if bit { b"1" } else { b"0" })?;
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use g16ckt::{
+        ark::{self, ark_serialize::CanonicalSerialize},
+        circuit::{CircuitBuilder, StreamingResult},
+        groth16_verify_compressed,
+    };
+
+    use super::*;
+    use crate::proof_setup::{generate_test_proof, load_proof};
+
+    #[test]
+    fn test_binary_file_records_bit_count() {
+        let inputs = generate_test_proof(1 << 4, 1);
+        let bits = input_bits(&inputs);
+        let wire_ids =
+            Groth16VerifyCompressedInput::collect_wire_ids(&inputs.allocate(|| WireId(0)));
+
+        let path = std::env::temp_dir().join("g16gen_input_bits.bin");
+        let path = path.to_str().unwrap();
+        write_bits(path, &bits, InputBitsFormat::Binary).unwrap();
+
+        let bytes = fs::read(path).unwrap();
+        let count = u64::from_le_bytes(bytes[4..12].try_into().unwrap());
+        assert_eq!(count as usize, wire_ids.len());
+        assert_eq!(read_input_bits(path).unwrap(), bits);
+
+        // The ASCII file reads back the same, at eight times the size
+        write_bits(path, &bits, InputBitsFormat::Ascii).unwrap();
+        assert_eq!(read_input_bits(path).unwrap(), bits);
+        assert!(fs::metadata(path).unwrap().len() as usize > 7 * bytes.len());
+
+        fs::remove_file(path).unwrap();
     }
-    writer.flush()?;
 
-    println!("Wrote {} input bits to {}", wire_ids.len(), INPUT_BITS_FILE);
-
-    Ok(())
+    #[test]
+    fn test_proof_file_bits_verify() {
+        let inputs = generate_test_proof(1 << 4, 1);
+
+        let path = std::env::temp_dir().join("g16gen_input_bits_proof.bin");
+        let proof = ark::Proof::<ark::Bn254> {
+            a: inputs.0.a.into_affine(),
+            b: inputs.0.b.into_affine(),
+            c: inputs.0.c.into_affine(),
+        };
+        let mut bytes = Vec::new();
+        (proof, inputs.0.vk.clone(), inputs.0.public.clone())
+            .serialize_compressed(&mut bytes)
+            .unwrap();
+        fs::write(&path, bytes).unwrap();
+
+        let loaded = load_proof(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // Same bits as the in-process proof the circuit was generated alongside
+        assert_eq!(input_bits(&loaded), input_bits(&inputs));
+
+        let result: StreamingResult<_, _, bool> =
+            CircuitBuilder::streaming_execute(loaded, 80_000, |ctx, input| {
+                groth16_verify_compressed(ctx, input).ok
+            });
+        assert!(result.output_value);
+    }
 }
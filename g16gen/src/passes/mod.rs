@@ -1,3 +1,7 @@
+pub mod aiger;
+pub mod bristol;
 pub mod credits;
+pub mod gate_estimate;
 pub mod input_bits;
 pub mod translation;
+pub mod verify;
@@ -2,63 +2,225 @@ use std::time::Instant;
 
 use g16ckt::{
     WireId,
-    circuit::{StreamingMode, component_meta::ComponentMetaBuilder},
-    gadgets::groth16::Groth16VerifyCompressedInput,
-    groth16_verify_compressed,
+    circuit::{CircuitInput, EncodeInput, StreamingMode, component_meta::ComponentMetaBuilder},
 };
 use tracing::info;
 
-use crate::modes::translate::TranslationMode;
+use crate::{
+    modes::{
+        component_map::sidecar_path,
+        decompose::BasisConfig,
+        translate::{StaleCredits, TranslationMode},
+    },
+    passes::credits::{CreditsGadget, count_wires},
+};
 
-const OUTPUT_FILE: &str = "g16.ckt";
+/// Circuit file written when no output path is given
+pub const DEFAULT_OUTPUT_FILE: &str = "g16.ckt";
+/// Gates between checkpoints of a resumable translation
+const CHECKPOINT_INTERVAL: u64 = 1 << 24;
 
-/// Run the translation pass to generate the circuit file
-pub async fn run_translation_pass(
-    inputs: &Groth16VerifyCompressedInput,
+/// Run the translation pass to write the circuit `gadget` emits to the file at `output`.
+///
+/// With `resumable`, progress is checkpointed so that a rerun after an interruption picks up
+/// from the last checkpoint instead of starting over. With `components`, the components that
+/// allocated each wire are written next to the circuit, see `ComponentMapWriter`. Returns the
+/// number of gates written, and fails before translating anything if `credits` don't have one
+/// entry per wire of this circuit.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_translation_pass<I, G>(
+    inputs: &I,
     primary_input_count: usize,
-    credits: Vec<u16>,
+    credits: Vec<u32>,
     output_wires: Vec<WireId>,
-) {
+    basis: BasisConfig,
+    output: &str,
+    resumable: bool,
+    components: bool,
+    gadget: &G,
+) -> Result<u64, StaleCredits>
+where
+    I: CircuitInput + EncodeInput<TranslationMode>,
+    G: CreditsGadget<I>,
+{
     let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(inputs);
     let mut metadata_mode = StreamingMode::<TranslationMode>::MetadataPass(root_meta);
 
     let metadata_start = Instant::now();
     // Run circuit construction in metadata mode
-    let meta_output_wires = {
-        let ok = groth16_verify_compressed(&mut metadata_mode, &allocated_inputs);
-        vec![ok]
-    };
+    let meta_output = gadget.build(&mut metadata_mode, &allocated_inputs);
     let metadata_time = metadata_start.elapsed();
     println!("Translation metadata time: {:?}", metadata_time);
 
-    let meta_output_wires = meta_output_wires.to_vec();
-
-    let (mut ctx, allocated_inputs) = metadata_mode.to_root_ctx(
+    // Counted apart from the credits, which may come from a cache written for another circuit
+    let wires = count_wires(inputs, primary_input_count, basis, gadget);
+    let mode = if resumable {
+        TranslationMode::new_resumable(
+            credits,
+            wires,
+            output,
+            primary_input_count as u64,
+            output_wires.clone(),
+            basis,
+            CHECKPOINT_INTERVAL,
+        )
+        .await?
+    } else {
         TranslationMode::new(
             credits,
-            OUTPUT_FILE,
+            wires,
+            output,
             primary_input_count as u64,
             output_wires.clone(),
+            basis,
         )
-        .await,
-        inputs,
-        &meta_output_wires,
-    );
+        .await?
+    };
+    let (mut ctx, allocated_inputs) = metadata_mode.to_root_ctx(mode, inputs, &meta_output);
+    if components {
+        let path = sidecar_path(output);
+        ctx.get_mut_mode()
+            .unwrap()
+            .write_component_map(&path)
+            .unwrap_or_else(|e| panic!("can't create {}: {e}", path.display()));
+    }
 
     let translation_start = Instant::now();
     // Run the translation pass
-    let translation_output_wires = {
-        let ok = groth16_verify_compressed(&mut ctx, &allocated_inputs);
-        vec![ok]
-    };
+    let real_output_wires = gadget.build(&mut ctx, &allocated_inputs);
 
-    assert_eq!(translation_output_wires, output_wires);
+    assert_eq!(real_output_wires, output_wires);
 
     let elapsed_translation = translation_start.elapsed();
     info!(
-        "Completed translation pass ({} inputs) in {:?}",
-        allocated_inputs.public.len(),
-        elapsed_translation
+        "Completed translation pass ({} primary inputs) in {:?}",
+        primary_input_count, elapsed_translation
     );
-    ctx.get_mut_mode().unwrap().finish();
+    let mode = ctx.get_mut_mode().unwrap();
+    mode.finish();
+    Ok(mode.gates())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use g16ckt::{CircuitContext, storage::CreditConfig};
+    use monoio::{FusionDriver, RuntimeBuilder};
+
+    use super::*;
+    use crate::passes::{
+        credits::run_credits_pass,
+        verify::{
+            run_verify,
+            tests::{BITS, gadget},
+        },
+    };
+
+    /// The stand-in verifier of the verify pass tests
+    struct Equal;
+
+    impl CreditsGadget<[bool; 2 * BITS]> for Equal {
+        fn build<C: CircuitContext>(
+            &self,
+            ctx: &mut C,
+            inputs: &[WireId; 2 * BITS],
+        ) -> Vec<WireId> {
+            vec![gadget(ctx, inputs)]
+        }
+    }
+
+    #[test]
+    fn test_translation_writes_to_output_path() {
+        let dir = std::env::temp_dir().join("g16gen_output_test");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let output = dir.join("equal.ckt");
+        let output = output.to_str().unwrap();
+
+        let inputs = [false; 2 * BITS];
+        let (credits, output_wires) = run_credits_pass(
+            &inputs,
+            inputs.len(),
+            BasisConfig::default(),
+            CreditConfig::default(),
+            &Equal,
+        );
+
+        RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                run_translation_pass(
+                    &inputs,
+                    inputs.len(),
+                    credits,
+                    output_wires,
+                    BasisConfig::default(),
+                    output,
+                    false,
+                    false,
+                    &Equal,
+                )
+                .await
+                .unwrap();
+
+                let input_bits = [[false, true, true, false, true, false, false, true]; 2].concat();
+                assert!(run_verify(output, &input_bits).await);
+            });
+
+        let written = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect::<Vec<_>>();
+        assert_eq!(written, ["equal.ckt"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_credits_of_another_circuit_are_rejected() {
+        let output = std::env::temp_dir().join("g16gen_stale_credits_test.ckt");
+        let output = output.to_str().unwrap();
+        let _ = fs::remove_file(output);
+
+        let inputs = [false; 2 * BITS];
+        let (mut credits, output_wires) = run_credits_pass(
+            &inputs,
+            inputs.len(),
+            BasisConfig::default(),
+            CreditConfig::default(),
+            &Equal,
+        );
+        // Still covers every output, but has an entry for a wire `Equal` doesn't have
+        let wires = credits.len();
+        credits.push(1);
+
+        let error = RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run_translation_pass(
+                &inputs,
+                inputs.len(),
+                credits,
+                output_wires,
+                BasisConfig::default(),
+                output,
+                false,
+                false,
+                &Equal,
+            ))
+            .unwrap_err();
+
+        assert_eq!(
+            error,
+            StaleCredits {
+                expected: wires,
+                got: wires + 1
+            }
+        );
+        assert!(fs::metadata(output).is_err());
+    }
 }
@@ -0,0 +1,184 @@
+pub use g16io::eval::simulate_ckt;
+
+/// Run the circuit at `path` on `input_bits` and check that every output is `true`
+pub async fn run_verify(path: &str, input_bits: &[bool]) -> bool {
+    let outputs = simulate_ckt(path, input_bits).await;
+    println!("Output values: {:?}", outputs);
+
+    !outputs.is_empty() && outputs.iter().all(|ok| *ok)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use g16ckt::{
+        CircuitContext, WireId,
+        circuit::{CircuitInput, StreamingMode, component_meta::ComponentMetaBuilder},
+        gadgets::{
+            bigint::{self, BigIntWires},
+            bn254::fr::Fr,
+            field::FpImpl,
+            groth16::Groth16VerifyCompressedInput,
+        },
+        storage::CreditConfig,
+    };
+    use monoio::{FusionDriver, RuntimeBuilder};
+
+    use super::*;
+    use crate::{
+        modes::{decompose::BasisConfig, fanout_ctr::FanoutCounter, translate::TranslationMode},
+        passes::{
+            credits::{Groth16Verifier, run_credits_pass},
+            input_bits::input_bits,
+            translation::run_translation_pass,
+        },
+        proof_setup::generate_test_proof,
+    };
+
+    pub(crate) const BITS: usize = 8;
+
+    /// `ok = (a == b)` over two `BITS`-wide integers, standing in for the verifier.
+    pub(crate) fn gadget<C: CircuitContext>(ctx: &mut C, inputs: &[WireId; 2 * BITS]) -> WireId {
+        let a = BigIntWires::from_bits(inputs[..BITS].iter().copied());
+        let b = BigIntWires::from_bits(inputs[BITS..].iter().copied());
+        bigint::equal(ctx, &a, &b)
+    }
+
+    /// Credits and translation passes for `gadget`, writing the circuit to `path`.
+    pub(crate) async fn generate(path: &str) {
+        let inputs = [false; 2 * BITS];
+
+        let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(&inputs);
+        let mut metadata_mode = StreamingMode::<FanoutCounter>::MetadataPass(root_meta);
+        let meta_output = gadget(&mut metadata_mode, &allocated_inputs);
+        let (mut ctx, allocated_inputs) = metadata_mode.to_root_ctx(
+            FanoutCounter::new(
+                inputs.len(),
+                BasisConfig::default(),
+                CreditConfig::default(),
+            ),
+            &inputs,
+            &meta_output,
+        );
+        let ok = gadget(&mut ctx, &allocated_inputs);
+        let (mut credits, _, _, _) = ctx.get_mut_mode().unwrap().finish();
+        // The counter gave every wire of `gadget` an entry
+        let wires = credits.len();
+        // One more read off the finished circuit, as the credits pass gives every output
+        credits[ok.0] += 1;
+
+        let (allocated_inputs, root_meta) = ComponentMetaBuilder::new_with_input(&inputs);
+        let mut metadata_mode = StreamingMode::<TranslationMode>::MetadataPass(root_meta);
+        let meta_output = gadget(&mut metadata_mode, &allocated_inputs);
+        let (mut ctx, allocated_inputs) = metadata_mode.to_root_ctx(
+            TranslationMode::new(
+                credits,
+                wires,
+                path,
+                inputs.len() as u64,
+                vec![ok],
+                BasisConfig::default(),
+            )
+            .await
+            .unwrap(),
+            &inputs,
+            &meta_output,
+        );
+        assert_eq!(gadget(&mut ctx, &allocated_inputs), ok);
+        ctx.get_mut_mode().unwrap().finish();
+    }
+
+    #[test]
+    fn test_generate_then_verify() {
+        let path = std::env::temp_dir().join("g16gen_verify_test.ckt");
+        let path = path.to_str().unwrap();
+
+        RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                generate(path).await;
+
+                let mut input_bits =
+                    [[true, false, true, true, false, false, true, false]; 2].concat();
+                assert!(run_verify(path, &input_bits).await);
+
+                input_bits[3] = !input_bits[3];
+                assert!(!run_verify(path, &input_bits).await);
+            });
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_simulate_ckt_round_trip() {
+        let path = std::env::temp_dir().join("g16gen_simulate_test.ckt");
+        let path = path.to_str().unwrap();
+
+        RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                generate(path).await;
+
+                let a = [false, true, true, false, true, false, false, true];
+                assert_eq!(simulate_ckt(path, &[a; 2].concat()).await, [true]);
+
+                let b = [true, true, true, false, true, false, false, true];
+                assert_eq!(simulate_ckt(path, &[a, b].concat()).await, [false]);
+            });
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// The whole pipeline on the real verifier: the generated circuit must accept a valid proof
+    /// and reject it once point A is corrupted
+    #[test]
+    fn test_groth16_circuit_accepts_valid_proof() {
+        let path = std::env::temp_dir().join("g16gen_groth16_test.ckt");
+        let path = path.to_str().unwrap();
+
+        let inputs = generate_test_proof(1 << 4, 1);
+        let primary_input_count =
+            Groth16VerifyCompressedInput::input_wire_count(&inputs.allocate(|| WireId(0)));
+        let (credits, output_wires) = run_credits_pass(
+            &inputs,
+            primary_input_count,
+            BasisConfig::default(),
+            CreditConfig::default(),
+            &Groth16Verifier,
+        );
+
+        RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                run_translation_pass(
+                    &inputs,
+                    primary_input_count,
+                    credits,
+                    output_wires,
+                    BasisConfig::default(),
+                    path,
+                    false,
+                    false,
+                    &Groth16Verifier,
+                )
+                .await
+                .unwrap();
+
+                let mut input_bits = input_bits(&inputs);
+                assert_eq!(input_bits.len(), primary_input_count);
+                assert!(run_verify(path, &input_bits).await);
+
+                // Lowest bit of A's x-coordinate, right after the public inputs
+                let a_x = inputs.0.public.len() * Fr::N_BITS;
+                input_bits[a_x] = !input_bits[a_x];
+                assert!(!run_verify(path, &input_bits).await);
+            });
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,6 +1,11 @@
+use std::{fs::File, io};
+
 use g16ckt::{
     Groth16VerifyInput,
-    ark::{self, AffineRepr, CircuitSpecificSetupSNARK, SNARK, UniformRand},
+    ark::{
+        self, AffineRepr, CircuitSpecificSetupSNARK, SNARK, UniformRand,
+        ark_serialize::CanonicalDeserialize,
+    },
     gadgets::groth16::Groth16VerifyCompressedInput,
 };
 use rand::SeedableRng;
@@ -8,26 +13,90 @@ use rand_chacha::ChaCha20Rng;
 
 use crate::dummy_circuit::DummyCircuit;
 
-/// Generate a test proof and return compressed inputs for verification
-pub fn generate_test_proof(num_constraints: usize) -> Groth16VerifyCompressedInput {
+/// Generate a test proof with `num_public` public inputs and return compressed inputs for
+/// verification
+pub fn generate_test_proof(
+    num_constraints: usize,
+    num_public: usize,
+) -> Groth16VerifyCompressedInput {
     let mut rng = ChaCha20Rng::seed_from_u64(12345);
     let circuit = DummyCircuit::<ark::Fr> {
         a: Some(ark::Fr::rand(&mut rng)),
         b: Some(ark::Fr::rand(&mut rng)),
         num_variables: 10,
         num_constraints,
+        num_public,
     };
 
     let (pk, vk) = ark::Groth16::<ark::Bn254>::setup(circuit, &mut rng).expect("setup failed");
-    let c_val = circuit.a.unwrap() * circuit.b.unwrap();
+    let public = circuit.public_inputs().unwrap();
     let proof = ark::Groth16::<ark::Bn254>::prove(&pk, circuit, &mut rng).expect("prove failed");
 
     Groth16VerifyInput {
-        public: vec![c_val],
+        public,
         a: proof.a.into_group(),
         b: proof.b.into_group(),
         c: proof.c.into_group(),
         vk: vk.clone(),
     }
     .compress()
+    .expect("setup produces a verifying key for its own public inputs")
+}
+
+/// Load a proof together with its verifying key and public inputs.
+///
+/// The file holds the arkworks compressed serialization of the tuple
+/// `(Proof<Bn254>, VerifyingKey<Bn254>, Vec<Fr>)`. Points are checked to be on the curve and in
+/// the prime-order subgroup while deserializing.
+pub fn load_proof(path: &str) -> io::Result<Groth16VerifyCompressedInput> {
+    let file = io::BufReader::new(File::open(path)?);
+    let (proof, vk, public) = <(
+        ark::Proof<ark::Bn254>,
+        ark::VerifyingKey<ark::Bn254>,
+        Vec<ark::Fr>,
+    )>::deserialize_compressed(file)
+    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    Groth16VerifyInput {
+        public,
+        a: proof.a.into_group(),
+        b: proof.b.into_group(),
+        c: proof.c.into_group(),
+        vk,
+    }
+    .compress()
+    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use g16ckt::{
+        ark::CurveGroup,
+        circuit::{CircuitBuilder, StreamingResult},
+        groth16_verify_compressed,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_multiple_public_inputs_verify() {
+        let inputs = generate_test_proof(1 << 4, 4);
+        assert_eq!(inputs.0.public.len(), 4);
+        assert_eq!(inputs.0.vk.gamma_abc_g1.len(), 5);
+
+        let proof = ark::Proof::<ark::Bn254> {
+            a: inputs.0.a.into_affine(),
+            b: inputs.0.b.into_affine(),
+            c: inputs.0.c.into_affine(),
+        };
+        assert!(
+            ark::Groth16::<ark::Bn254>::verify(&inputs.0.vk, &inputs.0.public, &proof).unwrap()
+        );
+
+        let result: StreamingResult<_, _, bool> =
+            CircuitBuilder::streaming_execute(inputs, 80_000, |ctx, input| {
+                groth16_verify_compressed(ctx, input).ok
+            });
+        assert!(result.output_value);
+    }
 }
@@ -0,0 +1,174 @@
+use std::{collections::HashMap, time::Instant};
+
+use ckt_fmtv5_types::{GateType, v5::a::reader::CircuitReaderV5a};
+use indicatif::ProgressBar;
+use tracing::info;
+
+/// Reference evaluator for `.ckt` v5a gate streams.
+///
+/// Wire IDs follow the translation layout: FALSE at 0, TRUE at 1, then the primary inputs. Every
+/// other wire is kept only until its credits are used up, so a gate reading a wire that was never
+/// written or already released is reported instead of silently evaluated.
+#[derive(Debug)]
+pub struct CktEvaluator {
+    /// FALSE, TRUE, then the primary inputs
+    fixed: Vec<bool>,
+    live: HashMap<u64, (bool, u32)>,
+    outputs: Vec<u64>,
+    output_values: HashMap<u64, bool>,
+}
+
+impl CktEvaluator {
+    pub fn new(primary_inputs: &[bool], outputs: &[u64]) -> Self {
+        let mut fixed = vec![false, true];
+        fixed.extend_from_slice(primary_inputs);
+
+        Self {
+            fixed,
+            live: HashMap::new(),
+            outputs: outputs.to_vec(),
+            output_values: HashMap::new(),
+        }
+    }
+
+    pub fn evaluate(&mut self, gate_type: GateType, in1: u64, in2: u64, out: u64, credits: u32) {
+        let (a, b) = (self.read(in1), self.read(in2));
+        let value = match gate_type {
+            GateType::AND => a & b,
+            GateType::XOR => a ^ b,
+        };
+
+        if self.outputs.contains(&out) {
+            self.output_values.insert(out, value);
+        }
+        if credits > 0 {
+            self.live.insert(out, (value, credits));
+        }
+    }
+
+    /// Values of the output wires, in header order.
+    pub fn outputs(&self) -> Vec<bool> {
+        self.outputs
+            .iter()
+            .map(|wire| match self.fixed.get(*wire as usize) {
+                Some(value) => *value,
+                None => *self
+                    .output_values
+                    .get(wire)
+                    .unwrap_or_else(|| panic!("output wire {wire} was never written")),
+            })
+            .collect()
+    }
+
+    fn read(&mut self, wire: u64) -> bool {
+        if let Some(value) = self.fixed.get(wire as usize) {
+            return *value;
+        }
+
+        let (value, credits) = self
+            .live
+            .get_mut(&wire)
+            .unwrap_or_else(|| panic!("wire {wire} read while not available"));
+        let value = *value;
+        *credits -= 1;
+        if *credits == 0 {
+            self.live.remove(&wire);
+        }
+        value
+    }
+}
+
+/// Simulate the circuit at `path` on `input_bits`, applying its gates in file order, and return
+/// the output wire values.
+///
+/// Unlike `g16check`'s credit check, this computes every gate, so it closes the loop between
+/// generation and evaluation for `g16gen verify`, `g16check --inputs` and round-trip tests.
+pub async fn simulate_ckt(path: &str, input_bits: &[bool]) -> Vec<bool> {
+    let mut reader = CircuitReaderV5a::open(path).unwrap();
+    let primary_inputs = reader.header().primary_inputs;
+    let total_gates = reader.header().total_gates();
+    assert_eq!(
+        primary_inputs as usize,
+        input_bits.len(),
+        "circuit expects {primary_inputs} input bits, got {}",
+        input_bits.len()
+    );
+
+    let outputs = reader.outputs().to_vec();
+    let mut evaluator = CktEvaluator::new(input_bits, &outputs);
+
+    let start = Instant::now();
+    let pb = ProgressBar::new(total_gates);
+    while let Some(block) = reader.next_block_soa().await.unwrap() {
+        for i in 0..block.gates_in_block {
+            evaluator.evaluate(
+                block.gate_types[i],
+                block.in1[i],
+                block.in2[i],
+                block.out[i],
+                block.credits[i],
+            );
+        }
+        pb.inc(block.gates_in_block as u64);
+    }
+    pb.finish();
+    info!("Evaluated {} gates in {:?}", total_gates, start.elapsed());
+
+    evaluator.outputs()
+}
+
+#[cfg(test)]
+mod tests {
+    use ckt_fmtv5_types::v5::a::{GateV5a, writer::CircuitWriterV5a};
+    use monoio::{FusionDriver, RuntimeBuilder};
+
+    use super::*;
+
+    fn gate(gate_type: GateType, in1: u64, in2: u64, out: u64, credits: u32) -> GateV5a {
+        GateV5a {
+            in1,
+            in2,
+            out,
+            credits,
+            gate_type,
+        }
+    }
+
+    #[test]
+    fn test_simulate_equality_circuit() {
+        let path = std::env::temp_dir().join("g16io_eval.ckt");
+
+        RuntimeBuilder::<FusionDriver>::new()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                // ok = !(a0 ^ b0) & !(a1 ^ b1), with inputs a0 a1 b0 b1 at wires 2..6
+                let mut writer = CircuitWriterV5a::new(path.clone(), 4, vec![10])
+                    .await
+                    .unwrap();
+                for gate in [
+                    gate(GateType::XOR, 2, 4, 6, 1),
+                    gate(GateType::XOR, 3, 5, 7, 1),
+                    gate(GateType::XOR, 6, 1, 8, 1),
+                    gate(GateType::XOR, 7, 1, 9, 1),
+                    gate(GateType::AND, 8, 9, 10, 0),
+                ] {
+                    writer.write_gate(gate).await.unwrap();
+                }
+                writer.finalize().await.unwrap();
+
+                let path = path.to_str().unwrap();
+                assert_eq!(
+                    simulate_ckt(path, &[true, false, true, false]).await,
+                    [true]
+                );
+                assert_eq!(
+                    simulate_ckt(path, &[true, false, true, true]).await,
+                    [false]
+                );
+            });
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::{self, BufWriter, Write},
+    str::FromStr,
+};
+
+const INPUT_BITS_MAGIC: [u8; 4] = *b"G16I";
+
+/// Encoding of an input bits file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InputBitsFormat {
+    /// One `'0'` or `'1'` character per bit, for human inspection
+    #[default]
+    Ascii,
+    /// The `G16I` magic and the bit count as a little-endian `u64`, followed by the bits packed
+    /// eight to a byte, least significant bit first
+    Binary,
+}
+
+impl FromStr for InputBitsFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ascii" => Ok(InputBitsFormat::Ascii),
+            "bin" => Ok(InputBitsFormat::Binary),
+            _ => Err(format!(
+                "unknown input bits format {s:?}, expected ascii or bin"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for InputBitsFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputBitsFormat::Ascii => write!(f, "ascii"),
+            InputBitsFormat::Binary => write!(f, "bin"),
+        }
+    }
+}
+
+/// Write `bits` to `path` in `format`
+pub fn write_bits(path: &str, bits: &[bool], format: InputBitsFormat) -> io::Result<()> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
+
+    let mut writer = BufWriter::new(file);
+    match format {
+        InputBitsFormat::Ascii => {
+            for &bit in bits {
+                writer.write_all(if bit { b"1" } else { b"0" })?;
+            }
+        }
+        InputBitsFormat::Binary => writer.write_all(&pack_bits(bits))?,
+    }
+    writer.flush()
+}
+
+/// Read back input bits written by [`write_bits`], in either format
+pub fn read_input_bits(path: &str) -> io::Result<Vec<bool>> {
+    let bytes = fs::read(path)?;
+    if bytes.starts_with(&INPUT_BITS_MAGIC) {
+        return unpack_bits(&bytes)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{path}: {e}")));
+    }
+
+    String::from_utf8(bytes)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{path}: {e}")))?
+        .trim_end()
+        .chars()
+        .map(|c| match c {
+            '0' => Ok(false),
+            '1' => Ok(true),
+            c => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unexpected character {c:?} in {path}"),
+            )),
+        })
+        .collect()
+}
+
+/// Encode `bits` in [`InputBitsFormat::Binary`]
+fn pack_bits(bits: &[bool]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(12 + bits.len().div_ceil(8));
+    bytes.extend_from_slice(&INPUT_BITS_MAGIC);
+    bytes.extend_from_slice(&(bits.len() as u64).to_le_bytes());
+    bytes.extend(bits.chunks(8).map(|chunk| {
+        chunk
+            .iter()
+            .enumerate()
+            .fold(0u8, |byte, (i, &bit)| byte | (u8::from(bit) << i))
+    }));
+    bytes
+}
+
+/// Decode bits in [`InputBitsFormat::Binary`], checking the byte count against the header
+fn unpack_bits(bytes: &[u8]) -> Result<Vec<bool>, String> {
+    let Some((header, packed)) = bytes.split_at_checked(12) else {
+        return Err("truncated header".to_string());
+    };
+    if header[..4] != INPUT_BITS_MAGIC {
+        return Err("not a binary input bits file".to_string());
+    }
+    let count = u64::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+    if packed.len() != count.div_ceil(8) {
+        return Err(format!(
+            "{} bytes of bits, expected {} for {count} bits",
+            packed.len(),
+            count.div_ceil(8)
+        ));
+    }
+
+    Ok((0..count)
+        .map(|i| (packed[i / 8] >> (i % 8)) & 1 == 1)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packed_bits_round_trip() {
+        for len in [0usize, 1, 7, 8, 9, 1000] {
+            let bits = (0..len)
+                .map(|i| (i * i + i / 3) % 5 < 2)
+                .collect::<Vec<_>>();
+            let packed = pack_bits(&bits);
+            assert_eq!(packed.len(), 12 + len.div_ceil(8));
+            assert_eq!(unpack_bits(&packed).unwrap(), bits);
+
+            // Cut short, or with a trailing byte
+            if len > 0 {
+                assert!(unpack_bits(&packed[..packed.len() - 1]).is_err());
+            }
+            assert!(unpack_bits(&[packed.as_slice(), &[0]].concat()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_read_packed_input_bits() {
+        let path = std::env::temp_dir().join("g16io_inputs.bin");
+        let path = path.to_str().unwrap();
+
+        // 10 bits, least significant first in each byte: 10110001 then 01
+        let mut bytes = INPUT_BITS_MAGIC.to_vec();
+        bytes.extend_from_slice(&10u64.to_le_bytes());
+        bytes.extend_from_slice(&[0b1000_1101, 0b10]);
+        fs::write(path, &bytes).unwrap();
+        assert_eq!(
+            read_input_bits(path).unwrap(),
+            [
+                true, false, true, true, false, false, false, true, false, true
+            ]
+        );
+
+        // One byte short of the bit count in the header
+        fs::write(path, &bytes[..bytes.len() - 1]).unwrap();
+        assert_eq!(
+            read_input_bits(path).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        fs::remove_file(path).unwrap();
+    }
+}
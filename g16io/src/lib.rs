@@ -0,0 +1,5 @@
+//! Circuit files shared by `g16gen`, which writes them, and `g16check`, which reads them back:
+//! the input bits format and a reference evaluator for `.ckt` circuits.
+
+pub mod eval;
+pub mod input_bits;